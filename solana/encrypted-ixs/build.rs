@@ -0,0 +1,30 @@
+//! Copies the plain-Rust parts of the `circuits` module, the lines between `// BEGIN PLAINTEXT`
+//! and `// END PLAINTEXT` in `src/lib.rs`, to `$OUT_DIR/circuit_plaintext.rs`. `hand_eval` includes
+//! that file to expose the circuit's hand evaluator, and the `hand_eval` and `five_card_draw` tests
+//! include it to run the circuit's own logic outside the MPC environment, since an `#[encrypted]`
+//! module cannot be called from ordinary code.
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+
+    let source = fs::read_to_string("src/lib.rs").expect("failed to read src/lib.rs");
+    let mut plaintext = String::new();
+    let mut inside = false;
+    for line in source.lines() {
+        match line.trim() {
+            "// BEGIN PLAINTEXT" => inside = true,
+            "// END PLAINTEXT" => inside = false,
+            _ if inside => {
+                plaintext.push_str(line);
+                plaintext.push('\n');
+            }
+            _ => {}
+        }
+    }
+    assert!(!inside, "unterminated PLAINTEXT region in src/lib.rs");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out_dir).join("circuit_plaintext.rs"), plaintext)
+        .expect("failed to write circuit_plaintext.rs");
+}
@@ -0,0 +1,174 @@
+/**
+ * @description
+ * This module produces a reproducible deal from a fixed seed, for use by the non-MPC reference
+ * evaluator and integration tests. It never runs inside the MPC environment and must not be
+ * used for real games: the `shuffle_and_deal` circuit shuffles with `ArcisRNG` instead.
+ *
+ * @key_features
+ * - Seeded Fisher-Yates shuffle, so the same seed always yields the same deck.
+ * - Mirrors the dealing order of `shuffle_and_deal`: player `p` gets cards `2p` and `2p + 1`,
+ *   and the board is drawn from the remaining deck. `deal_players_from_seed` deals to any
+ *   number of players, as the circuit does for its `DEAL_PLAYERS`; `deal_from_seed` is the
+ *   heads-up deal the program plays.
+ * - Resolves the winner with the reference evaluator in `hand_eval`, using the same
+ *   0 / 1 / 2 (tie) encoding as `preview_winner`. `hand_eval::showdown_winners` gives the
+ *   `determine_winner` result for a deal to any number of players.
+ *
+ * @dependencies
+ * - crate::hand_eval: The reference hand evaluator.
+ *
+ * @notes
+ * - Only compiled for tests, or for off-chain tooling that enables the `test-utils` feature, so a
+ *   predictable deal can never be wired into a build that plays for real.
+ */
+use crate::hand_eval::find_best_hand_from_seven;
+
+/// A complete heads-up deal: both players' hole cards and the five community cards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeterministicDeal {
+    pub hole_cards: [[u8; 2]; 2],
+    pub board: [u8; 5],
+}
+
+/// Shuffles a 52-card deck using a seeded SplitMix64 generator.
+pub fn shuffled_deck(seed: u64) -> [u8; 52] {
+    let mut deck = [0u8; 52];
+    for (i, card) in deck.iter_mut().enumerate() {
+        *card = i as u8;
+    }
+
+    let mut state = seed;
+    for i in (1..52).rev() {
+        let j = (next_u64(&mut state) % (i as u64 + 1)) as usize;
+        deck.swap(i, j);
+    }
+    deck
+}
+
+/// Deals `N` players' hole cards and the five board cards from the deck produced by
+/// `shuffled_deck(seed)`, in the same order as the `shuffle_and_deal` circuit compiled for
+/// `N` players.
+pub fn deal_players_from_seed<const N: usize>(seed: u64) -> ([[u8; 2]; N], [u8; 5]) {
+    let deck = shuffled_deck(seed);
+    let hole_cards = std::array::from_fn(|p| [deck[2 * p], deck[2 * p + 1]]);
+    let board = std::array::from_fn(|i| deck[2 * N + i]);
+    (hole_cards, board)
+}
+
+/// Deals a heads-up hand from the deck produced by `shuffled_deck(seed)`, in the same order as
+/// the `shuffle_and_deal` circuit.
+pub fn deal_from_seed(seed: u64) -> DeterministicDeal {
+    let (hole_cards, board) = deal_players_from_seed::<2>(seed);
+    DeterministicDeal { hole_cards, board }
+}
+
+impl DeterministicDeal {
+    /// Returns the winner of the deal: 0 or 1 for a single winner, 2 for a split pot.
+    pub fn winner(&self) -> u8 {
+        let scores = self.hole_cards.map(|hole| {
+            find_best_hand_from_seven([
+                hole[0],
+                hole[1],
+                self.board[0],
+                self.board[1],
+                self.board[2],
+                self.board[3],
+                self.board[4],
+            ])
+        });
+        match scores[0].cmp(&scores[1]) {
+            std::cmp::Ordering::Greater => 0,
+            std::cmp::Ordering::Less => 1,
+            std::cmp::Ordering::Equal => 2,
+        }
+    }
+}
+
+/// SplitMix64: a small, well-distributed generator that is trivial to reproduce in other
+/// languages, so off-chain tooling can derive the same deals.
+fn next_u64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand_eval::showdown_winners;
+
+    #[test]
+    fn shuffled_deck_is_a_reproducible_permutation() {
+        let deck = shuffled_deck(7);
+        assert_eq!(deck, shuffled_deck(7));
+        assert_ne!(deck, shuffled_deck(8));
+
+        let mut sorted = deck;
+        sorted.sort();
+        assert_eq!(sorted, std::array::from_fn(|i| i as u8));
+    }
+
+    #[test]
+    fn deals_in_circuit_order() {
+        let deck = shuffled_deck(3);
+        let (hole_cards, board) = deal_players_from_seed::<3>(3);
+        assert_eq!(
+            hole_cards,
+            [[deck[0], deck[1]], [deck[2], deck[3]], [deck[4], deck[5]]]
+        );
+        assert_eq!(board, [deck[6], deck[7], deck[8], deck[9], deck[10]]);
+    }
+
+    #[test]
+    fn known_seeds_give_known_deals_and_winners() {
+        // Seed 0: Kings and Fives beat Fives and Fours.
+        let deal = deal_from_seed(0);
+        assert_eq!(
+            deal,
+            DeterministicDeal {
+                hole_cards: [[46, 36], [8, 40]],
+                board: [14, 24, 44, 9, 15]
+            }
+        );
+        assert_eq!(deal.winner(), 0);
+
+        // Seed 1: both players make Sevens and Sixes with a King kicker and split.
+        let deal = deal_from_seed(1);
+        assert_eq!(
+            deal,
+            DeterministicDeal {
+                hole_cards: [[19, 8], [16, 33]],
+                board: [20, 43, 23, 18, 47]
+            }
+        );
+        assert_eq!(deal.winner(), 2);
+
+        // Seed 42: a pair of Eights each, and player 2's Jack outkicks player 1's Nine.
+        let deal = deal_from_seed(42);
+        assert_eq!(
+            deal,
+            DeterministicDeal {
+                hole_cards: [[6, 28], [12, 39]],
+                board: [40, 0, 26, 25, 22]
+            }
+        );
+        assert_eq!(deal.winner(), 1);
+    }
+
+    #[test]
+    fn winner_agrees_with_showdown_winners() {
+        for seed in 0..500u64 {
+            let deal = deal_from_seed(seed);
+            let (mask, _) = showdown_winners(&deal.hole_cards, deal.board);
+            let expected = match mask {
+                0b01 => 0,
+                0b10 => 1,
+                _ => 2,
+            };
+            assert_eq!(deal.winner(), expected, "seed {seed}");
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// This module contains the confidential logic for the five-card draw variant: dealing five cards
+// to each player, replacing discarded cards during the draw, and comparing the final hands.
+//
+// Note: The actual implementation is in the circuits module in lib.rs due to Arcis restrictions.
+#[cfg(test)]
+#[allow(dead_code, clippy::all)]
+mod tests {
+    include!(concat!(env!("OUT_DIR"), "/circuit_plaintext.rs"));
+
+    /// A hand of cards 0-4 and a deck of the cards 10-51, as `shuffle_and_deal_draw` deals an
+    /// unshuffled deck: the first player's five cards, then the second player's, then the rest.
+    fn dealt() -> ([u8; 5], [u8; 42]) {
+        (
+            std::array::from_fn(|i| i as u8),
+            std::array::from_fn(|i| i as u8 + 10),
+        )
+    }
+
+    #[test]
+    fn standing_pat_keeps_the_hand() {
+        let (hand, deck) = dealt();
+        assert_eq!(replace_discards(hand, deck, 0, 0), (hand, 0));
+    }
+
+    #[test]
+    fn draws_replace_discards_in_hand_order() {
+        let (hand, deck) = dealt();
+        // Discarding the second and fourth cards takes the deck's first two cards.
+        assert_eq!(
+            replace_discards(hand, deck, 0, 0b01010),
+            ([0, 10, 2, 11, 4], 2)
+        );
+        // Drawing five replaces the whole hand.
+        assert_eq!(
+            replace_discards(hand, deck, 0, 0b11111),
+            ([10, 11, 12, 13, 14], 5)
+        );
+    }
+
+    #[test]
+    fn every_discard_count_draws_that_many_cards() {
+        let (hand, deck) = dealt();
+        for discard_mask in 0..32u8 {
+            let (cards, drawn) = replace_discards(hand, deck, 0, discard_mask);
+            assert_eq!(
+                drawn as u32,
+                discard_mask.count_ones(),
+                "mask {discard_mask:05b}"
+            );
+
+            let mut next = 0;
+            for i in 0..5 {
+                if discard_mask & (1 << i) != 0 {
+                    assert_eq!(cards[i], deck[next], "mask {discard_mask:05b}");
+                    next += 1;
+                } else {
+                    assert_eq!(cards[i], hand[i], "mask {discard_mask:05b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn second_draw_continues_from_the_first() {
+        let (hand, deck) = dealt();
+        let second_hand = std::array::from_fn(|i| i as u8 + 5);
+        for first_mask in 0..32u8 {
+            let (_, drawn) = replace_discards(hand, deck, 0, first_mask);
+            // Both players drawing five never runs past the 42 cards left.
+            let (cards, total) = replace_discards(second_hand, deck, drawn, 0b11111);
+            assert_eq!(total, drawn + 5);
+            assert_eq!(cards, std::array::from_fn(|i| deck[drawn as usize + i]));
+        }
+    }
+}
@@ -15,6 +15,8 @@
  * - Unique Scoring: Each possible hand maps to a distinct `u64` score.
  * - Kicker Handling: Correctly sorts and incorporates kickers into the score for all hand types.
  * - Special Cases: Properly handles edge cases like the A-2-3-4-5 "wheel" straight.
+ * - Decoding: `hand_category` and `kickers` unpack a score into a readable hand type and its
+ *   tie-breaking ranks, for clients and tooling.
  *
  * @dependencies
  * - None. This is a pure logic module.
@@ -24,30 +26,32 @@
  * - `rank = card / 4` (0=Two, ..., 12=Ace)
  * - `suit = card % 4`
  * - The final score is composed as: `(HandRank << 20) | (Kicker1 << 16) | ... | (Kicker5 << 0)`.
+ * - `evaluate_hand` and `find_best_hand_from_seven` run the `circuits` module's own evaluator,
+ *   which `build.rs` copies out of `lib.rs`. An `#[encrypted]` module cannot call code outside
+ *   it, so the circuit holds the only copy and this module includes it.
  */
-// No imports needed for this module
-
-// --- Constants for Hand Ranks ---
-// These values determine the base score for each hand type.
-const HIGH_CARD_RANK: u64 = 0;
-const ONE_PAIR_RANK: u64 = 1;
-const TWO_PAIR_RANK: u64 = 2;
-const THREE_OF_A_KIND_RANK: u64 = 3;
-const STRAIGHT_RANK: u64 = 4;
-const FLUSH_RANK: u64 = 5;
-const FULL_HOUSE_RANK: u64 = 6;
-const FOUR_OF_A_KIND_RANK: u64 = 7;
-const STRAIGHT_FLUSH_RANK: u64 = 8;
-
-// --- Constants for Card Ranks ---
-// Ace is high (12), Two is low (0).
-const RANK_ACE: u8 = 12;
-const RANK_FIVE: u8 = 3;
-const RANK_FOUR: u8 = 2;
-const RANK_THREE: u8 = 1;
-const RANK_TWO: u8 = 0;
-
-/// The main evaluation function. It orchestrates the entire process of scoring a 5-card hand.
+
+/// The `circuits` module's plain-Rust code, copied out of `lib.rs` by `build.rs`. It keeps the
+/// circuit's Arcis-compatible form, so lints that form trips are allowed here.
+#[allow(dead_code, clippy::all)]
+mod circuit {
+    include!(concat!(env!("OUT_DIR"), "/circuit_plaintext.rs"));
+
+    pub(super) fn evaluate_five(hand: [u8; 5]) -> u64 {
+        evaluate_hand(hand)
+    }
+
+    pub(super) fn best_of_seven(seven_cards: [u8; 7]) -> u64 {
+        find_best_hand_from_seven(seven_cards)
+    }
+}
+
+use circuit::{
+    FLUSH_RANK, FOUR_OF_A_KIND_RANK, FULL_HOUSE_RANK, HIGH_CARD_RANK, ONE_PAIR_RANK,
+    STRAIGHT_FLUSH_RANK, STRAIGHT_RANK, THREE_OF_A_KIND_RANK, TWO_PAIR_RANK,
+};
+
+/// Scores a 5-card hand with the circuit's evaluator.
 ///
 /// # Arguments
 /// * `hand` - A fixed-size array of 5 `u8` values, where each value represents a card from 0-51.
@@ -55,155 +59,543 @@ const RANK_TWO: u8 = 0;
 /// # Returns
 /// A `u64` score representing the hand's strength.
 pub fn evaluate_hand(hand: [u8; 5]) -> u64 {
-    // 1. Prepare card data: extract and sort ranks, get suits.
-    let mut ranks = [0u8; 5];
-    let mut suits = [0u8; 5];
-    for i in 0..5 {
-        ranks[i] = hand[i] / 4;
-        suits[i] = hand[i] % 4;
-    }
-    // Sorting ranks in descending order simplifies many downstream calculations.
-    // Arcis provides a data-independent sort for integer arrays.
-    ranks.sort();
-    ranks.reverse();
-
-    // 2. Create a frequency map (histogram) of ranks.
-    let mut rank_counts = [0u8; 13];
-    for &rank in ranks.iter() {
-        rank_counts[rank as usize] += 1;
-    }
+    circuit::evaluate_five(hand)
+}
 
-    // 3. Detect hand features (flush, straight) in a data-independent way.
-    let is_flush = (suits[0] == suits[1])
-        & (suits[0] == suits[2])
-        & (suits[0] == suits[3])
-        & (suits[0] == suits[4]);
-
-    let is_straight_gapped = (ranks[0] - ranks[4] == 4) & (ranks[0] != ranks[1]) & (ranks[1] != ranks[2]) & (ranks[2] != ranks[3]) & (ranks[3] != ranks[4]);
-
-    // Handle the A-2-3-4-5 "wheel" straight.
-    let is_wheel = (ranks[0] == RANK_ACE)
-        & (ranks[1] == RANK_FIVE)
-        & (ranks[2] == RANK_FOUR)
-        & (ranks[3] == RANK_THREE)
-        & (ranks[4] == RANK_TWO);
-
-    let is_straight = is_straight_gapped | is_wheel;
-    let is_straight_flush = is_straight & is_flush;
-
-    // 4. Analyze rank counts to identify pairs, trips, etc.
-    let mut num_quads = 0;
-    let mut num_trips = 0;
-    let mut num_pairs = 0;
-    for &count in rank_counts.iter() {
-        num_quads += (count == 4) as u8;
-        num_trips += (count == 3) as u8;
-        num_pairs += (count == 2) as u8;
-    }
+/// Finds the highest possible score from a 7-card hand by evaluating all 21
+/// possible 5-card combinations, as the circuit does.
+///
+/// # Arguments
+/// * `seven_cards` - A fixed-size array of 7 `u8` values representing the cards.
+///
+/// # Returns
+/// The `u64` score of the best 5-card hand.
+pub fn find_best_hand_from_seven(seven_cards: [u8; 7]) -> u64 {
+    circuit::best_of_seven(seven_cards)
+}
 
-    let is_four_of_a_kind = num_quads == 1;
-    let is_full_house = (num_trips == 1) & (num_pairs == 1);
-    let is_three_of_a_kind = (num_trips == 1) & (num_pairs == 0);
-    let is_two_pair = num_pairs == 2;
-    let is_one_pair = (num_pairs == 1) & (num_trips == 0);
-
-    // 5. Determine the final hand rank using mutually exclusive conditions.
-    // This chain of boolean logic ensures only the highest possible rank is selected.
-    let hand_rank = (is_straight_flush as u64 * STRAIGHT_FLUSH_RANK)
-        + ((!is_straight_flush & is_four_of_a_kind) as u64 * FOUR_OF_A_KIND_RANK)
-        + ((!is_straight_flush & !is_four_of_a_kind & is_full_house) as u64 * FULL_HOUSE_RANK)
-        + ((!is_straight_flush & !is_four_of_a_kind & !is_full_house & is_flush) as u64 * FLUSH_RANK)
-        + ((!is_straight_flush & !is_four_of_a_kind & !is_full_house & !is_flush & is_straight) as u64 * STRAIGHT_RANK)
-        + ((!is_straight & !is_flush & is_three_of_a_kind) as u64 * THREE_OF_A_KIND_RANK)
-        + ((!is_straight & !is_flush & !is_three_of_a_kind & is_two_pair) as u64 * TWO_PAIR_RANK)
-        + ((!is_straight & !is_flush & !is_three_of_a_kind & !is_two_pair & is_one_pair) as u64 * ONE_PAIR_RANK)
-        + ((!is_straight & !is_flush & !is_one_pair & !is_two_pair & !is_three_of_a_kind & !is_full_house & !is_four_of_a_kind) as u64 * HIGH_CARD_RANK);
-
-
-    // 6. Determine the kickers in the correct order.
-    // We sort ranks first by their frequency (count), then by their value.
-    // This universally orders kickers correctly for any hand type.
-    // For example, in a full house KKKQQ, K (count 3) comes before Q (count 2).
-    // In two pair AAKKQ, A (count 2) comes before K (count 2) because it's a higher rank.
-    let mut packed_ranks = [0u16; 13];
-    for i in 0..13 {
-        // Pack count and rank into a u16 for sorting: (count << 8) | rank
-        packed_ranks[i] = ((rank_counts[i] as u16) << 8) | (i as u16);
-    }
-    packed_ranks.sort();
-    packed_ranks.reverse();
-
-    let mut ordered_kickers = [0u8; 5];
-    let mut kicker_idx = 0;
-    for i in 0..13 {
-        let count = (packed_ranks[i] >> 8) as u8;
-        let rank = (packed_ranks[i] & 0xFF) as u8;
-        // This loop is data-independent because its iteration count is fixed.
-        for _ in 0..count {
-            if kicker_idx < 5 {
-                ordered_kickers[kicker_idx] = rank;
-                kicker_idx += 1;
-            }
+/// The category of a poker hand, in increasing order of strength. The discriminants match the
+/// hand rank stored in the top bits of an `evaluate_hand` score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum HandCategory {
+    HighCard = HIGH_CARD_RANK as u8,
+    OnePair = ONE_PAIR_RANK as u8,
+    TwoPair = TWO_PAIR_RANK as u8,
+    ThreeOfAKind = THREE_OF_A_KIND_RANK as u8,
+    Straight = STRAIGHT_RANK as u8,
+    Flush = FLUSH_RANK as u8,
+    FullHouse = FULL_HOUSE_RANK as u8,
+    FourOfAKind = FOUR_OF_A_KIND_RANK as u8,
+    StraightFlush = STRAIGHT_FLUSH_RANK as u8,
+}
+
+impl HandCategory {
+    /// Returns the category's name for display, e.g. "Full house".
+    pub fn name(self) -> &'static str {
+        match self {
+            HandCategory::HighCard => "High card",
+            HandCategory::OnePair => "One pair",
+            HandCategory::TwoPair => "Two pair",
+            HandCategory::ThreeOfAKind => "Three of a kind",
+            HandCategory::Straight => "Straight",
+            HandCategory::Flush => "Flush",
+            HandCategory::FullHouse => "Full house",
+            HandCategory::FourOfAKind => "Four of a kind",
+            HandCategory::StraightFlush => "Straight flush",
         }
     }
-    
-    // Special case for the wheel straight (A-5-4-3-2), the '5' is the high card for rank, not the Ace.
-    let wheel_kicker_override = [RANK_FIVE, RANK_FOUR, RANK_THREE, RANK_TWO, RANK_ACE];
-    for i in 0..5 {
-        // This is a multiplexer: `(cond * val_if_true) + (!cond * val_if_false)`
-        ordered_kickers[i] = (is_wheel as u8 * wheel_kicker_override[i]) + ((!is_wheel) as u8 * ordered_kickers[i]);
-    }
+}
 
-    // 7. Assemble the final score by bit-shifting the rank and kickers together.
-    // Hand Rank (4 bits) | Kicker 1 (4 bits) | Kicker 2 (4 bits) | ... | Kicker 5 (4 bits)
-    let mut score = hand_rank << 20;
-    score |= (ordered_kickers[0] as u64) << 16;
-    score |= (ordered_kickers[1] as u64) << 12;
-    score |= (ordered_kickers[2] as u64) << 8;
-    score |= (ordered_kickers[3] as u64) << 4;
-    score |= (ordered_kickers[4] as u64) << 0;
+/// Decodes the category of a hand from its `evaluate_hand` score.
+///
+/// # Arguments
+/// * `score` - A score produced by `evaluate_hand` or `find_best_hand_from_seven`.
+///
+/// # Returns
+/// The `HandCategory` of the scored hand, or `None` if the score's top bits are above
+/// `STRAIGHT_FLUSH_RANK` and so could not have come from the evaluator.
+pub fn hand_category(score: u64) -> Option<HandCategory> {
+    match score >> 20 {
+        HIGH_CARD_RANK => Some(HandCategory::HighCard),
+        ONE_PAIR_RANK => Some(HandCategory::OnePair),
+        TWO_PAIR_RANK => Some(HandCategory::TwoPair),
+        THREE_OF_A_KIND_RANK => Some(HandCategory::ThreeOfAKind),
+        STRAIGHT_RANK => Some(HandCategory::Straight),
+        FLUSH_RANK => Some(HandCategory::Flush),
+        FULL_HOUSE_RANK => Some(HandCategory::FullHouse),
+        FOUR_OF_A_KIND_RANK => Some(HandCategory::FourOfAKind),
+        STRAIGHT_FLUSH_RANK => Some(HandCategory::StraightFlush),
+        _ => None,
+    }
+}
 
-    score
+/// Unpacks the five kicker fields of an `evaluate_hand` score.
+///
+/// # Arguments
+/// * `score` - A score produced by `evaluate_hand` or `find_best_hand_from_seven`.
+///
+/// # Returns
+/// The hand's card ranks (0=Two, ..., 12=Ace) in the order they break ties: each rank as many
+/// times as it appears, most frequent first and higher first among equals. For the wheel
+/// straight the Ace is last.
+pub fn kickers(score: u64) -> [u8; 5] {
+    [
+        ((score >> 16) & 0xF) as u8,
+        ((score >> 12) & 0xF) as u8,
+        ((score >> 8) & 0xF) as u8,
+        ((score >> 4) & 0xF) as u8,
+        (score & 0xF) as u8,
+    ]
 }
 
-/// Finds the highest possible score from a 7-card hand by evaluating all 21
-/// possible 5-card combinations.
+/// Evaluates a showdown between any number of players, as the `determine_winner` circuit does.
 ///
 /// # Arguments
-/// * `seven_cards` - A fixed-size array of 7 `u8` values representing the cards.
+/// * `hole_cards` - Each player's two hole cards, in seat order (at most six players).
+/// * `board` - The five community cards.
 ///
 /// # Returns
-/// The `u64` score of the best 5-card hand.
-pub fn find_best_hand_from_seven(seven_cards: [u8; 7]) -> u64 {
-    // All 21 combinations of 5-card hands from 7 cards, represented by indices.
-    const COMBINATIONS: [[usize; 5]; 21] = [
-        [0,1,2,3,4], [0,1,2,3,5], [0,1,2,3,6], [0,1,2,4,5], [0,1,2,4,6],
-        [0,1,2,5,6], [0,1,3,4,5], [0,1,3,4,6], [0,1,3,5,6], [0,1,4,5,6],
-        [0,2,3,4,5], [0,2,3,4,6], [0,2,3,5,6], [0,2,4,5,6], [0,3,4,5,6],
-        [1,2,3,4,5], [1,2,3,4,6], [1,2,3,5,6], [1,2,4,5,6], [1,3,4,5,6],
-        [2,3,4,5,6]
-    ];
-
-    let mut max_score = 0u64;
-
-    // Iterate through all combinations, evaluate each 5-card hand, and keep track of the max score.
-    // This loop is data-independent as it always runs 21 times.
-    for combo in COMBINATIONS {
-        let mut current_hand = [0u8; 5];
-        current_hand[0] = seven_cards[combo[0]];
-        current_hand[1] = seven_cards[combo[1]];
-        current_hand[2] = seven_cards[combo[2]];
-        current_hand[3] = seven_cards[combo[3]];
-        current_hand[4] = seven_cards[combo[4]];
-        
-        let score = evaluate_hand(current_hand);
-        
-        // Data-independent update of max_score using an arithmetic multiplexer.
-        // This is equivalent to `if score > max_score { max_score = score; }`
-        // but avoids data-dependent branching.
-        let is_greater = score > max_score;
-        max_score = (is_greater as u64 * score) + ((!is_greater) as u64 * max_score);
+/// `(winner_mask, winning_rank)`: bit `p` of the mask is set for each player `p` holding the
+/// best hand, and the rank is that hand's category (`HIGH_CARD_RANK` to `STRAIGHT_FLUSH_RANK`).
+pub fn showdown_winners(hole_cards: &[[u8; 2]], board: [u8; 5]) -> (u8, u8) {
+    let scores: Vec<u64> = hole_cards
+        .iter()
+        .map(|hole| {
+            find_best_hand_from_seven([
+                hole[0], hole[1], board[0], board[1], board[2], board[3], board[4],
+            ])
+        })
+        .collect();
+    let best_score = scores.iter().copied().max().unwrap_or(0);
+
+    let winner_mask = scores
+        .iter()
+        .enumerate()
+        .filter(|&(_, &score)| score == best_score)
+        .fold(0u8, |mask, (p, _)| mask | (1 << p));
+    (winner_mask, (best_score >> 20) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLUBS: u8 = 0;
+    const DIAMONDS: u8 = 1;
+    const HEARTS: u8 = 2;
+    const SPADES: u8 = 3;
+
+    /// Builds a card from a rank (0=Two, ..., 12=Ace) and a suit.
+    fn card(rank: u8, suit: u8) -> u8 {
+        rank * 4 + suit
+    }
+
+    #[test]
+    fn straight_flush_beats_four_of_a_kind() {
+        // 9-T-J-Q-K of hearts against four Aces.
+        let straight_flush = [
+            card(7, HEARTS),
+            card(8, HEARTS),
+            card(9, HEARTS),
+            card(10, HEARTS),
+            card(11, HEARTS),
+        ];
+        let quads = [
+            card(12, CLUBS),
+            card(12, DIAMONDS),
+            card(12, HEARTS),
+            card(12, SPADES),
+            card(11, CLUBS),
+        ];
+        assert!(evaluate_hand(straight_flush) > evaluate_hand(quads));
+    }
+
+    #[test]
+    fn flush_beats_straight() {
+        // 2-4-6-8-T of clubs against an Ace-high straight.
+        let flush = [
+            card(0, CLUBS),
+            card(2, CLUBS),
+            card(4, CLUBS),
+            card(6, CLUBS),
+            card(8, CLUBS),
+        ];
+        let straight = [
+            card(8, HEARTS),
+            card(9, SPADES),
+            card(10, DIAMONDS),
+            card(11, CLUBS),
+            card(12, HEARTS),
+        ];
+        assert!(evaluate_hand(flush) > evaluate_hand(straight));
+    }
+
+    #[test]
+    fn wheel_loses_to_six_high_straight() {
+        let wheel = [
+            card(12, SPADES),
+            card(0, HEARTS),
+            card(1, CLUBS),
+            card(2, DIAMONDS),
+            card(3, HEARTS),
+        ];
+        let six_high = [
+            card(0, SPADES),
+            card(1, HEARTS),
+            card(2, CLUBS),
+            card(3, DIAMONDS),
+            card(4, HEARTS),
+        ];
+        let wheel_score = evaluate_hand(wheel);
+        assert_eq!(hand_category(wheel_score), Some(HandCategory::Straight));
+        assert!(wheel_score < evaluate_hand(six_high));
+    }
+
+    #[test]
+    fn higher_second_pair_wins_two_pair() {
+        // A-A-K-K-Q against A-A-Q-Q-K.
+        let aces_and_kings = [
+            card(12, CLUBS),
+            card(12, HEARTS),
+            card(11, DIAMONDS),
+            card(11, SPADES),
+            card(10, CLUBS),
+        ];
+        let aces_and_queens = [
+            card(12, DIAMONDS),
+            card(12, SPADES),
+            card(10, HEARTS),
+            card(10, SPADES),
+            card(11, CLUBS),
+        ];
+        assert!(evaluate_hand(aces_and_kings) > evaluate_hand(aces_and_queens));
+    }
+
+    #[test]
+    fn identical_hands_score_equal() {
+        // The same ranks in different suits and orders are the same hand.
+        let first = [
+            card(12, CLUBS),
+            card(9, HEARTS),
+            card(7, DIAMONDS),
+            card(4, SPADES),
+            card(1, CLUBS),
+        ];
+        let second = [
+            card(1, HEARTS),
+            card(4, CLUBS),
+            card(12, SPADES),
+            card(7, HEARTS),
+            card(9, DIAMONDS),
+        ];
+        assert_eq!(evaluate_hand(first), evaluate_hand(second));
+    }
+
+    #[test]
+    fn hand_category_round_trips_every_category() {
+        let hands = [
+            (
+                [
+                    card(12, CLUBS),
+                    card(10, HEARTS),
+                    card(7, DIAMONDS),
+                    card(4, SPADES),
+                    card(1, CLUBS),
+                ],
+                HandCategory::HighCard,
+            ),
+            (
+                [
+                    card(5, CLUBS),
+                    card(5, HEARTS),
+                    card(7, DIAMONDS),
+                    card(4, SPADES),
+                    card(1, CLUBS),
+                ],
+                HandCategory::OnePair,
+            ),
+            (
+                [
+                    card(5, CLUBS),
+                    card(5, HEARTS),
+                    card(7, DIAMONDS),
+                    card(7, SPADES),
+                    card(1, CLUBS),
+                ],
+                HandCategory::TwoPair,
+            ),
+            (
+                [
+                    card(5, CLUBS),
+                    card(5, HEARTS),
+                    card(5, DIAMONDS),
+                    card(7, SPADES),
+                    card(1, CLUBS),
+                ],
+                HandCategory::ThreeOfAKind,
+            ),
+            (
+                [
+                    card(3, CLUBS),
+                    card(4, HEARTS),
+                    card(5, DIAMONDS),
+                    card(6, SPADES),
+                    card(7, CLUBS),
+                ],
+                HandCategory::Straight,
+            ),
+            (
+                [
+                    card(0, SPADES),
+                    card(3, SPADES),
+                    card(5, SPADES),
+                    card(9, SPADES),
+                    card(11, SPADES),
+                ],
+                HandCategory::Flush,
+            ),
+            (
+                [
+                    card(5, CLUBS),
+                    card(5, HEARTS),
+                    card(5, DIAMONDS),
+                    card(7, SPADES),
+                    card(7, CLUBS),
+                ],
+                HandCategory::FullHouse,
+            ),
+            (
+                [
+                    card(5, CLUBS),
+                    card(5, HEARTS),
+                    card(5, DIAMONDS),
+                    card(5, SPADES),
+                    card(7, CLUBS),
+                ],
+                HandCategory::FourOfAKind,
+            ),
+            (
+                [
+                    card(3, HEARTS),
+                    card(4, HEARTS),
+                    card(5, HEARTS),
+                    card(6, HEARTS),
+                    card(7, HEARTS),
+                ],
+                HandCategory::StraightFlush,
+            ),
+        ];
+        for (hand, category) in hands {
+            let score = evaluate_hand(hand);
+            assert_eq!(hand_category(score), Some(category));
+            assert_eq!((score >> 20) as u8, category as u8);
+        }
     }
 
-    max_score
-}
\ No newline at end of file
+    #[test]
+    fn hand_category_rejects_out_of_range_scores() {
+        assert_eq!(hand_category((STRAIGHT_FLUSH_RANK + 1) << 20), None);
+        assert_eq!(hand_category(u64::MAX), None);
+    }
+
+    #[test]
+    fn kickers_round_trip_tie_break_order() {
+        // Full house, Sevens over Twos: the trips come before the pair.
+        let full_house = [
+            card(0, CLUBS),
+            card(5, HEARTS),
+            card(0, DIAMONDS),
+            card(5, SPADES),
+            card(5, CLUBS),
+        ];
+        assert_eq!(kickers(evaluate_hand(full_house)), [5, 5, 5, 0, 0]);
+
+        // Two pair, Kings and Fours with a Nine: pairs high to low, then the kicker.
+        let two_pair = [
+            card(2, CLUBS),
+            card(7, HEARTS),
+            card(11, DIAMONDS),
+            card(2, SPADES),
+            card(11, CLUBS),
+        ];
+        assert_eq!(kickers(evaluate_hand(two_pair)), [11, 11, 2, 2, 7]);
+
+        // The wheel plays the Ace low, so it comes last.
+        let wheel = [
+            card(12, SPADES),
+            card(0, HEARTS),
+            card(1, CLUBS),
+            card(2, DIAMONDS),
+            card(3, HEARTS),
+        ];
+        assert_eq!(kickers(evaluate_hand(wheel)), [3, 2, 1, 0, 12]);
+
+        // Packing the decoded kickers back under the category reproduces the score.
+        for hand in [full_house, two_pair, wheel] {
+            let score = evaluate_hand(hand);
+            let repacked = kickers(score)
+                .iter()
+                .fold(score >> 20, |packed, &rank| (packed << 4) | rank as u64);
+            assert_eq!(repacked, score);
+        }
+    }
+
+    /// The rest of the `circuits` module's plain-Rust code, copied out of `lib.rs` by `build.rs`.
+    /// It keeps the circuit's Arcis-compatible form, so lints that form trips are allowed here.
+    #[allow(dead_code, clippy::all)]
+    mod circuit {
+        include!(concat!(env!("OUT_DIR"), "/circuit_plaintext.rs"));
+
+        use crate::deterministic_deal::{deal_from_seed, shuffled_deck};
+        use crate::hand_eval::{hand_category, kickers, showdown_winners, HandCategory};
+
+        /// Hands of every category that random deals rarely produce, with their expected
+        /// category and kickers: every straight and straight flush (including the wheel), and
+        /// every four of a kind and full house.
+        fn made_hands() -> Vec<([u8; 5], HandCategory, [u8; 5])> {
+            let card = |rank: u8, suit: u8| rank * 4 + suit;
+            let mut hands = Vec::new();
+            for high in 3..13u8 {
+                let ranks = std::array::from_fn::<u8, 5, _>(|i| (high + 13 - i as u8) % 13);
+                hands.push((ranks.map(|rank| card(rank, 0)), HandCategory::StraightFlush, ranks));
+                hands.push((
+                    std::array::from_fn(|i| card(ranks[i], i as u8 % 4)),
+                    HandCategory::Straight,
+                    ranks,
+                ));
+            }
+            for big in 0..13u8 {
+                for small in (0..13u8).filter(|&small| small != big) {
+                    hands.push((
+                        [
+                            card(big, 0),
+                            card(big, 1),
+                            card(big, 2),
+                            card(big, 3),
+                            card(small, 0),
+                        ],
+                        HandCategory::FourOfAKind,
+                        [big, big, big, big, small],
+                    ));
+                    hands.push((
+                        [
+                            card(big, 0),
+                            card(big, 1),
+                            card(big, 2),
+                            card(small, 0),
+                            card(small, 1),
+                        ],
+                        HandCategory::FullHouse,
+                        [big, big, big, small, small],
+                    ));
+                }
+            }
+            hands
+        }
+
+        #[test]
+        fn made_hands_score_their_category_and_kickers() {
+            for (hand, category, expected_kickers) in made_hands() {
+                let score = evaluate_hand(hand);
+                assert_eq!(hand_category(score), Some(category), "{hand:?}");
+                assert_eq!(kickers(score), expected_kickers, "{hand:?}");
+            }
+        }
+
+        #[test]
+        fn seven_card_evaluation_keeps_the_best_of_every_five() {
+            for seed in 0..2000u64 {
+                let deck = shuffled_deck(seed);
+                let seven = std::array::from_fn::<u8, 7, _>(|i| deck[i]);
+                // Every choice of five of the seven cards, independently of the circuit's table.
+                let expected = (0u8..128)
+                    .filter(|mask| mask.count_ones() == 5)
+                    .map(|mask| {
+                        let mut cards = (0..7).filter(|i| mask & (1 << i) != 0).map(|i| seven[i]);
+                        evaluate_hand(std::array::from_fn(|_| cards.next().unwrap()))
+                    })
+                    .max()
+                    .unwrap();
+
+                assert_eq!(find_best_hand_from_seven(seven), expected, "{seven:?}");
+                let (score, indices) = find_best_hand_cards_from_seven(seven);
+                assert_eq!(score, expected, "{seven:?}");
+                assert_eq!(
+                    evaluate_hand(indices.map(|i| seven[i as usize])),
+                    expected,
+                    "{seven:?}"
+                );
+            }
+        }
+
+        #[test]
+        fn compare_hands_matches_reference() {
+            for seed in 0..2000u64 {
+                let deal = deal_from_seed(seed);
+                let result = compare_hands(deal.hole_cards[0], deal.hole_cards[1], deal.board);
+                assert_eq!(result, deal.winner(), "seed {seed}");
+            }
+        }
+
+        #[test]
+        fn showdown_reveals_winner_rank_and_cards() {
+            // Seed 0: player 1's Kings and Fives with a Jack beat player 2's Fives and Fours.
+            // Seven cards: K J | 5 8 K 4 5, so the best hand is indices 0, 1, 2, 4 and 6.
+            let deal = deal_from_seed(0);
+            let (mask, rank, cards) = showdown(deal.hole_cards, deal.board);
+            assert_eq!(mask, 0b01);
+            assert_eq!(rank, TWO_PAIR_RANK as u8);
+            assert_eq!(cards, [[0, 1, 2, 4, 6], [255; 5]]);
+
+            // Seed 42: a pair of Eights each; player 2's Jack outkicks player 1's Nine.
+            // Seven cards: 5 J | Q 2 8 8 7, so the best hand is indices 1, 2, 4, 5 and 6.
+            let deal = deal_from_seed(42);
+            let (mask, rank, cards) = showdown(deal.hole_cards, deal.board);
+            assert_eq!(mask, 0b10);
+            assert_eq!(rank, ONE_PAIR_RANK as u8);
+            assert_eq!(cards, [[255; 5], [1, 2, 4, 5, 6]]);
+        }
+
+        #[test]
+        fn showdown_reveals_both_hands_on_a_split() {
+            // Seed 1: both players make Sevens and Sixes with the board's King.
+            // Seven cards: 6 x | 7 Q 7 6 K, so both use indices 0, 2, 4, 5 and 6.
+            let deal = deal_from_seed(1);
+            let (mask, rank, cards) = showdown(deal.hole_cards, deal.board);
+            assert_eq!(mask, 0b11);
+            assert_eq!(rank, TWO_PAIR_RANK as u8);
+            assert_eq!(cards, [[0, 2, 4, 5, 6], [0, 2, 4, 5, 6]]);
+        }
+
+        #[test]
+        fn showdown_reveals_flush_over_straight() {
+            let card = |rank: u8, suit: u8| rank * 4 + suit;
+            // Board 7h 8h 9c Th 2s. Player 1's Ah 3h make a flush with the board's hearts;
+            // player 2's Jc 6d make a Jack-high straight and must stay hidden.
+            let board = [card(5, 2), card(6, 2), card(7, 0), card(8, 2), card(0, 3)];
+            let hole_cards = [[card(12, 2), card(1, 2)], [card(9, 0), card(4, 1)]];
+            let (mask, rank, cards) = showdown(hole_cards, board);
+            assert_eq!(mask, 0b01);
+            assert_eq!(rank, FLUSH_RANK as u8);
+            assert_eq!(cards, [[0, 1, 2, 3, 5], [255; 5]]);
+        }
+
+        #[test]
+        fn showdown_matches_reference_winners() {
+            for seed in 0..2000u64 {
+                let deal = deal_from_seed(seed);
+                let (mask, rank, cards) = showdown(deal.hole_cards, deal.board);
+                assert_eq!(
+                    (mask, rank),
+                    showdown_winners(&deal.hole_cards, deal.board),
+                    "seed {seed}"
+                );
+                for p in 0..DEAL_PLAYERS {
+                    let is_winner = mask & PLAYER_BITS[p] != 0;
+                    assert_eq!(cards[p] == [255; 5], !is_winner, "seed {seed}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+// This module contains the confidential logic for pricing river insurance during an all-in runout.
+// Only the leading player and their opponent's number of outs are revealed; the hole cards stay encrypted.
+//
+// Note: The actual implementation is in the circuits module in lib.rs due to Arcis restrictions.
\ No newline at end of file
@@ -1,17 +1,32 @@
 use arcis_imports::*;
 
 pub mod determine_winner;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod deterministic_deal;
+pub mod five_card_draw;
 pub mod hand_eval;
+pub mod insurance_odds;
 pub mod reveal_community_cards;
+pub mod reveal_hole_cards;
 pub mod shuffle_and_deal;
 
 #[encrypted]
 mod circuits {
     use arcis_imports::*;
 
+    // BEGIN PLAINTEXT
+    /// The number of players `shuffle_and_deal` deals to. The program seats two players, so
+    /// the circuit is compiled for two; each extra player takes two cards from the board deck.
+    pub const DEAL_PLAYERS: usize = 2;
+    /// The cards left for the board once every player has their hole cards.
+    pub const BOARD_DECK_SIZE: usize = 52 - 2 * DEAL_PLAYERS;
+    /// The bit for each player in `determine_winner`'s winner mask, for up to six players.
+    const PLAYER_BITS: [u8; 6] = [1, 2, 4, 8, 16, 32];
+    // END PLAINTEXT
+
     #[derive(Clone, Copy)]
     pub struct Deck {
-        pub cards: [u8; 48],
+        pub cards: [u8; BOARD_DECK_SIZE],
         pub dealt_community_cards: u8,
     }
 
@@ -30,11 +45,29 @@ mod circuits {
         pub revealed_cards: [u8; 3],
     }
 
+    /// A player's five cards in the five-card draw variant.
+    #[derive(Clone, Copy)]
+    pub struct DrawHand {
+        pub cards: [u8; 5],
+    }
+
+    /// The undealt cards in the five-card draw variant, and how many have been drawn.
+    #[derive(Clone, Copy)]
+    pub struct DrawDeck {
+        pub cards: [u8; 42],
+        pub drawn_cards: u8,
+    }
+
+    /// Shuffles the deck and deals two hole cards to each of the `DEAL_PLAYERS` players, in
+    /// seat order: player `p` gets cards `2p` and `2p + 1`. The remaining `BOARD_DECK_SIZE` cards
+    /// are kept by the MXE for the board. Each player's cards are encrypted to their own key.
+    ///
+    /// Also returns, in plaintext, whether the shuffled deck passed the integrity check: every
+    /// card from 0 to 51 present exactly once. The callback voids the hand if it did not.
     #[instruction]
     pub fn shuffle_and_deal(
-        player1_pubkey: ArcisPublicKey,
-        player2_pubkey: ArcisPublicKey,
-    ) -> (Enc<Shared, PlayerEncryptedData>, Enc<Shared, PlayerEncryptedData>, Enc<Mxe, Deck>) {
+        player_pubkeys: [ArcisPublicKey; DEAL_PLAYERS],
+    ) -> ([Enc<Shared, PlayerEncryptedData>; DEAL_PLAYERS], Enc<Mxe, Deck>, bool) {
         let mut deck: [u8; 52] = [0; 52];
         for i in 0..52 {
             deck[i] = i as u8;
@@ -42,12 +75,26 @@ mod circuits {
 
         ArcisRNG::shuffle(&mut deck);
 
-        let p1_cards = [deck[0], deck[1]];
-        let p2_cards = [deck[2], deck[3]];
+        let mut deck_valid = true;
+        for card in 0..52 {
+            let mut count = 0u8;
+            for i in 0..52 {
+                if deck[i] == card as u8 {
+                    count += 1;
+                }
+            }
+            deck_valid = deck_valid & (count == 1);
+        }
 
-        let mut board_deck_cards = [0u8; 48];
-        for i in 0..48 {
-            board_deck_cards[i] = deck[i + 4];
+        // The positions dealt are fixed, so the deal does not depend on the cards.
+        let mut player_data = [PlayerEncryptedData { hole_cards: [0u8; 2] }; DEAL_PLAYERS];
+        for p in 0..DEAL_PLAYERS {
+            player_data[p].hole_cards = [deck[2 * p], deck[2 * p + 1]];
+        }
+
+        let mut board_deck_cards = [0u8; BOARD_DECK_SIZE];
+        for i in 0..BOARD_DECK_SIZE {
+            board_deck_cards[i] = deck[i + 2 * DEAL_PLAYERS];
         }
 
         let board_deck = Deck {
@@ -55,31 +102,27 @@ mod circuits {
             dealt_community_cards: 0,
         };
 
-        // Create consolidated data structures
-        let p1_data = PlayerEncryptedData {
-            hole_cards: p1_cards,
-        };
-        let p2_data = PlayerEncryptedData {
-            hole_cards: p2_cards,
-        };
-
         // Single call to from_arcis per owner (optimized)
-        let player1_owner = Shared::new(player1_pubkey);
-        let player2_owner = Shared::new(player2_pubkey);
+        let mut enc_player_data = [Shared::new(player_pubkeys[0]).from_arcis(player_data[0]); DEAL_PLAYERS];
+        for p in 1..DEAL_PLAYERS {
+            enc_player_data[p] = Shared::new(player_pubkeys[p]).from_arcis(player_data[p]);
+        }
         let mxe_owner = Mxe::get();
-
-        let enc_p1_data = player1_owner.from_arcis(p1_data);
-        let enc_p2_data = player2_owner.from_arcis(p2_data);
         let enc_board_deck = mxe_owner.from_arcis(board_deck);
 
-        (enc_p1_data, enc_p2_data, enc_board_deck)
+        (enc_player_data, enc_board_deck, deck_valid.reveal())
     }
 
+    /// `phase` selects the street: 0 (flop), 1 (turn) or 2 (river). The program validates it
+    /// before queueing; any other value reveals nothing and leaves the deck unchanged.
+    ///
+    /// The board is public, so the street's cards are revealed in plaintext: all three for the
+    /// flop, or the first one for the turn and river, with unused slots set to `255`.
     #[instruction]
     pub fn reveal_community_cards(
         deck_ctxt: Enc<Mxe, Deck>,
         phase: u8,
-    ) -> (Enc<Mxe, Deck>, Enc<Mxe, [u8; 3]>) {
+    ) -> (Enc<Mxe, Deck>, [u8; 3]) {
         let mut deck = deck_ctxt.to_arcis();
 
         let is_flop = phase == 0;
@@ -104,230 +147,289 @@ mod circuits {
             deck.dealt_community_cards += 1;
         }
 
-        // Single call to from_arcis per owner (optimized)
-        let mxe_owner1 = Mxe::get();
-        let mxe_owner2 = Mxe::get();
-        let enc_deck = mxe_owner1.from_arcis(deck);
-        let enc_revealed_cards = mxe_owner2.from_arcis(revealed_cards);
+        let mxe_owner = Mxe::get();
+        let enc_deck = mxe_owner.from_arcis(deck);
 
-        (enc_deck, enc_revealed_cards)
+        (enc_deck, revealed_cards.reveal())
     }
 
-    /// Determines the winner of a poker hand at showdown.
+    /// Reveals every community card still missing from the board at once, for an all-in runout.
+    /// `dealt_so_far` is the number of board cards already public (0, 3 or 4); those positions
+    /// are returned as `255`. The board is dealt from the front of the deck in order, so board
+    /// card `i` is always `deck.cards[i]`.
+    #[instruction]
+    pub fn reveal_all_community_cards(
+        deck_ctxt: Enc<Mxe, Deck>,
+        dealt_so_far: u8,
+    ) -> (Enc<Mxe, Deck>, [u8; 5]) {
+        let mut deck = deck_ctxt.to_arcis();
+
+        let mut revealed_cards = [255u8; 5];
+        for i in 0..5 {
+            let reveal = (i as u8) >= dealt_so_far;
+            revealed_cards[i] = (reveal as u8 * deck.cards[i]) + ((!reveal) as u8 * 255);
+        }
+        deck.dealt_community_cards = 5;
+
+        let mxe_owner = Mxe::get();
+        let enc_deck = mxe_owner.from_arcis(deck);
+
+        (enc_deck, revealed_cards.reveal())
+    }
+
+    /// Reveals a folded player's chosen hole cards.
     ///
-    /// This instruction takes the encrypted hole cards for two players and the public community
-    /// cards, confidentially evaluates each player's best 5-card hand, and returns the index
-    /// of the winning player without revealing the losing hand.
+    /// A player who has folded may opt to show one or both of their hole cards. Only the
+    /// selected cards are revealed; any card that is not shown is returned as `255`.
     ///
     /// # Arguments
-    /// * `p1_cards_ctxt` - Player 1's two hole cards, encrypted with a shared key.
-    /// * `p2_cards_ctxt` - Player 2's two hole cards, encrypted with a shared key.
-    /// * `board` - The five public community cards (unencrypted).
-    ///
-    /// # Returns
-    /// A `u8` indicating the winner:
-    /// - `0`: Player 1 wins.
-    /// - `1`: Player 2 wins.
-    /// - `2`: It's a tie (split pot).
+    /// * `cards_ctxt` - The folded player's two hole cards, encrypted with a shared key.
+    /// * `show_mask` - Which cards to show: `1` for the first, `2` for the second, `3` for both.
     #[instruction]
-    pub fn determine_winner(
-        p1_cards_ctxt: Enc<Shared, PlayerEncryptedData>,
-        p2_cards_ctxt: Enc<Shared, PlayerEncryptedData>,
-        board: [u8; 5],
-    ) -> u8 {
-        // Define the hand evaluation functions directly here since we can't import them
-        // due to Arcis restrictions
-        
-        // --- Constants for Hand Ranks ---
-        const HIGH_CARD_RANK: u64 = 0;
-        const ONE_PAIR_RANK: u64 = 1;
-        const TWO_PAIR_RANK: u64 = 2;
-        const THREE_OF_A_KIND_RANK: u64 = 3;
-        const STRAIGHT_RANK: u64 = 4;
-        const FLUSH_RANK: u64 = 5;
-        const FULL_HOUSE_RANK: u64 = 6;
-        const FOUR_OF_A_KIND_RANK: u64 = 7;
-        const STRAIGHT_FLUSH_RANK: u64 = 8;
-
-        // --- Constants for Card Ranks ---
-        const RANK_ACE: u8 = 12;
-        const RANK_FIVE: u8 = 3;
-        const RANK_FOUR: u8 = 2;
-        const RANK_THREE: u8 = 1;
-        const RANK_TWO: u8 = 0;
-
-        // The main evaluation function for a 5-card hand
-        fn evaluate_hand(hand: [u8; 5]) -> u64 {
-            // 1. Prepare card data: extract and sort ranks, get suits.
-            let mut ranks = [0u8; 5];
-            let mut suits = [0u8; 5];
-            for i in 0..5 {
-                ranks[i] = hand[i] / 4;
-                suits[i] = hand[i] % 4;
-            }
-            // Sorting ranks in descending order simplifies many downstream calculations.
-            // Arcis provides a data-independent sort for integer arrays.
-            ranks.sort();
-            ranks.reverse();
-
-            // 2. Create a frequency map (histogram) of ranks.
-            let mut rank_counts = [0u8; 13];
-            for &rank in ranks.iter() {
-                rank_counts[rank as usize] += 1;
-            }
+    pub fn reveal_hole_cards(
+        cards_ctxt: Enc<Shared, PlayerEncryptedData>,
+        show_mask: u8,
+    ) -> [u8; 2] {
+        let data = cards_ctxt.to_arcis();
+
+        let show_first = (show_mask == 1) | (show_mask == 3);
+        let show_second = show_mask >= 2;
+
+        // Arithmetic multiplexers keep the selection data-independent.
+        let shown = [
+            (show_first as u8 * data.hole_cards[0]) + ((!show_first) as u8 * 255),
+            (show_second as u8 * data.hole_cards[1]) + ((!show_second) as u8 * 255),
+        ];
 
-            // 3. Detect hand features (flush, straight) in a data-independent way.
-            let is_flush = (suits[0] == suits[1])
-                & (suits[0] == suits[2])
-                & (suits[0] == suits[3])
-                & (suits[0] == suits[4]);
-
-            let is_straight_gapped = (ranks[0] - ranks[4] == 4) & (ranks[0] != ranks[1]) & (ranks[1] != ranks[2]) & (ranks[2] != ranks[3]) & (ranks[3] != ranks[4]);
-
-            // Handle the A-2-3-4-5 "wheel" straight.
-            let is_wheel = (ranks[0] == RANK_ACE)
-                & (ranks[1] == RANK_FIVE)
-                & (ranks[2] == RANK_FOUR)
-                & (ranks[3] == RANK_THREE)
-                & (ranks[4] == RANK_TWO);
-
-            let is_straight = is_straight_gapped | is_wheel;
-            let is_straight_flush = is_straight & is_flush;
-
-            // 4. Analyze rank counts to identify pairs, trips, etc.
-            let mut num_quads = 0;
-            let mut num_trips = 0;
-            let mut num_pairs = 0;
-            for &count in rank_counts.iter() {
-                num_quads += (count == 4) as u8;
-                num_trips += (count == 3) as u8;
-                num_pairs += (count == 2) as u8;
-            }
+        shown.reveal()
+    }
 
-            let is_four_of_a_kind = num_quads == 1;
-            let is_full_house = (num_trips == 1) & (num_pairs == 1);
-            let is_three_of_a_kind = (num_trips == 1) & (num_pairs == 0);
-            let is_two_pair = num_pairs == 2;
-            let is_one_pair = (num_pairs == 1) & (num_trips == 0);
-
-            // 5. Determine the final hand rank using mutually exclusive conditions.
-            // This chain of boolean logic ensures only the highest possible rank is selected.
-            let hand_rank = (is_straight_flush as u64 * STRAIGHT_FLUSH_RANK)
-                + ((!is_straight_flush & is_four_of_a_kind) as u64 * FOUR_OF_A_KIND_RANK)
-                + ((!is_straight_flush & !is_four_of_a_kind & is_full_house) as u64 * FULL_HOUSE_RANK)
-                + ((!is_straight_flush & !is_four_of_a_kind & !is_full_house & is_flush) as u64 * FLUSH_RANK)
-                + ((!is_straight_flush & !is_four_of_a_kind & !is_full_house & !is_flush & is_straight) as u64 * STRAIGHT_RANK)
-                + ((!is_straight & !is_flush & is_three_of_a_kind) as u64 * THREE_OF_A_KIND_RANK)
-                + ((!is_straight & !is_flush & !is_three_of_a_kind & is_two_pair) as u64 * TWO_PAIR_RANK)
-                + ((!is_straight & !is_flush & !is_three_of_a_kind & !is_two_pair & is_one_pair) as u64 * ONE_PAIR_RANK)
-                + ((!is_straight & !is_flush & !is_one_pair & !is_two_pair & !is_three_of_a_kind & !is_full_house & !is_four_of_a_kind) as u64 * HIGH_CARD_RANK);
-
-
-            // 6. Determine the kickers in the correct order.
-            // We sort ranks first by their frequency (count), then by their value.
-            // This universally orders kickers correctly for any hand type.
-            // For example, in a full house KKKQQ, K (count 3) comes before Q (count 2).
-            // In two pair AAKKQ, A (count 2) comes before K (count 2) because it's a higher rank.
-            let mut packed_ranks = [0u16; 13];
-            for i in 0..13 {
-                // Pack count and rank into a u16 for sorting: (count << 8) | rank
-                // Since bit shifting is not supported, we use multiplication:
-                packed_ranks[i] = ((rank_counts[i] as u16) * 256) + (i as u16);
-            }
-            packed_ranks.sort();
-            packed_ranks.reverse();
-
-            let mut ordered_kickers = [0u8; 5];
-            let mut kicker_idx = 0u8;
-            for i in 0..13 {
-                let count = (packed_ranks[i] / 256) as u8;
-                let rank = (packed_ranks[i] % 256) as u8;
-                
-                // Unroll the loop since count can vary between 0 and 5
-                // Use arithmetic multiplexers to conditionally add kickers
-                let should_add_0 = (count > 0) & (kicker_idx < 5);
-                ordered_kickers[kicker_idx as usize] = (should_add_0 as u8 * rank) + ((!should_add_0) as u8 * ordered_kickers[kicker_idx as usize]);
-                kicker_idx += should_add_0 as u8;
-                
-                let should_add_1 = (count > 1) & (kicker_idx < 5);
-                ordered_kickers[kicker_idx as usize] = (should_add_1 as u8 * rank) + ((!should_add_1) as u8 * ordered_kickers[kicker_idx as usize]);
-                kicker_idx += should_add_1 as u8;
-                
-                let should_add_2 = (count > 2) & (kicker_idx < 5);
-                ordered_kickers[kicker_idx as usize] = (should_add_2 as u8 * rank) + ((!should_add_2) as u8 * ordered_kickers[kicker_idx as usize]);
-                kicker_idx += should_add_2 as u8;
-                
-                let should_add_3 = (count > 3) & (kicker_idx < 5);
-                ordered_kickers[kicker_idx as usize] = (should_add_3 as u8 * rank) + ((!should_add_3) as u8 * ordered_kickers[kicker_idx as usize]);
-                kicker_idx += should_add_3 as u8;
-                
-                let should_add_4 = (count > 4) & (kicker_idx < 5);
-                ordered_kickers[kicker_idx as usize] = (should_add_4 as u8 * rank) + ((!should_add_4) as u8 * ordered_kickers[kicker_idx as usize]);
-                kicker_idx += should_add_4 as u8;
-            }
-            
-            // Special case for the wheel straight (A-5-4-3-2), the '5' is the high card for rank, not the Ace.
-            let wheel_kicker_override = [RANK_FIVE, RANK_FOUR, RANK_THREE, RANK_TWO, RANK_ACE];
-            for i in 0..5 {
-                // This is a multiplexer: `(cond * val_if_true) + (!cond * val_if_false)`
-                ordered_kickers[i] = (is_wheel as u8 * wheel_kicker_override[i]) + ((!is_wheel) as u8 * ordered_kickers[i]);
-            }
+    // --- Hand evaluation ---
+    // Shared by `determine_winner`, `preview_winner` and `determine_winner_draw`. These are
+    // defined here rather than in `hand_eval` because an `#[encrypted]` module cannot call code
+    // outside it. This is the only copy: `hand_eval` exposes `evaluate_hand` and
+    // `find_best_hand_from_seven` to ordinary code by including this source.
+    //
+    // Everything between the PLAINTEXT markers must stay plain Rust: `build.rs` copies it out for
+    // `hand_eval` and for the crate's tests.
+
+    // BEGIN PLAINTEXT
+    // --- Constants for Hand Ranks ---
+    pub const HIGH_CARD_RANK: u64 = 0;
+    pub const ONE_PAIR_RANK: u64 = 1;
+    pub const TWO_PAIR_RANK: u64 = 2;
+    pub const THREE_OF_A_KIND_RANK: u64 = 3;
+    pub const STRAIGHT_RANK: u64 = 4;
+    pub const FLUSH_RANK: u64 = 5;
+    pub const FULL_HOUSE_RANK: u64 = 6;
+    pub const FOUR_OF_A_KIND_RANK: u64 = 7;
+    pub const STRAIGHT_FLUSH_RANK: u64 = 8;
+
+    // --- Constants for Card Ranks ---
+    const RANK_ACE: u8 = 12;
+    const RANK_FIVE: u8 = 3;
+    const RANK_FOUR: u8 = 2;
+    const RANK_THREE: u8 = 1;
+    const RANK_TWO: u8 = 0;
+
+    // The main evaluation function for a 5-card hand
+    fn evaluate_hand(hand: [u8; 5]) -> u64 {
+        // 1. Prepare card data: extract and sort ranks, get suits.
+        let mut ranks = [0u8; 5];
+        let mut suits = [0u8; 5];
+        for i in 0..5 {
+            ranks[i] = hand[i] / 4;
+            suits[i] = hand[i] % 4;
+        }
+        // Sorting ranks in descending order simplifies many downstream calculations.
+        // Arcis provides a data-independent sort for integer arrays.
+        ranks.sort();
+        ranks.reverse();
+
+        // 2. Create a frequency map (histogram) of ranks.
+        let mut rank_counts = [0u8; 13];
+        for &rank in ranks.iter() {
+            rank_counts[rank as usize] += 1;
+        }
+
+        // 3. Detect hand features (flush, straight) in a data-independent way.
+        let is_flush = (suits[0] == suits[1])
+            & (suits[0] == suits[2])
+            & (suits[0] == suits[3])
+            & (suits[0] == suits[4]);
+
+        let is_straight_gapped = (ranks[0] - ranks[4] == 4) & (ranks[0] != ranks[1]) & (ranks[1] != ranks[2]) & (ranks[2] != ranks[3]) & (ranks[3] != ranks[4]);
+
+        // Handle the A-2-3-4-5 "wheel" straight.
+        let is_wheel = (ranks[0] == RANK_ACE)
+            & (ranks[1] == RANK_FIVE)
+            & (ranks[2] == RANK_FOUR)
+            & (ranks[3] == RANK_THREE)
+            & (ranks[4] == RANK_TWO);
+
+        let is_straight = is_straight_gapped | is_wheel;
+        let is_straight_flush = is_straight & is_flush;
+
+        // 4. Analyze rank counts to identify pairs, trips, etc.
+        let mut num_quads = 0;
+        let mut num_trips = 0;
+        let mut num_pairs = 0;
+        for &count in rank_counts.iter() {
+            num_quads += (count == 4) as u8;
+            num_trips += (count == 3) as u8;
+            num_pairs += (count == 2) as u8;
+        }
 
-            // 7. Assemble the final score by bit-shifting the rank and kickers together.
-            // Hand Rank (4 bits) | Kicker 1 (4 bits) | Kicker 2 (4 bits) | ... | Kicker 5 (4 bits)
+        let is_four_of_a_kind = num_quads == 1;
+        let is_full_house = (num_trips == 1) & (num_pairs == 1);
+        let is_three_of_a_kind = (num_trips == 1) & (num_pairs == 0);
+        let is_two_pair = num_pairs == 2;
+        let is_one_pair = (num_pairs == 1) & (num_trips == 0);
+
+        // 5. Determine the final hand rank using mutually exclusive conditions.
+        // This chain of boolean logic ensures only the highest possible rank is selected.
+        let hand_rank = (is_straight_flush as u64 * STRAIGHT_FLUSH_RANK)
+            + ((!is_straight_flush & is_four_of_a_kind) as u64 * FOUR_OF_A_KIND_RANK)
+            + ((!is_straight_flush & !is_four_of_a_kind & is_full_house) as u64 * FULL_HOUSE_RANK)
+            + ((!is_straight_flush & !is_four_of_a_kind & !is_full_house & is_flush) as u64 * FLUSH_RANK)
+            + ((!is_straight_flush & !is_four_of_a_kind & !is_full_house & !is_flush & is_straight) as u64 * STRAIGHT_RANK)
+            + ((!is_straight & !is_flush & is_three_of_a_kind) as u64 * THREE_OF_A_KIND_RANK)
+            + ((!is_straight & !is_flush & !is_three_of_a_kind & is_two_pair) as u64 * TWO_PAIR_RANK)
+            + ((!is_straight & !is_flush & !is_three_of_a_kind & !is_two_pair & is_one_pair) as u64 * ONE_PAIR_RANK)
+            + ((!is_straight & !is_flush & !is_one_pair & !is_two_pair & !is_three_of_a_kind & !is_full_house & !is_four_of_a_kind) as u64 * HIGH_CARD_RANK);
+
+
+        // 6. Determine the kickers in the correct order.
+        // We sort ranks first by their frequency (count), then by their value.
+        // This universally orders kickers correctly for any hand type.
+        // For example, in a full house KKKQQ, K (count 3) comes before Q (count 2).
+        // In two pair AAKKQ, A (count 2) comes before K (count 2) because it's a higher rank.
+        let mut packed_ranks = [0u16; 13];
+        for i in 0..13 {
+            // Pack count and rank into a u16 for sorting: (count << 8) | rank
             // Since bit shifting is not supported, we use multiplication:
-            let mut score = hand_rank * 1048576; // 2^20
-            score = score + (ordered_kickers[0] as u64) * 65536; // 2^16
-            score = score + (ordered_kickers[1] as u64) * 4096; // 2^12
-            score = score + (ordered_kickers[2] as u64) * 256; // 2^8
-            score = score + (ordered_kickers[3] as u64) * 16; // 2^4
-            score = score + (ordered_kickers[4] as u64) * 1; // 2^0
-
-            score
+            packed_ranks[i] = ((rank_counts[i] as u16) * 256) + (i as u16);
         }
+        packed_ranks.sort();
+        packed_ranks.reverse();
+
+        // Once five kickers are placed, the writes below (which are multiplexed, not skipped) land
+        // in a sixth spare slot, so the index never runs past the array.
+        let mut ordered_kickers = [0u8; 6];
+        let mut kicker_idx = 0u8;
+        for i in 0..13 {
+            let count = (packed_ranks[i] / 256) as u8;
+            let rank = (packed_ranks[i] % 256) as u8;
+
+            // Unroll the loop since count can vary between 0 and 5
+            // Use arithmetic multiplexers to conditionally add kickers
+            let should_add_0 = (count > 0) & (kicker_idx < 5);
+            ordered_kickers[kicker_idx as usize] = (should_add_0 as u8 * rank) + ((!should_add_0) as u8 * ordered_kickers[kicker_idx as usize]);
+            kicker_idx += should_add_0 as u8;
+
+            let should_add_1 = (count > 1) & (kicker_idx < 5);
+            ordered_kickers[kicker_idx as usize] = (should_add_1 as u8 * rank) + ((!should_add_1) as u8 * ordered_kickers[kicker_idx as usize]);
+            kicker_idx += should_add_1 as u8;
+
+            let should_add_2 = (count > 2) & (kicker_idx < 5);
+            ordered_kickers[kicker_idx as usize] = (should_add_2 as u8 * rank) + ((!should_add_2) as u8 * ordered_kickers[kicker_idx as usize]);
+            kicker_idx += should_add_2 as u8;
+
+            let should_add_3 = (count > 3) & (kicker_idx < 5);
+            ordered_kickers[kicker_idx as usize] = (should_add_3 as u8 * rank) + ((!should_add_3) as u8 * ordered_kickers[kicker_idx as usize]);
+            kicker_idx += should_add_3 as u8;
+
+            let should_add_4 = (count > 4) & (kicker_idx < 5);
+            ordered_kickers[kicker_idx as usize] = (should_add_4 as u8 * rank) + ((!should_add_4) as u8 * ordered_kickers[kicker_idx as usize]);
+            kicker_idx += should_add_4 as u8;
+        }
+
+        // Special case for the wheel straight (A-5-4-3-2), the '5' is the high card for rank, not the Ace.
+        let wheel_kicker_override = [RANK_FIVE, RANK_FOUR, RANK_THREE, RANK_TWO, RANK_ACE];
+        for i in 0..5 {
+            // This is a multiplexer: `(cond * val_if_true) + (!cond * val_if_false)`
+            ordered_kickers[i] = (is_wheel as u8 * wheel_kicker_override[i]) + ((!is_wheel) as u8 * ordered_kickers[i]);
+        }
+
+        // 7. Assemble the final score by bit-shifting the rank and kickers together.
+        // Hand Rank (4 bits) | Kicker 1 (4 bits) | Kicker 2 (4 bits) | ... | Kicker 5 (4 bits)
+        // Since bit shifting is not supported, we use multiplication:
+        let mut score = hand_rank * 1048576; // 2^20
+        score = score + (ordered_kickers[0] as u64) * 65536; // 2^16
+        score = score + (ordered_kickers[1] as u64) * 4096; // 2^12
+        score = score + (ordered_kickers[2] as u64) * 256; // 2^8
+        score = score + (ordered_kickers[3] as u64) * 16; // 2^4
+        score = score + (ordered_kickers[4] as u64) * 1; // 2^0
+
+        score
+    }
 
-        // Finds the highest possible score from a 7-card hand
-        fn find_best_hand_from_seven(seven_cards: [u8; 7]) -> u64 {
-            // All 21 combinations of 5-card hands from 7 cards, represented by indices.
-            const COMBINATIONS: [[usize; 5]; 21] = [
-                [0,1,2,3,4], [0,1,2,3,5], [0,1,2,3,6], [0,1,2,4,5], [0,1,2,4,6],
-                [0,1,2,5,6], [0,1,3,4,5], [0,1,3,4,6], [0,1,3,5,6], [0,1,4,5,6],
-                [0,2,3,4,5], [0,2,3,4,6], [0,2,3,5,6], [0,2,4,5,6], [0,3,4,5,6],
-                [1,2,3,4,5], [1,2,3,4,6], [1,2,3,5,6], [1,2,4,5,6], [1,3,4,5,6],
-                [2,3,4,5,6]
-            ];
-
-            let mut max_score = 0u64;
-
-            // Iterate through all combinations, evaluate each 5-card hand, and keep track of the max score.
-            // This loop is data-independent as it always runs 21 times.
-            for combo in COMBINATIONS {
-                let mut current_hand = [0u8; 5];
-                current_hand[0] = seven_cards[combo[0]];
-                current_hand[1] = seven_cards[combo[1]];
-                current_hand[2] = seven_cards[combo[2]];
-                current_hand[3] = seven_cards[combo[3]];
-                current_hand[4] = seven_cards[combo[4]];
-                
-                let score = evaluate_hand(current_hand);
-                
-                // Data-independent update of max_score using an arithmetic multiplexer.
-                // This is equivalent to `if score > max_score { max_score = score; }`
-                // but avoids data-dependent branching.
-                let is_greater = score > max_score;
-                max_score = (is_greater as u64 * score) + ((!is_greater) as u64 * max_score);
+    // All 21 combinations of 5-card hands from 7 cards, represented by indices.
+    const SEVEN_CARD_COMBINATIONS: [[usize; 5]; 21] = [
+        [0,1,2,3,4], [0,1,2,3,5], [0,1,2,3,6], [0,1,2,4,5], [0,1,2,4,6],
+        [0,1,2,5,6], [0,1,3,4,5], [0,1,3,4,6], [0,1,3,5,6], [0,1,4,5,6],
+        [0,2,3,4,5], [0,2,3,4,6], [0,2,3,5,6], [0,2,4,5,6], [0,3,4,5,6],
+        [1,2,3,4,5], [1,2,3,4,6], [1,2,3,5,6], [1,2,4,5,6], [1,3,4,5,6],
+        [2,3,4,5,6]
+    ];
+
+    // Finds the highest possible score from a 7-card hand
+    fn find_best_hand_from_seven(seven_cards: [u8; 7]) -> u64 {
+        let mut max_score = 0u64;
+
+        // Iterate through all combinations, evaluate each 5-card hand, and keep track of the max score.
+        // This loop is data-independent as it always runs 21 times.
+        for combo in SEVEN_CARD_COMBINATIONS {
+            let mut current_hand = [0u8; 5];
+            current_hand[0] = seven_cards[combo[0]];
+            current_hand[1] = seven_cards[combo[1]];
+            current_hand[2] = seven_cards[combo[2]];
+            current_hand[3] = seven_cards[combo[3]];
+            current_hand[4] = seven_cards[combo[4]];
+
+            let score = evaluate_hand(current_hand);
+
+            // Data-independent update of max_score using an arithmetic multiplexer.
+            // This is equivalent to `if score > max_score { max_score = score; }`
+            // but avoids data-dependent branching.
+            let is_greater = score > max_score;
+            max_score = (is_greater as u64 * score) + ((!is_greater) as u64 * max_score);
+        }
+
+        max_score
+    }
+
+    // Like `find_best_hand_from_seven`, but also returns which of the seven cards (by index)
+    // make up the best hand. Of equal-scoring combinations, the first is kept.
+    fn find_best_hand_cards_from_seven(seven_cards: [u8; 7]) -> (u64, [u8; 5]) {
+        let mut max_score = 0u64;
+        let mut best_cards = [0u8; 5];
+
+        for combo in SEVEN_CARD_COMBINATIONS {
+            let mut current_hand = [0u8; 5];
+            for k in 0..5 {
+                current_hand[k] = seven_cards[combo[k]];
             }
 
-            max_score
+            let score = evaluate_hand(current_hand);
+
+            let is_greater = score > max_score;
+            max_score = (is_greater as u64 * score) + ((!is_greater) as u64 * max_score);
+            for k in 0..5 {
+                best_cards[k] = (is_greater as u8 * combo[k] as u8) + ((!is_greater) as u8 * best_cards[k]);
+            }
         }
-        
-        let p1_data = p1_cards_ctxt.to_arcis();
-        let p2_data = p2_cards_ctxt.to_arcis();
 
+        (max_score, best_cards)
+    }
+
+    /// Compares two players' hole cards against the board.
+    /// Returns 0 if player 1 wins, 1 if player 2 wins, and 2 for a tie.
+    fn compare_hands(p1_hole_cards: [u8; 2], p2_hole_cards: [u8; 2], board: [u8; 5]) -> u8 {
         // Combine hole cards and board for player 1
         let p1_seven_cards = [
-            p1_data.hole_cards[0],
-            p1_data.hole_cards[1],
+            p1_hole_cards[0],
+            p1_hole_cards[1],
             board[0],
             board[1],
             board[2],
@@ -337,8 +439,8 @@ mod circuits {
 
         // Combine hole cards and board for player 2
         let p2_seven_cards = [
-            p2_data.hole_cards[0],
-            p2_data.hole_cards[1],
+            p2_hole_cards[0],
+            p2_hole_cards[1],
             board[0],
             board[1],
             board[2],
@@ -358,6 +460,251 @@ mod circuits {
         // If p1_wins is true (1), the first term is 0.
         // If p2_wins is true (1), the second term is 1.
         // If neither is true (tie), the third term is 2.
+        (p1_wins as u8 * 0) + (p2_wins as u8 * 1) + ((!p1_wins & !p2_wins) as u8 * 2)
+    }
+
+    /// The plaintext core of `determine_winner`: scores every player's hand and works out
+    /// `(winner_mask, winning_rank, winning_cards)` as that instruction documents, before
+    /// anything is revealed.
+    fn showdown(
+        hole_cards: [[u8; 2]; DEAL_PLAYERS],
+        board: [u8; 5],
+    ) -> (u8, u8, [[u8; 5]; DEAL_PLAYERS]) {
+        // Every player is evaluated, and the best score is kept with a multiplexer rather
+        // than a branch, so the work done never depends on the cards.
+        let mut scores = [0u64; DEAL_PLAYERS];
+        let mut best_cards = [[0u8; 5]; DEAL_PLAYERS];
+        let mut best_score = 0u64;
+        for p in 0..DEAL_PLAYERS {
+            let (score, cards) = find_best_hand_cards_from_seven([
+                hole_cards[p][0],
+                hole_cards[p][1],
+                board[0],
+                board[1],
+                board[2],
+                board[3],
+                board[4],
+            ]);
+            scores[p] = score;
+            best_cards[p] = cards;
+
+            let is_greater = score > best_score;
+            best_score = (is_greater as u64 * score) + ((!is_greater) as u64 * best_score);
+        }
+
+        // Every player who matches the best score shares the pot. Only the winners' best
+        // five cards are shown; a loser's are masked out before anything is revealed.
+        let mut winner_mask = 0u8;
+        let mut winning_cards = [[255u8; 5]; DEAL_PLAYERS];
+        for p in 0..DEAL_PLAYERS {
+            let is_winner = scores[p] == best_score;
+            winner_mask = winner_mask + (is_winner as u8 * PLAYER_BITS[p]);
+            for k in 0..5 {
+                winning_cards[p][k] = (is_winner as u8 * best_cards[p][k]) + ((!is_winner) as u8 * 255);
+            }
+        }
+        // The hand category occupies the score's top four bits; see `evaluate_hand`.
+        let winning_rank = (best_score / 1048576) as u8;
+
+        (winner_mask, winning_rank, winning_cards)
+    }
+    // END PLAINTEXT
+
+    /// Determines the winners of a poker hand at showdown.
+    ///
+    /// This instruction takes the encrypted hole cards of every player dealt in, in seat order,
+    /// and the public community cards, confidentially evaluates each player's best 5-card hand,
+    /// and reveals only who holds the best hand, its category and which cards make it up,
+    /// never anything about the losing hands.
+    /// Like `shuffle_and_deal`, it is compiled for `DEAL_PLAYERS` players; the winner mask
+    /// has room for the table's maximum of six.
+    ///
+    /// # Arguments
+    /// * `hands_ctxt` - Each player's two hole cards, encrypted with a shared key.
+    /// * `board` - The five public community cards (unencrypted).
+    ///
+    /// # Returns
+    /// `(winner_mask, winning_rank, winning_cards)`: bit `p` of the mask is set for each
+    /// player `p` holding the best hand, so a split pot has more than one bit set; the rank is
+    /// the best hand's category, from 0 (high card) to 8 (straight flush). For each winner,
+    /// `winning_cards` gives the five cards of their best hand as indices into their seven
+    /// cards (0-1 their hole cards, 2-6 the board); a losing player's entry is all 255.
+    #[instruction]
+    pub fn determine_winner(
+        hands_ctxt: [Enc<Shared, PlayerEncryptedData>; DEAL_PLAYERS],
+        board: [u8; 5],
+    ) -> (u8, u8, [[u8; 5]; DEAL_PLAYERS]) {
+        let mut hole_cards = [[0u8; 2]; DEAL_PLAYERS];
+        for p in 0..DEAL_PLAYERS {
+            hole_cards[p] = hands_ctxt[p].to_arcis().hole_cards;
+        }
+        let (winner_mask, winning_rank, winning_cards) = showdown(hole_cards, board);
+
+        (winner_mask.reveal(), winning_rank.reveal(), winning_cards.reveal())
+    }
+
+    /// Runs the same evaluation as `determine_winner` for a completed heads-up hand, so clients
+    /// can preview a result (e.g. for replays or tutorials). It is registered as its own
+    /// computation so its result can never be mistaken for a live showdown.
+    #[instruction]
+    pub fn preview_winner(
+        p1_cards_ctxt: Enc<Shared, PlayerEncryptedData>,
+        p2_cards_ctxt: Enc<Shared, PlayerEncryptedData>,
+        board: [u8; 5],
+    ) -> u8 {
+        let p1_data = p1_cards_ctxt.to_arcis();
+        let p2_data = p2_cards_ctxt.to_arcis();
+
+        compare_hands(p1_data.hole_cards, p2_data.hole_cards, board).reveal()
+    }
+
+    /// Prices river insurance during an all-in runout. With the turn out, every card that can
+    /// still come on the river is tried and the rivers each player would win are counted.
+    ///
+    /// # Returns
+    /// `(leader, outs)`: the player who wins on more rivers (2 if neither does), and the number
+    /// of rivers on which the other player would outdraw them.
+    #[instruction]
+    pub fn insurance_odds(
+        p1_cards_ctxt: Enc<Shared, PlayerEncryptedData>,
+        p2_cards_ctxt: Enc<Shared, PlayerEncryptedData>,
+        board: [u8; 4],
+    ) -> (u8, u8) {
+        let p1_cards = p1_cards_ctxt.to_arcis().hole_cards;
+        let p2_cards = p2_cards_ctxt.to_arcis().hole_cards;
+
+        let mut p1_rivers = 0u8;
+        let mut p2_rivers = 0u8;
+        for river in 0..52u8 {
+            // Cards already in a hand or on the board cannot come on the river.
+            let in_hand = river == p1_cards[0]
+                || river == p1_cards[1]
+                || river == p2_cards[0]
+                || river == p2_cards[1];
+            let on_board = river == board[0] || river == board[1] || river == board[2] || river == board[3];
+            let is_live = !in_hand && !on_board;
+
+            let result = compare_hands(p1_cards, p2_cards, [board[0], board[1], board[2], board[3], river]);
+            p1_rivers = p1_rivers + (is_live && result == 0) as u8;
+            p2_rivers = p2_rivers + (is_live && result == 1) as u8;
+        }
+
+        let p1_leads = p1_rivers > p2_rivers;
+        let p2_leads = p2_rivers > p1_rivers;
+        let leader = (p2_leads as u8) + ((!p1_leads && !p2_leads) as u8 * 2);
+        let outs = (p1_leads as u8 * p2_rivers) + (p2_leads as u8 * p1_rivers);
+
+        (leader.reveal(), outs.reveal())
+    }
+
+    // --- Five-card draw ---
+
+    // BEGIN PLAINTEXT
+    /// The plaintext core of `draw_cards`: replaces each card whose bit is set in
+    /// `discard_mask` with the next undrawn card of `deck`, in hand order.
+    ///
+    /// # Returns
+    /// The new hand and the new count of cards drawn from the deck.
+    fn replace_discards(
+        hand: [u8; 5],
+        deck: [u8; 42],
+        drawn_cards: u8,
+        discard_mask: u8,
+    ) -> ([u8; 5], u8) {
+        let mut cards = hand;
+        let mut drawn = drawn_cards;
+        // Bit shifting is not supported, so each bit is read by division.
+        const BITS: [u8; 5] = [1, 2, 4, 8, 16];
+        for i in 0..5 {
+            let discard = (discard_mask / BITS[i]) % 2 == 1;
+            let next_card = deck[drawn as usize];
+            cards[i] = (discard as u8 * next_card) + ((!discard) as u8 * cards[i]);
+            drawn += discard as u8;
+        }
+        (cards, drawn)
+    }
+    // END PLAINTEXT
+
+    /// Shuffles the deck and deals five cards to each player for the five-card draw variant.
+    /// Player 1 gets cards 0-4, player 2 gets cards 5-9, and the remaining 42 cards are kept
+    /// by the MXE for the draw.
+    #[instruction]
+    pub fn shuffle_and_deal_draw(
+        player1_pubkey: ArcisPublicKey,
+        player2_pubkey: ArcisPublicKey,
+    ) -> (Enc<Shared, DrawHand>, Enc<Shared, DrawHand>, Enc<Mxe, DrawDeck>) {
+        let mut deck: [u8; 52] = [0; 52];
+        for i in 0..52 {
+            deck[i] = i as u8;
+        }
+
+        ArcisRNG::shuffle(&mut deck);
+
+        let mut p1_cards = [0u8; 5];
+        let mut p2_cards = [0u8; 5];
+        for i in 0..5 {
+            p1_cards[i] = deck[i];
+            p2_cards[i] = deck[i + 5];
+        }
+
+        let mut remaining = [0u8; 42];
+        for i in 0..42 {
+            remaining[i] = deck[i + 10];
+        }
+
+        let player1_owner = Shared::new(player1_pubkey);
+        let player2_owner = Shared::new(player2_pubkey);
+        let mxe_owner = Mxe::get();
+
+        let enc_p1_hand = player1_owner.from_arcis(DrawHand { cards: p1_cards });
+        let enc_p2_hand = player2_owner.from_arcis(DrawHand { cards: p2_cards });
+        let enc_deck = mxe_owner.from_arcis(DrawDeck {
+            cards: remaining,
+            drawn_cards: 0,
+        });
+
+        (enc_p1_hand, enc_p2_hand, enc_deck)
+    }
+
+    /// Replaces a player's discarded cards with the next cards from the deck.
+    ///
+    /// # Arguments
+    /// * `hand_ctxt` - The player's five cards, encrypted with a shared key.
+    /// * `deck_ctxt` - The remaining deck, encrypted for the MXE.
+    /// * `discard_mask` - Bit `i` set means card `i` is discarded (0 stands pat, 31 draws five).
+    #[instruction]
+    pub fn draw_cards(
+        hand_ctxt: Enc<Shared, DrawHand>,
+        deck_ctxt: Enc<Mxe, DrawDeck>,
+        discard_mask: u8,
+    ) -> (Enc<Shared, DrawHand>, Enc<Mxe, DrawDeck>) {
+        let mut hand = hand_ctxt.to_arcis();
+        let mut deck = deck_ctxt.to_arcis();
+
+        let (cards, drawn_cards) = replace_discards(hand.cards, deck.cards, deck.drawn_cards, discard_mask);
+        hand.cards = cards;
+        deck.drawn_cards = drawn_cards;
+
+        let enc_hand = hand_ctxt.owner.from_arcis(hand);
+        let enc_deck = Mxe::get().from_arcis(deck);
+
+        (enc_hand, enc_deck)
+    }
+
+    /// Determines the winner of a five-card draw hand by scoring each player's five cards.
+    ///
+    /// # Returns
+    /// A `u8`: 0 if player 1 wins, 1 if player 2 wins, and 2 for a tie.
+    #[instruction]
+    pub fn determine_winner_draw(
+        p1_hand_ctxt: Enc<Shared, DrawHand>,
+        p2_hand_ctxt: Enc<Shared, DrawHand>,
+    ) -> u8 {
+        let p1_score = evaluate_hand(p1_hand_ctxt.to_arcis().cards);
+        let p2_score = evaluate_hand(p2_hand_ctxt.to_arcis().cards);
+
+        let p1_wins = p1_score > p2_score;
+        let p2_wins = p2_score > p1_score;
         let winner_index =
             (p1_wins as u8 * 0) + (p2_wins as u8 * 1) + ((!p1_wins & !p2_wins) as u8 * 2);
 
@@ -0,0 +1,4 @@
+// This module contains the confidential logic for showing a folded player's hole cards.
+// Only the cards the player chooses to show are revealed; the rest stay encrypted.
+//
+// Note: The actual implementation is in the circuits module in lib.rs due to Arcis restrictions.
\ No newline at end of file
@@ -1,4 +1,5 @@
 // This module contains the confidential logic for creating, shuffling, and dealing a 52-card deck.
 // The entire process is executed securely within the Arcium MPC environment to guarantee fairness.
+// The only plaintext output is whether the shuffled deck passed the integrity check.
 //
 // Note: The actual implementation is in the circuits module in lib.rs due to Arcis restrictions.
\ No newline at end of file
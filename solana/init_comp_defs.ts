@@ -57,6 +57,41 @@ async function main() {
       name: "determine_winner",
       circuitPath: "./build/determine_winner_testnet.arcis",
       methodName: "initDetermineWinnerCompDef"
+    },
+    {
+      name: "reveal_hole_cards",
+      circuitPath: "./build/reveal_hole_cards_testnet.arcis",
+      methodName: "initRevealHoleCardsCompDef"
+    },
+    {
+      name: "preview_winner",
+      circuitPath: "./build/preview_winner_testnet.arcis",
+      methodName: "initPreviewWinnerCompDef"
+    },
+    {
+      name: "shuffle_and_deal_draw",
+      circuitPath: "./build/shuffle_and_deal_draw_testnet.arcis",
+      methodName: "initShuffleAndDealDrawCompDef"
+    },
+    {
+      name: "draw_cards",
+      circuitPath: "./build/draw_cards_testnet.arcis",
+      methodName: "initDrawCardsCompDef"
+    },
+    {
+      name: "determine_winner_draw",
+      circuitPath: "./build/determine_winner_draw_testnet.arcis",
+      methodName: "initDetermineWinnerDrawCompDef"
+    },
+    {
+      name: "insurance_odds",
+      circuitPath: "./build/insurance_odds_testnet.arcis",
+      methodName: "initInsuranceOddsCompDef"
+    },
+    {
+      name: "reveal_all_community_cards",
+      circuitPath: "./build/reveal_all_community_cards_testnet.arcis",
+      methodName: "initRevealAllCommunityCardsCompDef"
     }
   ];
 
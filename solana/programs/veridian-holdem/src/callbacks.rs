@@ -5,21 +5,37 @@
  * delivering the results back on-chain to update the public `GameState`.
  *
  * @key_features
- * - `DealNewHandCallback`: Processes the encrypted cards and deck from the shuffle computation.
+ * - `DealNewHandCallback`: Processes the encrypted cards and deck from the shuffle computation,
+ *   or voids the hand if the deal fails or fails its integrity checks.
  * - `RevealCommunityCardsCallback`: Updates the public board with newly revealed cards.
- * - `DetermineWinnerCallback`: Processes the winner index, calculates rake, distributes the pot, and resets the hand.
+ * - `RevealAllCommunityCardsCallback`: Completes the board in one step during an all-in runout
+ *   and moves the hand to showdown.
+ * - `DetermineWinnerCallback`: Records the winner index and marks the hand ready for `settle_hand`.
+ * - `RevealHoleCardsCallback`: Publishes the hole cards a folded player chose to show.
+ * - `PreviewWinnerCallback`: Emits the result of a dry-run showdown without changing any state.
+ * - `ShuffleAndDealDrawCallback`, `DrawCardsCallback`, `DetermineWinnerDrawCallback`: The
+ *   five-card draw equivalents of the deal, draw and showdown callbacks.
+ * - Callbacks that write to `GameState` or `HandState` only accept the result of the hand's
+ *   latest queued computation, rejecting a superseded one with `StaleCallback`. Only the
+ *   dry-run `PreviewWinnerCallback`, which writes nothing, is exempt.
+ * - Every callback must be delivered by the Arcium program; one called directly is rejected with
+ *   `UnauthorizedCallback`.
  *
  * @dependencies
  * - arcium_anchor & arcium_macros: For defining callback instructions and handling `ComputationOutputs`.
  * - crate::state & crate::error: For accessing account structures and custom errors.
- * - anchor_spl::token: For performing secure token transfers (CPIs) during pot distribution.
  */
 use crate::{
     error::ErrorCode,
-    state::{Config, GamePhase, GameState, HandState, TableConfig, MAX_PLAYERS},
+    events::{CardRevealCompleted, CardsShown, HandVoided, ShowdownPreviewed, ShowdownResolved},
+    instructions::player_action::handle_round_transition,
+    state::{GamePhase, GameState, GameVariant, HandState, TableConfig, MAX_PLAYERS},
+};
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    AccountsClose,
 };
-use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use arcium_anchor::prelude::*;
 use arcium_client::idl::arcium::ID_CONST;
 use arcium_macros::arcium_callback;
@@ -28,21 +44,62 @@ use arcium_client::idl::arcium::types::CallbackInstruction;
 // Define output types for Arcium computations
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ShuffleAndDealOutput {
-    pub field_0: (Vec<u8>, Vec<u8>, Vec<u8>), // (p1_encrypted_cards, p2_encrypted_cards, encrypted_deck)
+    pub field_0: (Vec<Vec<u8>>, Vec<u8>, bool), // (encrypted_hole_cards per player in seat order, encrypted_deck, deck_valid)
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct RevealCommunityCardsOutput {
-    pub field_0: (Vec<u8>, Vec<Vec<u8>>), // (encrypted_deck, revealed_cards)
+    pub field_0: (Vec<u8>, [u8; 3]), // (encrypted_deck, revealed cards, 255 for unused slots)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RevealAllCommunityCardsOutput {
+    pub field_0: (Vec<u8>, [u8; 5]), // (encrypted_deck, board cards, 255 for those already dealt)
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct DetermineWinnerOutput {
+    pub field_0: (u8, u8, [[u8; 5]; MAX_PLAYERS]), // (winner_mask, winning_rank, winners' best-hand card indices; 255 for losers)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RevealHoleCardsOutput {
+    pub field_0: [u8; 2], // shown hole cards (255 for a card kept hidden)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PreviewWinnerOutput {
+    pub field_0: u8, // winner_index (0, 1, or 2 for tie)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ShuffleAndDealDrawOutput {
+    pub field_0: (Vec<u8>, Vec<u8>, Vec<u8>), // (p1_encrypted_hand, p2_encrypted_hand, encrypted_deck)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DrawCardsOutput {
+    pub field_0: (Vec<u8>, Vec<u8>), // (encrypted_hand, encrypted_deck)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DetermineWinnerDrawOutput {
     pub field_0: u8, // winner_index (0, 1, or 2 for tie)
 }
 
-// This function is required by the arcium_callback macro
-fn validate_callback_ixs(_account_info: &AccountInfo, _program_id: &Pubkey) -> Result<()> {
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InsuranceOddsOutput {
+    pub field_0: (u8, u8), // (leader, outs)
+}
+
+// This function is required by the arcium_callback macro, which runs it before each callback.
+// A callback must be delivered by the Arcium program: the transaction's current top-level
+// instruction has to be Arcium's, so the callback runs inside it. Otherwise anyone could call
+// a callback directly and forge a computation's output.
+fn validate_callback_ixs(instructions_sysvar: &AccountInfo, _program_id: &Pubkey) -> Result<()> {
+    let current = load_current_index_checked(instructions_sysvar)?;
+    let instruction = load_instruction_at_checked(current as usize, instructions_sysvar)?;
+    require_keys_eq!(instruction.program_id, ID_CONST, ErrorCode::UnauthorizedCallback);
     Ok(())
 }
 
@@ -63,6 +120,11 @@ pub struct DealNewHandCallback<'info> {
     )]
     pub hand_state: Box<Account<'info, HandState>>,
 
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
+
     #[account(
         seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
         bump
@@ -79,6 +141,14 @@ pub struct DealNewHandCallback<'info> {
     pub instructions_sysvar: AccountInfo<'info>,
     
     pub arcium_program: Program<'info, Arcium>,
+
+    /// CHECK: The dealer who paid for the `HandState` account's rent, refunded if the deal is
+    /// voided and the account closed.
+    #[account(
+        mut,
+        constraint = hand_state.dealer == dealer_account.key() @ ErrorCode::Unauthorized
+    )]
+    pub dealer_account: UncheckedAccount<'info>,
 }
 
 impl<'info> DealNewHandCallback<'info> {
@@ -117,6 +187,11 @@ pub struct RevealCommunityCardsCallback<'info> {
         bump
     )]
     pub hand_state: Box<Account<'info, HandState>>,
+
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
     
     #[account(
         address = derive_comp_def_pda!(comp_def_offset("reveal_community_cards"))
@@ -130,6 +205,50 @@ pub struct RevealCommunityCardsCallback<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
+impl<'info> RevealAllCommunityCardsCallback<'info> {
+    pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
+        CallbackInstruction {
+            program_id: crate::ID,
+            accounts: vec![],
+            discriminator: vec![0u8; 8], // This will be set by the Arcium system
+        }
+    }
+}
+
+/// Accounts required for the `reveal_all_community_cards` callback.
+#[derive(Accounts)]
+pub struct RevealAllCommunityCardsCallback<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(
+        mut,
+        seeds = [b"hand", game_state.key().as_ref()],
+        bump
+    )]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("reveal_all_community_cards"))
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+}
+
 impl<'info> DetermineWinnerCallback<'info> {
     pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
         CallbackInstruction {
@@ -141,6 +260,8 @@ impl<'info> DetermineWinnerCallback<'info> {
 }
 
 /// Accounts required for the `determine_winner` callback.
+/// The callback only records the result, so beyond the `GameState` it needs only what proves
+/// the result is for the hand's current computation.
 #[derive(Accounts)]
 pub struct DetermineWinnerCallback<'info> {
     #[account(
@@ -150,37 +271,192 @@ pub struct DetermineWinnerCallback<'info> {
     )]
     pub game_state: Account<'info, GameState>,
 
+    /// The hand the computation was queued for. Read only to check the computation is current.
+    #[account(seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("determine_winner"))
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+impl<'info> RevealHoleCardsCallback<'info> {
+    pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
+        CallbackInstruction {
+            program_id: crate::ID,
+            accounts: vec![],
+            discriminator: vec![0u8; 8], // This will be set by the Arcium system
+        }
+    }
+}
+
+/// Accounts required for the `reveal_hole_cards` callback.
+#[derive(Accounts)]
+pub struct RevealHoleCardsCallback<'info> {
     #[account(
         mut,
-        seeds = [b"hand", game_state.key().as_ref()],
-        bump,
-        close = dealer_account // Close the HandState account and refund rent to the dealer.
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
     )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The hand the computation was queued for. Read only to check the computation is current.
+    #[account(seeds = [b"hand", game_state.key().as_ref()], bump)]
     pub hand_state: Box<Account<'info, HandState>>,
 
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("reveal_hole_cards"))
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+impl<'info> PreviewWinnerCallback<'info> {
+    pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
+        CallbackInstruction {
+            program_id: crate::ID,
+            accounts: vec![],
+            discriminator: vec![0u8; 8], // This will be set by the Arcium system
+        }
+    }
+}
+
+/// Accounts required for the `preview_winner` callback.
+/// The `GameState` is read-only: a dry-run never moves chips.
+#[derive(Accounts)]
+pub struct PreviewWinnerCallback<'info> {
     #[account(
-        seeds = [b"config"],
-        bump,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
     )]
-    pub config: Account<'info, Config>,
+    pub game_state: Account<'info, GameState>,
 
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("preview_winner"))
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+impl<'info> ShuffleAndDealDrawCallback<'info> {
+    pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
+        CallbackInstruction {
+            program_id: crate::ID,
+            accounts: vec![],
+            discriminator: vec![0u8; 8], // This will be set by the Arcium system
+        }
+    }
+}
+
+/// Accounts required for the five-card draw `shuffle_and_deal_draw` callback.
+#[derive(Accounts)]
+pub struct ShuffleAndDealDrawCallback<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", game_state.key().as_ref()],
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
         bump
     )]
-    pub escrow_account: Account<'info, TokenAccount>,
+    pub game_state: Account<'info, GameState>,
 
-    /// CHECK: This is the dealer of the hand who paid for the HandState account's rent.
-    #[account(mut)]
-    pub dealer_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"hand", game_state.key().as_ref()],
+        bump
+    )]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
     
-    /// CHECK: This is the treasury wallet that receives rake.
-    #[account(mut, address = config.treasury_wallet)]
-    pub treasury_token_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("shuffle_and_deal_draw"))
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    pub arcium_program: Program<'info, Arcium>,
 
+    /// CHECK: The dealer who paid for the `HandState` account's rent, refunded if the deal is
+    /// voided and the account closed.
     #[account(
-        address = derive_comp_def_pda!(comp_def_offset("determine_winner"))
+        mut,
+        constraint = hand_state.dealer == dealer_account.key() @ ErrorCode::Unauthorized
+    )]
+    pub dealer_account: UncheckedAccount<'info>,
+}
+
+impl<'info> DrawCardsCallback<'info> {
+    pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
+        CallbackInstruction {
+            program_id: crate::ID,
+            accounts: vec![],
+            discriminator: vec![0u8; 8], // This will be set by the Arcium system
+        }
+    }
+}
+
+/// Accounts required for the five-card draw `draw_cards` callback.
+#[derive(Accounts)]
+pub struct DrawCardsCallback<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(
+        mut,
+        seeds = [b"hand", game_state.key().as_ref()],
+        bump
+    )]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("draw_cards"))
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     
@@ -188,37 +464,116 @@ pub struct DetermineWinnerCallback<'info> {
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
     
-    pub token_program: Program<'info, Token>,
     pub arcium_program: Program<'info, Arcium>,
 }
 
-// --- Callback Implementations ---
+impl<'info> DetermineWinnerDrawCallback<'info> {
+    pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
+        CallbackInstruction {
+            program_id: crate::ID,
+            accounts: vec![],
+            discriminator: vec![0u8; 8], // This will be set by the Arcium system
+        }
+    }
+}
 
-/// Callback for the `shuffle_and_deal` confidential instruction.
-/// It receives the encrypted card data and updates the on-chain state to start the hand.
-#[arcium_callback(encrypted_ix = "shuffle_and_deal")]
-pub fn shuffle_and_deal_callback(
-    ctx: Context<DealNewHandCallback>,
-    output: ComputationOutputs<ShuffleAndDealOutput>,
-) -> Result<()> {
-    let (p1_data, p2_data, deck_data) = match output {
-        ComputationOutputs::Success(ShuffleAndDealOutput { field_0: data }) => {
-            (data.0, data.1, data.2)
+/// Accounts required for the five-card draw `determine_winner_draw` callback.
+#[derive(Accounts)]
+pub struct DetermineWinnerDrawCallback<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The hand the computation was queued for. Read only to check the computation is current.
+    #[account(seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("determine_winner_draw"))
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+impl<'info> InsuranceOddsCallback<'info> {
+    pub fn callback_ix(_args: &[&[u8]]) -> CallbackInstruction {
+        CallbackInstruction {
+            program_id: crate::ID,
+            accounts: vec![],
+            discriminator: vec![0u8; 8], // This will be set by the Arcium system
         }
-        _ => return err!(ErrorCode::InvalidAction), // Or a more specific error
-    };
+    }
+}
 
-    let hand_state = &mut ctx.accounts.hand_state;
+/// Accounts required for the `insurance_odds` callback.
+/// The callback only records the quote, so beyond the `GameState` it needs only what proves
+/// the quote is for the hand's current computation.
+#[derive(Accounts)]
+pub struct InsuranceOddsCallback<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The hand the computation was queued for. Read only to check the computation is current.
+    #[account(seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
 
-    // Serialize and store the encrypted data blobs into the HandState account.
-    let p1_vec = p1_data.try_to_vec()?;
-    hand_state.encrypted_hole_cards[0][..p1_vec.len()].copy_from_slice(&p1_vec);
+    /// CHECK: The computation whose result this callback delivers. It must be the hand's latest
+    /// queued computation; a superseded one is stale.
+    #[account(address = derive_comp_pda!(hand_state.computation_offset) @ ErrorCode::StaleCallback)]
+    pub computation_account: UncheckedAccount<'info>,
 
-    let p2_vec = p2_data.try_to_vec()?;
-    hand_state.encrypted_hole_cards[1][..p2_vec.len()].copy_from_slice(&p2_vec);
+    #[account(
+        address = derive_comp_def_pda!(comp_def_offset("insurance_odds"))
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     
-    let deck_vec = deck_data.try_to_vec()?;
-    // Split the deck data across the four parts
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+// --- Shared Helpers ---
+
+/// The most encrypted deck data the `encrypted_deck_part*` fields can hold.
+const DECK_CAPACITY: usize = (HandState::DECK_NONCE_LEN + HandState::DECK_CIPHERTEXTS_LEN) as usize;
+
+/// Voids a deal that failed its integrity checks: the hand ends before any chips go in, and the
+/// `HandState` is closed with its rent refunded to the dealer who paid it.
+fn void_deal<'info>(
+    game_state: &mut Account<'info, GameState>,
+    hand_state: &Account<'info, HandState>,
+    dealer_account: AccountInfo<'info>,
+) -> Result<()> {
+    game_state.record_misdeal();
+    emit!(HandVoided {
+        table_id: game_state.table_id,
+        dealer: game_state.players[game_state.dealer_index as usize],
+    });
+    hand_state.close(dealer_account)
+}
+
+/// Splits a serialized encrypted deck across the four `encrypted_deck_part*` fields and commits
+/// to it.
+fn store_encrypted_deck(hand_state: &mut HandState, deck_vec: &[u8]) {
     let total_len = deck_vec.len();
     let part1_len = total_len.min(512);
     let part2_len = (total_len - part1_len).min(512);
@@ -235,23 +590,180 @@ pub fn shuffle_and_deal_callback(
     if part4_len > 0 {
         hand_state.encrypted_deck_part4[..part4_len].copy_from_slice(&deck_vec[part1_len + part2_len + part3_len..]);
     }
+    hand_state.deck_commitment = hand_state.hash_deck();
+}
 
-    // Post blinds.
-    let game_state = &mut ctx.accounts.game_state;
-    let table_config = &ctx.accounts.table_config;
+/// Posts the antes, the blinds (or the button's straddle), and opens the first betting round of
+/// a freshly dealt hand.
+fn post_blinds_and_start(game_state: &mut GameState, table_config: &TableConfig) -> Result<()> {
     let small_blind_idx = game_state.dealer_index as usize;
     let big_blind_idx = (1 - game_state.dealer_index) as usize;
 
-    game_state.stacks[small_blind_idx] -= table_config.small_blind;
-    game_state.bets[small_blind_idx] = table_config.small_blind;
+    // The table ante is dead money from both players. A short stack antes all it has, and the
+    // opponent antes no more than that, since the excess could never be won back.
+    let ante = table_config
+        .ante
+        .min(game_state.stacks[0])
+        .min(game_state.stacks[1]);
+    for seat in 0..MAX_PLAYERS {
+        game_state.post_dead_money(seat, ante)?;
+    }
+
+    // The button ante is dead money too: it goes straight into the pot and does not count
+    // towards the button's bet.
+    let button_ante = game_state
+        .button_ante(table_config)?
+        .min(game_state.stacks[small_blind_idx]);
+    game_state.post_dead_money(small_blind_idx, button_ante)?;
+
+    // After the table ante, a player may no longer cover their whole blind. They post what
+    // they have and are all-in.
+    let button_blind = game_state
+        .button_blind(table_config)?
+        .min(game_state.stacks[small_blind_idx]);
+    let big_blind = table_config.big_blind.min(game_state.stacks[big_blind_idx]);
+
+    // Post any blinds missed while sitting out as dead money, keeping enough back for the
+    // blind each player posts this hand. Whatever cannot be covered stays owed. This happens
+    // only once the deal has landed, so a voided deal has nothing to refund.
+    let blinds = [(small_blind_idx, button_blind), (big_blind_idx, big_blind)];
+    for (seat, blind) in blinds {
+        let dead = game_state.missed_blinds[seat].min(game_state.stacks[seat] - blind);
+        game_state.post_dead_money(seat, dead)?;
+        game_state.missed_blinds[seat] = game_state.missed_blinds[seat]
+            .checked_sub(dead)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+
+    game_state.commit_chips(small_blind_idx, button_blind, table_config.hand_cap)?;
+    game_state.commit_chips(big_blind_idx, big_blind, table_config.hand_cap)?;
+    game_state.last_raise_size = table_config.big_blind;
 
-    game_state.stacks[big_blind_idx] -= table_config.big_blind;
-    game_state.bets[big_blind_idx] = table_config.big_blind;
+    // A straddle declaration only covers one hand. Its levels are kept for the hand's action.
+    game_state.straddled = game_state.straddle_declared();
+    game_state.straddler = Pubkey::default();
+    if !game_state.straddled {
+        game_state.straddle_levels = 0;
+    }
 
-    // Set the game phase and first player to act (dealer/small blind acts first pre-flop).
+    // Set the game phase and first player to act. The dealer/small blind acts first pre-flop,
+    // unless they straddled, in which case the big blind acts first and the straddler last.
     game_state.game_phase = GamePhase::PreFlop;
-    game_state.current_turn_index = game_state.dealer_index;
-    
+    game_state.current_turn_index = if game_state.straddled {
+        big_blind_idx as u8
+    } else {
+        game_state.dealer_index
+    };
+
+    // A player all-in from the antes and blinds has no decision left. If they posted no more
+    // than their opponent, neither has the opponent: the excess goes back and the betting is
+    // over. Otherwise the opponent must still call or fold.
+    if let Some(all_in_idx) = (0..MAX_PLAYERS).find(|&seat| game_state.is_all_in[seat]) {
+        let opponent_idx = 1 - all_in_idx;
+        if game_state.is_all_in[opponent_idx]
+            || game_state.bets[all_in_idx] <= game_state.bets[opponent_idx]
+        {
+            game_state.return_uncalled_bet(table_config.hand_cap)?;
+            handle_round_transition(game_state, table_config.variant)?;
+        } else {
+            game_state.current_turn_index = opponent_idx as u8;
+        }
+    }
+    Ok(())
+}
+
+/// Records a showdown result and marks the hand ready for `settle_hand`, which splits the pot
+/// among the seats set in `winner_mask`.
+fn record_winners(
+    game_state: &mut GameState,
+    winner_mask: u8,
+    winning_rank: Option<u8>,
+) -> Result<()> {
+    require!(
+        winner_mask != 0 && winner_mask < 1 << MAX_PLAYERS,
+        ErrorCode::InvalidAction
+    );
+    require!(winning_rank.map_or(true, |rank| rank <= 8), ErrorCode::InvalidAction);
+    require!(game_state.game_phase == GamePhase::Showdown, ErrorCode::InvalidAction);
+
+    game_state.winner_mask = winner_mask;
+    game_state.winning_rank = winning_rank;
+    game_state.winner_index = if winner_mask.count_ones() == 1 {
+        winner_mask.trailing_zeros() as u8
+    } else {
+        2
+    };
+    game_state.game_phase = GamePhase::ReadyToSettle;
+    Ok(())
+}
+
+/// Records a heads-up showdown result given as 0 or 1 for a single winner, 2 for a split pot,
+/// whose hand category is not known.
+fn record_winner(game_state: &mut GameState, winner_index: u8) -> Result<()> {
+    require!(winner_index <= 2, ErrorCode::InvalidAction);
+    let winner_mask = if winner_index == 2 {
+        (1 << MAX_PLAYERS) - 1
+    } else {
+        1 << winner_index
+    };
+    record_winners(game_state, winner_mask, None)
+}
+
+// --- Callback Implementations ---
+
+/// Callback for the `shuffle_and_deal` confidential instruction.
+/// It receives the encrypted card data and updates the on-chain state to start the hand.
+#[arcium_callback(encrypted_ix = "shuffle_and_deal")]
+pub fn shuffle_and_deal_callback(
+    ctx: Context<DealNewHandCallback>,
+    output: ComputationOutputs<ShuffleAndDealOutput>,
+) -> Result<()> {
+    // A deal only ever lands on the hand it was queued for, never once the hand is under way.
+    require!(
+        ctx.accounts.game_state.game_phase == GamePhase::Dealing,
+        ErrorCode::InvalidAction
+    );
+    // A failed shuffle is voided like a corrupt one, since the deal is queued only once.
+    let (hole_cards, deck_data, deck_valid) = match output {
+        ComputationOutputs::Success(ShuffleAndDealOutput { field_0: data }) => data,
+        _ => {
+            return void_deal(
+                &mut ctx.accounts.game_state,
+                &ctx.accounts.hand_state,
+                ctx.accounts.dealer_account.to_account_info(),
+            )
+        }
+    };
+
+    let deck_vec = deck_data.try_to_vec()?;
+
+    // Never play with a corrupt deal: the circuit's integrity check on the shuffled deck must
+    // have passed, there must be hole cards for every seat, each with exactly the layout
+    // clients decrypt (public key, nonce, ciphertexts), and the deck must fit its `HandState`
+    // fields. Otherwise the hand is voided before any chips go in.
+    let hole_cards_len = HandState::HOLE_CARDS_LEN as usize;
+    let deal_valid = deck_valid
+        && hole_cards.len() == MAX_PLAYERS
+        && hole_cards.iter().all(|cards| cards.len() == hole_cards_len)
+        && deck_vec.len() <= DECK_CAPACITY;
+    if !deal_valid {
+        return void_deal(
+            &mut ctx.accounts.game_state,
+            &ctx.accounts.hand_state,
+            ctx.accounts.dealer_account.to_account_info(),
+        );
+    }
+
+    let hand_state = &mut ctx.accounts.hand_state;
+
+    // Store the encrypted data blobs into the HandState account.
+    for (seat, cards) in hole_cards.iter().enumerate() {
+        hand_state.encrypted_hole_cards[seat].copy_from_slice(cards);
+    }
+    store_encrypted_deck(hand_state, &deck_vec);
+
+    post_blinds_and_start(&mut ctx.accounts.game_state, &ctx.accounts.table_config)?;
+
     Ok(())
 }
 
@@ -261,131 +773,347 @@ pub fn reveal_community_cards_callback(
     ctx: Context<RevealCommunityCardsCallback>,
     output: ComputationOutputs<RevealCommunityCardsOutput>,
 ) -> Result<()> {
-    let (deck_data, revealed_cards_data) = match output {
-        ComputationOutputs::Success(RevealCommunityCardsOutput { field_0: data }) => {
-            (data.0, data.1)
+    // A failed reveal leaves the board as it was. The street may be requested again, up to
+    // the platform's retry limit (see `request_community_cards`).
+    let hand_state = &mut ctx.accounts.hand_state;
+    let (deck_data, revealed_cards) = match output {
+        ComputationOutputs::Success(RevealCommunityCardsOutput { field_0: data }) => data,
+        _ => {
+            hand_state.reveal_failed = true;
+            return Ok(());
         }
-        _ => return err!(ErrorCode::InvalidAction),
     };
+    hand_state.reset_reveal_attempts();
 
     // Update the encrypted deck in HandState.
-    let hand_state = &mut ctx.accounts.hand_state;
     let deck_vec = deck_data.try_to_vec()?;
-    // Split the deck data across the four parts
-    let total_len = deck_vec.len();
-    let part1_len = total_len.min(512);
-    let part2_len = (total_len - part1_len).min(512);
-    let part3_len = (total_len - part1_len - part2_len).min(512);
-    let part4_len = total_len - part1_len - part2_len - part3_len;
-    
-    hand_state.encrypted_deck_part1[..part1_len].copy_from_slice(&deck_vec[..part1_len]);
-    if part2_len > 0 {
-        hand_state.encrypted_deck_part2[..part2_len].copy_from_slice(&deck_vec[part1_len..part1_len + part2_len]);
-    }
-    if part3_len > 0 {
-        hand_state.encrypted_deck_part3[..part3_len].copy_from_slice(&deck_vec[part1_len + part2_len..part1_len + part2_len + part3_len]);
-    }
-    if part4_len > 0 {
-        hand_state.encrypted_deck_part4[..part4_len].copy_from_slice(&deck_vec[part1_len + part2_len + part3_len..]);
-    }
+    store_encrypted_deck(hand_state, &deck_vec);
 
-    // Update the public community cards in GameState.
+    // Update the public community cards in GameState. A duplicate reveal of a street that
+    // is already on the board is rejected.
     let game_state = &mut ctx.accounts.game_state;
-    let revealed_cards = revealed_cards_data; // This assumes they are revealed as plaintext in a real scenario.
-                                                            // For now, let's assume the callback gives us plaintext cards.
-                                                            // NOTE: Arcis instruction needs adjustment to return plaintext.
-                                                            // For now, we'll work with this assumption.
-
-    if game_state.game_phase == GamePhase::Flop {
-        if revealed_cards.len() >= 3 {
-            game_state.community_cards[0] = revealed_cards[0][0]; // Simplified extraction
-            game_state.community_cards[1] = revealed_cards[1][0];
-            game_state.community_cards[2] = revealed_cards[2][0];
-        }
-    } else if game_state.game_phase == GamePhase::Turn {
-        if revealed_cards.len() >= 1 {
-            game_state.community_cards[3] = revealed_cards[0][0];
-        }
-    } else if game_state.game_phase == GamePhase::River {
-        if revealed_cards.len() >= 1 {
-            game_state.community_cards[4] = revealed_cards[0][0];
-        }
+    require!(
+        game_state.cards_before_street() == Some(game_state.dealt_community_cards()),
+        ErrorCode::NoCardsToReveal
+    );
+    // The street's cards arrive in plaintext.
+    let count = game_state.place_street(&revealed_cards)?;
+    if hand_state.reveal_announced {
+        hand_state.reveal_announced = false;
+        emit!(CardRevealCompleted {
+            table_id: game_state.table_id,
+            game_phase: game_state.game_phase,
+            cards: revealed_cards[..count].to_vec(),
+        });
     }
 
     // Set turn for the next betting round (player out of position acts first).
     game_state.current_turn_index = 1 - game_state.dealer_index;
 
-    Ok(())
+    // During an all-in runout there is no betting round: move on to the next street, or to
+    // showdown once the river is out.
+    if game_state.runout {
+        game_state.game_phase = match game_state.game_phase {
+            GamePhase::Flop => GamePhase::Turn,
+            GamePhase::Turn => GamePhase::River,
+            _ => GamePhase::Showdown,
+        };
+    }
+
+    game_state.check_board(GameVariant::Holdem)
+}
+
+/// Callback for the `reveal_all_community_cards` confidential instruction.
+/// It fills in the rest of the board during an all-in runout and moves the hand to showdown.
+#[arcium_callback(encrypted_ix = "reveal_all_community_cards")]
+pub fn reveal_all_community_cards_callback(
+    ctx: Context<RevealAllCommunityCardsCallback>,
+    output: ComputationOutputs<RevealAllCommunityCardsOutput>,
+) -> Result<()> {
+    let (deck_data, revealed_cards) = match output {
+        ComputationOutputs::Success(RevealAllCommunityCardsOutput { field_0: data }) => data,
+        _ => return err!(ErrorCode::InvalidAction),
+    };
+
+    let hand_state = &mut ctx.accounts.hand_state;
+    let deck_vec = deck_data.try_to_vec()?;
+    store_encrypted_deck(hand_state, &deck_vec);
+
+    // Only the streets still missing from the board are filled in, and only during a runout.
+    let game_state = &mut ctx.accounts.game_state;
+    let dealt = game_state.dealt_community_cards();
+    require!(
+        game_state.runout && game_state.cards_before_street() == Some(dealt),
+        ErrorCode::NoCardsToReveal
+    );
+    game_state.community_cards[dealt..].copy_from_slice(&revealed_cards[dealt..]);
+
+    // The board is complete, so the hand goes straight to showdown.
+    game_state.current_turn_index = 1 - game_state.dealer_index;
+    game_state.game_phase = GamePhase::Showdown;
+
+    game_state.check_board(GameVariant::Holdem)
 }
 
 /// Callback for the `determine_winner` confidential instruction.
+/// It only records the winners; the payout is performed by the `settle_hand` instruction.
 #[arcium_callback(encrypted_ix = "determine_winner")]
 pub fn determine_winner_callback(
     ctx: Context<DetermineWinnerCallback>,
     output: ComputationOutputs<DetermineWinnerOutput>,
 ) -> Result<()> {
-    let winner_index = match output {
-        ComputationOutputs::Success(DetermineWinnerOutput { field_0: index }) => index,
+    let (winner_mask, winning_rank, winning_cards) = match output {
+        ComputationOutputs::Success(DetermineWinnerOutput { field_0: result }) => result,
+        _ => return err!(ErrorCode::InvalidAction),
+    };
+
+    let game_state = &mut ctx.accounts.game_state;
+    record_winners(game_state, winner_mask, Some(winning_rank))?;
+    emit!(ShowdownResolved {
+        table_id: game_state.table_id,
+        winner_mask,
+        winning_rank,
+        winning_cards,
+    });
+
+    Ok(())
+}
+
+/// Callback for the `reveal_hole_cards` confidential instruction.
+/// It publishes the cards a folded player chose to show.
+#[arcium_callback(encrypted_ix = "reveal_hole_cards")]
+pub fn reveal_hole_cards_callback(
+    ctx: Context<RevealHoleCardsCallback>,
+    output: ComputationOutputs<RevealHoleCardsOutput>,
+) -> Result<()> {
+    let cards = match output {
+        ComputationOutputs::Success(RevealHoleCardsOutput { field_0: cards }) => cards,
         _ => return err!(ErrorCode::InvalidAction),
     };
 
+    // The cards can only be shown once, and not after the next hand has been dealt.
     let game_state = &mut ctx.accounts.game_state;
-    let config = &ctx.accounts.config;
+    require!(game_state.folded_player != Pubkey::default(), ErrorCode::InvalidAction);
 
-    let total_pot = game_state.pot + game_state.bets[0] + game_state.bets[1];
-    let mut rake = 0;
+    emit!(CardsShown {
+        table_id: game_state.table_id,
+        player: game_state.folded_player,
+        cards,
+    });
+    game_state.folded_player = Pubkey::default();
 
-    // Rake Calculation ("No Flop, No Drop").
-    if game_state.community_cards[0] != 255 {
-        rake = (total_pot * config.rake_percentage as u64) / 100;
-        if rake > config.rake_cap {
-            rake = config.rake_cap;
+    Ok(())
+}
+
+/// Callback for the `preview_winner` confidential instruction.
+/// It only emits the result; the table's state is left untouched.
+#[arcium_callback(encrypted_ix = "preview_winner")]
+pub fn preview_winner_callback(
+    ctx: Context<PreviewWinnerCallback>,
+    output: ComputationOutputs<PreviewWinnerOutput>,
+) -> Result<()> {
+    let winner_index = match output {
+        ComputationOutputs::Success(PreviewWinnerOutput { field_0: index }) => index,
+        _ => return err!(ErrorCode::InvalidAction),
+    };
+    require!(winner_index <= 2, ErrorCode::InvalidAction);
+
+    emit!(ShowdownPreviewed {
+        table_id: ctx.accounts.game_state.table_id,
+        winner_index,
+    });
+
+    Ok(())
+}
+
+/// Callback for the five-card draw `shuffle_and_deal_draw` confidential instruction.
+/// It stores both players' encrypted hands and the remaining deck, then posts the blinds, or
+/// voids the hand if the deal does not fit the `HandState`.
+#[arcium_callback(encrypted_ix = "shuffle_and_deal_draw")]
+pub fn shuffle_and_deal_draw_callback(
+    ctx: Context<ShuffleAndDealDrawCallback>,
+    output: ComputationOutputs<ShuffleAndDealDrawOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.game_state.game_phase == GamePhase::Dealing,
+        ErrorCode::InvalidAction
+    );
+    let (p1_data, p2_data, deck_data) = match output {
+        ComputationOutputs::Success(ShuffleAndDealDrawOutput { field_0: data }) => {
+            (data.0, data.1, data.2)
         }
+        _ => {
+            return void_deal(
+                &mut ctx.accounts.game_state,
+                &ctx.accounts.hand_state,
+                ctx.accounts.dealer_account.to_account_info(),
+            )
+        }
+    };
+
+    let hand_vecs = [p1_data.try_to_vec()?, p2_data.try_to_vec()?];
+    let deck_vec = deck_data.try_to_vec()?;
+
+    // As in `shuffle_and_deal_callback`, a deal whose hands or deck do not have the layout the
+    // `HandState` holds is voided before any chips go in.
+    let deal_valid = hand_vecs
+        .iter()
+        .all(|hand| hand.len() == HandState::DRAW_HAND_LEN as usize)
+        && deck_vec.len() <= DECK_CAPACITY;
+    if !deal_valid {
+        return void_deal(
+            &mut ctx.accounts.game_state,
+            &ctx.accounts.hand_state,
+            ctx.accounts.dealer_account.to_account_info(),
+        );
     }
 
-    let pot_after_rake = total_pot - rake;
-
-    let seeds = &[
-        b"game",
-        &game_state.table_id.to_le_bytes()[..],
-        &[ctx.bumps.game_state],
-    ];
-    let signer = &[&seeds[..]];
-
-    // Transfer rake to treasury.
-    if rake > 0 {
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_account.to_account_info(),
-            to: ctx.accounts.treasury_token_account.to_account_info(),
-            authority: game_state.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, rake)?;
+    let hand_state = &mut ctx.accounts.hand_state;
+    for (seat, hand) in hand_vecs.iter().enumerate() {
+        hand_state.encrypted_draw_hands[seat][..hand.len()].copy_from_slice(hand);
     }
+    store_encrypted_deck(hand_state, &deck_vec);
 
-    // Distribute pot.
-    if winner_index == 2 { // Tie
-        let split_amount = pot_after_rake / 2;
-        game_state.stacks[0] += split_amount;
-        game_state.stacks[1] += split_amount;
-        // Handle odd chip if pot is not even.
-        if pot_after_rake % 2 == 1 {
-            let odd_chip_recipient = 1 - game_state.dealer_index; // Out of position
-            game_state.stacks[odd_chip_recipient as usize] += 1;
-        }
-    } else { // Single winner
-        game_state.stacks[winner_index as usize] += pot_after_rake;
+    post_blinds_and_start(&mut ctx.accounts.game_state, &ctx.accounts.table_config)?;
+
+    Ok(())
+}
+
+/// Callback for the five-card draw `draw_cards` confidential instruction.
+/// It stores the drawing player's new hand and passes the draw to the next player. Once both
+/// players have drawn, the final betting round begins, or the hand goes straight to showdown
+/// if a player is already all-in.
+#[arcium_callback(encrypted_ix = "draw_cards")]
+pub fn draw_cards_callback(
+    ctx: Context<DrawCardsCallback>,
+    output: ComputationOutputs<DrawCardsOutput>,
+) -> Result<()> {
+    let (hand_data, deck_data) = match output {
+        ComputationOutputs::Success(DrawCardsOutput { field_0: data }) => (data.0, data.1),
+        _ => return err!(ErrorCode::InvalidAction),
+    };
+
+    let game_state = &mut ctx.accounts.game_state;
+    require!(
+        game_state.game_phase == GamePhase::Draw && game_state.draw_pending,
+        ErrorCode::InvalidAction
+    );
+    let seat = game_state.current_turn_index as usize;
+
+    let hand_state = &mut ctx.accounts.hand_state;
+    let hand_vec = hand_data.try_to_vec()?;
+    let deck_vec = deck_data.try_to_vec()?;
+    require!(
+        hand_vec.len() == HandState::DRAW_HAND_LEN as usize && deck_vec.len() <= DECK_CAPACITY,
+        ErrorCode::InvalidAction
+    );
+    hand_state.encrypted_draw_hands[seat][..hand_vec.len()].copy_from_slice(&hand_vec);
+    store_encrypted_deck(hand_state, &deck_vec);
+
+    // The player out of position draws first, so the dealer's draw completes the round.
+    game_state.draw_pending = false;
+    game_state.record_action_time(&Clock::get()?);
+    if seat != game_state.dealer_index as usize {
+        game_state.current_turn_index = game_state.dealer_index;
+    } else if game_state.is_all_in.iter().any(|&all_in| all_in) {
+        game_state.game_phase = GamePhase::Showdown;
+    } else {
+        game_state.game_phase = GamePhase::River;
+        game_state.current_turn_index = 1 - game_state.dealer_index;
     }
 
-    // Reset game state for the next hand.
-    game_state.game_phase = GamePhase::HandOver;
-    game_state.pot = 0;
-    game_state.bets = [0; MAX_PLAYERS];
-    game_state.community_cards = [255; 5];
-    game_state.is_all_in = [false; MAX_PLAYERS];
-    game_state.dealer_index = 1 - game_state.dealer_index;
-    game_state.current_turn_index = game_state.dealer_index;
-    
+    game_state.check_board(GameVariant::FiveCardDraw)
+}
+
+/// Callback for the five-card draw `determine_winner_draw` confidential instruction.
+/// Like `determine_winner_callback`, it only records the winner for `settle_hand`.
+#[arcium_callback(encrypted_ix = "determine_winner_draw")]
+pub fn determine_winner_draw_callback(
+    ctx: Context<DetermineWinnerDrawCallback>,
+    output: ComputationOutputs<DetermineWinnerDrawOutput>,
+) -> Result<()> {
+    let winner_index = match output {
+        ComputationOutputs::Success(DetermineWinnerDrawOutput { field_0: index }) => index,
+        _ => return err!(ErrorCode::InvalidAction),
+    };
+
+    record_winner(&mut ctx.accounts.game_state, winner_index)
+}
+
+/// Callback for the `insurance_odds` confidential instruction.
+/// It records the insurance quote for the river of an all-in runout.
+#[arcium_callback(encrypted_ix = "insurance_odds")]
+pub fn insurance_odds_callback(
+    ctx: Context<InsuranceOddsCallback>,
+    output: ComputationOutputs<InsuranceOddsOutput>,
+) -> Result<()> {
+    let (leader, outs) = match output {
+        ComputationOutputs::Success(InsuranceOddsOutput { field_0: quote }) => quote,
+        _ => return err!(ErrorCode::InvalidAction),
+    };
+
+    // A quote that arrives after the river was revealed or cover was bought is stale.
+    let game_state = &mut ctx.accounts.game_state;
+    require!(
+        game_state.runout
+            && game_state.game_phase == GamePhase::River
+            && game_state.dealt_community_cards() == 4
+            && game_state.insured_amount == 0,
+        ErrorCode::InsuranceNotAvailable
+    );
+
+    game_state.insurance_leader = if leader < 2 { leader } else { 255 };
+    game_state.insurance_outs = outs;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::sysvar::instructions::{
+        self, construct_instructions_data, BorrowedInstruction,
+    };
+
+    /// Runs `validate_callback_ixs` as if called inside the instruction at `current` of a
+    /// transaction made of one instruction to each of `programs`, with the sysvar at `sysvar_key`.
+    fn validate_in(programs: &[Pubkey], current: u16, sysvar_key: Pubkey) -> Result<()> {
+        let transaction: Vec<BorrowedInstruction> = programs
+            .iter()
+            .map(|program_id| BorrowedInstruction {
+                program_id,
+                accounts: vec![],
+                data: &[],
+            })
+            .collect();
+        let mut data = construct_instructions_data(&transaction);
+        let len = data.len();
+        data[len - 2..].copy_from_slice(&current.to_le_bytes());
+        let mut lamports = 0;
+        let owner = Pubkey::default();
+        let sysvar = AccountInfo::new(&sysvar_key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        validate_callback_ixs(&sysvar, &crate::ID)
+    }
+
+    fn is_unauthorized(result: Result<()>) -> bool {
+        result.unwrap_err() == ErrorCode::UnauthorizedCallback.into()
+    }
+
+    #[test]
+    fn callback_delivered_by_arcium_is_accepted() {
+        assert!(validate_in(&[ID_CONST], 0, instructions::ID).is_ok());
+        assert!(validate_in(&[Pubkey::new_unique(), ID_CONST], 1, instructions::ID).is_ok());
+    }
+
+    #[test]
+    fn callback_called_directly_is_rejected() {
+        assert!(is_unauthorized(validate_in(&[crate::ID], 0, instructions::ID)));
+        // An Arcium instruction elsewhere in the transaction does not vouch for this one.
+        assert!(is_unauthorized(validate_in(&[ID_CONST, crate::ID], 1, instructions::ID)));
+    }
+
+    #[test]
+    fn forged_instructions_sysvar_is_rejected() {
+        assert!(validate_in(&[ID_CONST], 0, Pubkey::new_unique()).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+/**
+ * @description
+ * This file contains shared validation performed before queueing confidential computations
+ * on the Arcium network, and helpers for building their arguments. Failing early with a specific error is far easier to diagnose than
+ * an opaque failure inside the Arcium program's CPI.
+ *
+ * @dependencies
+ * - arcium_anchor & arcium_client: For the MXE and computation definition account types.
+ * - crate::error: Defines custom error codes.
+ */
+
+use crate::{error::ErrorCode, state::GameState};
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CircuitSource;
+
+/// Ensures the MXE account has been assigned to an MPC cluster. Without a cluster no
+/// computation can be executed, so instructions that lead to a queue call check this first.
+pub fn require_cluster_set(mxe_account: &MXEAccount) -> Result<()> {
+    require_cluster_assigned(mxe_account.cluster)
+}
+
+/// The check behind `require_cluster_set`, on the MXE's cluster offset alone.
+fn require_cluster_assigned(cluster: Option<u32>) -> Result<()> {
+    if cluster.is_none() {
+        msg!("The MXE account has no MPC cluster assigned. Deploy the MXE with a cluster offset before dealing.");
+        return err!(ErrorCode::ClusterNotSet);
+    }
+    Ok(())
+}
+
+/// Verifies that a computation definition was registered with the expected off-chain circuit hash.
+/// Called before queueing any computation so that a stale or swapped circuit is never executed.
+pub fn verify_circuit_hash(
+    comp_def_account: &ComputationDefinitionAccount,
+    expected_hash: &[u8; 32],
+) -> Result<()> {
+    match &comp_def_account.circuit_source {
+        CircuitSource::OffChain(source) => {
+            require!(source.hash == *expected_hash, ErrorCode::CircuitHashMismatch);
+            Ok(())
+        }
+        _ => err!(ErrorCode::CircuitHashMismatch),
+    }
+}
+
+/// Builds the arguments of a deal computation: the encryption key of each seat, in seat order.
+/// If either player has not registered a key, no arguments are passed and the client must
+/// supply both keys itself.
+pub fn deal_args(game_state: &GameState) -> Vec<Argument> {
+    if game_state.encryption_keys.iter().any(|key| *key == [0; 32]) {
+        return vec![];
+    }
+    game_state
+        .encryption_keys
+        .iter()
+        .map(|key| Argument::ArcisPubkey(*key))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unassigned_cluster_is_rejected() {
+        assert_eq!(
+            require_cluster_assigned(None).unwrap_err(),
+            ErrorCode::ClusterNotSet.into()
+        );
+    }
+
+    #[test]
+    fn assigned_cluster_is_accepted() {
+        assert!(require_cluster_assigned(Some(1116522165)).is_ok());
+    }
+}
\ No newline at end of file
@@ -42,6 +42,168 @@ pub enum ErrorCode {
     #[msg("The turn timer has not yet expired.")]
     TimerNotExpired,
 
-    #[msg("Cluster not set")]
+    #[msg("The MXE account has no MPC cluster assigned.")]
     ClusterNotSet,
+
+    #[msg("The action would exceed the table's per-hand investment cap.")]
+    HandCapExceeded,
+
+    #[msg("The computation definition does not match the expected circuit hash.")]
+    CircuitHashMismatch,
+
+    #[msg("The waitlist for this table is full.")]
+    WaitlistFull,
+
+    #[msg("Another challenger is ahead of this player on the waitlist.")]
+    NotNextOnWaitlist,
+
+    #[msg("There is no completed hand awaiting settlement.")]
+    NothingToSettle,
+
+    #[msg("The referrer token account is missing or does not belong to the winner's referrer.")]
+    InvalidReferrerAccount,
+
+    #[msg("A player's stack cannot cover the blind they must post.")]
+    CannotCoverBlind,
+
+    #[msg("Limping is not allowed at this table: raise or fold.")]
+    LimpNotAllowed,
+
+    #[msg("There is no rake-back to claim.")]
+    NothingToClaim,
+
+    #[msg("The community cards are inconsistent with the current game phase.")]
+    BoardPhaseMismatch,
+
+    #[msg("The current street's community cards have already been revealed.")]
+    NoCardsToReveal,
+
+    #[msg("A player is sitting out, so no hand can be dealt.")]
+    PlayerSittingOut,
+
+    #[msg("All-in insurance is not available for this hand right now.")]
+    InsuranceNotAvailable,
+
+    #[msg("The insurance pool or the insured player's token account is missing or invalid.")]
+    InvalidInsuranceAccount,
+
+    #[msg("The player has reached the table's rebuy limit.")]
+    RebuyLimitReached,
+
+    #[msg("The hand has not been dealt yet.")]
+    HandNotDealt,
+
+    #[msg("The stored encrypted deck does not match its commitment.")]
+    DeckCommitmentMismatch,
+
+    #[msg("Auto-rebuy is not switched on for this seat.")]
+    AutoRebuyUnavailable,
+
+    #[msg("This table is being consolidated; its players must move to the designated table.")]
+    TableMustMove,
+
+    #[msg("This table does not allow the button to straddle.")]
+    StraddleNotAllowed,
+
+    #[msg("The table has reached its maximum number of hands per hour.")]
+    PaceLimitExceeded,
+
+    #[msg("The accounts needed to pay the time charge are missing or invalid.")]
+    InvalidTimeChargeAccount,
+
+    #[msg("Community cards can only be revealed on the flop, turn or river.")]
+    InvalidRevealPhase,
+
+    #[msg("All five community cards must be revealed before the showdown.")]
+    BoardIncomplete,
+
+    #[msg("This hand is still in play or within its retention period.")]
+    HandStillRetained,
+
+    #[msg("A payout structure must pay between 1 and 10 places, with shares summing to 100%.")]
+    InvalidPayoutStructure,
+
+    #[msg("This tournament's prize pool has already been paid out.")]
+    TournamentAlreadySettled,
+
+    #[msg("The rake cannot exceed the pot.")]
+    RakeExceedsPot,
+
+    #[msg("The runout delay has not passed since the last street was revealed.")]
+    RunoutDelayNotElapsed,
+
+    #[msg("The callback is for a computation the hand has since superseded.")]
+    StaleCallback,
+
+    #[msg("The table does not allow another straddle level.")]
+    StraddleCapReached,
+
+    #[msg("A bet must be at least the big blind unless it is all-in.")]
+    BetBelowMinimum,
+
+    #[msg("The settlement does not account for every chip in the pot.")]
+    SettlementImbalance,
+
+    #[msg("A hand is already in progress at this table.")]
+    HandAlreadyInProgress,
+
+    #[msg("There is no hand in progress at this table.")]
+    NoHandInProgress,
+
+    #[msg("The buy-in exceeds the platform's maximum buy-in.")]
+    ExceedsPlatformCap,
+
+    #[msg("The table is not part of this fast-fold pool.")]
+    TableNotInPool,
+
+    #[msg("The fast-fold pool has no room for another table.")]
+    FastFoldPoolFull,
+
+    #[msg("This seat sent an emote too recently.")]
+    EmoteRateLimited,
+
+    #[msg("The buy-in amount is outside the table's buy-in range.")]
+    InvalidBuyInAmount,
+
+    #[msg("The street's reveal is still in flight; it can only be requested again after it fails.")]
+    RevealInFlight,
+
+    #[msg("Players cannot act during an all-in runout; the remaining cards are dealt without betting.")]
+    ActionNotAllowedInRunout,
+
+    #[msg("The last hand was not voided by a misdeal.")]
+    NoMisdeal,
+
+    #[msg("The token mint already has the maximum number of open tables.")]
+    MintTableCapReached,
+
+    #[msg("The signer is not the administrator proposed by transfer_admin.")]
+    NotPendingAdmin,
+
+    #[msg("The platform is paused; no new hands can be dealt.")]
+    PlatformPaused,
+
+    #[msg("The rake percentage exceeds the platform maximum.")]
+    RakeTooHigh,
+
+    #[msg("An arithmetic operation overflowed.")]
+    ArithmeticOverflow,
+
+    #[msg("The token account does not use the table's token mint.")]
+    TokenMintMismatch,
+
+    #[msg("Tables cannot be time-raked until a time charge period has been configured.")]
+    TimeChargeNotConfigured,
+
+    #[msg("Only tables using \"winner stays\" have a waitlist.")]
+    WaitlistDisabled,
+
+    #[msg("The per-hand investment cap must be at least the big blind.")]
+    HandCapBelowBigBlind,
+
+    #[msg("This hand's deal has already been queued.")]
+    DealAlreadyQueued,
+
+    #[msg("Callbacks can only be delivered by the Arcium program.")]
+    UnauthorizedCallback,
 }
\ No newline at end of file
@@ -0,0 +1,252 @@
+/**
+ * @description
+ * This file defines the events emitted by the Veridian Hold'em program.
+ * Events are written to the transaction logs and allow off-chain clients and indexers
+ * to react to important state changes without polling accounts.
+ *
+ * @dependencies
+ * - anchor_lang: The core Anchor framework library.
+ * - crate::state: Defines shared types carried by events.
+ */
+
+use crate::state::{CircuitHashes, GamePhase, MintExposure, MAX_PLAYERS};
+use anchor_lang::prelude::*;
+
+/// Emitted when a proposed administrator accepts the role and replaces the previous admin.
+#[event]
+pub struct AdminTransferred {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+/// Emitted when the admin updates the expected hashes of the published Arcis circuits.
+#[event]
+pub struct CircuitHashesUpdated {
+    /// The admin who performed the update.
+    pub admin: Pubkey,
+    /// The newly expected circuit hashes.
+    pub hashes: CircuitHashes,
+}
+
+/// Emitted when a player who folded chooses to show one or both of their hole cards.
+#[event]
+pub struct CardsShown {
+    pub table_id: u64,
+    pub player: Pubkey,
+    /// The shown hole cards; a card the player kept hidden is `255`.
+    pub cards: [u8; 2],
+}
+
+/// Emitted with the result of a dry-run showdown. No chips move and no state changes.
+#[event]
+pub struct ShowdownPreviewed {
+    pub table_id: u64,
+    /// 0 or 1 for a single winner, 2 for a split pot.
+    pub winner_index: u8,
+}
+
+/// Emitted when a player leaves the table, summarising their session.
+#[event]
+pub struct SessionEnded {
+    pub table_id: u64,
+    pub player: Pubkey,
+    /// Everything the player bought in for during the session.
+    pub total_buy_in: u64,
+    /// Everything the player withdrew during the session before leaving.
+    pub cashed_out: u64,
+    /// The stack withdrawn on leaving.
+    pub final_withdrawal: u64,
+    /// `cashed_out + final_withdrawal - total_buy_in`, saturated to the range of an `i64`.
+    pub net: i64,
+}
+
+/// Emitted when a hand ends with its pot awarded, either to the player left in after a fold
+/// (including a timeout or a fast-fold) or at showdown by `settle_hand`. A voided hand emits
+/// `HandVoided` instead.
+#[event]
+pub struct HandResult {
+    pub table_id: u64,
+    /// The seat awarded the pot, or 2 for a split pot.
+    pub winner_index: u8,
+    /// The chips paid out to the winner (or split), after the rake.
+    pub pot_awarded: u64,
+    /// The rake taken from the pot. A pot won by a fold is never raked.
+    pub rake_taken: u64,
+    pub was_showdown: bool,
+    /// The winning hand's category at a Hold'em showdown, from 0 (high card) to 8 (straight
+    /// flush). The cards themselves stay hidden. `None` for a pot won by a fold or a five-card
+    /// draw showdown.
+    pub winning_rank: Option<u8>,
+}
+
+/// Emitted at tables with `reveal_events` on when a street's community cards are requested,
+/// so clients can start animating the reveal. A retried reveal is announced again.
+#[event]
+pub struct CardRevealStarted {
+    pub table_id: u64,
+    /// The street being revealed.
+    pub game_phase: GamePhase,
+    /// 1 for the first request of the street, higher for retries.
+    pub attempt: u8,
+}
+
+/// Emitted at tables with `reveal_events` on once a street announced by `CardRevealStarted`
+/// is on the board.
+#[event]
+pub struct CardRevealCompleted {
+    pub table_id: u64,
+    /// The street revealed.
+    pub game_phase: GamePhase,
+    /// The street's cards, in board order.
+    pub cards: Vec<u8>,
+}
+
+/// Emitted when a player leaving while ahead soon after sitting down pays the leave penalty
+/// to their opponent (see `Config::leave_penalty_bps`).
+#[event]
+pub struct LeavePenaltyPaid {
+    pub table_id: u64,
+    pub player: Pubkey,
+    pub opponent: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when a "winner stays" match ends and the busted player's seat is opened
+/// for the next challenger.
+#[event]
+pub struct ChallengerSeatOpened {
+    pub table_id: u64,
+    /// The player who won the match and keeps their seat.
+    pub winner: Pubkey,
+    /// The seat index now available to the next challenger.
+    pub open_seat: u8,
+}
+
+/// Emitted when a hand is voided: a deal failed its integrity checks before any chips went in,
+/// or a community card reveal failed more often than the platform allows, in which case the
+/// chips were returned. The table returns to `HandOver` with the same button, ready to deal again.
+#[event]
+pub struct HandVoided {
+    pub table_id: u64,
+    /// The dealer of the voided hand.
+    pub dealer: Pubkey,
+}
+
+/// Emitted when a player moves their seat and stack to the table designated by the operator.
+#[event]
+pub struct SeatMoved {
+    pub player: Pubkey,
+    pub from_table_id: u64,
+    pub to_table_id: u64,
+    /// The stack moved from one escrow to the other.
+    pub stack: u64,
+}
+
+/// Emitted when a seated player pays the time charge at a time-raked table.
+#[event]
+pub struct TimeCharged {
+    pub table_id: u64,
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when a tournament's prize pool is paid out.
+#[event]
+pub struct TournamentSettled {
+    pub tournament_id: u64,
+    /// The token accounts paid, first place first.
+    pub recipients: Vec<Pubkey>,
+    /// The amount paid to each recipient.
+    pub amounts: Vec<u64>,
+}
+
+/// Emitted when a player claims their accrued rake-back from the treasury.
+#[event]
+pub struct RakebackClaimed {
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when a player forces the fold of an opponent whose turn timer expired.
+#[event]
+pub struct OpponentTimeoutForced {
+    pub table_id: u64,
+    /// The waiting player who forced the timeout.
+    pub forced_by: Pubkey,
+    /// The player who timed out and was folded.
+    pub timed_out_player: Pubkey,
+}
+
+/// Emitted when the first button of a match is drawn from a slot hash. The dealer is seat
+/// `sha256("veridian:slot_hash_seed" || slot_hash || player 0 || player 1 || their
+/// encryption keys)[0] % 2`.
+#[event]
+pub struct ButtonDrawn {
+    pub table_id: u64,
+    /// The slot whose hash was used.
+    pub slot: u64,
+    pub slot_hash: [u8; 32],
+    pub dealer_index: u8,
+}
+
+/// Emitted when a seated player sends an emote for clients to render.
+#[event]
+pub struct Emote {
+    pub table_id: u64,
+    pub seat: u8,
+    /// The emote's code. Its meaning is up to the clients.
+    pub code: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted when a called all-in closes the betting. The rest of the hand needs no player
+/// decisions: anyone can drive the remaining reveals, the showdown and the settlement with the
+/// permissionless request and settle instructions.
+#[event]
+pub struct AutoProgressRequired {
+    pub table_id: u64,
+    /// The phase the hand moved to when the betting closed.
+    pub game_phase: GamePhase,
+}
+
+/// Emitted by `reconcile_table_state` with the table's `is_active` flag before and after it was
+/// recomputed. The two differ only if the flag had drifted.
+#[event]
+pub struct TableStateReconciled {
+    pub table_id: u64,
+    pub was_active: bool,
+    pub is_active: bool,
+}
+
+/// Emitted whenever a mint's risk-isolation totals change, for monitoring exposure per token.
+#[event]
+pub struct MintExposureUpdated {
+    pub token_mint: Pubkey,
+    pub max_tables: u32,
+    pub active_tables: u32,
+    pub escrowed: u64,
+}
+
+impl From<&MintExposure> for MintExposureUpdated {
+    fn from(exposure: &MintExposure) -> Self {
+        Self {
+            token_mint: exposure.token_mint,
+            max_tables: exposure.max_tables,
+            active_tables: exposure.active_tables,
+            escrowed: exposure.escrowed,
+        }
+    }
+}
+
+/// Emitted by the `determine_winner` callback with the showdown's result, before it is settled.
+#[event]
+pub struct ShowdownResolved {
+    pub table_id: u64,
+    /// A bit per seat holding the best hand; more than one bit set is a split pot.
+    pub winner_mask: u8,
+    /// The best hand's category, from 0 (high card) to 8 (straight flush).
+    pub winning_rank: u8,
+    /// For each winning seat, the five cards of its best hand as indices into its seven cards:
+    /// 0-1 are its hole cards and 2-6 the board. Losing seats' entries are all 255.
+    pub winning_cards: [[u8; 5]; MAX_PLAYERS],
+}
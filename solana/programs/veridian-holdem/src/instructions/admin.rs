@@ -1,12 +1,15 @@
 /**
  * @description
  * This file defines the account contexts for administrative instructions related to the
- * Veridian Hold'em platform. These instructions manage the global `Config` account.
+ * Veridian Hold'em platform. These instructions manage the global `Config` account and the
+ * operator-controlled limits of individual tables.
  *
  * @dependencies
  * - anchor_lang: The core Anchor framework library.
- * - crate::state: Defines the `Config` account structure.
+ * - crate::state: Defines the `Config` and `MintExposure` account structures.
+ * - anchor_spl: Provides the `Mint` account type.
  * - crate::error: Defines custom error codes for validation.
+ * - crate::events: Defines the events emitted by admin instructions.
  *
  * @notes
  * - The use of Anchor constraints (`constraint = ...`) is critical for enforcing on-chain
@@ -14,8 +17,13 @@
  */
 
 use crate::error::ErrorCode;
-use crate::state::Config;
+use crate::events::{AdminTransferred, CircuitHashesUpdated, MintExposureUpdated};
+use crate::state::{
+    CircuitHashes, Config, GamePhase, GameState, MintExposure, RakeTier, RakeType, TableConfig,
+    DEFAULT_MAX_REVEAL_RETRIES, MAX_BPS, MAX_LEAVE_PENALTY_BPS, MAX_RAKE_PERCENTAGE, MAX_RAKE_TIERS,
+};
 use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
 
 /// Defines the accounts required to initialize the global configuration PDA.
 /// This instruction should only be executed once during the initial deployment and setup
@@ -63,6 +71,287 @@ pub struct SetRakeConfig<'info> {
     pub admin: Signer<'info>,
 }
 
+/// Defines the accounts required to update the all-in insurance settings in the global
+/// configuration PDA. Only the current admin may update them.
+#[derive(Accounts)]
+pub struct SetInsuranceConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required to update the street from which pots are raked in the global
+/// configuration PDA. Only the current admin may update it.
+#[derive(Accounts)]
+pub struct SetRakeMinStreet<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required to update the smallest raked pot in the global configuration
+/// PDA. Only the current admin may update it.
+#[derive(Accounts)]
+pub struct SetRakeMinPot<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required to update how often a failed community card reveal may be
+/// retried in the global configuration PDA. Only the current admin may update it.
+#[derive(Accounts)]
+pub struct SetMaxRevealRetries<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required to update the early-leave penalty in the global configuration
+/// PDA. Only the current admin may update it.
+#[derive(Accounts)]
+pub struct SetLeavePenalty<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required for the admin to set a table's hourly pace limit.
+#[derive(Accounts)]
+pub struct SetMaxHandsPerHour<'info> {
+    #[account(
+        mut,
+        seeds = [b"table_config", &table_config.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required for the admin to cap the number of open tables for a mint.
+/// The mint's `MintExposure` is created on first use, paid for by the admin.
+#[derive(Accounts)]
+pub struct SetMintTableCap<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MintExposure::INIT_SPACE,
+        seeds = [b"mint_exposure", token_mint.key().as_ref()],
+        bump
+    )]
+    pub mint_exposure: Account<'info, MintExposure>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Defines the accounts required for the admin to pause or resume new hands.
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required for the admin to propose a new administrator.
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required for the proposed administrator to take over.
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.pending_admin == new_admin.key() @ ErrorCode::NotPendingAdmin
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the administrator proposed by `transfer_admin`.
+    pub new_admin: Signer<'info>,
+}
+
+/// Defines the accounts required to update the time-charge settings in the global
+/// configuration PDA. Only the current admin may update them.
+#[derive(Accounts)]
+pub struct SetTimeChargeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required to update the platform-wide buy-in cap in the global
+/// configuration PDA. Only the current admin may update it.
+#[derive(Accounts)]
+pub struct SetMaxBuyInCap<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required for the admin to choose how a table is charged.
+#[derive(Accounts)]
+pub struct SetTableRakeType<'info> {
+    #[account(
+        mut,
+        seeds = [b"table_config", &table_config.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required for the admin to change a table's blinds between hands.
+#[derive(Accounts)]
+pub struct SetTableBlinds<'info> {
+    #[account(
+        mut,
+        seeds = [b"table_config", &table_config.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    /// The table's `GameState`, which must be between hands.
+    #[account(
+        seeds = [b"game", &table_config.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required to update the expected circuit hashes in the global
+/// configuration PDA. Used when circuits are re-published without redeploying the program.
+#[derive(Accounts)]
+pub struct SetCircuitHashes<'info> {
+    /// The global `Config` account to be modified. Only the current admin may update it.
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Checks a rake setting shared by `initialize_config` and `set_rake_config`. The percentage
+/// may not exceed `MAX_RAKE_PERCENTAGE`, and a cap is only meaningful alongside a rake.
+fn validate_rake(rake_percentage: u8, rake_cap: u64) -> Result<()> {
+    require!(rake_percentage <= MAX_RAKE_PERCENTAGE, ErrorCode::RakeTooHigh);
+    require!(rake_percentage > 0 || rake_cap == 0, ErrorCode::InvalidAction);
+    Ok(())
+}
+
 /// The handler function for the `initialize_config` instruction.
 pub fn initialize_config(
     ctx: Context<InitializeConfig>,
@@ -70,11 +359,27 @@ pub fn initialize_config(
     rake_percentage: u8,
     rake_cap: u64,
 ) -> Result<()> {
+    validate_rake(rake_percentage, rake_cap)?;
     let config = &mut ctx.accounts.config;
     config.admin = ctx.accounts.admin.key();
     config.treasury_wallet = treasury_wallet;
     config.rake_percentage = rake_percentage;
     config.rake_cap = rake_cap;
+    config.referrer_bps = 0;
+    config.rakeback_bps = 0;
+    config.insurance_markup_bps = 0;
+    config.time_charge = 0;
+    config.time_charge_period_seconds = 0;
+    config.circuit_hashes = CircuitHashes::default();
+    config.max_buy_in_cap = 0;
+    config.rake_min_street = 0;
+    config.max_reveal_retries = DEFAULT_MAX_REVEAL_RETRIES;
+    config.leave_penalty_bps = 0;
+    config.leave_penalty_hands = 0;
+    config.pending_admin = Pubkey::default();
+    config.paused = false;
+    config.rake_tiers = Vec::new();
+    config.rake_min_pot = 0;
     Ok(())
 }
 
@@ -83,9 +388,192 @@ pub fn set_rake_config(
     ctx: Context<SetRakeConfig>,
     rake_percentage: u8,
     rake_cap: u64,
+    referrer_bps: u16,
+    rakeback_bps: u16,
 ) -> Result<()> {
+    validate_rake(rake_percentage, rake_cap)?;
+    require!(referrer_bps <= MAX_BPS, ErrorCode::InvalidAction);
+    require!(rakeback_bps <= MAX_BPS, ErrorCode::InvalidAction);
+
     let config = &mut ctx.accounts.config;
     config.rake_percentage = rake_percentage;
     config.rake_cap = rake_cap;
+    config.referrer_bps = referrer_bps;
+    config.rakeback_bps = rakeback_bps;
+    Ok(())
+}
+
+/// The handler function for the `set_rake_tiers` instruction.
+/// Each tier is checked like the flat rake, and thresholds must strictly increase so every pot
+/// falls in exactly one tier. An empty list returns the platform to the flat rake.
+pub fn set_rake_tiers(ctx: Context<SetRakeConfig>, rake_tiers: Vec<RakeTier>) -> Result<()> {
+    require!(rake_tiers.len() <= MAX_RAKE_TIERS, ErrorCode::InvalidAction);
+    for tier in &rake_tiers {
+        validate_rake(tier.percentage, tier.cap)?;
+    }
+    require!(
+        rake_tiers.windows(2).all(|pair| pair[0].pot_threshold < pair[1].pot_threshold),
+        ErrorCode::InvalidAction
+    );
+    ctx.accounts.config.rake_tiers = rake_tiers;
+    Ok(())
+}
+
+/// The handler function for the `set_insurance_config` instruction.
+pub fn set_insurance_config(ctx: Context<SetInsuranceConfig>, insurance_markup_bps: u16) -> Result<()> {
+    require!(insurance_markup_bps <= MAX_BPS, ErrorCode::InvalidAction);
+    ctx.accounts.config.insurance_markup_bps = insurance_markup_bps;
+    Ok(())
+}
+
+/// The handler function for the `set_rake_min_street` instruction.
+/// 1 is "no flop, no drop"; 2 only rakes hands whose betting reached the turn.
+pub fn set_rake_min_street(ctx: Context<SetRakeMinStreet>, rake_min_street: u8) -> Result<()> {
+    require!(rake_min_street <= GamePhase::River.betting_street(), ErrorCode::InvalidAction);
+    ctx.accounts.config.rake_min_street = rake_min_street;
+    Ok(())
+}
+
+/// The handler function for the `set_rake_min_pot` instruction.
+pub fn set_rake_min_pot(ctx: Context<SetRakeMinPot>, rake_min_pot: u64) -> Result<()> {
+    ctx.accounts.config.rake_min_pot = rake_min_pot;
+    Ok(())
+}
+
+/// The handler function for the `set_max_reveal_retries` instruction.
+/// 0 voids the hand at the first reveal that fails.
+pub fn set_max_reveal_retries(ctx: Context<SetMaxRevealRetries>, max_reveal_retries: u8) -> Result<()> {
+    ctx.accounts.config.max_reveal_retries = max_reveal_retries;
+    Ok(())
+}
+
+/// The handler function for the `set_max_hands_per_hour` instruction.
+/// Sets the maximum number of hands the table may deal in any rolling hour. 0 removes the cap.
+pub fn set_max_hands_per_hour(ctx: Context<SetMaxHandsPerHour>, max_hands_per_hour: u16) -> Result<()> {
+    ctx.accounts.table_config.max_hands_per_hour = max_hands_per_hour;
+    Ok(())
+}
+
+/// The handler function for the `set_mint_table_cap` instruction.
+/// Sets the most tables that may be open at once for the mint. 0 removes the cap. Tables already
+/// open above a lowered cap are unaffected; only new tables are refused.
+pub fn set_mint_table_cap(ctx: Context<SetMintTableCap>, max_tables: u32) -> Result<()> {
+    let exposure = &mut ctx.accounts.mint_exposure;
+    exposure.token_mint = ctx.accounts.token_mint.key();
+    exposure.max_tables = max_tables;
+    emit!(MintExposureUpdated::from(&**exposure));
+    Ok(())
+}
+
+/// The handler function for the `set_paused` instruction.
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+    Ok(())
+}
+
+/// The handler function for the `transfer_admin` instruction.
+/// Proposes `new_admin` as the next administrator. Nothing changes until they accept with
+/// `accept_admin`, so a mistyped or unowned key cannot take control. A later proposal replaces
+/// a pending one, and proposing `Pubkey::default()` cancels it.
+pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.config.pending_admin = new_admin;
+    Ok(())
+}
+
+/// The handler function for the `accept_admin` instruction.
+/// Makes the pending administrator the admin and clears the proposal.
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+    config.admin = ctx.accounts.new_admin.key();
+    config.pending_admin = Pubkey::default();
+
+    emit!(AdminTransferred {
+        previous_admin,
+        new_admin: config.admin,
+    });
+
+    Ok(())
+}
+
+/// The handler function for the `set_time_charge_config` instruction.
+/// `time_charge` is charged to each seated player every `period_seconds` at time-raked tables.
+pub fn set_time_charge_config(
+    ctx: Context<SetTimeChargeConfig>,
+    time_charge: u64,
+    period_seconds: i64,
+) -> Result<()> {
+    require!(period_seconds > 0, ErrorCode::InvalidAction);
+    let config = &mut ctx.accounts.config;
+    config.time_charge = time_charge;
+    config.time_charge_period_seconds = period_seconds;
+    Ok(())
+}
+
+/// The handler function for the `set_max_buy_in_cap` instruction.
+/// Caps the buy-in of newly created tables and of joins at existing ones. 0 removes the cap.
+pub fn set_max_buy_in_cap(ctx: Context<SetMaxBuyInCap>, max_buy_in_cap: u64) -> Result<()> {
+    ctx.accounts.config.max_buy_in_cap = max_buy_in_cap;
+    Ok(())
+}
+
+/// The handler function for the `set_table_rake_type` instruction.
+pub fn set_table_rake_type(ctx: Context<SetTableRakeType>, rake_type: RakeType) -> Result<()> {
+    // A time-raked table charges per period, so the platform must have set one first.
+    require!(
+        rake_type != RakeType::Time || ctx.accounts.config.time_charge_period_seconds > 0,
+        ErrorCode::TimeChargeNotConfigured
+    );
+    ctx.accounts.table_config.rake_type = rake_type;
+    Ok(())
+}
+
+/// The handler function for the `set_table_blinds` instruction.
+/// Raises (or lowers) the blinds, e.g. at a tournament level change. Takes effect from the next
+/// hand, along with anything derived from them such as the button ante.
+pub fn set_table_blinds(ctx: Context<SetTableBlinds>, small_blind: u64, big_blind: u64) -> Result<()> {
+    let game_state = &ctx.accounts.game_state;
+    require!(
+        game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::HandNotOver
+    );
+    let table_config = &mut ctx.accounts.table_config;
+    require!(small_blind <= big_blind, ErrorCode::InvalidAction);
+    require!(
+        table_config.hand_cap == 0 || table_config.hand_cap >= big_blind,
+        ErrorCode::HandCapBelowBigBlind
+    );
+    require!(
+        big_blind.checked_mul(table_config.button_ante_multiplier as u64).is_some(),
+        ErrorCode::ArithmeticOverflow
+    );
+    table_config.small_blind = small_blind;
+    table_config.big_blind = big_blind;
+    Ok(())
+}
+
+/// The handler function for the `set_circuit_hashes` instruction.
+pub fn set_circuit_hashes(ctx: Context<SetCircuitHashes>, hashes: CircuitHashes) -> Result<()> {
+    ctx.accounts.config.circuit_hashes = hashes;
+
+    emit!(CircuitHashesUpdated {
+        admin: ctx.accounts.admin.key(),
+        hashes,
+    });
+
+    Ok(())
+}
+
+/// The handler function for the `set_leave_penalty` instruction.
+/// A `leave_penalty_bps` of 0 turns the penalty off.
+pub fn set_leave_penalty(
+    ctx: Context<SetLeavePenalty>,
+    leave_penalty_bps: u16,
+    leave_penalty_hands: u32,
+) -> Result<()> {
+    require!(leave_penalty_bps <= MAX_LEAVE_PENALTY_BPS, ErrorCode::InvalidAction);
+    let config = &mut ctx.accounts.config;
+    config.leave_penalty_bps = leave_penalty_bps;
+    config.leave_penalty_hands = leave_penalty_hands;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,383 @@
+/**
+ * @description
+ * This file contains the read-only `get_computation_args_spec` instruction, which describes the
+ * arguments the next confidential computation at a table expects. Clients building an Arcium
+ * queue call read the spec from the transaction's return data (or simulate the instruction)
+ * instead of hard-coding each circuit's argument layout.
+ *
+ * @key_features
+ * - Picks the next computation from the game phase and the table's variant.
+ * - For every argument, states its type and where it comes from: passed by the program itself,
+ *   chosen by the caller, or read from a byte range of the `HandState` account.
+ * - The spec is versioned so clients can detect layout changes.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState`, `HandState` and `TableConfig` account structures.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::state::{GamePhase, GameState, GameVariant, HandState, TableConfig};
+use anchor_lang::prelude::*;
+
+/// The current version of `ComputationArgsSpec`. Bumped whenever its layout or meaning changes.
+pub const COMPUTATION_ARGS_SPEC_VERSION: u8 = 3;
+
+/// The confidential computation a table is waiting for.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum NextComputation {
+    /// No computation can be queued in the current phase.
+    None,
+    /// `shuffle_and_deal`, queued by `deal_new_hand_queue`.
+    ShuffleAndDeal,
+    /// `shuffle_and_deal_draw`, queued by `deal_draw_hand_queue`.
+    ShuffleAndDealDraw,
+    /// `reveal_community_cards`, queued by `request_community_cards`.
+    RevealCommunityCards,
+    /// `draw_cards`, queued by `request_draw`.
+    DrawCards,
+    /// `determine_winner`, queued by `request_showdown`.
+    DetermineWinner,
+    /// `determine_winner_draw`, queued by `request_showdown_draw`.
+    DetermineWinnerDraw,
+    /// `reveal_all_community_cards`, queued by `request_run_out`.
+    RevealAllCommunityCards,
+}
+
+/// The type of a computation argument, in terms of the Arcium arguments that make it up.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// An x25519 public key (`Argument::ArcisPubkey`).
+    ArcisPubkey,
+    /// A single plaintext byte (`Argument::PlaintextU8`).
+    PlaintextU8,
+    /// A plaintext byte array, passed as `len` consecutive `Argument::PlaintextU8`.
+    PlaintextU8Array { len: u8 },
+    /// A value encrypted to a player (`Enc<Shared, _>`): public key, nonce, then ciphertexts.
+    EncryptedShared,
+    /// A value encrypted to the MXE (`Enc<Mxe, _>`): nonce, then ciphertexts.
+    EncryptedMxe,
+}
+
+/// Where the client sources a computation argument from.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ArgSource {
+    /// The queueing instruction passes the argument itself; the client supplies nothing.
+    Program,
+    /// The argument is an instruction argument chosen by the caller.
+    Caller,
+    /// The serialized ciphertext stored in the `HandState` account, at `offset` bytes into the
+    /// account data (discriminator included) and `length` bytes long.
+    HandState { offset: u32, length: u32 },
+    /// The public board, `GameState::community_cards`.
+    CommunityCards,
+}
+
+/// A single argument of a computation.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ArgSpec {
+    pub kind: ArgKind,
+    pub source: ArgSource,
+}
+
+/// The argument contract of the next computation at a table, returned by
+/// `get_computation_args_spec`. Arguments are listed in the circuit's parameter order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ComputationArgsSpec {
+    /// The spec layout version, `COMPUTATION_ARGS_SPEC_VERSION`.
+    pub version: u8,
+    /// The game phase the spec was built for.
+    pub phase: GamePhase,
+    pub computation: NextComputation,
+    pub args: Vec<ArgSpec>,
+}
+
+/// Defines the accounts required to read the computation argument spec for a table.
+#[derive(Accounts)]
+pub struct GetComputationArgsSpec<'info> {
+    #[account(
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, whose variant decides which circuits are used.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+}
+
+/// The `HandState` byte range holding a player's encrypted hole cards.
+fn hole_cards(seat: usize) -> ArgSpec {
+    ArgSpec {
+        kind: ArgKind::EncryptedShared,
+        source: ArgSource::HandState {
+            offset: 8 + seat as u32 * HandState::HOLE_CARDS_LEN,
+            length: HandState::HOLE_CARDS_LEN,
+        },
+    }
+}
+
+/// The `HandState` byte range holding a player's encrypted five-card draw hand.
+fn draw_hand(seat: usize) -> ArgSpec {
+    ArgSpec {
+        kind: ArgKind::EncryptedShared,
+        source: ArgSource::HandState {
+            offset: HandState::DRAW_HANDS_OFFSET + (seat * 256) as u32,
+            length: 256,
+        },
+    }
+}
+
+/// Handler for the `get_computation_args_spec` instruction.
+pub fn get_computation_args_spec(ctx: Context<GetComputationArgsSpec>) -> Result<ComputationArgsSpec> {
+    Ok(computation_args_spec(&ctx.accounts.game_state, &ctx.accounts.table_config))
+}
+
+/// Builds the argument spec of the next computation at a table in its current state.
+fn computation_args_spec(game_state: &GameState, table_config: &TableConfig) -> ComputationArgsSpec {
+    let variant = table_config.variant;
+
+    let deck = |source| ArgSpec {
+        kind: ArgKind::EncryptedMxe,
+        source,
+    };
+    let stored_deck = deck(ArgSource::HandState {
+        offset: HandState::DECK_OFFSET,
+        length: HandState::DECK_NONCE_LEN + HandState::DECK_CIPHERTEXTS_LEN,
+    });
+
+    let (computation, args) = match (game_state.game_phase, variant) {
+        (GamePhase::Dealing, _) => {
+            // The deal queue passes the seated players' keys when both registered one.
+            let key_source = if game_state.encryption_keys.iter().all(|key| *key != [0; 32]) {
+                ArgSource::Program
+            } else {
+                ArgSource::Caller
+            };
+            let key = ArgSpec {
+                kind: ArgKind::ArcisPubkey,
+                source: key_source,
+            };
+            let computation = match variant {
+                GameVariant::Holdem => NextComputation::ShuffleAndDeal,
+                GameVariant::FiveCardDraw => NextComputation::ShuffleAndDealDraw,
+            };
+            (computation, vec![key, key])
+        }
+        (GamePhase::Flop | GamePhase::Turn | GamePhase::River, GameVariant::Holdem)
+            if game_state.cards_before_street() == Some(game_state.dealt_community_cards()) =>
+        {
+            // An all-in runout without a delay reveals the rest of the board at once.
+            let computation = if game_state.runout
+                && table_config.runout_delay_seconds == 0
+            {
+                NextComputation::RevealAllCommunityCards
+            } else {
+                NextComputation::RevealCommunityCards
+            };
+            (
+                computation,
+                vec![
+                    deck(ArgSource::Program),
+                    ArgSpec {
+                        kind: ArgKind::PlaintextU8,
+                        source: ArgSource::Program,
+                    },
+                ],
+            )
+        }
+        (GamePhase::Draw, GameVariant::FiveCardDraw) => (
+            NextComputation::DrawCards,
+            vec![
+                draw_hand(game_state.current_turn_index as usize),
+                stored_deck,
+                ArgSpec {
+                    kind: ArgKind::PlaintextU8,
+                    source: ArgSource::Caller,
+                },
+            ],
+        ),
+        (GamePhase::Showdown, GameVariant::Holdem) => (
+            NextComputation::DetermineWinner,
+            vec![
+                hole_cards(0),
+                hole_cards(1),
+                ArgSpec {
+                    kind: ArgKind::PlaintextU8Array { len: 5 },
+                    source: ArgSource::CommunityCards,
+                },
+            ],
+        ),
+        (GamePhase::Showdown, GameVariant::FiveCardDraw) => {
+            (NextComputation::DetermineWinnerDraw, vec![draw_hand(0), draw_hand(1)])
+        }
+        _ => (NextComputation::None, vec![]),
+    };
+
+    ComputationArgsSpec {
+        version: COMPUTATION_ARGS_SPEC_VERSION,
+        phase: game_state.game_phase,
+        computation,
+        args,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game_state(phase: GamePhase) -> GameState {
+        let mut game_state =
+            GameState::deserialize(&mut &vec![0u8; GameState::INIT_SPACE][..]).unwrap();
+        game_state.game_phase = phase;
+        game_state.community_cards = [255; 5];
+        game_state
+    }
+
+    fn table_config(variant: GameVariant) -> TableConfig {
+        let mut table_config =
+            TableConfig::deserialize(&mut &vec![0u8; TableConfig::INIT_SPACE][..]).unwrap();
+        table_config.variant = variant;
+        table_config
+    }
+
+    fn program(kind: ArgKind) -> ArgSpec {
+        ArgSpec {
+            kind,
+            source: ArgSource::Program,
+        }
+    }
+
+    fn stored(kind: ArgKind, offset: u32, length: u32) -> ArgSpec {
+        ArgSpec {
+            kind,
+            source: ArgSource::HandState { offset, length },
+        }
+    }
+
+    #[test]
+    fn deal_takes_the_players_keys_from_the_program_once_both_are_registered() {
+        let mut game_state = game_state(GamePhase::Dealing);
+        let caller_key = ArgSpec {
+            kind: ArgKind::ArcisPubkey,
+            source: ArgSource::Caller,
+        };
+
+        let spec = computation_args_spec(&game_state, &table_config(GameVariant::Holdem));
+        assert!(spec.version == COMPUTATION_ARGS_SPEC_VERSION && spec.phase == GamePhase::Dealing);
+        assert!(spec.computation == NextComputation::ShuffleAndDeal);
+        assert!(spec.args == vec![caller_key, caller_key]);
+
+        game_state.encryption_keys = [[1; 32], [2; 32]];
+        let spec = computation_args_spec(&game_state, &table_config(GameVariant::FiveCardDraw));
+        assert!(spec.computation == NextComputation::ShuffleAndDealDraw);
+        let key = program(ArgKind::ArcisPubkey);
+        assert!(spec.args == vec![key, key]);
+    }
+
+    #[test]
+    fn street_reveal_is_specified_only_while_the_street_is_missing() {
+        let reveal_args = vec![program(ArgKind::EncryptedMxe), program(ArgKind::PlaintextU8)];
+        let cases = [
+            (GamePhase::Flop, [255; 5]),
+            (GamePhase::Turn, [0, 1, 2, 255, 255]),
+            (GamePhase::River, [0, 1, 2, 3, 255]),
+        ];
+        for (phase, community_cards) in cases {
+            let mut game_state = game_state(phase);
+            game_state.community_cards = community_cards;
+            let spec = computation_args_spec(&game_state, &table_config(GameVariant::Holdem));
+            assert!(spec.computation == NextComputation::RevealCommunityCards);
+            assert!(spec.args == reveal_args);
+        }
+
+        // Once the street is out, the betting round comes before any computation.
+        let mut game_state = game_state(GamePhase::Flop);
+        game_state.community_cards = [0, 1, 2, 255, 255];
+        let spec = computation_args_spec(&game_state, &table_config(GameVariant::Holdem));
+        assert!(spec.computation == NextComputation::None && spec.args.is_empty());
+    }
+
+    #[test]
+    fn runout_reveals_the_rest_of_the_board_at_once_unless_delayed() {
+        let mut game_state = game_state(GamePhase::Turn);
+        game_state.community_cards = [0, 1, 2, 255, 255];
+        game_state.runout = true;
+        let mut table_config = table_config(GameVariant::Holdem);
+
+        let spec = computation_args_spec(&game_state, &table_config);
+        assert!(spec.computation == NextComputation::RevealAllCommunityCards);
+
+        table_config.runout_delay_seconds = 5;
+        let spec = computation_args_spec(&game_state, &table_config);
+        assert!(spec.computation == NextComputation::RevealCommunityCards);
+    }
+
+    #[test]
+    fn draw_reads_the_drawing_players_hand_and_the_stored_deck() {
+        let mut game_state = game_state(GamePhase::Draw);
+        game_state.current_turn_index = 1;
+
+        let spec = computation_args_spec(&game_state, &table_config(GameVariant::FiveCardDraw));
+        assert!(spec.computation == NextComputation::DrawCards);
+        assert!(
+            spec.args
+                == vec![
+                    stored(ArgKind::EncryptedShared, HandState::DRAW_HANDS_OFFSET + 256, 256),
+                    stored(
+                        ArgKind::EncryptedMxe,
+                        HandState::DECK_OFFSET,
+                        HandState::DECK_NONCE_LEN + HandState::DECK_CIPHERTEXTS_LEN,
+                    ),
+                    ArgSpec {
+                        kind: ArgKind::PlaintextU8,
+                        source: ArgSource::Caller,
+                    },
+                ]
+        );
+    }
+
+    #[test]
+    fn showdown_reads_both_players_cards() {
+        let game_state = game_state(GamePhase::Showdown);
+
+        let spec = computation_args_spec(&game_state, &table_config(GameVariant::Holdem));
+        assert!(spec.computation == NextComputation::DetermineWinner);
+        assert!(
+            spec.args
+                == vec![
+                    stored(ArgKind::EncryptedShared, 8, HandState::HOLE_CARDS_LEN),
+                    stored(
+                        ArgKind::EncryptedShared,
+                        8 + HandState::HOLE_CARDS_LEN,
+                        HandState::HOLE_CARDS_LEN,
+                    ),
+                    ArgSpec {
+                        kind: ArgKind::PlaintextU8Array { len: 5 },
+                        source: ArgSource::CommunityCards,
+                    },
+                ]
+        );
+
+        let spec = computation_args_spec(&game_state, &table_config(GameVariant::FiveCardDraw));
+        assert!(spec.computation == NextComputation::DetermineWinnerDraw);
+        assert!(
+            spec.args
+                == vec![
+                    stored(ArgKind::EncryptedShared, HandState::DRAW_HANDS_OFFSET, 256),
+                    stored(ArgKind::EncryptedShared, HandState::DRAW_HANDS_OFFSET + 256, 256),
+                ]
+        );
+    }
+
+    #[test]
+    fn phases_without_a_computation_have_no_arguments() {
+        for phase in [GamePhase::Idle, GamePhase::PreFlop, GamePhase::HandOver] {
+            let spec = computation_args_spec(&game_state(phase), &table_config(GameVariant::Holdem));
+            assert!(spec.phase == phase);
+            assert!(spec.computation == NextComputation::None && spec.args.is_empty());
+        }
+    }
+}
@@ -0,0 +1,64 @@
+/**
+ * @description
+ * This file contains the read-only `audit_escrow` instruction, which lets anyone check that a
+ * table's escrow holds exactly the chips in play: both players' stacks plus the pot. Players
+ * read the result from the transaction's return data (or simulate the instruction).
+ *
+ * @key_features
+ * - Reports the escrow balance, the sum of the stacks and the pot (including the current
+ *   street's bets), and whether they reconcile.
+ * - Never fails on a mismatch; reporting it is the point.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` account structure.
+ * - anchor_lang & anchor_spl: For Solana and SPL Token account access.
+ */
+
+use crate::state::GameState;
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+/// The result of an escrow audit, returned by `audit_escrow`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct EscrowAudit {
+    /// The token balance of the table's escrow.
+    pub escrow_balance: u64,
+    /// The sum of both players' stacks.
+    pub stacks_total: u64,
+    /// The pot, including the bets of the current street.
+    pub pot: u64,
+    /// Whether the escrow holds exactly `stacks_total + pot`.
+    pub reconciled: bool,
+}
+
+/// Defines the accounts required to audit a table's escrow.
+#[derive(Accounts)]
+pub struct AuditEscrow<'info> {
+    #[account(
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's escrow account.
+    #[account(
+        seeds = [b"escrow", game_state.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Account<'info, TokenAccount>,
+}
+
+/// Handler for the `audit_escrow` instruction.
+pub fn audit_escrow(ctx: Context<AuditEscrow>) -> Result<EscrowAudit> {
+    let game_state = &ctx.accounts.game_state;
+    let escrow_balance = ctx.accounts.escrow_account.amount;
+    let stacks_total = game_state.stacks.iter().sum::<u64>();
+    let pot = game_state.pot + game_state.bets.iter().sum::<u64>();
+
+    Ok(EscrowAudit {
+        escrow_balance,
+        stacks_total,
+        pot,
+        reconciled: stacks_total.checked_add(pot) == Some(escrow_balance),
+    })
+}
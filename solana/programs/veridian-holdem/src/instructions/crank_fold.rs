@@ -8,21 +8,27 @@
  * - Permissionless: Can be called by any account, ensuring the game can always proceed.
  * - Time-based Validation: Uses Solana's on-chain `Clock` to check if the turn duration has exceeded a predefined limit.
  * - State Transition: Folds the current player's hand, awards the pot to the opponent, and resets the game state for the next hand.
+ * - Sits the timed-out player out, so they are not dealt another hand until they sit back in.
+ * - `force_opponent_timeout`: The same fold, called by the waiting player, who is recorded as
+ *   having forced it in an `OpponentTimeoutForced` event.
  *
  * @dependencies
- * - crate::state: Defines `GameState`, `GamePhase`, and the `TURN_TIME_SECONDS` constant.
+ * - crate::state: Defines `GameState`, `GamePhase`, and the per-seat turn timer.
  * - crate::error: Defines custom error codes for validation.
+ * - crate::events: Defines the `ChallengerSeatOpened` and `OpponentTimeoutForced` events.
  * - anchor_lang: The core Anchor framework library.
  */
 
 use crate::{
     error::ErrorCode,
-    state::{GamePhase, GameState, MAX_PLAYERS, TURN_TIME_SECONDS},
+    events::{ChallengerSeatOpened, HandResult, OpponentTimeoutForced},
+    state::{GamePhase, GameState, PlayerStats, TableConfig, MAX_PLAYERS},
 };
 use anchor_lang::prelude::*;
 
 /// Defines the accounts required for the `crank_fold` instruction.
-/// Since this is a permissionless crank, it only needs mutable access to the `GameState`.
+/// Since this is a permissionless crank, it only needs mutable access to the `GameState`
+/// and read access to the table's rules.
 /// The caller of this instruction will be the transaction fee payer.
 #[derive(Accounts)]
 pub struct CrankFold<'info> {
@@ -34,56 +40,200 @@ pub struct CrankFold<'info> {
         bump
     )]
     pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, needed to apply the "winner stays" format.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    /// The `PlayerStats` of the player in seat 0, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[0].as_ref()],
+        bump
+    )]
+    pub seat0_stats: Option<Box<Account<'info, PlayerStats>>>,
+
+    /// The `PlayerStats` of the player in seat 1, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[1].as_ref()],
+        bump
+    )]
+    pub seat1_stats: Option<Box<Account<'info, PlayerStats>>>,
+}
+
+/// Defines the accounts required for a seated player to force their opponent's timeout.
+#[derive(Accounts)]
+pub struct ForceOpponentTimeout<'info> {
+    /// The seated player who is waiting on their opponent.
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, needed to apply the "winner stays" format.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    /// The `PlayerStats` of the player in seat 0, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[0].as_ref()],
+        bump
+    )]
+    pub seat0_stats: Option<Box<Account<'info, PlayerStats>>>,
+
+    /// The `PlayerStats` of the player in seat 1, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[1].as_ref()],
+        bump
+    )]
+    pub seat1_stats: Option<Box<Account<'info, PlayerStats>>>,
 }
 
 /// The handler function for the `crank_fold` instruction.
 pub fn crank_fold(ctx: Context<CrankFold>) -> Result<()> {
-    let game_state = &mut ctx.accounts.game_state;
+    let accounts = &mut *ctx.accounts;
+    fold_timed_out_player(
+        &mut accounts.game_state,
+        &accounts.table_config,
+        [
+            accounts.seat0_stats.as_deref_mut().map(|stats| &mut **stats),
+            accounts.seat1_stats.as_deref_mut().map(|stats| &mut **stats),
+        ],
+    )
+}
 
+/// The handler function for the `force_opponent_timeout` instruction.
+/// Applies exactly the same checks and fold as `crank_fold`, but only for the player waiting
+/// on the action, and records that they forced it.
+pub fn force_opponent_timeout(ctx: Context<ForceOpponentTimeout>) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let game_state = &mut accounts.game_state;
+    let player_key = accounts.player.key();
+    let waiting_index = (1 - game_state.current_turn_index) as usize;
+    require!(
+        game_state.players[waiting_index] == player_key,
+        ErrorCode::Unauthorized
+    );
+
+    // Under "winner stays", the fold can vacate the timed-out player's seat, so note them first.
+    let timed_out_player = game_state.players[game_state.current_turn_index as usize];
+    fold_timed_out_player(
+        game_state,
+        &accounts.table_config,
+        [
+            accounts.seat0_stats.as_deref_mut().map(|stats| &mut **stats),
+            accounts.seat1_stats.as_deref_mut().map(|stats| &mut **stats),
+        ],
+    )?;
+    emit!(OpponentTimeoutForced {
+        table_id: game_state.table_id,
+        forced_by: player_key,
+        timed_out_player,
+    });
+    Ok(())
+}
+
+/// Folds the player on the clock once their turn timer has expired and awards the pot to their
+/// opponent, recording the hand in whichever seats' `PlayerStats` were passed.
+fn fold_timed_out_player(
+    game_state: &mut Account<GameState>,
+    table_config: &TableConfig,
+    seat_stats: [Option<&mut PlayerStats>; MAX_PLAYERS],
+) -> Result<()> {
     // 1. Validate that the game is in an active betting phase where a player can time out.
+    //    A five-card draw player can also time out before requesting their draw, but not
+    //    while their draw computation is in flight.
     require!(
         matches!(
             game_state.game_phase,
-            GamePhase::PreFlop | GamePhase::Flop | GamePhase::Turn | GamePhase::River
+            GamePhase::PreFlop | GamePhase::Draw | GamePhase::Flop | GamePhase::Turn | GamePhase::River
         ),
         ErrorCode::InvalidAction
     );
+    require!(!game_state.draw_pending, ErrorCode::InvalidAction);
+    // Nobody is on the clock during an all-in runout.
+    require!(!game_state.runout, ErrorCode::InvalidAction);
 
-    // 2. Check if the turn timer has actually expired using the on-chain clock.
-    let current_timestamp = Clock::get()?.unix_timestamp;
-    require!(
-        current_timestamp > game_state.last_action_timestamp + TURN_TIME_SECONDS,
-        ErrorCode::TimerNotExpired
+    // 2. Check if the turn timer has actually expired using the on-chain clock, counted in
+    //    slots on slot-timed tables. The timer only starts after the table's grace period,
+    //    and slow players have a shorter effective timer (see `GameState::turn_time_for`).
+    //    The player's remaining time bank extends the timer.
+    let clock = Clock::get()?;
+    let (elapsed, turn_time) = game_state.turn_progress(
+        game_state.current_turn_index as usize,
+        &clock,
+        table_config,
     );
+    let time_bank = game_state.time_bank_for(game_state.current_turn_index as usize, table_config);
+    require!(elapsed > turn_time + time_bank, ErrorCode::TimerNotExpired);
 
-    // 3. Identify the player who timed out and their opponent.
+    // 3. Identify the player who timed out and their opponent. Timing out uses up what was
+    //    left of their time bank.
     let timed_out_player_index = game_state.current_turn_index as usize;
     let opponent_index = (1 - game_state.current_turn_index) as usize;
+    game_state.time_bank_seconds[timed_out_player_index] = 0;
 
     // 4. Perform the fold logic:
-    //    a. Calculate the total pot size, including all bets from the current street.
-    let total_pot = game_state.pot + game_state.bets[0] + game_state.bets[1];
+    //    a. Return any uncalled bet to the opponent, then calculate the total pot size,
+    //       including all bets from the current street.
+    game_state.return_uncalled_bet(table_config.hand_cap)?;
+    let total_pot = game_state
+        .pot
+        .checked_add(game_state.bets[0])
+        .and_then(|total| total.checked_add(game_state.bets[1]))
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
     
     //    b. Award the entire pot to the opponent.
-    game_state.stacks[opponent_index] += total_pot;
-
-    // 5. Transition the game to the "HandOver" state to prepare for the next deal.
-    game_state.game_phase = GamePhase::HandOver;
-    game_state.pot = 0;
-    game_state.bets = [0; MAX_PLAYERS];
-    game_state.community_cards = [255; 5];
-    game_state.is_all_in = [false; MAX_PLAYERS];
-    
-    //    c. Swap the dealer button for the next hand.
-    game_state.dealer_index = 1 - game_state.dealer_index;
-    
-    //    d. The turn for the next hand starts with the player who is now the small blind/button.
-    game_state.current_turn_index = game_state.dealer_index;
+    game_state.stacks[opponent_index] = game_state.stacks[opponent_index]
+        .checked_add(total_pot)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let mut awarded = [0; MAX_PLAYERS];
+    awarded[opponent_index] = total_pot;
+    game_state.record_hand_stats(seat_stats, awarded, 0)?;
+    emit!(HandResult {
+        table_id: game_state.table_id,
+        winner_index: opponent_index as u8,
+        pot_awarded: total_pot,
+        rake_taken: 0,
+        was_showdown: false,
+        winning_rank: None,
+    });
 
-    //    e. Update the action timestamp to reset the timer for the next hand's pre-deal phase.
-    game_state.last_action_timestamp = Clock::get()?.unix_timestamp;
+    // 5. Transition the game to the "HandOver" state to prepare for the next deal,
+    //    and reset the timer for the next hand's pre-deal phase.
+    game_state.reset_for_next_hand();
+    game_state.record_action_time(&clock);
+
+    // 6. A player who let their timer run out is treated as away: they are sat out, so no
+    //    further hand is dealt until they sit back in with `set_sitting_out`.
+    game_state.sitting_out[timed_out_player_index] = true;
+
+    // 7. Under "winner stays", a busted player's seat is opened for the next challenger.
+    if table_config.winner_stays {
+        if let Some(seat) = game_state.open_busted_seat() {
+            emit!(ChallengerSeatOpened {
+                table_id: game_state.table_id,
+                winner: game_state.players[opponent_index],
+                open_seat: seat as u8,
+            });
+        }
+    }
 
     msg!("Player {} timed out. Awarded pot of {} to player {}.", timed_out_player_index, total_pot, opponent_index);
 
+    game_state.check_board(table_config.variant)?;
     Ok(())
-}
\ No newline at end of file
+}
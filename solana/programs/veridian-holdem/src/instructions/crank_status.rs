@@ -0,0 +1,142 @@
+/**
+ * @description
+ * This file contains the read-only `crank_status` instruction, which reports which of the
+ * table's permissionless cranks would currently succeed and how long until the timed ones do.
+ * Bots and UIs read the status from the transaction's return data (or simulate the
+ * instruction) instead of replicating each crank's preconditions or sending trial transactions.
+ *
+ * @key_features
+ * - `crank_fold`: a betting player's turn timer has run out.
+ * - `pass_sitting_out_hand`: between hands, the only player sitting out has used up their turn
+ *   timer, so the hand can be passed.
+ * - `advance_to_showdown`: the betting is over, so `request_community_cards`, `request_run_out`
+ *   or `request_showdown` can move the hand on. During an all-in runout, the table's runout
+ *   delay is the timer.
+ * - `settle_hand`: a showdown result is waiting to be paid out.
+ * - Timers are reported in the table's own unit: seconds, or slots on slot-timed tables. The
+ *   grace period, slow-action penalties and time bank are included, exactly as the cranks
+ *   apply them.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` and `TableConfig` account structures.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::state::{GamePhase, GameState, TableConfig};
+use anchor_lang::prelude::*;
+
+/// The readiness of a timed crank.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CrankReadiness {
+    /// Whether the game is in a state the crank applies to at all.
+    pub applicable: bool,
+    /// How long until the crank's timer allows it, in seconds or, on slot-timed tables, in
+    /// slots. The crank is valid now when it is applicable and this is 0.
+    pub remaining: u64,
+}
+
+/// The crank status of a table, returned by `crank_status`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CrankStatus {
+    /// The game phase the status was computed for.
+    pub phase: GamePhase,
+    /// Whether `remaining` values are in slots rather than seconds.
+    pub slot_timed: bool,
+    pub crank_fold: CrankReadiness,
+    pub pass_sitting_out_hand: CrankReadiness,
+    /// Whether the next reveal or the showdown can be requested without a player decision. Its
+    /// `remaining` time is always in seconds.
+    pub advance_to_showdown: CrankReadiness,
+    /// Whether `settle_hand` can be called. It has no timer.
+    pub settle_hand: bool,
+}
+
+/// Defines the accounts required to read a table's crank status.
+#[derive(Accounts)]
+pub struct CrankStatusQuery<'info> {
+    #[account(
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, whose timer settings the cranks apply.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+}
+
+/// Handler for the `crank_status` instruction.
+pub fn crank_status(ctx: Context<CrankStatusQuery>) -> Result<CrankStatus> {
+    let game_state = &ctx.accounts.game_state;
+    let table_config = &ctx.accounts.table_config;
+    let clock = Clock::get()?;
+
+    // The cranks require the elapsed time to be strictly past the turn time, extended by the
+    // player's time bank for `crank_fold`.
+    let readiness = |applicable: bool, seat: usize, time_bank: i64| {
+        let (elapsed, turn_time) = game_state.turn_progress(seat, &clock, table_config);
+        CrankReadiness {
+            applicable,
+            remaining: if applicable {
+                (turn_time + time_bank + 1 - elapsed).max(0) as u64
+            } else {
+                0
+            },
+        }
+    };
+
+    // Mirrors the preconditions of `crank_fold`.
+    let betting = matches!(
+        game_state.game_phase,
+        GamePhase::PreFlop | GamePhase::Draw | GamePhase::Flop | GamePhase::Turn | GamePhase::River
+    ) && !game_state.draw_pending
+        && !game_state.runout;
+    let on_clock = game_state.current_turn_index as usize;
+    let crank_fold = readiness(betting, on_clock, game_state.time_bank_for(on_clock, table_config));
+
+    // Mirrors the preconditions of `pass_sitting_out_hand`.
+    let sitting_out_seat = match game_state.sitting_out {
+        [true, false] => Some(0),
+        [false, true] => Some(1),
+        _ => None,
+    };
+    let pass_sitting_out_hand = readiness(
+        game_state.game_phase == GamePhase::HandOver && sitting_out_seat.is_some(),
+        sitting_out_seat.unwrap_or(0),
+        0,
+    );
+
+    // Mirrors the preconditions of the reveal and showdown requests once the betting is over.
+    let runout_street = game_state.runout
+        && matches!(
+            game_state.game_phase,
+            GamePhase::Flop | GamePhase::Turn | GamePhase::River
+        );
+    let advance_applicable = game_state.hand_in_progress
+        && (runout_street || game_state.game_phase == GamePhase::Showdown);
+    let runout_delay = table_config.runout_delay_seconds as i64;
+    let advance_to_showdown = CrankReadiness {
+        applicable: advance_applicable,
+        remaining: if advance_applicable
+            && runout_street
+            && runout_delay > 0
+            && game_state.last_reveal_timestamp != 0
+        {
+            (game_state.last_reveal_timestamp + runout_delay - clock.unix_timestamp).max(0) as u64
+        } else {
+            0
+        },
+    };
+
+    Ok(CrankStatus {
+        phase: game_state.game_phase,
+        slot_timed: table_config.turn_time_slots > 0,
+        crank_fold,
+        pass_sitting_out_hand,
+        advance_to_showdown,
+        settle_hand: game_state.game_phase == GamePhase::ReadyToSettle,
+    })
+}
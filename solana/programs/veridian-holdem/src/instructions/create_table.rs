@@ -7,6 +7,11 @@
  * @key_features
  * - Initializes `TableConfig`, `GameState`, and an SPL Token `escrow` account.
  * - Seeds PDAs with a unique `table_id` to ensure each table has a distinct set of accounts.
+ * - Seats the creator in the seat of their choice; the button is drawn once the table fills.
+ * - Sets the table's buy-in range; the creator buys in for any amount within it.
+ * - Rejects buy-ins above the platform's maximum buy-in, if one is set.
+ * - Counts the table and its buy-in in the mint's `MintExposure`, refusing it if the mint is
+ *   already at its table cap.
  * - Transfers the creator's funds using a secure CPI to the SPL Token Program.
  *
  * @dependencies
@@ -16,7 +21,12 @@
  * - anchor_spl: Anchor's helpers for interacting with SPL Token Program.
  */
 use crate::{
-    state::{GamePhase, GameState, TableConfig, MAX_PLAYERS},
+    error::ErrorCode,
+    events::MintExposureUpdated,
+    state::{
+        Config, GamePhase, GameState, MintExposure, PlayerProfile, RakeType, TableConfig,
+        TableOptions, MAX_HAND_RETENTION_SECONDS, MAX_PLAYERS, MAX_STRADDLES, MAX_WAITLIST,
+    },
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
@@ -27,7 +37,7 @@ use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 #[derive(Accounts)]
 #[instruction(table_id: u64)]
 pub struct CreateTable<'info> {
-    /// The `TableConfig` account, which stores the immutable rules of the table (blinds, buy-in).
+    /// The `TableConfig` account, which stores the immutable rules of the table (blinds, buy-in range).
     /// Initialized as a PDA seeded with "table_config" and the unique table ID.
     #[account(
         init,
@@ -77,6 +87,28 @@ pub struct CreateTable<'info> {
     )]
     pub creator_token_account: Account<'info, TokenAccount>,
 
+    /// The creator's `PlayerProfile`, if they pre-registered an encryption key.
+    #[account(
+        seeds = [b"player_profile", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Option<Account<'info, PlayerProfile>>,
+
+    /// The global `Config` account, needed to enforce the platform's maximum buy-in.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    /// The token mint's `MintExposure`, which enforces the mint's table cap. Created by the first
+    /// table for the mint if the admin has not set a cap yet.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MintExposure::INIT_SPACE,
+        seeds = [b"mint_exposure", token_mint.key().as_ref()],
+        bump
+    )]
+    pub mint_exposure: Box<Account<'info, MintExposure>>,
+
     /// Standard Solana programs required for account creation and token operations.
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -89,33 +121,137 @@ pub fn create_table(
     table_id: u64,
     small_blind: u64,
     big_blind: u64,
-    buy_in: u64,
+    min_buy_in: u64,
+    max_buy_in: u64,
+    buy_in_amount: u64,
+    options: TableOptions,
 ) -> Result<()> {
+    // A cap below the big blind could not even cover the forced bets.
+    require!(
+        options.hand_cap == 0 || options.hand_cap >= big_blind,
+        ErrorCode::HandCapBelowBigBlind
+    );
+    require!((options.creator_seat as usize) < MAX_PLAYERS, ErrorCode::InvalidAction);
+    require!(options.max_straddles <= MAX_STRADDLES, ErrorCode::InvalidAction);
+    require!(
+        options.hand_retention_seconds <= MAX_HAND_RETENTION_SECONDS,
+        ErrorCode::InvalidAction
+    );
+    require!(
+        big_blind.checked_mul(options.button_ante_multiplier as u64).is_some(),
+        ErrorCode::ArithmeticOverflow
+    );
+    require!(
+        min_buy_in > 0 && min_buy_in <= max_buy_in,
+        ErrorCode::InvalidAction
+    );
+    require!(
+        buy_in_amount >= min_buy_in && buy_in_amount <= max_buy_in,
+        ErrorCode::InvalidBuyInAmount
+    );
+    let max_buy_in_cap = ctx.accounts.config.max_buy_in_cap;
+    require!(
+        max_buy_in_cap == 0 || buy_in_amount <= max_buy_in_cap,
+        ErrorCode::ExceedsPlatformCap
+    );
+    let creator_seat = options.creator_seat as usize;
+
+    let mint_exposure = &mut ctx.accounts.mint_exposure;
+    mint_exposure.token_mint = ctx.accounts.token_mint.key();
+    mint_exposure.open_table()?;
+    mint_exposure.deposit(buy_in_amount);
+    emit!(MintExposureUpdated::from(&***mint_exposure));
+
     // 1. Initialize the TableConfig account with the specified game rules.
     let table_config = &mut ctx.accounts.table_config;
     table_config.table_id = table_id;
     table_config.small_blind = small_blind;
     table_config.big_blind = big_blind;
-    table_config.buy_in = buy_in;
+    table_config.min_buy_in = min_buy_in;
+    table_config.max_buy_in = max_buy_in;
     table_config.token_mint = ctx.accounts.token_mint.key();
+    table_config.hand_cap = options.hand_cap;
+    table_config.winner_stays = options.winner_stays;
+    table_config.no_limp = options.no_limp;
+    table_config.variant = options.variant;
+    table_config.turn_time_slots = options.turn_time_slots;
+    table_config.sitout_posts_blinds = options.sitout_posts_blinds;
+    table_config.max_rebuys = options.max_rebuys;
+    table_config.button_straddle = options.button_straddle;
+    table_config.max_straddles = options.max_straddles.max(1);
+    table_config.turn_grace_seconds = options.turn_grace_seconds;
+    table_config.max_hands_per_hour = 0;
+    table_config.min_raise_multiple = options.min_raise_multiple.max(1);
+    table_config.rake_type = RakeType::Pot;
+    table_config.hand_retention_seconds = options.hand_retention_seconds;
+    table_config.runout_delay_seconds = options.runout_delay_seconds;
+    table_config.button_ante_multiplier = options.button_ante_multiplier;
+    table_config.ante = options.ante;
+    table_config.time_bank_seconds = options.time_bank_seconds;
+    table_config.reveal_events = options.reveal_events;
 
     // 2. Initialize the GameState account with default values for a new, empty table.
     let game_state = &mut ctx.accounts.game_state;
     game_state.table_id = table_id; // Set the table_id for consistent PDA derivation.
     game_state.table_config = table_config.key();
-    game_state.players[0] = ctx.accounts.creator.key();
-    game_state.players[1] = Pubkey::default(); // Represents an empty seat.
-    game_state.stacks[0] = buy_in;
-    game_state.stacks[1] = 0;
+    game_state.players = [Pubkey::default(); MAX_PLAYERS]; // Represents empty seats.
+    game_state.players[creator_seat] = ctx.accounts.creator.key();
+    game_state.stacks = [0; MAX_PLAYERS];
+    game_state.stacks[creator_seat] = buy_in_amount;
     game_state.game_phase = GamePhase::Idle; // Waiting for another player.
     game_state.pot = 0;
     game_state.bets = [0; MAX_PLAYERS];
+    game_state.total_contributed = [0; MAX_PLAYERS];
     game_state.community_cards = [255; 5]; // 255 indicates an un-dealt card.
     game_state.is_all_in = [false; MAX_PLAYERS];
     game_state.current_turn_index = 0;
-    game_state.dealer_index = 0; // The creator is the first dealer.
+    game_state.dealer_index = 0; // The first dealer is drawn when the second player joins.
     game_state.last_action_timestamp = 0;
-    game_state.is_active = false; // Game becomes active when the second player joins.
+    game_state.last_action_slot = 0;
+    game_state.recompute_is_active(); // Game becomes active when the second player joins.
+    game_state.slow_action_count = [0; MAX_PLAYERS];
+    game_state.waitlist = [Pubkey::default(); MAX_WAITLIST];
+    game_state.winner_index = 0;
+    game_state.winner_mask = 0;
+    game_state.side_pots = Vec::new();
+    game_state.winning_rank = None;
+    game_state.total_rake_collected = 0;
+    game_state.token_mint = ctx.accounts.token_mint.key();
+    game_state.folded_player = Pubkey::default();
+    game_state.referrers = [Pubkey::default(); MAX_PLAYERS];
+    game_state.session_buy_in = [0; MAX_PLAYERS];
+    game_state.session_buy_in[creator_seat] = buy_in_amount;
+    game_state.session_payout = [0; MAX_PLAYERS];
+    game_state.draw_pending = false;
+    game_state.runout = false;
+    game_state.clear_insurance();
+    game_state.sitting_out = [false; MAX_PLAYERS];
+    game_state.missed_blinds = [0; MAX_PLAYERS];
+    game_state.rebuy_count = [0; MAX_PLAYERS];
+    game_state.auto_rebuy_min = [None; MAX_PLAYERS];
+    game_state.auto_rebuy_accounts = [Pubkey::default(); MAX_PLAYERS];
+    game_state.must_move_target = None;
+    game_state.straddler = Pubkey::default();
+    game_state.straddled = false;
+    game_state.pace_window_start = 0;
+    game_state.pace_hands = [0; 2];
+    game_state.last_raise_size = 0;
+    game_state.time_charged_at = [0; MAX_PLAYERS];
+    game_state.last_reveal_timestamp = 0;
+    game_state.straddle_levels = 0;
+    game_state.hand_in_progress = false;
+    game_state.deal_queued = false;
+    game_state.has_acted = [false; MAX_PLAYERS];
+    game_state.final_betting_street = 0;
+    game_state.last_emote_timestamp = [0; MAX_PLAYERS];
+    game_state.time_bank_seconds = [0; MAX_PLAYERS];
+    game_state.time_bank_seconds[creator_seat] = options.time_bank_seconds as i64;
+    game_state.hands_played = [0; MAX_PLAYERS];
+    game_state.misdeal = false;
+    game_state.encryption_keys = [[0; 32]; MAX_PLAYERS];
+    if let Some(profile) = &ctx.accounts.creator_profile {
+        game_state.encryption_keys[creator_seat] = profile.encryption_pubkey;
+    }
 
     // 3. Perform a CPI to the SPL Token Program to transfer the creator's buy-in to the escrow account.
     let cpi_accounts = Transfer {
@@ -125,7 +261,7 @@ pub fn create_table(
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, buy_in)?;
+    token::transfer(cpi_ctx, buy_in_amount)?;
 
     Ok(())
 }
\ No newline at end of file
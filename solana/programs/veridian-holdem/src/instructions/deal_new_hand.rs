@@ -8,6 +8,15 @@
  * - Initializes a new `HandState` account to store encrypted card data for the hand.
  * - Triggers the `shuffle_and_deal` confidential instruction via a CPI to Arcium.
  * - Validates that the game is in a state ready for a new hand and that the caller is the dealer.
+ * - Refuses to start a hand while the admin has paused the platform. Hands already dealt play
+ *   out and settle as normal.
+ * - Refuses to deal unless both players can post their blind, so the blinds posted by the
+ *   deal callback can never exceed a player's stack.
+ * - At time-raked tables, takes any time charge due from each player's stack and pays it to
+ *   the treasury before the hand starts.
+ * - `redeal_after_misdeal`: after a deal voided by its integrity check, lets anyone start the
+ *   same hand again with the same button and blinds. The void already returned every chip, and
+ *   the re-deal takes no time charge and does not count against the hourly pace.
  *
  * @dependencies
  * - crate::state: Defines the `GameState` and `HandState` account structures.
@@ -16,11 +25,14 @@
  */
 
 use crate::{
+    computation::{deal_args, require_cluster_set, verify_circuit_hash},
     error::ErrorCode,
-    state::{GamePhase, GameState, HandState, SignerAccount},
+    events::TimeCharged,
+    state::{Config, GamePhase, GameState, GameVariant, HandState, RakeType, SignerAccount, TableConfig},
     ID,
 };
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use arcium_anchor::prelude::*;
 use arcium_client::idl::arcium::accounts::{ClockAccount, FeePool};
 use arcium_client::idl::arcium::ID_CONST;
@@ -52,8 +64,40 @@ pub struct DealNewHandSetup<'info> {
     )]
     pub hand_state: UncheckedAccount<'info>,
 
+    /// The table's `TableConfig`, needed to check that both players can cover their blinds.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    /// The program's MXE account, checked so a hand is never started without a cluster to deal it.
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    /// The global `Config` account, checked for a platform pause and which sets the time charge.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The game's escrow account, from which time charges are paid. Required only at
+    /// time-raked tables.
+    #[account(
+        mut,
+        seeds = [b"escrow", game_state.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: The treasury wallet that receives time charges, validated against `config` in the
+    /// handler. Required only at time-raked tables.
+    #[account(mut)]
+    pub treasury_token_account: Option<UncheckedAccount<'info>>,
+
     /// System program required for init constraints
     pub system_program: Program<'info, System>,
+
+    /// Required only at time-raked tables.
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 /// The handler function for the setup step of `deal_new_hand`.
@@ -62,29 +106,193 @@ pub fn deal_new_hand_setup(ctx: Context<DealNewHandSetup>, _computation_offset:
     let payer = &ctx.accounts.payer;
 
     // 1. Validation Checks
+    // Fail before moving to `Dealing`, otherwise the table would be stuck waiting on a
+    // computation that can never be queued.
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    require!(!ctx.accounts.config.paused, ErrorCode::PlatformPaused);
+    require!(!game_state.hand_in_progress, ErrorCode::HandAlreadyInProgress);
     require!(
         game_state.game_phase == GamePhase::HandOver || game_state.game_phase == GamePhase::Idle,
         ErrorCode::InvalidAction
     );
+    // Self-heal a button left on an empty seat before checking who may deal.
+    game_state.seat_dealer();
     require!(
         game_state.players[game_state.dealer_index as usize] == payer.key(),
         ErrorCode::Unauthorized
     );
+    let clock = Clock::get()?;
+    let table_config = &ctx.accounts.table_config;
+
+    // At time-raked tables, collect the seat charges that have come due before the blinds are
+    // checked, so a player the charge leaves short cannot be dealt in.
+    if table_config.rake_type == RakeType::Time {
+        let config = &ctx.accounts.config;
+        let (Some(escrow_account), Some(treasury_token_account), Some(token_program)) = (
+            &ctx.accounts.escrow_account,
+            &ctx.accounts.treasury_token_account,
+            &ctx.accounts.token_program,
+        ) else {
+            return err!(ErrorCode::InvalidTimeChargeAccount);
+        };
+        require!(
+            treasury_token_account.key() == config.treasury_wallet,
+            ErrorCode::InvalidTimeChargeAccount
+        );
+        let seeds = &[
+            b"game",
+            &game_state.table_id.to_le_bytes()[..],
+            &[ctx.bumps.game_state],
+        ];
+        let signer = &[&seeds[..]];
+        for seat in 0..game_state.players.len() {
+            let amount = game_state.take_time_charge(
+                seat,
+                clock.unix_timestamp,
+                config.time_charge,
+                config.time_charge_period_seconds,
+            )?;
+            if amount == 0 {
+                continue;
+            }
+            let cpi_accounts = Transfer {
+                from: escrow_account.to_account_info(),
+                to: treasury_token_account.to_account_info(),
+                authority: game_state.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+            token::transfer(cpi_ctx, amount)?;
+            emit!(TimeCharged {
+                table_id: game_state.table_id,
+                player: game_state.players[seat],
+                amount,
+            });
+        }
+    }
+
+    game_state.record_deal_pace(clock.unix_timestamp, table_config.max_hands_per_hour)?;
+    start_hand(game_state, table_config, &clock)?;
+
+    // Defer setting fields on HandState to the queue step to minimize setup stack usage.
+
+    Ok(())
+}
+
+/// Checks that a hand can start and resets the hand-specific state of `GameState` for it,
+/// leaving the table in `Dealing` until the deal is queued. Shared by `deal_new_hand_setup` and
+/// `redeal_after_misdeal`.
+pub(crate) fn start_hand(
+    game_state: &mut GameState,
+    table_config: &TableConfig,
+    clock: &Clock,
+) -> Result<()> {
     require!(
         game_state.players[0] != Pubkey::default() && game_state.players[1] != Pubkey::default(),
         ErrorCode::InvalidAction // Not enough players
     );
+    require!(!game_state.sitting_out.contains(&true), ErrorCode::PlayerSittingOut);
+    require!(game_state.must_move_target.is_none(), ErrorCode::TableMustMove);
+
+    // Each player needs to cover the blind they will post, and the button their ante too. The
+    // table ante is not required: a stack too short for it goes all-in when the blinds are posted.
+    let small_blind_idx = game_state.dealer_index as usize;
+    let big_blind_idx = (1 - game_state.dealer_index) as usize;
+    let button_blind = game_state.button_blind(table_config)?;
+    let button_total = button_blind
+        .checked_add(game_state.button_ante(table_config)?)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    require!(
+        game_state.stacks[small_blind_idx] >= button_total
+            && game_state.stacks[big_blind_idx] >= table_config.big_blind,
+        ErrorCode::CannotCoverBlind
+    );
 
     // 2. Reset hand-specific state in GameState and initialize HandState.
     game_state.pot = 0;
+    game_state.side_pots.clear();
     game_state.bets = [0, 0];
+    game_state.last_raise_size = 0;
+    game_state.has_acted = [false, false];
+    game_state.final_betting_street = 0;
+    game_state.last_reveal_timestamp = 0;
+    game_state.total_contributed = [0, 0];
+    game_state.folded_player = Pubkey::default();
+    game_state.draw_pending = false;
+    game_state.runout = false;
+    game_state.straddled = false;
+    game_state.clear_insurance();
     game_state.community_cards = [255; 5];
     game_state.is_all_in = [false, false];
+    game_state.misdeal = false;
+    game_state.refill_time_banks(table_config);
+    for hands in game_state.hands_played.iter_mut() {
+        *hands = hands.saturating_add(1);
+    }
     game_state.game_phase = GamePhase::Dealing;
-    game_state.last_action_timestamp = Clock::get()?.unix_timestamp;
-    
-    // Defer setting fields on HandState to the queue step to minimize setup stack usage.
+    game_state.hand_in_progress = true;
+    game_state.deal_queued = false;
+    game_state.record_action_time(clock);
+    Ok(())
+}
+
+/// Defines the accounts required to re-deal a hand voided by a misdeal. The deal is then queued
+/// with `deal_new_hand_queue` (or `deal_draw_hand_queue`), as after `deal_new_hand_setup`.
+#[derive(Accounts)]
+pub struct RedealAfterMisdeal<'info> {
+    /// Anyone may re-deal a misdealt hand. The payer funds `HandState` if the void closed it,
+    /// and is refunded when it is closed again.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Box<Account<'info, GameState>>,
 
+    /// The `HandState` account, recreated if the misdeal closed it.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + HandState::INIT_SPACE,
+        seeds = [b"hand", game_state.key().as_ref()],
+        bump,
+    )]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    /// The table's `TableConfig`, needed to check that both players can cover their blinds.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    /// The program's MXE account, checked so a hand is never started without a cluster to deal it.
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// The handler function for `redeal_after_misdeal`.
+pub fn redeal_after_misdeal(ctx: Context<RedealAfterMisdeal>) -> Result<()> {
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    let game_state = &mut ctx.accounts.game_state;
+    require!(game_state.misdeal, ErrorCode::NoMisdeal);
+    require!(
+        !game_state.hand_in_progress && game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::InvalidAction
+    );
+
+    // The void kept the button where it was, so the same player deals and posts the same blind.
+    start_hand(game_state, &ctx.accounts.table_config, &Clock::get()?)?;
+
+    // A recreated `HandState` refunds whoever paid for it here.
+    let hand_state = &mut ctx.accounts.hand_state;
+    if hand_state.dealer == Pubkey::default() {
+        hand_state.dealer = ctx.accounts.payer.key();
+    }
     Ok(())
 }
 
@@ -120,6 +328,18 @@ pub struct DealNewHandQueue<'info> {
     )]
     pub sign_pda_account: Box<Account<'info, SignerAccount>>,
 
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The table's `TableConfig`. This computation is only valid at Hold'em tables.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::Holdem @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
     // Arcium
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Box<Account<'info, MXEAccount>>,
@@ -150,8 +370,38 @@ pub fn deal_new_hand_queue(ctx: Context<DealNewHandQueue>, computation_offset: u
 	// set bump for sign PDA so CPI can sign with seeds
 	ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
-    // queue computation only
-    let args = vec![];
+    require!(ctx.accounts.game_state.hand_in_progress, ErrorCode::NoHandInProgress);
+    // Only the hand set up for dealing is queued, and only once: a second shuffle would
+    // overwrite the hole cards and post the blinds again in the middle of the hand.
+    require!(
+        ctx.accounts.game_state.game_phase == GamePhase::Dealing,
+        ErrorCode::HandAlreadyInProgress
+    );
+    require!(!ctx.accounts.game_state.deal_queued, ErrorCode::DealAlreadyQueued);
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.shuffle_and_deal,
+    )?;
+    ctx.accounts.game_state.deal_queued = true;
+
+    // Snapshot the dealer who paid for `HandState`. A hand that ended on a fold leaves the
+    // account open for reuse, in which case the original payer keeps the refund.
+    let hand_state = &mut ctx.accounts.hand_state;
+    if hand_state.dealer == Pubkey::default() {
+        let game_state = &ctx.accounts.game_state;
+        hand_state.dealer = game_state.players[game_state.dealer_index as usize];
+    }
+    // The account may still hold the previous hand's deck; it is unusable until the new deal lands.
+    // A retained hand becomes the new hand, so it can no longer be reaped.
+    hand_state.deck_commitment = [0; 32];
+    hand_state.retained_until = 0;
+    hand_state.reset_reveal_attempts();
+    hand_state.reveal_announced = false;
+    hand_state.computation_offset = computation_offset;
+
+    // queue computation only, encrypting each player's cards to their registered key
+    let args = deal_args(&ctx.accounts.game_state);
     queue_computation(
         ctx.accounts,
         computation_offset,
@@ -160,4 +410,32 @@ pub fn deal_new_hand_queue(ctx: Context<DealNewHandQueue>, computation_offset: u
         vec![],
     )?;
     Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redeal_after_a_misdeal_keeps_the_button() {
+        let mut game_state =
+            GameState::deserialize(&mut &vec![0u8; GameState::INIT_SPACE][..]).unwrap();
+        let mut table_config =
+            TableConfig::deserialize(&mut &vec![0u8; TableConfig::INIT_SPACE][..]).unwrap();
+        table_config.small_blind = 50;
+        table_config.big_blind = 100;
+        game_state.players = [Pubkey::new_unique(), Pubkey::new_unique()];
+        game_state.stacks = [1_000, 1_000];
+        game_state.dealer_index = 1;
+        game_state.game_phase = GamePhase::Dealing;
+        game_state.hand_in_progress = true;
+
+        game_state.record_misdeal();
+        start_hand(&mut game_state, &table_config, &Clock::default()).unwrap();
+
+        assert_eq!(game_state.dealer_index, 1);
+        assert!(!game_state.misdeal && game_state.hand_in_progress);
+        assert!(game_state.game_phase == GamePhase::Dealing);
+        assert_eq!(game_state.stacks, [1_000, 1_000]);
+    }
 }
\ No newline at end of file
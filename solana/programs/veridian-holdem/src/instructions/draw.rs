@@ -0,0 +1,308 @@
+/**
+ * @description
+ * This file contains the instructions specific to the five-card draw variant. Draw tables share
+ * `deal_new_hand_setup`, `player_action` and `settle_hand` with Hold'em, but deal, draw and
+ * compare hands with their own confidential computations.
+ *
+ * @key_features
+ * - `deal_draw_hand_queue`: Queues the `shuffle_and_deal_draw` computation after setup.
+ * - `request_draw`: Lets the player whose turn it is discard and draw 0-5 cards.
+ * - `request_showdown_draw`: Queues the `determine_winner_draw` computation.
+ *
+ * @dependencies
+ * - crate::state: Defines `GameState`, `HandState` and `TableConfig`.
+ * - crate::error: Defines custom error codes.
+ * - anchor_lang & arcium_anchor: For Solana and Arcium integration.
+ */
+use crate::{
+    callbacks::{DetermineWinnerDrawCallback, DrawCardsCallback, ShuffleAndDealDrawCallback},
+    computation::{deal_args, require_cluster_set, verify_circuit_hash},
+    error::ErrorCode,
+    state::{Config, GamePhase, GameState, GameVariant, HandState, SignerAccount, TableConfig},
+    ID,
+};
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::accounts::{ClockAccount, FeePool};
+use arcium_client::idl::arcium::ID_CONST;
+
+/// The largest discard mask: all five cards discarded.
+const MAX_DISCARD_MASK: u8 = 0b1_1111;
+
+/// Accounts for queueing the five-card draw deal once `deal_new_hand_setup` has run.
+#[queue_computation_accounts("shuffle_and_deal_draw", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct DealDrawHandQueue<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    #[account(
+        mut,
+        seeds = [b"hand", game_state.key().as_ref()],
+        bump,
+    )]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + SignerAccount::INIT_SPACE,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Box<Account<'info, SignerAccount>>,
+
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::FiveCardDraw @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    // --- Arcium Required Accounts ---
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: Checked by Arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Accounts for a player to discard and draw during the draw phase.
+#[queue_computation_accounts("draw_cards", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RequestDraw<'info> {
+    /// The player whose turn it is to draw.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    #[account(mut, seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + SignerAccount::INIT_SPACE,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Box<Account<'info, SignerAccount>>,
+
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    // --- Arcium Required Accounts ---
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: Checked by Arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS,)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Accounts for requesting the showdown of a five-card draw hand.
+#[queue_computation_accounts("determine_winner_draw", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RequestShowdownDraw<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    #[account(mut, seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + SignerAccount::INIT_SPACE,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Box<Account<'info, SignerAccount>>,
+
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::FiveCardDraw @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    // --- Arcium Required Accounts ---
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: Checked by Arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS,)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Handler for the `deal_draw_hand_queue` instruction.
+pub fn deal_draw_hand_queue(ctx: Context<DealDrawHandQueue>, computation_offset: u64) -> Result<()> {
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+    require!(ctx.accounts.game_state.hand_in_progress, ErrorCode::NoHandInProgress);
+    require!(
+        ctx.accounts.game_state.game_phase == GamePhase::Dealing,
+        ErrorCode::InvalidAction
+    );
+    require!(!ctx.accounts.game_state.deal_queued, ErrorCode::DealAlreadyQueued);
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.shuffle_and_deal_draw,
+    )?;
+    ctx.accounts.game_state.deal_queued = true;
+
+    // Snapshot the dealer who paid for `HandState`, as for Hold'em.
+    let hand_state = &mut ctx.accounts.hand_state;
+    if hand_state.dealer == Pubkey::default() {
+        let game_state = &ctx.accounts.game_state;
+        hand_state.dealer = game_state.players[game_state.dealer_index as usize];
+    }
+    hand_state.deck_commitment = [0; 32];
+    hand_state.retained_until = 0;
+    hand_state.computation_offset = computation_offset;
+
+    let args = deal_args(&ctx.accounts.game_state);
+
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![ShuffleAndDealDrawCallback::callback_ix(&[])])?;
+
+    Ok(())
+}
+
+/// Handler for the `request_draw` instruction.
+/// Bit `i` of `discard_mask` discards card `i`; 0 stands pat and 31 draws five new cards.
+pub fn request_draw(
+    ctx: Context<RequestDraw>,
+    computation_offset: u64,
+    discard_mask: u8,
+) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    require!(game_state.hand_in_progress, ErrorCode::NoHandInProgress);
+    require!(game_state.game_phase == GamePhase::Draw, ErrorCode::InvalidAction);
+    require!(
+        game_state.players[game_state.current_turn_index as usize] == ctx.accounts.payer.key(),
+        ErrorCode::NotPlayerTurn
+    );
+    require!(!game_state.draw_pending, ErrorCode::InvalidAction);
+    require!(discard_mask <= MAX_DISCARD_MASK, ErrorCode::InvalidAction);
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.draw_cards,
+    )?;
+
+    // Only one draw may be in flight; the callback clears this and passes the turn on.
+    game_state.draw_pending = true;
+    ctx.accounts.hand_state.computation_offset = computation_offset;
+
+    let args = vec![Argument::PlaintextU8(discard_mask)]; // Client must also pass the encrypted hand and deck.
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![DrawCardsCallback::callback_ix(&[])])?;
+
+    Ok(())
+}
+
+/// Handler for the `request_showdown_draw` instruction.
+pub fn request_showdown_draw(ctx: Context<RequestShowdownDraw>, computation_offset: u64) -> Result<()> {
+    require!(ctx.accounts.game_state.hand_in_progress, ErrorCode::NoHandInProgress);
+    require!(
+        ctx.accounts.game_state.game_phase == GamePhase::Showdown,
+        ErrorCode::InvalidAction
+    );
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.determine_winner_draw,
+    )?;
+
+    let args = vec![]; // Client will pass both players' encrypted hands.
+    ctx.accounts.hand_state.computation_offset = computation_offset;
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![DetermineWinnerDrawCallback::callback_ix(&[])])?;
+
+    Ok(())
+}
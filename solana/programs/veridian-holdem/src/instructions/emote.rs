@@ -0,0 +1,66 @@
+/**
+ * @description
+ * This file contains the `send_emote` instruction, which lets a seated player send a quick
+ * reaction to the table. The emote is only emitted as an event for clients to render; the
+ * program does not interpret its code.
+ *
+ * @key_features
+ * - Only players seated at the table can send emotes, at any point in a hand.
+ * - Each seat may send at most one emote every `EMOTE_COOLDOWN_SECONDS`, so a player cannot
+ *   flood their opponent's client. The time of the seat's last emote is the only state kept.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` account structure.
+ * - crate::error: Defines custom error codes for validation.
+ * - crate::events: Defines the `Emote` event.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::{
+    error::ErrorCode,
+    events::Emote,
+    state::{GameState, EMOTE_COOLDOWN_SECONDS},
+};
+use anchor_lang::prelude::*;
+
+/// Defines the accounts required for a player to send an emote.
+#[derive(Accounts)]
+pub struct SendEmote<'info> {
+    /// The seated player sending the emote.
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Handler for the `send_emote` instruction.
+pub fn send_emote(ctx: Context<SendEmote>, code: u8) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let player_key = ctx.accounts.player.key();
+
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == player_key)
+        .ok_or(ErrorCode::PlayerNotInGame)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    require!(
+        timestamp - game_state.last_emote_timestamp[seat] >= EMOTE_COOLDOWN_SECONDS,
+        ErrorCode::EmoteRateLimited
+    );
+    game_state.last_emote_timestamp[seat] = timestamp;
+
+    emit!(Emote {
+        table_id: game_state.table_id,
+        seat: seat as u8,
+        code,
+        timestamp,
+    });
+
+    Ok(())
+}
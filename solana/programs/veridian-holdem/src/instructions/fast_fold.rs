@@ -0,0 +1,360 @@
+/**
+ * @description
+ * This file contains the fast-fold pool instructions. A fast-fold pool groups tables with the
+ * same stakes so that a player who folds does not sit out the rest of the hand: they are
+ * reseated at another pool table with a fresh opponent straight away. Choosing that table is
+ * left to off-chain matchmaking; on-chain, the pool records its tables and who is waiting at
+ * them, and the fold and the reseating happen in a single instruction.
+ * 1. `create_fast_fold_pool` creates a pool for a token and a set of blinds.
+ * 2. `add_fast_fold_table` adds a table with those stakes to the pool.
+ * 3. `fast_fold_and_rematch` folds the player's hand and moves them to another pool table.
+ * 4. `sync_fast_fold_table` refreshes a pool table's waiting entry after a join or a leave.
+ *
+ * @key_features
+ * - Only the admin can create pools and add tables. Pool tables cannot use "winner stays",
+ *   since a fast-folding player gives up their seat anyway.
+ * - The fold is an ordinary fold and must be made on the player's turn. Between hands, the
+ *   player simply moves.
+ * - The move works as `move_seat` does: the stack goes straight from one escrow to the other,
+ *   and the player keeps their encryption key and session totals.
+ *
+ * @dependencies
+ * - crate::state: Defines the `Config`, `FastFoldPool`, `GameState` and `TableConfig` accounts.
+ * - crate::error: Defines custom error codes for validation.
+ * - crate::events: Defines the `SeatMoved` event.
+ * - crate::instructions::move_seat: The seating helpers shared with `move_seat`.
+ * - anchor_lang & anchor_spl: For Solana and SPL Token operations.
+ */
+
+use crate::{
+    error::ErrorCode,
+    events::{HandResult, SeatMoved},
+    instructions::move_seat::{seat_moved_player, vacate_moved_seat},
+    state::{
+        Config, FastFoldPool, GamePhase, GameState, PlayerStats, TableConfig, MAX_PLAYERS, MAX_POOL_TABLES,
+        MAX_WAITLIST,
+    },
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+/// Defines the accounts required for the admin to create a fast-fold pool.
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct CreateFastFoldPool<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FastFoldPool::INIT_SPACE,
+        seeds = [b"fast_fold_pool", &pool_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub pool: Account<'info, FastFoldPool>,
+
+    /// The mint of the SPL Token the pool's tables play for.
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Defines the accounts required for the admin to add a table to a fast-fold pool.
+#[derive(Accounts)]
+pub struct AddFastFoldTable<'info> {
+    #[account(
+        mut,
+        seeds = [b"fast_fold_pool", &pool.pool_id.to_le_bytes()[..]],
+        bump,
+        constraint = !pool.contains_table(&game_state.key()) @ ErrorCode::InvalidAction
+    )]
+    pub pool: Account<'info, FastFoldPool>,
+
+    #[account(
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, which must match the pool's stakes.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.token_mint == pool.token_mint @ ErrorCode::InvalidAction,
+        constraint = table_config.small_blind == pool.small_blind @ ErrorCode::InvalidAction,
+        constraint = table_config.big_blind == pool.big_blind @ ErrorCode::InvalidAction,
+        constraint = !table_config.winner_stays @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required to refresh a pool table's waiting entry.
+#[derive(Accounts)]
+pub struct SyncFastFoldTable<'info> {
+    #[account(
+        mut,
+        seeds = [b"fast_fold_pool", &pool.pool_id.to_le_bytes()[..]],
+        bump,
+        constraint = pool.contains_table(&game_state.key()) @ ErrorCode::TableNotInPool
+    )]
+    pub pool: Account<'info, FastFoldPool>,
+
+    #[account(
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Defines the accounts required for a player to fold and move to another pool table.
+#[derive(Accounts)]
+pub struct FastFoldAndRematch<'info> {
+    /// The player folding and moving.
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"fast_fold_pool", &pool.pool_id.to_le_bytes()[..]],
+        bump,
+        constraint = pool.contains_table(&game_state.key()) @ ErrorCode::TableNotInPool,
+        constraint = pool.contains_table(&target_game_state.key()) @ ErrorCode::TableNotInPool
+    )]
+    pub pool: Box<Account<'info, FastFoldPool>>,
+
+    /// The `GameState` of the table being left.
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    /// The source table's `TableConfig`, needed for the fold.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    /// The `PlayerStats` of the player in the source table's seat 0, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[0].as_ref()],
+        bump
+    )]
+    pub seat0_stats: Option<Box<Account<'info, PlayerStats>>>,
+
+    /// The `PlayerStats` of the player in the source table's seat 1, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[1].as_ref()],
+        bump
+    )]
+    pub seat1_stats: Option<Box<Account<'info, PlayerStats>>>,
+
+    /// The source table's escrow, which the stack is withdrawn from.
+    #[account(
+        mut,
+        seeds = [b"escrow", game_state.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Box<Account<'info, TokenAccount>>,
+
+    /// The `GameState` of the pool table being joined.
+    #[account(
+        mut,
+        seeds = [b"game", &target_game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = !target_game_state.is_active @ ErrorCode::GameAlreadyInProgress,
+        constraint = target_game_state.must_move_target.is_none() @ ErrorCode::TableMustMove,
+        constraint = !target_game_state.players.contains(&player.key()) @ ErrorCode::InvalidAction
+    )]
+    pub target_game_state: Box<Account<'info, GameState>>,
+
+    /// The target table's escrow, which the stack is deposited into. Pool tables all play for
+    /// the pool's token.
+    #[account(
+        mut,
+        seeds = [b"escrow", target_game_state.key().as_ref()],
+        bump
+    )]
+    pub target_escrow_account: Box<Account<'info, TokenAccount>>,
+
+    /// The `SlotHashes` sysvar, which the button is drawn from if the target table fills.
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    /// CHECK: slot hashes sysvar
+    pub slot_hashes: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Handler for the `create_fast_fold_pool` instruction.
+pub fn create_fast_fold_pool(
+    ctx: Context<CreateFastFoldPool>,
+    pool_id: u64,
+    small_blind: u64,
+    big_blind: u64,
+) -> Result<()> {
+    require!(small_blind <= big_blind, ErrorCode::InvalidAction);
+    let pool = &mut ctx.accounts.pool;
+    pool.pool_id = pool_id;
+    pool.token_mint = ctx.accounts.token_mint.key();
+    pool.small_blind = small_blind;
+    pool.big_blind = big_blind;
+    pool.tables = [Pubkey::default(); MAX_POOL_TABLES];
+    pool.waiting = [Pubkey::default(); MAX_POOL_TABLES];
+    Ok(())
+}
+
+/// Handler for the `add_fast_fold_table` instruction.
+pub fn add_fast_fold_table(ctx: Context<AddFastFoldTable>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let table = ctx.accounts.game_state.key();
+    let slot = pool
+        .tables
+        .iter()
+        .position(|&t| t == Pubkey::default())
+        .ok_or(ErrorCode::FastFoldPoolFull)?;
+    pool.tables[slot] = table;
+    pool.sync_waiting(&table, &ctx.accounts.game_state);
+    Ok(())
+}
+
+/// Handler for the `sync_fast_fold_table` instruction.
+pub fn sync_fast_fold_table(ctx: Context<SyncFastFoldTable>) -> Result<()> {
+    let table = ctx.accounts.game_state.key();
+    ctx.accounts.pool.sync_waiting(&table, &ctx.accounts.game_state);
+    Ok(())
+}
+
+/// Handler for the `fast_fold_and_rematch` instruction.
+pub fn fast_fold_and_rematch(ctx: Context<FastFoldAndRematch>) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let target_game_state = &mut ctx.accounts.target_game_state;
+    let player_key = ctx.accounts.player.key();
+
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == player_key)
+        .ok_or(ErrorCode::PlayerNotInGame)?;
+
+    // 1. Fold the hand in progress, as `player_action` would: on the player's turn, returning
+    //    any uncalled bet and awarding the pot to the opponent. Between hands there is nothing
+    //    to fold.
+    if matches!(
+        game_state.game_phase,
+        GamePhase::PreFlop | GamePhase::Flop | GamePhase::Turn | GamePhase::River
+    ) {
+        require!(game_state.current_turn_index as usize == seat, ErrorCode::NotPlayerTurn);
+        require!(!game_state.runout, ErrorCode::InvalidAction);
+        let opponent = 1 - seat;
+        game_state.return_uncalled_bet(ctx.accounts.table_config.hand_cap)?;
+        let pot_awarded = game_state
+            .pot
+            .checked_add(game_state.bets[seat])
+            .and_then(|total| total.checked_add(game_state.bets[opponent]))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        game_state.stacks[opponent] = game_state.stacks[opponent]
+            .checked_add(pot_awarded)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let mut awarded = [0; MAX_PLAYERS];
+        awarded[opponent] = pot_awarded;
+        game_state.record_hand_stats(
+            [
+                ctx.accounts.seat0_stats.as_deref_mut().map(|stats| &mut **stats),
+                ctx.accounts.seat1_stats.as_deref_mut().map(|stats| &mut **stats),
+            ],
+            awarded,
+            0,
+        )?;
+        emit!(HandResult {
+            table_id: game_state.table_id,
+            winner_index: opponent as u8,
+            pot_awarded,
+            rake_taken: 0,
+            was_showdown: false,
+            winning_rank: None,
+        });
+        game_state.folded_player = player_key;
+        game_state.reset_for_next_hand();
+        game_state.record_action_time(&Clock::get()?);
+    } else {
+        require!(
+            game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+            ErrorCode::HandNotOver
+        );
+    }
+    let stack = game_state.stacks[seat];
+    require!(stack > 0, ErrorCode::InsufficientFunds);
+
+    // 2. Find the open seat at the target table. Queued challengers keep their priority.
+    let target_seat = target_game_state
+        .players
+        .iter()
+        .position(|&p| p == Pubkey::default())
+        .ok_or(ErrorCode::TableFull)?;
+    if target_game_state.waitlist[0] != Pubkey::default() {
+        require!(target_game_state.waitlist[0] == player_key, ErrorCode::NotNextOnWaitlist);
+        target_game_state.waitlist.rotate_left(1);
+        target_game_state.waitlist[MAX_WAITLIST - 1] = Pubkey::default();
+    }
+
+    // 3. Move the stack straight from one escrow to the other.
+    let seeds = &[
+        b"game",
+        &game_state.table_id.to_le_bytes()[..],
+        &[ctx.bumps.game_state],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.escrow_account.to_account_info(),
+        to: ctx.accounts.target_escrow_account.to_account_info(),
+        authority: game_state.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, stack)?;
+
+    emit!(SeatMoved {
+        player: player_key,
+        from_table_id: game_state.table_id,
+        to_table_id: target_game_state.table_id,
+        stack,
+    });
+
+    // 4. Reseat the player. The opponent left behind now waits for a new one at the source
+    //    table, and whoever was waiting at the target table has been matched.
+    seat_moved_player(
+        target_game_state,
+        target_seat,
+        game_state,
+        seat,
+        &ctx.accounts.slot_hashes,
+    )?;
+    vacate_moved_seat(game_state, seat);
+    let pool = &mut ctx.accounts.pool;
+    pool.sync_waiting(&game_state.key(), game_state);
+    pool.sync_waiting(&target_game_state.key(), target_game_state);
+
+    Ok(())
+}
@@ -0,0 +1,255 @@
+/**
+ * @description
+ * This file contains the instructions for heads-up all-in insurance. During an all-in runout,
+ * once the turn is out, the player who is ahead can insure the pot against being outdrawn on
+ * the river:
+ * 1. `request_insurance_quote` queues the `insurance_odds` computation, whose callback records
+ *    the leading player and how many river cards would beat them.
+ * 2. `request_insurance` lets the leader buy cover for up to the pot. The premium is the fair
+ *    price for the opponent's outs plus `Config::insurance_markup_bps`, paid from the leader's
+ *    wallet into the insurance pool.
+ * 3. If the leader loses the hand, `settle_hand` pays the cover out of the pool.
+ *
+ * @dependencies
+ * - crate::state: Defines `Config`, `GameState`, `HandState` and `TableConfig`.
+ * - crate::error: Defines custom error codes.
+ * - anchor_lang, anchor_spl & arcium_anchor: For Solana, SPL Token and Arcium integration.
+ *
+ * @notes
+ * - There is one insurance pool per token mint, a PDA token account owned by the `config` PDA.
+ *   The admin creates it with `init_insurance_pool` and funds it with ordinary transfers.
+ */
+
+use crate::{
+    callbacks::InsuranceOddsCallback,
+    computation::{require_cluster_set, verify_circuit_hash},
+    error::ErrorCode,
+    state::{Config, GamePhase, GameState, GameVariant, HandState, SignerAccount, TableConfig, MAX_BPS},
+    ID,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::accounts::{ClockAccount, FeePool};
+use arcium_client::idl::arcium::ID_CONST;
+
+/// The number of cards that can still come on the river, seen from the table: 52 minus both
+/// players' hole cards and the four cards on the board.
+pub const LIVE_RIVER_CARDS: u64 = 44;
+
+/// Defines the accounts required for the admin to create the insurance pool for a token mint.
+#[derive(Accounts)]
+pub struct InitInsurancePool<'info> {
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"insurance_pool", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = config,
+    )]
+    pub insurance_pool: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Accounts for requesting an insurance quote during an all-in runout.
+#[queue_computation_accounts("insurance_odds", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RequestInsuranceQuote<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    #[account(mut, seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + SignerAccount::INIT_SPACE,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Box<Account<'info, SignerAccount>>,
+
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The table's `TableConfig`. This computation is only valid at Hold'em tables.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::Holdem @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    // --- Arcium Required Accounts ---
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: Checked by Arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS,)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Defines the accounts required for the leading player to buy insurance.
+#[derive(Accounts)]
+pub struct RequestInsurance<'info> {
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    /// The insurance pool for the table's token mint, which receives the premium.
+    #[account(
+        mut,
+        seeds = [b"insurance_pool", table_config.token_mint.as_ref()],
+        bump
+    )]
+    pub insurance_pool: Account<'info, TokenAccount>,
+
+    /// The player's token account the premium is paid from.
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::Unauthorized,
+        constraint = player_token_account.mint == table_config.token_mint @ ErrorCode::InvalidAction
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Handler for the `init_insurance_pool` instruction.
+pub fn init_insurance_pool(_ctx: Context<InitInsurancePool>) -> Result<()> {
+    Ok(())
+}
+
+/// Handler for the `request_insurance_quote` instruction.
+pub fn request_insurance_quote(ctx: Context<RequestInsuranceQuote>, computation_offset: u64) -> Result<()> {
+    // Insurance is only offered for the river of an all-in runout, once the turn is out.
+    let game_state = &ctx.accounts.game_state;
+    require!(
+        game_state.runout
+            && game_state.game_phase == GamePhase::River
+            && game_state.dealt_community_cards() == 4,
+        ErrorCode::InsuranceNotAvailable
+    );
+    // Queueing the quote supersedes the hand's latest computation, so it must not be queued
+    // over a river reveal that is still in flight.
+    let hand_state = &ctx.accounts.hand_state;
+    require!(
+        hand_state.reveal_attempts == 0 || hand_state.reveal_failed,
+        ErrorCode::RevealInFlight
+    );
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.insurance_odds,
+    )?;
+
+    let args = vec![]; // Client will pass encrypted cards and board state.
+    ctx.accounts.hand_state.computation_offset = computation_offset;
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![InsuranceOddsCallback::callback_ix(&[])])?;
+
+    Ok(())
+}
+
+/// Handler for the `request_insurance` instruction.
+/// `cover` is the amount paid out of the insurance pool if the leader is outdrawn.
+pub fn request_insurance(ctx: Context<RequestInsurance>, cover: u64) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+
+    // The quote must be for the river that is still to come, and cover can be bought once.
+    require!(
+        game_state.runout
+            && game_state.game_phase == GamePhase::River
+            && game_state.dealt_community_cards() == 4
+            && game_state.insured_amount == 0,
+        ErrorCode::InsuranceNotAvailable
+    );
+    let leader = game_state.insurance_leader as usize;
+    require!(leader < 2, ErrorCode::InsuranceNotAvailable);
+    require!(
+        game_state.players[leader] == ctx.accounts.player.key(),
+        ErrorCode::Unauthorized
+    );
+
+    // There is nothing to insure against without outs, and at most the pot can be insured.
+    let outs = game_state.insurance_outs as u64;
+    let total_pot = game_state.pot + game_state.bets[0] + game_state.bets[1];
+    require!(outs > 0 && outs < LIVE_RIVER_CARDS, ErrorCode::InsuranceNotAvailable);
+    require!(cover > 0 && cover <= total_pot, ErrorCode::InvalidAction);
+    require!(ctx.accounts.insurance_pool.amount >= cover, ErrorCode::InsuranceNotAvailable);
+
+    // The fair premium pays out `cover` on `outs` of the live rivers, rounded up.
+    let fair_premium = (cover as u128 * outs as u128).div_ceil((LIVE_RIVER_CARDS - outs) as u128);
+    let premium = (fair_premium * (MAX_BPS as u128 + ctx.accounts.config.insurance_markup_bps as u128))
+        .div_ceil(MAX_BPS as u128) as u64;
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.player_token_account.to_account_info(),
+        to: ctx.accounts.insurance_pool.to_account_info(),
+        authority: ctx.accounts.player.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, premium)?;
+
+    game_state.insured_amount = cover;
+
+    Ok(())
+}
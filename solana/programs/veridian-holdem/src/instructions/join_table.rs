@@ -1,25 +1,33 @@
 /**
  * @description
- * This file contains the logic for the `join_table` instruction, which allows a second player
- * to join an existing, open game table. It handles validation, updates the game state, and
+ * This file contains the logic for the `join_table` instruction, which allows a player
+ * to take the open seat at an existing game table. It handles validation, updates the game state, and
  * transfers the player's buy-in to the escrow account.
  *
  * @key_features
  * - Validates that the table is open and not already active.
  * - Prevents a player from joining their own game.
- * - Updates the `GameState` with the new player's information.
+ * - Accepts any buy-in within the table's buy-in range, up to the platform's maximum buy-in.
+ * - Gives priority to the challenger at the front of the table's waitlist.
+ * - Updates the `GameState` with the new player's information, including the encryption key
+ *   from their `PlayerProfile` if they registered one.
+ * - Draws the button from the `SlotHashes` sysvar, so sitting down first does not decide who
+ *   deals first.
  * - Transfers the joiner's buy-in using a secure CPI to the SPL Token Program.
  * - Transitions the game to the `HandOver` phase, making it ready for the first deal.
  *
  * @dependencies
  * - crate::state: Defines the `GameState` and `TableConfig` account structures.
  * - crate::error: Defines custom error codes for validation.
+ * - crate::randomness: Draws the button from the latest slot hash.
  * - anchor_lang: The core Anchor framework library.
  * - anchor_spl: Anchor's helpers for interacting with SPL Token Program.
  */
 use crate::{
     error::ErrorCode,
-    state::{GamePhase, GameState, TableConfig},
+    events::MintExposureUpdated,
+    randomness::draw_button,
+    state::{Config, GamePhase, GameState, MintExposure, PlayerProfile, TableConfig, MAX_PLAYERS},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
@@ -33,12 +41,12 @@ pub struct JoinTable<'info> {
         seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
         bump,
         constraint = !game_state.is_active @ ErrorCode::GameAlreadyInProgress,
-        constraint = game_state.players[1] == Pubkey::default() @ ErrorCode::TableFull,
+        constraint = game_state.must_move_target.is_none() @ ErrorCode::TableMustMove,
         constraint = game_state.table_id == table_config.table_id
     )]
     pub game_state: Account<'info, GameState>,
 
-    /// The `TableConfig` account, needed to verify the `buy_in` amount and `token_mint`.
+    /// The `TableConfig` account, needed to verify the buy-in amount and `token_mint`.
     #[account(
         seeds = [b"table_config", &table_config.table_id.to_le_bytes()[..]],
         bump
@@ -61,27 +69,101 @@ pub struct JoinTable<'info> {
     #[account(
         mut,
         constraint = joiner_token_account.mint == table_config.token_mint,
-        constraint = joiner.key() != game_state.players[0] @ ErrorCode::InvalidAction
+        constraint = !game_state.players.contains(&joiner.key()) @ ErrorCode::InvalidAction
     )]
     pub joiner_token_account: Account<'info, TokenAccount>,
 
+    /// The joiner's `PlayerProfile`, if they pre-registered an encryption key.
+    #[account(
+        seeds = [b"player_profile", joiner.key().as_ref()],
+        bump
+    )]
+    pub joiner_profile: Option<Account<'info, PlayerProfile>>,
+
+    /// The global `Config` account, needed to enforce the platform's maximum buy-in.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    /// The table's token mint's `MintExposure`, which counts the buy-in.
+    #[account(
+        mut,
+        seeds = [b"mint_exposure", table_config.token_mint.as_ref()],
+        bump
+    )]
+    pub mint_exposure: Account<'info, MintExposure>,
+
+    /// The `SlotHashes` sysvar, which the button is drawn from.
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    /// CHECK: slot hashes sysvar
+    pub slot_hashes: AccountInfo<'info>,
+
     /// The SPL Token Program.
     pub token_program: Program<'info, Token>,
 }
 
 /// The handler function for the `join_table` instruction.
-pub fn join_table(ctx: Context<JoinTable>) -> Result<()> {
+/// `buy_in_amount` is the joiner's chosen buy-in, within the table's buy-in range.
+pub fn join_table(ctx: Context<JoinTable>, buy_in_amount: u64) -> Result<()> {
     let game_state = &mut ctx.accounts.game_state;
     let table_config = &ctx.accounts.table_config;
 
-    // 1. Update GameState: Add the new player to the empty seat, set their stack,
+    let joiner_key = ctx.accounts.joiner.key();
+
+    require!(
+        buy_in_amount >= table_config.min_buy_in && buy_in_amount <= table_config.max_buy_in,
+        ErrorCode::InvalidBuyInAmount
+    );
+    // The cap may have been lowered since the table was created.
+    let max_buy_in_cap = ctx.accounts.config.max_buy_in_cap;
+    require!(
+        max_buy_in_cap == 0 || buy_in_amount <= max_buy_in_cap,
+        ErrorCode::ExceedsPlatformCap
+    );
+
+    // 1. Find the open seat. Under "winner stays" this may be either seat.
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == Pubkey::default())
+        .ok_or(ErrorCode::TableFull)?;
+
+    // 2. Under "winner stays", queued challengers get the seat in order; anyone may join if
+    //    nobody is waiting.
+    if table_config.winner_stays && game_state.waitlist[0] != Pubkey::default() {
+        require!(game_state.waitlist[0] == joiner_key, ErrorCode::NotNextOnWaitlist);
+        game_state.remove_from_waitlist(joiner_key)?;
+    }
+
+    // 3. Update GameState: Add the new player to the empty seat, set their stack,
     //    and mark the game as active and ready for a new hand.
-    game_state.players[1] = ctx.accounts.joiner.key();
-    game_state.stacks[1] = table_config.buy_in;
-    game_state.is_active = true;
+    game_state.players[seat] = joiner_key;
+    game_state.stacks[seat] = buy_in_amount;
+    game_state.slow_action_count[seat] = 0;
+    game_state.referrers[seat] = Pubkey::default();
+    game_state.session_buy_in[seat] = buy_in_amount;
+    game_state.session_payout[seat] = 0;
+    game_state.sitting_out[seat] = false;
+    game_state.missed_blinds[seat] = 0;
+    game_state.time_charged_at[seat] = 0;
+    game_state.time_bank_seconds[seat] = table_config.time_bank_seconds as i64;
+    game_state.hands_played[seat] = 0;
+    // Joining starts a new match, so under "winner stays" the seated winner's rebuys reset too.
+    game_state.rebuy_count = [0; MAX_PLAYERS];
+    game_state.auto_rebuy_min[seat] = None;
+    game_state.auto_rebuy_accounts[seat] = Pubkey::default();
+    game_state.encryption_keys[seat] = ctx
+        .accounts
+        .joiner_profile
+        .as_ref()
+        .map_or([0; 32], |profile| profile.encryption_pubkey);
     game_state.game_phase = GamePhase::HandOver; // Ready for the first hand to be dealt.
+    game_state.recompute_is_active();
 
-    // 2. Perform a CPI to the SPL Token Program to transfer the joiner's buy-in.
+    // Draw for the button from the latest slot hash, so neither the creator's choice of seat
+    // nor the order of joining decides who deals first.
+    draw_button(game_state, &ctx.accounts.slot_hashes)?;
+
+    // 4. Perform a CPI to the SPL Token Program to transfer the joiner's buy-in.
     let cpi_accounts = Transfer {
         from: ctx.accounts.joiner_token_account.to_account_info(),
         to: ctx.accounts.escrow_account.to_account_info(),
@@ -89,7 +171,11 @@ pub fn join_table(ctx: Context<JoinTable>) -> Result<()> {
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, table_config.buy_in)?;
+    token::transfer(cpi_ctx, buy_in_amount)?;
+
+    let mint_exposure = &mut ctx.accounts.mint_exposure;
+    mint_exposure.deposit(buy_in_amount);
+    emit!(MintExposureUpdated::from(&**mint_exposure));
 
     Ok(())
 }
\ No newline at end of file
@@ -6,21 +6,28 @@
  * @key_features
  * - Validates that the game is in a non-active state (e.g., between hands).
  * - Transfers the player's chip balance from the escrow PDA back to their wallet.
+ * - A player who leaves while ahead soon after sitting down pays the platform's leave penalty,
+ *   if enabled, to their opponent: it moves from their withdrawal to the opponent's stack.
+ * - Emits a `SessionEnded` event with the player's net result for the session.
  * - Resets the player's slot in the `GameState` to allow a new player to join.
- * - Handles closing game accounts if the last player leaves, refunding rent.
+ * - When the last player leaves, closes the escrow, `TableConfig` and `GameState` accounts and
+ *   refunds their rent to that player. Leaves are processed one transaction at a time, so when
+ *   both players leave back-to-back, the first only vacates a seat and the second closes the table.
+ * - Records the withdrawal, and the closing of an emptied table, in the mint's `MintExposure`.
  *
  * @dependencies
- * - crate::state: Defines the `GameState` and `TableConfig`.
+ * - crate::state: Defines the `GameState`, `TableConfig`, `Config` and `MintExposure`.
  * - crate::error: Defines custom error codes.
  * - anchor_lang & anchor_spl: For Solana and SPL Token operations.
  */
 
 use crate::{
     error::ErrorCode,
-    state::{GamePhase, GameState, TableConfig},
+    events::{LeavePenaltyPaid, MintExposureUpdated, SessionEnded},
+    state::{Config, GamePhase, GameState, MintExposure, TableConfig},
 };
-use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
 
 #[derive(Accounts)]
 pub struct LeaveTable<'info> {
@@ -33,14 +40,13 @@ pub struct LeaveTable<'info> {
         mut,
         seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
         bump,
-        // The table can be closed if this is the last player leaving.
-        // A more robust implementation would also close the TableConfig and Escrow accounts.
-        close = player
     )]
     pub game_state: Account<'info, GameState>,
 
     /// The associated `TableConfig`, needed to verify the player is at the right table.
+    /// Closed along with the `GameState` when the last player leaves.
     #[account(
+        mut,
         seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
         bump
     )]
@@ -58,6 +64,18 @@ pub struct LeaveTable<'info> {
     #[account(mut)]
     pub player_token_account: Account<'info, TokenAccount>,
 
+    /// The global `Config` account, holding the leave penalty settings.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    /// The table's token mint's `MintExposure`, which counts the withdrawal and the table closing.
+    #[account(
+        mut,
+        seeds = [b"mint_exposure", table_config.token_mint.as_ref()],
+        bump
+    )]
+    pub mint_exposure: Account<'info, MintExposure>,
+
     pub token_program: Program<'info, Token>,
 }
 
@@ -79,17 +97,33 @@ pub fn leave_table(ctx: Context<LeaveTable>) -> Result<()> {
         .position(|&p| p == player_key)
         .ok_or(ErrorCode::PlayerNotInGame)?;
     
+    // A player leaving early with winnings leaves the penalty behind in the escrow, as part
+    // of their opponent's stack.
+    let penalty = game_state.leave_penalty(player_index, &ctx.accounts.config)?;
+    if penalty > 0 {
+        let opponent_index = 1 - player_index;
+        game_state.stacks[opponent_index] = game_state.stacks[opponent_index]
+            .checked_add(penalty)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        game_state.stacks[player_index] -= penalty;
+        emit!(LeavePenaltyPaid {
+            table_id: game_state.table_id,
+            player: player_key,
+            opponent: game_state.players[opponent_index],
+            amount: penalty,
+        });
+    }
     let amount_to_withdraw = game_state.stacks[player_index];
 
+    let seeds = &[
+        b"game",
+        &game_state.table_id.to_le_bytes()[..],
+        &[ctx.bumps.game_state],
+    ];
+    let signer = &[&seeds[..]];
+
     // 3. Transfer funds from escrow back to the player.
     if amount_to_withdraw > 0 {
-        let seeds = &[
-            b"game",
-            &game_state.table_config.key().to_bytes()[..],
-            &[ctx.bumps.game_state],
-        ];
-        let signer = &[&seeds[..]];
-
         let cpi_accounts = Transfer {
             from: ctx.accounts.escrow_account.to_account_info(),
             to: ctx.accounts.player_token_account.to_account_info(),
@@ -100,17 +134,60 @@ pub fn leave_table(ctx: Context<LeaveTable>) -> Result<()> {
         token::transfer(cpi_ctx, amount_to_withdraw)?;
     }
 
+    let total_buy_in = game_state.session_buy_in[player_index];
+    let cashed_out = game_state.session_payout[player_index];
+    // Summed in i128 so the summary can never overflow and block the withdrawal above.
+    let net = cashed_out as i128 + amount_to_withdraw as i128 - total_buy_in as i128;
+    emit!(SessionEnded {
+        table_id: game_state.table_id,
+        player: player_key,
+        total_buy_in,
+        cashed_out,
+        final_withdrawal: amount_to_withdraw,
+        net: net.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+    });
+
     // 4. Update the game state to remove the player.
     game_state.players[player_index] = Pubkey::default();
     game_state.stacks[player_index] = 0;
-    game_state.is_active = false; // The game is no longer active with one player.
+    game_state.slow_action_count[player_index] = 0;
+    game_state.referrers[player_index] = Pubkey::default();
+    game_state.encryption_keys[player_index] = [0; 32];
+    game_state.sitting_out[player_index] = false;
+    game_state.missed_blinds[player_index] = 0;
+    game_state.time_charged_at[player_index] = 0;
+    game_state.hands_played[player_index] = 0;
+    game_state.rebuy_count[player_index] = 0;
+    game_state.auto_rebuy_min[player_index] = None;
+    game_state.auto_rebuy_accounts[player_index] = Pubkey::default();
+    game_state.session_buy_in[player_index] = 0;
+    game_state.session_payout[player_index] = 0;
     game_state.game_phase = GamePhase::Idle;
+    game_state.recompute_is_active(); // The game is no longer active with one player.
+    game_state.seat_dealer();
+
+    // 5. If the table is now empty, close it. The escrow is closed first, while the
+    //    `GameState` that signs for it still exists. Its balance is zero at this point, since
+    //    every chip belonged to a stack and both stacks have now been withdrawn.
+    if game_state.players.iter().all(|&p| p == Pubkey::default()) {
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.escrow_account.to_account_info(),
+            destination: ctx.accounts.player.to_account_info(),
+            authority: game_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::close_account(cpi_ctx)?;
+
+        let player_info = ctx.accounts.player.to_account_info();
+        ctx.accounts.table_config.close(player_info.clone())?;
+        ctx.accounts.game_state.close(player_info)?;
+        ctx.accounts.mint_exposure.close_table();
+    }
 
-    // Note: The logic to close the `GameState` and `Escrow` accounts when the
-    // *last* player leaves is complex and requires checking if the other player slot
-    // is also empty. For simplicity in this step, we assume the `close` attribute
-    // on `game_state` will handle rent reclamation if it becomes empty, though
-    // a more robust implementation would explicitly handle closing the escrow as well.
+    let mint_exposure = &mut ctx.accounts.mint_exposure;
+    mint_exposure.withdraw(amount_to_withdraw);
+    emit!(MintExposureUpdated::from(&**mint_exposure));
 
     Ok(())
 }
\ No newline at end of file
@@ -16,6 +16,24 @@ pub mod player_action;
 pub mod request_cards;
 pub mod leave_table;
 pub mod crank_fold;
+pub mod waitlist;
+pub mod settlement;
+pub mod referral;
+pub mod draw;
+pub mod rakeback;
+pub mod profile;
+pub mod sit_out;
+pub mod insurance;
+pub mod rebuy;
+pub mod args_spec;
+pub mod move_seat;
+pub mod straddle;
+pub mod crank_status;
+pub mod tournament;
+pub mod audit;
+pub mod fast_fold;
+pub mod emote;
+pub mod reconcile;
 
 // Publicly re-export all items from the declared modules.
 pub use admin::*;
@@ -25,4 +43,22 @@ pub use deal_new_hand::*;
 pub use player_action::*;
 pub use request_cards::*;
 pub use leave_table::*;
-pub use crank_fold::*;
\ No newline at end of file
+pub use crank_fold::*;
+pub use waitlist::*;
+pub use settlement::*;
+pub use referral::*;
+pub use draw::*;
+pub use rakeback::*;
+pub use profile::*;
+pub use sit_out::*;
+pub use insurance::*;
+pub use rebuy::*;
+pub use args_spec::*;
+pub use move_seat::*;
+pub use straddle::*;
+pub use crank_status::*;
+pub use tournament::*;
+pub use audit::*;
+pub use fast_fold::*;
+pub use emote::*;
+pub use reconcile::*;
\ No newline at end of file
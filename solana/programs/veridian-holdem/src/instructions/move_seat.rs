@@ -0,0 +1,281 @@
+/**
+ * @description
+ * This file contains the "must-move" instructions used by platforms running many tables to
+ * consolidate players. The operator flags a table with `set_must_move_target`, naming the table
+ * its players should move to, and each seated player then moves with `move_seat`. The move
+ * withdraws the player's stack from one escrow and deposits it into the other in a single
+ * instruction, so the funds never pass through the player's wallet.
+ *
+ * @key_features
+ * - Only the admin can designate (or clear) a table's must-move target.
+ * - While a table is flagged, no new hand is dealt and nobody can join it.
+ * - The moving player keeps their stack, encryption key and session totals, and takes the open
+ *   seat at the target table with the same waitlist priority as `join_table`.
+ * - When the last player moves away, the source table is closed as in `leave_table`.
+ *
+ * @dependencies
+ * - crate::state: Defines the `Config`, `GameState` and `TableConfig` account structures.
+ * - crate::error: Defines custom error codes for validation.
+ * - crate::events: Defines the `SeatMoved` and `MintExposureUpdated` events.
+ * - crate::randomness: Draws the button when the target table fills.
+ * - anchor_lang & anchor_spl: For Solana and SPL Token operations.
+ */
+
+use crate::{
+    error::ErrorCode,
+    events::{MintExposureUpdated, SeatMoved},
+    randomness::draw_button,
+    state::{Config, GamePhase, GameState, MintExposure, TableConfig, MAX_PLAYERS, MAX_WAITLIST},
+};
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+
+/// Defines the accounts required for the admin to flag a table for consolidation.
+#[derive(Accounts)]
+pub struct SetMustMoveTarget<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Defines the accounts required for a player to move to the designated table.
+#[derive(Accounts)]
+pub struct MoveSeat<'info> {
+    /// The player moving, who receives the source table's rent if they were the last to leave.
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// The `GameState` of the table being left.
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = game_state.must_move_target == Some(target_game_state.key()) @ ErrorCode::InvalidAction
+    )]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    /// The source table's `TableConfig`, closed along with its `GameState` when it empties.
+    #[account(
+        mut,
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    /// The source table's escrow, which the stack is withdrawn from.
+    #[account(
+        mut,
+        seeds = [b"escrow", game_state.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Box<Account<'info, TokenAccount>>,
+
+    /// The `GameState` of the table being joined.
+    #[account(
+        mut,
+        seeds = [b"game", &target_game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = !target_game_state.is_active @ ErrorCode::GameAlreadyInProgress,
+        constraint = target_game_state.must_move_target.is_none() @ ErrorCode::TableMustMove,
+        constraint = !target_game_state.players.contains(&player.key()) @ ErrorCode::InvalidAction
+    )]
+    pub target_game_state: Box<Account<'info, GameState>>,
+
+    /// The target table's `TableConfig`. Both tables must play for the same token.
+    #[account(
+        seeds = [b"table_config", &target_game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = target_table_config.token_mint == table_config.token_mint @ ErrorCode::InvalidAction
+    )]
+    pub target_table_config: Box<Account<'info, TableConfig>>,
+
+    /// The target table's escrow, which the stack is deposited into.
+    #[account(
+        mut,
+        seeds = [b"escrow", target_game_state.key().as_ref()],
+        bump
+    )]
+    pub target_escrow_account: Box<Account<'info, TokenAccount>>,
+
+    /// The tables' token mint's `MintExposure`, which counts the source table closing. The stack
+    /// stays in the mint's escrows, so the escrowed total is unchanged.
+    #[account(
+        mut,
+        seeds = [b"mint_exposure", table_config.token_mint.as_ref()],
+        bump
+    )]
+    pub mint_exposure: Box<Account<'info, MintExposure>>,
+
+    /// The `SlotHashes` sysvar, which the button is drawn from if the target table fills.
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    /// CHECK: slot hashes sysvar
+    pub slot_hashes: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Handler for the `set_must_move_target` instruction.
+/// `target` is the `GameState` of the table to move to, or `None` to clear the flag.
+pub fn set_must_move_target(ctx: Context<SetMustMoveTarget>, target: Option<Pubkey>) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    require!(target != Some(game_state.key()), ErrorCode::InvalidAction);
+    game_state.must_move_target = target;
+    Ok(())
+}
+
+/// Handler for the `move_seat` instruction.
+pub fn move_seat(ctx: Context<MoveSeat>) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let target_game_state = &mut ctx.accounts.target_game_state;
+    let player_key = ctx.accounts.player.key();
+
+    // 1. Validate that the source table is between hands and the player has a stack to move.
+    require!(
+        game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::HandNotOver
+    );
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == player_key)
+        .ok_or(ErrorCode::PlayerNotInGame)?;
+    let stack = game_state.stacks[seat];
+    require!(stack > 0, ErrorCode::InvalidAction);
+
+    // 2. Find the open seat at the target table. Queued challengers keep their priority.
+    let target_seat = target_game_state
+        .players
+        .iter()
+        .position(|&p| p == Pubkey::default())
+        .ok_or(ErrorCode::TableFull)?;
+    if target_game_state.waitlist[0] != Pubkey::default() {
+        require!(target_game_state.waitlist[0] == player_key, ErrorCode::NotNextOnWaitlist);
+        target_game_state.waitlist.rotate_left(1);
+        target_game_state.waitlist[MAX_WAITLIST - 1] = Pubkey::default();
+    }
+
+    // 3. Move the stack straight from one escrow to the other.
+    let seeds = &[
+        b"game",
+        &game_state.table_id.to_le_bytes()[..],
+        &[ctx.bumps.game_state],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.escrow_account.to_account_info(),
+        to: ctx.accounts.target_escrow_account.to_account_info(),
+        authority: game_state.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, stack)?;
+
+    emit!(SeatMoved {
+        player: player_key,
+        from_table_id: game_state.table_id,
+        to_table_id: target_game_state.table_id,
+        stack,
+    });
+
+    // 4. Seat the player at the target table and vacate their seat at the source table.
+    seat_moved_player(
+        target_game_state,
+        target_seat,
+        game_state,
+        seat,
+        &ctx.accounts.slot_hashes,
+    )?;
+    vacate_moved_seat(game_state, seat);
+
+    // 5. If the source table is now empty, close it as `leave_table` does.
+    if game_state.players.iter().all(|&p| p == Pubkey::default()) {
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.escrow_account.to_account_info(),
+            destination: ctx.accounts.player.to_account_info(),
+            authority: game_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::close_account(cpi_ctx)?;
+
+        let player_info = ctx.accounts.player.to_account_info();
+        ctx.accounts.table_config.close(player_info.clone())?;
+        ctx.accounts.game_state.close(player_info)?;
+
+        let mint_exposure = &mut ctx.accounts.mint_exposure;
+        mint_exposure.close_table();
+        emit!(MintExposureUpdated::from(&***mint_exposure));
+    }
+
+    Ok(())
+}
+
+/// Seats the player moving from `seat` at `source` in the open `target_seat` at `target`, with
+/// their whole stack. The session carries over, so leaving the target table later reports the
+/// player's result across both tables. If the target table is now full, the match starts.
+pub(crate) fn seat_moved_player(
+    target: &mut GameState,
+    target_seat: usize,
+    source: &GameState,
+    seat: usize,
+    slot_hashes: &AccountInfo,
+) -> Result<()> {
+    target.players[target_seat] = source.players[seat];
+    target.stacks[target_seat] = source.stacks[seat];
+    target.slow_action_count[target_seat] = 0;
+    target.referrers[target_seat] = Pubkey::default();
+    target.session_buy_in[target_seat] = source.session_buy_in[seat];
+    target.session_payout[target_seat] = source.session_payout[seat];
+    target.sitting_out[target_seat] = false;
+    target.missed_blinds[target_seat] = 0;
+    target.time_charged_at[target_seat] = 0;
+    // The time bank carries over with the session; the next deal caps it at the target table's.
+    target.time_bank_seconds[target_seat] = source.time_bank_seconds[seat];
+    target.hands_played[target_seat] = 0;
+    // Sitting down starts a new match, so the rebuy counts reset as in `join_table`.
+    target.rebuy_count = [0; MAX_PLAYERS];
+    target.auto_rebuy_min[target_seat] = None;
+    target.auto_rebuy_accounts[target_seat] = Pubkey::default();
+    target.encryption_keys[target_seat] = source.encryption_keys[seat];
+    if !target.players.contains(&Pubkey::default()) {
+        target.game_phase = GamePhase::HandOver; // Ready for the first hand to be dealt.
+
+        // Draw for the button, as in `join_table`.
+        draw_button(target, slot_hashes)?;
+    }
+    target.recompute_is_active();
+    Ok(())
+}
+
+/// Empties the seat a player has moved away from, leaving the table waiting for a new opponent.
+pub(crate) fn vacate_moved_seat(game_state: &mut GameState, seat: usize) {
+    game_state.players[seat] = Pubkey::default();
+    game_state.stacks[seat] = 0;
+    game_state.slow_action_count[seat] = 0;
+    game_state.referrers[seat] = Pubkey::default();
+    game_state.encryption_keys[seat] = [0; 32];
+    game_state.sitting_out[seat] = false;
+    game_state.missed_blinds[seat] = 0;
+    game_state.time_charged_at[seat] = 0;
+    game_state.hands_played[seat] = 0;
+    game_state.rebuy_count[seat] = 0;
+    game_state.auto_rebuy_min[seat] = None;
+    game_state.auto_rebuy_accounts[seat] = Pubkey::default();
+    game_state.session_buy_in[seat] = 0;
+    game_state.session_payout[seat] = 0;
+    game_state.game_phase = GamePhase::Idle;
+    game_state.recompute_is_active();
+    game_state.seat_dealer();
+}
@@ -6,10 +6,15 @@
  *
  * @key_features
  * - A single entry point for all player actions, using an enum to differentiate.
- * - Rigorous validation of player turn, action legality, and bet amounts.
- * - Manages updates to player stacks, bets, and the pot.
+ * - Rigorous validation of player turn, action legality, and bet amounts. Acting while the
+ *   hand is being dealt or run out is rejected with its own error, so clients can tell why.
+ * - Manages updates to player stacks, bets, and the pot with checked arithmetic, so extreme
+ *   amounts are rejected with an error rather than aborting the transaction with a panic.
  * - Handles all-in logic and side pots (though side pots are simpler in heads-up).
- * - Determines when a betting round is complete and transitions the `game_phase`.
+ * - Tracks which players have acted on the street, closing the betting round once both have
+ *   acted with equal bets, and transitions the `game_phase`.
+ * - Emits `AutoProgressRequired` when a called all-in closes the betting, so cranks can run the
+ *   hand out to settlement without the players.
  *
  * @dependencies
  * - crate::state: Defines the `GameState` account structure and `GamePhase` enum.
@@ -19,7 +24,11 @@
 
 use crate::{
     error::ErrorCode,
-    state::{GamePhase, GameState, MAX_PLAYERS},
+    events::{AutoProgressRequired, ChallengerSeatOpened, HandResult},
+    state::{
+        GamePhase, GameState, GameVariant, PlayerStats, TableConfig, MAX_PLAYERS,
+        SLOW_ACTION_THRESHOLD_PERCENT,
+    },
 };
 use anchor_lang::prelude::*;
 
@@ -31,7 +40,10 @@ pub enum Action {
     Fold,
     Check,
     Call,
+    /// Opens the betting on a street with this many chips.
     Bet(u64),
+    /// Raises the player's bet on the street *to* this total, not by it. The raise itself is
+    /// the amount above the opponent's bet.
     Raise(u64),
 }
 
@@ -48,16 +60,46 @@ pub struct PlayerAction<'info> {
         bump
     )]
     pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, needed for rule variants such as the per-hand cap.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    /// The `PlayerStats` of the player in seat 0, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[0].as_ref()],
+        bump
+    )]
+    pub seat0_stats: Option<Box<Account<'info, PlayerStats>>>,
+
+    /// The `PlayerStats` of the player in seat 1, if they keep lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[1].as_ref()],
+        bump
+    )]
+    pub seat1_stats: Option<Box<Account<'info, PlayerStats>>>,
 }
 
 /// The handler function for the `player_action` instruction.
 pub fn player_action(ctx: Context<PlayerAction>, action: Action) -> Result<()> {
     let game_state = &mut ctx.accounts.game_state;
+    let hand_cap = ctx.accounts.table_config.hand_cap;
+    let variant = ctx.accounts.table_config.variant;
     let player = &ctx.accounts.player;
     let player_index = game_state.current_turn_index as usize;
     let opponent_index = (1 - game_state.current_turn_index) as usize;
 
     // --- 1. Validation ---
+    // While the shuffle is in flight nobody can act yet; tell clients to wait for the deal.
+    require!(game_state.game_phase != GamePhase::Dealing, ErrorCode::HandNotDealt);
+    // There is no betting during an all-in runout. This is checked before the turn, since
+    // nobody is on the clock once the runout has started.
+    require!(!game_state.runout, ErrorCode::ActionNotAllowedInRunout);
     // Ensure it's the correct player's turn.
     require!(
         game_state.players[player_index] == player.key(),
@@ -72,105 +114,235 @@ pub fn player_action(ctx: Context<PlayerAction>, action: Action) -> Result<()> {
         ErrorCode::InvalidAction
     );
 
+    // Record chronically slow play: acting after most of the clock has run down
+    // shortens this player's timer for future turns.
+    let clock = Clock::get()?;
+    let (elapsed, turn_time) =
+        game_state.turn_progress(player_index, &clock, &ctx.accounts.table_config);
+    if elapsed.saturating_mul(100) >= turn_time.saturating_mul(SLOW_ACTION_THRESHOLD_PERCENT) {
+        game_state.slow_action_count[player_index] =
+            game_state.slow_action_count[player_index].saturating_add(1);
+    }
+    // Any time taken beyond the turn timer comes out of the player's time bank.
+    game_state.use_time_bank(player_index, elapsed, turn_time, &ctx.accounts.table_config);
+
     // Get player stack and bet values
     let _player_stack = game_state.stacks[player_index];
     let _player_bet = game_state.bets[player_index];
     let opponent_bet = game_state.bets[opponent_index];
 
     // --- 2. Process Action ---
+    // Every action counts as acting on this street. A fold ends the hand, so it never
+    // reaches the end-of-round check.
+    game_state.has_acted[player_index] = true;
     match action {
         Action::Fold => {
-            // Award pot to the opponent.
-            game_state.stacks[opponent_index] += game_state.pot + game_state.bets[player_index] + opponent_bet;
+            // The opponent's bet beyond what this player put in was never called, so it goes
+            // straight back to them rather than being won. Then award them the pot.
+            game_state.return_uncalled_bet(hand_cap)?;
+            let pot_awarded = game_state
+                .pot
+                .checked_add(game_state.bets[player_index])
+                .and_then(|total| total.checked_add(game_state.bets[opponent_index]))
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            game_state.stacks[opponent_index] = game_state.stacks[opponent_index]
+                .checked_add(pot_awarded)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            let mut awarded = [0; MAX_PLAYERS];
+            awarded[opponent_index] = pot_awarded;
+            game_state.record_hand_stats(
+                [
+                    ctx.accounts.seat0_stats.as_deref_mut().map(|stats| &mut **stats),
+                    ctx.accounts.seat1_stats.as_deref_mut().map(|stats| &mut **stats),
+                ],
+                awarded,
+                0,
+            )?;
+            emit!(HandResult {
+                table_id: game_state.table_id,
+                winner_index: opponent_index as u8,
+                pot_awarded,
+                rake_taken: 0,
+                was_showdown: false,
+                winning_rank: None,
+            });
+            // Until the next deal, the folding player may opt to show their cards.
+            game_state.folded_player = player.key();
             // Transition to HandOver to await the next deal.
-            transition_to_next_hand(game_state);
+            game_state.reset_for_next_hand();
+            if ctx.accounts.table_config.winner_stays {
+                if let Some(seat) = game_state.open_busted_seat() {
+                    emit!(ChallengerSeatOpened {
+                        table_id: game_state.table_id,
+                        winner: game_state.players[opponent_index],
+                        open_seat: seat as u8,
+                    });
+                }
+            }
         }
         Action::Check => {
             // A check is only valid if the player's bet matches the opponent's bet.
             require!(game_state.bets[player_index] == opponent_bet, ErrorCode::InvalidAction);
-            // If the checker is the big blind (second to act pre-flop) or the small blind
-            // (first to act post-flop) and bets are equal, the round ends.
-            let is_round_over = game_state.current_turn_index != game_state.dealer_index;
-            if is_round_over {
-                handle_round_transition(game_state);
-            } else {
-                game_state.current_turn_index = opponent_index as u8;
-            }
         }
         Action::Call => {
-            let _amount_to_call = opponent_bet - game_state.bets[player_index];
+            // The call matches only what the opponent has committed on this street, so a caller
+            // who covers an all-in opponent keeps the rest of their stack.
+            let _amount_to_call = opponent_bet
+                .checked_sub(game_state.bets[player_index])
+                .ok_or(ErrorCode::InvalidAction)?;
             // Cannot call if no bet is pending.
             require!(_amount_to_call > 0, ErrorCode::InvalidAction);
+            // Under "no limp", the small blind may not just complete an unraised big blind.
+            let is_limp = game_state.game_phase == GamePhase::PreFlop
+                && player_index == game_state.dealer_index as usize
+                && opponent_bet <= ctx.accounts.table_config.big_blind;
+            require!(!(ctx.accounts.table_config.no_limp && is_limp), ErrorCode::LimpNotAllowed);
 
-            if _amount_to_call >= game_state.stacks[player_index] {
-                // Player is all-in.
-                game_state.bets[player_index] += game_state.stacks[player_index];
-                game_state.stacks[player_index] = 0;
-                game_state.is_all_in[player_index] = true;
-            } else {
-                // Regular call.
-                game_state.stacks[player_index] -= _amount_to_call;
-                game_state.bets[player_index] += _amount_to_call;
-            }
-            // A call always ends the betting round.
-            handle_round_transition(game_state);
+            // A call for more than the stack puts the player all-in for what they have.
+            let call_amount = _amount_to_call.min(game_state.stacks[player_index]);
+            require_within_cap(game_state, player_index, call_amount, hand_cap)?;
+            game_state.commit_chips(player_index, call_amount, hand_cap)?;
+            // If the caller was all-in for less, the rest of the bet is returned uncalled.
+            game_state.return_uncalled_bet(hand_cap)?;
         }
         Action::Bet(amount) => {
             // A bet is only valid if there are no outstanding bets.
             require!(game_state.bets[player_index] == opponent_bet, ErrorCode::InvalidAction);
             require!(amount > 0, ErrorCode::InvalidBetAmount);
             require!(amount <= game_state.stacks[player_index], ErrorCode::InsufficientFunds);
-            // TODO: Add validation for minimum bet size (e.g., must be at least the big blind).
+            require_within_cap(game_state, player_index, amount, hand_cap)?;
+            // An opening bet must be at least the big blind, unless it puts the player all-in or
+            // at the cap.
+            let all_in = amount == game_state.stacks[player_index]
+                || (hand_cap > 0
+                    && game_state.total_contributed[player_index].checked_add(amount) == Some(hand_cap));
+            require!(
+                amount >= ctx.accounts.table_config.big_blind || all_in,
+                ErrorCode::BetBelowMinimum
+            );
 
-            game_state.stacks[player_index] -= amount;
-            game_state.bets[player_index] += amount;
-            if game_state.stacks[player_index] == 0 {
-                game_state.is_all_in[player_index] = true;
-            }
-            game_state.current_turn_index = opponent_index as u8;
+            game_state.commit_chips(player_index, amount, hand_cap)?;
+            game_state.last_raise_size = amount;
+            game_state.has_acted[opponent_index] = false;
         }
         Action::Raise(amount) => {
-            let raise_amount = amount - game_state.bets[player_index];
-            let _amount_to_call = opponent_bet - game_state.bets[player_index];
-            let min_raise = opponent_bet - game_state.bets[player_index]; // The previous bet/raise size.
-            // A raise must be at least the size of the previous bet/raise.
-            require!(raise_amount >= min_raise, ErrorCode::InvalidBetAmount);
             require!(amount > opponent_bet, ErrorCode::InvalidBetAmount);
-            require!(amount <= game_state.stacks[player_index] + game_state.bets[player_index], ErrorCode::InsufficientFunds);
+            // A stack and bet that cannot be added together cannot cover any raise either.
+            let available = game_state.stacks[player_index]
+                .checked_add(game_state.bets[player_index])
+                .ok_or(ErrorCode::InsufficientFunds)?;
+            require!(amount <= available, ErrorCode::InsufficientFunds);
 
-            let total_investment = amount - game_state.bets[player_index];
-            game_state.stacks[player_index] -= total_investment;
-            game_state.bets[player_index] = amount;
+            // `amount` is the total bet after the raise; the player adds the difference.
+            let total_investment = amount
+                .checked_sub(game_state.bets[player_index])
+                .ok_or(ErrorCode::InvalidBetAmount)?;
+            require_within_cap(game_state, player_index, total_investment, hand_cap)?;
 
-            if game_state.stacks[player_index] == 0 {
-                game_state.is_all_in[player_index] = true;
-            }
+            // A raise must add at least the table's multiple of the previous bet or raise (and
+            // never less than the big blind), unless it puts the player all-in or at the cap.
+            let table_config = &ctx.accounts.table_config;
+            let raise_size = amount
+                .checked_sub(opponent_bet)
+                .ok_or(ErrorCode::InvalidBetAmount)?;
+            let min_raise = game_state
+                .last_raise_size
+                .max(table_config.big_blind)
+                .checked_mul(table_config.min_raise_multiple.max(1) as u64)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            let all_in = total_investment == game_state.stacks[player_index]
+                || (hand_cap > 0
+                    && game_state.total_contributed[player_index].checked_add(total_investment)
+                        == Some(hand_cap));
+            require!(raise_size >= min_raise || all_in, ErrorCode::InvalidBetAmount);
+
+            game_state.commit_chips(player_index, total_investment, hand_cap)?;
+            // A short all-in raise does not lower the size the next raise has to match.
+            game_state.last_raise_size = game_state.last_raise_size.max(raise_size);
+            game_state.has_acted[opponent_index] = false;
+        }
+    }
+
+    // --- 3. Advance the Betting Round ---
+    // A fold has already ended the hand. After any other action the round either closes or the
+    // turn passes to the opponent.
+    if !matches!(action, Action::Fold) {
+        if is_betting_round_complete(game_state) {
+            handle_round_transition(game_state, variant)?;
+        } else {
             game_state.current_turn_index = opponent_index as u8;
         }
     }
 
-    // --- 3. Update Timestamp ---
-    game_state.last_action_timestamp = Clock::get()?.unix_timestamp;
+    // --- 4. Update Timestamp ---
+    game_state.record_action_time(&clock);
+    game_state.check_board(variant)?;
 
     Ok(())
 }
 
-/// Helper function to transition the game state after a betting round concludes.
-fn handle_round_transition(game_state: &mut Account<GameState>) {
-    // 1. Collect bets into the main pot.
-    game_state.pot += game_state.bets[0] + game_state.bets[1];
+/// On capped tables, rejects any action that would push the player's total contribution
+/// for the hand beyond `hand_cap`.
+fn require_within_cap(game_state: &GameState, player_index: usize, amount: u64, hand_cap: u64) -> Result<()> {
+    if hand_cap > 0 {
+        require!(
+            game_state.total_contributed[player_index]
+                .checked_add(amount)
+                .is_some_and(|total| total <= hand_cap),
+            ErrorCode::HandCapExceeded
+        );
+    }
+    Ok(())
+}
+
+/// Returns whether the current betting round is over: the bets are equal and either both players
+/// have acted on the street or one of them is all-in, so nobody can act further. This is the
+/// second check of a street or a call, but not a call that completes the unraised preflop blind,
+/// whose poster still has the option. (A fold ends the hand outright and never gets here.)
+fn is_betting_round_complete(game_state: &GameState) -> bool {
+    let bets_equal = game_state.bets[0] == game_state.bets[1];
+    let all_acted = game_state.has_acted.iter().all(|&acted| acted);
+    let any_all_in = game_state.is_all_in.contains(&true);
+    bets_equal && (all_acted || any_all_in)
+}
+
+/// Helper function to transition the game state after a betting round concludes. Also used when
+/// the blinds and antes leave nobody a decision to make.
+pub(crate) fn handle_round_transition(game_state: &mut GameState, variant: GameVariant) -> Result<()> {
+    // 1. Collect bets into the pot and split it into the main and side pots, and note the
+    //    street whose betting just closed.
+    game_state.final_betting_street = game_state.game_phase.betting_street();
+    game_state.pot = game_state
+        .pot
+        .checked_add(game_state.bets[0])
+        .and_then(|pot| pot.checked_add(game_state.bets[1]))
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
     game_state.bets = [0; MAX_PLAYERS];
+    game_state.build_side_pots()?;
+    game_state.last_raise_size = 0;
+    game_state.has_acted = [false; MAX_PLAYERS];
 
-    // 2. Check for all-in showdown.
-    let p0_all_in = game_state.is_all_in[0];
-    let p1_all_in = game_state.is_all_in[1];
+    // In five-card draw, the first betting round is always followed by the draw, even if a
+    // player is all-in. The draw callback decides whether the final betting round is needed.
+    if variant == GameVariant::FiveCardDraw && game_state.game_phase == GamePhase::PreFlop {
+        game_state.game_phase = GamePhase::Draw;
+        game_state.current_turn_index = 1 - game_state.dealer_index;
+        return Ok(());
+    }
 
-    if p0_all_in || p1_all_in {
-        // If an all-in occurs and is called, the game proceeds directly to showdown.
-        // All remaining community cards will be dealt before the winner is determined.
-        // This is simplified as the logic to reveal all cards at once is not yet implemented.
+    // 2. Check for all-in showdown. Five-card draw has no board, so it goes straight to showdown.
+    let any_all_in = game_state.is_all_in[0] || game_state.is_all_in[1];
+    if any_all_in && variant == GameVariant::FiveCardDraw {
         game_state.game_phase = GamePhase::Showdown;
-        return;
+        emit_auto_progress_required(game_state);
+        return Ok(());
+    }
+
+    // If an all-in occurs and is called before the river, the betting is over but the missing
+    // streets still have to be revealed. The runout advances through them one reveal at a
+    // time (see `reveal_community_cards_callback`) and then goes to showdown.
+    if any_all_in {
+        game_state.runout = true;
     }
 
     // 3. Advance to the next game phase.
@@ -184,16 +356,105 @@ fn handle_round_transition(game_state: &mut Account<GameState>) {
 
     // 4. Set the turn to the player out of position (first to act post-flop).
     game_state.current_turn_index = 1 - game_state.dealer_index;
+
+    // 5. With the betting closed by a called all-in, nobody has a decision left: anyone may
+    //    crank the rest of the hand through the permissionless reveal, showdown and settlement
+    //    instructions.
+    if any_all_in {
+        emit_auto_progress_required(game_state);
+    }
+    Ok(())
 }
 
-/// Helper function to reset the game state for the next hand.
-fn transition_to_next_hand(game_state: &mut Account<GameState>) {
-    game_state.game_phase = GamePhase::HandOver;
-    game_state.pot = 0;
-    game_state.bets = [0; MAX_PLAYERS];
-    game_state.community_cards = [255; 5];
-    game_state.is_all_in = [false; MAX_PLAYERS];
-    // Swap the dealer button for the next hand.
-    game_state.dealer_index = 1 - game_state.dealer_index;
-    game_state.current_turn_index = game_state.dealer_index;
-}
\ No newline at end of file
+/// Announces that a hand can only progress through its permissionless cranks.
+fn emit_auto_progress_required(game_state: &GameState) {
+    emit!(AutoProgressRequired {
+        table_id: game_state.table_id,
+        game_phase: game_state.game_phase,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game_state() -> GameState {
+        GameState::deserialize(&mut &vec![0u8; GameState::INIT_SPACE][..]).unwrap()
+    }
+
+    /// Records `seat` acting with `bet` in front of them, as the handler does: a bet above the
+    /// opponent's reopens the action for them.
+    fn act(game_state: &mut GameState, seat: usize, bet: u64) {
+        game_state.has_acted[seat] = true;
+        if bet > game_state.bets[1 - seat] {
+            game_state.has_acted[1 - seat] = false;
+        }
+        game_state.bets[seat] = bet;
+    }
+
+    #[test]
+    fn check_check_closes_the_round() {
+        let mut game_state = game_state();
+        act(&mut game_state, 0, 0);
+        assert!(!is_betting_round_complete(&game_state));
+        act(&mut game_state, 1, 0);
+        assert!(is_betting_round_complete(&game_state));
+    }
+
+    #[test]
+    fn bet_call_closes_the_round() {
+        let mut game_state = game_state();
+        act(&mut game_state, 0, 100);
+        assert!(!is_betting_round_complete(&game_state));
+        act(&mut game_state, 1, 100);
+        assert!(is_betting_round_complete(&game_state));
+    }
+
+    #[test]
+    fn check_bet_reopens_the_action_until_called() {
+        let mut game_state = game_state();
+        act(&mut game_state, 0, 0);
+        act(&mut game_state, 1, 100);
+        assert!(!is_betting_round_complete(&game_state));
+        act(&mut game_state, 0, 100);
+        assert!(is_betting_round_complete(&game_state));
+    }
+
+    #[test]
+    fn bet_raise_call_closes_the_round_only_on_the_call() {
+        let mut game_state = game_state();
+        act(&mut game_state, 0, 100);
+        act(&mut game_state, 1, 300);
+        assert!(!is_betting_round_complete(&game_state));
+        act(&mut game_state, 0, 300);
+        assert!(is_betting_round_complete(&game_state));
+    }
+
+    #[test]
+    fn called_all_in_closes_the_round() {
+        let mut game_state = game_state();
+        act(&mut game_state, 0, 1_000);
+        game_state.is_all_in[0] = true;
+        assert!(!is_betting_round_complete(&game_state));
+        act(&mut game_state, 1, 1_000);
+        assert!(is_betting_round_complete(&game_state));
+    }
+
+    #[test]
+    fn limped_blind_keeps_its_option_unless_it_is_all_in() {
+        // Preflop, the blinds are posted without acting; the button limps.
+        let mut limped = game_state();
+        limped.bets = [50, 100];
+        act(&mut limped, 0, 100);
+        assert!(!is_betting_round_complete(&limped));
+        act(&mut limped, 1, 100);
+        assert!(is_betting_round_complete(&limped));
+
+        // A big blind that was all-in to post it has no option left.
+        let mut all_in_blind = game_state();
+        all_in_blind.bets = [50, 100];
+        all_in_blind.is_all_in[1] = true;
+        act(&mut all_in_blind, 0, 100);
+        assert!(is_betting_round_complete(&all_in_blind));
+    }
+}
@@ -0,0 +1,50 @@
+/**
+ * @description
+ * This file contains the logic for the `register_encryption_key` instruction, which lets a
+ * player register their x25519 encryption public key in a `PlayerProfile` PDA before joining
+ * any table. `create_table` and `join_table` copy the registered key into the seat, and the
+ * deal computation then encrypts the player's cards to it.
+ *
+ * @dependencies
+ * - crate::state: Defines the `PlayerProfile` account structure.
+ * - crate::error: Defines custom error codes for validation.
+ * - anchor_lang: The core Anchor framework library.
+ *
+ * @notes
+ * - Calling the instruction again replaces the registered key. Tables the player is already
+ *   seated at keep the key copied when they sat down.
+ */
+
+use crate::{error::ErrorCode, state::PlayerProfile};
+use anchor_lang::prelude::*;
+
+/// Defines the accounts required for a player to register their encryption key.
+#[derive(Accounts)]
+pub struct RegisterEncryptionKey<'info> {
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerProfile::INIT_SPACE,
+        seeds = [b"player_profile", player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for the `register_encryption_key` instruction.
+pub fn register_encryption_key(
+    ctx: Context<RegisterEncryptionKey>,
+    encryption_pubkey: [u8; 32],
+) -> Result<()> {
+    require!(encryption_pubkey != [0; 32], ErrorCode::InvalidAction);
+
+    let player_profile = &mut ctx.accounts.player_profile;
+    player_profile.player = ctx.accounts.player.key();
+    player_profile.encryption_pubkey = encryption_pubkey;
+    Ok(())
+}
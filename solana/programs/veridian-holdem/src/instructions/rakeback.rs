@@ -0,0 +1,128 @@
+/**
+ * @description
+ * This file contains the logic for rake-back, a loyalty mechanism that credits players with a
+ * share of the rake their hands generate. `settle_hand` accrues `Config::rakeback_bps` of each
+ * player's share of the rake into their `PlayerStats` account, and `claim_rakeback` pays the
+ * accrued amount out of the treasury.
+ *
+ * @dependencies
+ * - crate::state: Defines the `Config` and `PlayerStats` account structures.
+ * - crate::error: Defines custom error codes for validation.
+ * - crate::events: Defines the `RakebackClaimed` event.
+ * - anchor_lang & anchor_spl: For Solana and SPL Token operations.
+ *
+ * @notes
+ * - The treasury token account is owned by an external wallet, so the admin must first
+ *   `approve` the `config` PDA as a delegate on it for at least the outstanding rake-back.
+ *   Claims are then signed by the `config` PDA.
+ * - Accrual is opt-in: a player only accrues rake-back once their `PlayerStats` exists and
+ *   is passed to `settle_hand`.
+ */
+
+use crate::{
+    error::ErrorCode,
+    events::RakebackClaimed,
+    state::{Config, PlayerStats},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+/// Defines the accounts required for a player to create their `PlayerStats` account, which also
+/// keeps their lifetime results.
+#[derive(Accounts)]
+pub struct InitPlayerStats<'info> {
+    #[account(
+        init,
+        payer = player,
+        space = 8 + PlayerStats::INIT_SPACE,
+        seeds = [b"player_stats", player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Account<'info, PlayerStats>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Defines the accounts required for a player to claim their accrued rake-back.
+#[derive(Accounts)]
+pub struct ClaimRakeback<'info> {
+    #[account(
+        mut,
+        seeds = [b"player_stats", player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Account<'info, PlayerStats>,
+
+    pub player: Signer<'info>,
+
+    /// The global `Config` account, which signs the transfer as the treasury's delegate.
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The treasury token account the rake-back is paid from.
+    #[account(mut, address = config.treasury_wallet)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    /// The player's token account to receive the rake-back.
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::Unauthorized,
+        constraint = player_token_account.mint == treasury_token_account.mint @ ErrorCode::InvalidAction
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Handler for the `init_player_stats` instruction.
+pub fn init_player_stats(ctx: Context<InitPlayerStats>) -> Result<()> {
+    let player_stats = &mut ctx.accounts.player_stats;
+    player_stats.player = ctx.accounts.player.key();
+    player_stats.rakeback_owed = 0;
+    player_stats.rakeback_claimed = 0;
+    player_stats.hands_played = 0;
+    player_stats.hands_won = 0;
+    player_stats.net_profit = 0;
+    player_stats.total_rake_contributed = 0;
+    Ok(())
+}
+
+/// Handler for the `claim_rakeback` instruction.
+pub fn claim_rakeback(ctx: Context<ClaimRakeback>) -> Result<()> {
+    let amount = ctx.accounts.player_stats.rakeback_owed;
+    require!(amount > 0, ErrorCode::NothingToClaim);
+    let rakeback_claimed = ctx
+        .accounts
+        .player_stats
+        .rakeback_claimed
+        .checked_add(amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    let seeds = &[b"config".as_ref(), &[ctx.bumps.config]];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.treasury_token_account.to_account_info(),
+        to: ctx.accounts.player_token_account.to_account_info(),
+        authority: ctx.accounts.config.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    let player_stats = &mut ctx.accounts.player_stats;
+    player_stats.rakeback_owed = 0;
+    player_stats.rakeback_claimed = rakeback_claimed;
+
+    emit!(RakebackClaimed {
+        player: player_stats.player,
+        amount,
+    });
+
+    Ok(())
+}
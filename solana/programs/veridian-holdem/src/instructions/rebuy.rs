@@ -0,0 +1,276 @@
+/**
+ * @description
+ * This file contains the logic for the `rebuy` instruction, which lets a seated player add
+ * chips to their stack between hands, up to the table's maximum buy-in, and for auto-rebuy.
+ *
+ * @key_features
+ * - Only allowed between hands, and never above the table's maximum buy-in.
+ * - Enforces the table's `max_rebuys` limit for the current match.
+ * - Counts the rebuy towards the player's session buy-in and the mint's `MintExposure`.
+ * - Auto-rebuy: a player can opt in with `set_auto_rebuy` and approve the `GameState` PDA as a
+ *   delegate on their token account. When they bust, the permissionless `auto_rebuy` crank pulls
+ *   their chosen minimum stack through that allowance. Once the allowance (or the rebuy limit)
+ *   is exhausted, the crank switches auto-rebuy off and the bust takes its normal course.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` and `TableConfig` account structures.
+ * - crate::error: Defines custom error codes for validation.
+ * - anchor_lang & anchor_spl: For Solana and SPL Token operations.
+ */
+
+use crate::{
+    error::ErrorCode,
+    events::{ChallengerSeatOpened, MintExposureUpdated},
+    state::{GamePhase, GameState, MintExposure, TableConfig},
+};
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+/// Defines the accounts required for a player to rebuy.
+#[derive(Accounts)]
+pub struct Rebuy<'info> {
+    /// The seated player adding chips.
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, which holds the maximum buy-in and the rebuy limit.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    /// The game's escrow account, which receives the rebuy.
+    #[account(
+        mut,
+        seeds = [b"escrow", game_state.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Account<'info, TokenAccount>,
+
+    /// The player's token account the rebuy is paid from.
+    #[account(
+        mut,
+        constraint = player_token_account.mint == table_config.token_mint @ ErrorCode::InvalidAction
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    /// The table's token mint's `MintExposure`, which counts the rebuy.
+    #[account(
+        mut,
+        seeds = [b"mint_exposure", table_config.token_mint.as_ref()],
+        bump
+    )]
+    pub mint_exposure: Account<'info, MintExposure>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Defines the accounts required for a player to configure auto-rebuy.
+#[derive(Accounts)]
+pub struct SetAutoRebuy<'info> {
+    /// The seated player configuring auto-rebuy.
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    /// The token account auto-rebuys are pulled from. The player must approve the `GameState`
+    /// PDA as its delegate.
+    #[account(
+        constraint = player_token_account.owner == player.key() @ ErrorCode::Unauthorized,
+        constraint = player_token_account.mint == table_config.token_mint @ ErrorCode::InvalidAction
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+}
+
+/// Defines the accounts required to crank an auto-rebuy for a busted player.
+#[derive(Accounts)]
+pub struct AutoRebuy<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_state.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Account<'info, TokenAccount>,
+
+    /// The token account registered for the busted player's auto-rebuys.
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    /// The table's token mint's `MintExposure`, which counts the rebuy.
+    #[account(
+        mut,
+        seeds = [b"mint_exposure", table_config.token_mint.as_ref()],
+        bump
+    )]
+    pub mint_exposure: Account<'info, MintExposure>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Handler for the `rebuy` instruction.
+pub fn rebuy(ctx: Context<Rebuy>, amount: u64) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let table_config = &ctx.accounts.table_config;
+    let player_key = ctx.accounts.player.key();
+
+    require!(
+        game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::HandNotOver
+    );
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == player_key)
+        .ok_or(ErrorCode::PlayerNotInGame)?;
+
+    require!(
+        amount > 0
+            && game_state.stacks[seat]
+                .checked_add(amount)
+                .is_some_and(|stack| stack <= table_config.max_buy_in),
+        ErrorCode::InvalidAction
+    );
+    require!(
+        table_config.max_rebuys == 0 || game_state.rebuy_count[seat] < table_config.max_rebuys,
+        ErrorCode::RebuyLimitReached
+    );
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.player_token_account.to_account_info(),
+        to: ctx.accounts.escrow_account.to_account_info(),
+        authority: ctx.accounts.player.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    game_state.record_rebuy(seat, amount)?;
+
+    let mint_exposure = &mut ctx.accounts.mint_exposure;
+    mint_exposure.deposit(amount);
+    emit!(MintExposureUpdated::from(&**mint_exposure));
+
+    Ok(())
+}
+
+/// Handler for the `set_auto_rebuy` instruction.
+/// `min_stack` is the stack to restore after busting, or `None` to switch auto-rebuy off.
+pub fn set_auto_rebuy(ctx: Context<SetAutoRebuy>, min_stack: Option<u64>) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let player_key = ctx.accounts.player.key();
+
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == player_key)
+        .ok_or(ErrorCode::PlayerNotInGame)?;
+    if let Some(min_stack) = min_stack {
+        require!(
+            min_stack > 0 && min_stack <= ctx.accounts.table_config.max_buy_in,
+            ErrorCode::InvalidAction
+        );
+    }
+
+    game_state.auto_rebuy_min[seat] = min_stack;
+    game_state.auto_rebuy_accounts[seat] = ctx.accounts.player_token_account.key();
+
+    Ok(())
+}
+
+/// Handler for the `auto_rebuy` instruction.
+pub fn auto_rebuy(ctx: Context<AutoRebuy>, seat: u8) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let table_config = &ctx.accounts.table_config;
+    let seat = seat as usize;
+
+    require!(
+        game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::HandNotOver
+    );
+    require!(seat < game_state.players.len(), ErrorCode::InvalidAction);
+    require!(
+        game_state.players[seat] != Pubkey::default() && game_state.stacks[seat] == 0,
+        ErrorCode::InvalidAction
+    );
+    let min_stack = game_state.auto_rebuy_min[seat].ok_or(ErrorCode::AutoRebuyUnavailable)?;
+    require!(
+        ctx.accounts.player_token_account.key() == game_state.auto_rebuy_accounts[seat],
+        ErrorCode::InvalidAction
+    );
+
+    // Stop auto-rebuying once the allowance or the rebuy limit runs out. The bust then takes
+    // its normal course, which under "winner stays" opens the seat.
+    let player_token_account = &ctx.accounts.player_token_account;
+    let allowance_left = player_token_account.delegate == COption::Some(game_state.key())
+        && player_token_account.delegated_amount >= min_stack
+        && player_token_account.amount >= min_stack;
+    let rebuys_left =
+        table_config.max_rebuys == 0 || game_state.rebuy_count[seat] < table_config.max_rebuys;
+    if !allowance_left || !rebuys_left {
+        game_state.auto_rebuy_min[seat] = None;
+        if table_config.winner_stays {
+            if let Some(open_seat) = game_state.open_busted_seat() {
+                emit!(ChallengerSeatOpened {
+                    table_id: game_state.table_id,
+                    winner: game_state.players[1 - open_seat],
+                    open_seat: open_seat as u8,
+                });
+            }
+        }
+        return Ok(());
+    }
+
+    let seeds = &[
+        b"game",
+        &game_state.table_id.to_le_bytes()[..],
+        &[ctx.bumps.game_state],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: player_token_account.to_account_info(),
+        to: ctx.accounts.escrow_account.to_account_info(),
+        authority: game_state.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, min_stack)?;
+
+    game_state.record_rebuy(seat, min_stack)?;
+
+    let mint_exposure = &mut ctx.accounts.mint_exposure;
+    mint_exposure.deposit(min_stack);
+    emit!(MintExposureUpdated::from(&**mint_exposure));
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+/**
+ * @description
+ * This file contains the permissionless `reconcile_table_state` instruction, which recomputes a
+ * table's `is_active` flag from its seats and phase. It repairs tables whose flag drifted out of
+ * sync before every seat change derived it, such as a full table that no one can join but that
+ * is not marked active.
+ *
+ * @key_features
+ * - Anyone can call it, at any time; it only ever sets the flag to what the seats and phase
+ *   imply (see `GameState::recompute_is_active`).
+ * - Emits `TableStateReconciled` with the flag before and after, so a correction is visible.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` account structure.
+ * - crate::events: Defines the `TableStateReconciled` event.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::{events::TableStateReconciled, state::GameState};
+use anchor_lang::prelude::*;
+
+/// Defines the accounts required to reconcile a table's state.
+#[derive(Accounts)]
+pub struct ReconcileTableState<'info> {
+    /// The table to reconcile. No signer is required: anyone may reconcile a table.
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Handler for the `reconcile_table_state` instruction.
+pub fn reconcile_table_state(ctx: Context<ReconcileTableState>) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let was_active = game_state.is_active;
+    game_state.recompute_is_active();
+
+    emit!(TableStateReconciled {
+        table_id: game_state.table_id,
+        was_active,
+        is_active: game_state.is_active,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+/**
+ * @description
+ * This file contains the logic for the `set_referrer` instruction, which lets a seated player
+ * register the referrer who brought them to the platform. When that player wins a raked pot,
+ * `settle_hand` pays the referrer `Config::referrer_bps` of the rake instead of the treasury.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` account structure.
+ * - crate::error: Defines custom error codes for validation.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::{
+    error::ErrorCode,
+    state::{GamePhase, GameState},
+};
+use anchor_lang::prelude::*;
+
+/// Defines the accounts required for a player to set their referrer.
+#[derive(Accounts)]
+pub struct SetReferrer<'info> {
+    /// The seated player registering a referrer.
+    pub player: Signer<'info>,
+
+    /// The `GameState` account of the table the player is seated at.
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Handler for the `set_referrer` instruction.
+/// Passing `Pubkey::default()` removes the player's referrer.
+pub fn set_referrer(ctx: Context<SetReferrer>, referrer: Pubkey) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let player_key = ctx.accounts.player.key();
+
+    // The referrer cannot change while a hand is in progress.
+    require!(
+        game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::HandNotOver
+    );
+    require!(referrer != player_key, ErrorCode::InvalidAction);
+
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == player_key)
+        .ok_or(ErrorCode::PlayerNotInGame)?;
+    game_state.referrers[seat] = referrer;
+
+    Ok(())
+}
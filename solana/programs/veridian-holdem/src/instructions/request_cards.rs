@@ -1,11 +1,20 @@
 /**
  * @description
  * This file contains instructions for requesting confidential computations related to cards.
- * This includes revealing community cards and initiating a showdown to determine the winner.
+ * This includes revealing community cards, initiating a showdown to determine the winner, and
+ * letting a player who folded show their hole cards.
  *
  * @key_features
  * - `request_community_cards`: Triggers the Arcium computation to reveal the flop, turn, or river.
+ *   Each street can be revealed only once, which also bounds an all-in runout to the missing
+ *   streets. A failed reveal can be requested again up to `Config::max_reveal_retries` times,
+ *   after which the hand is voided and the chips returned. At tables with `reveal_events` on,
+ *   each request emits `CardRevealStarted`, answered by `CardRevealCompleted` from its callback.
+ * - `request_run_out`: Reveals every missing community card in one computation during an all-in
+ *   runout, at tables without a runout delay, and moves the hand straight to showdown.
  * - `request_showdown`: Triggers the Arcium computation to confidentially compare hands and find a winner.
+ * - `show_folded_cards`: Opt-in reveal of one or both hole cards by the player who folded the last hand.
+ * - `preview_showdown`: Dry-run of the showdown for a completed hand that only emits the result.
  *
  * @dependencies
  * - crate::state: Defines `GameState` and `HandState`.
@@ -13,9 +22,14 @@
  * - anchor_lang & arcium_anchor: For Solana and Arcium integration.
  */
 use crate::{
-    callbacks::{RevealCommunityCardsCallback, DetermineWinnerCallback},
+    callbacks::{
+        DetermineWinnerCallback, PreviewWinnerCallback, RevealAllCommunityCardsCallback,
+        RevealCommunityCardsCallback, RevealHoleCardsCallback,
+    },
+    computation::{require_cluster_set, verify_circuit_hash},
     error::ErrorCode,
-    state::{GamePhase, GameState, HandState, SignerAccount},
+    events::{CardRevealStarted, HandVoided},
+    state::{Config, GamePhase, GameState, GameVariant, HandState, SignerAccount, TableConfig},
     ID,
 };
 use anchor_lang::prelude::*;
@@ -34,7 +48,69 @@ pub struct RequestCommunityCards<'info> {
     #[account(mut, seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
     pub game_state: Box<Account<'info, GameState>>,
 
-    #[account(seeds = [b"hand", game_state.key().as_ref()], bump)]
+    #[account(mut, seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + SignerAccount::INIT_SPACE,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Box<Account<'info, SignerAccount>>,
+
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The table's `TableConfig`. This computation is only valid at Hold'em tables.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::Holdem @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    // --- Arcium Required Accounts ---
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: Checked by Arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS,)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Accounts for requesting the rest of the board at once during an all-in runout.
+#[queue_computation_accounts("reveal_all_community_cards", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RequestRunOut<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    #[account(mut, seeds = [b"hand", game_state.key().as_ref()], bump)]
     pub hand_state: Box<Account<'info, HandState>>,
 
     #[account(
@@ -47,6 +123,18 @@ pub struct RequestCommunityCards<'info> {
     )]
     pub sign_pda_account: Box<Account<'info, SignerAccount>>,
 
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The table's `TableConfig`. This computation is only valid at Hold'em tables.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::Holdem @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
     // --- Arcium Required Accounts ---
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Box<Account<'info, MXEAccount>>,
@@ -84,16 +172,133 @@ pub struct RequestShowdown<'info> {
     #[account(mut, seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
     pub game_state: Box<Account<'info, GameState>>,
 
-    #[account(seeds = [b"hand", game_state.key().as_ref()], bump)]
+    #[account(mut, seeds = [b"hand", game_state.key().as_ref()], bump)]
     pub hand_state: Box<Account<'info, HandState>>,
-    
-    /// CHECK: The treasury wallet from the config, to be used in the callback.
+
+    #[account(
+        init_if_needed,
+        space = 8 + SignerAccount::INIT_SPACE,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Box<Account<'info, SignerAccount>>,
+
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The table's `TableConfig`. This computation is only valid at Hold'em tables.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::Holdem @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    // --- Arcium Required Accounts ---
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: Checked by Arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS,)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Accounts for a folded player to show one or both of their hole cards.
+#[queue_computation_accounts("reveal_hole_cards", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ShowFoldedCards<'info> {
+    /// The player who folded the last hand.
     #[account(mut)]
-    pub treasury_token_account: UncheckedAccount<'info>,
-    
-    /// CHECK: The dealer of the hand, who will receive the rent refund from HandState.
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    #[account(mut, seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + SignerAccount::INIT_SPACE,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Box<Account<'info, SignerAccount>>,
+
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The table's `TableConfig`. This computation is only valid at Hold'em tables.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::Holdem @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    // --- Arcium Required Accounts ---
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: Checked by Arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: Checked by Arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS,)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Accounts for a dry-run showdown of a completed hand.
+#[queue_computation_accounts("preview_winner", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct PreviewShowdown<'info> {
     #[account(mut)]
-    pub dealer_account: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"game", &game_state.table_id.to_le_bytes()[..]], bump)]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    #[account(seeds = [b"hand", game_state.key().as_ref()], bump)]
+    pub hand_state: Box<Account<'info, HandState>>,
 
     #[account(
         init_if_needed,
@@ -105,6 +310,18 @@ pub struct RequestShowdown<'info> {
     )]
     pub sign_pda_account: Box<Account<'info, SignerAccount>>,
 
+    /// The global `Config` account, used to verify the circuit hash before queueing.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The table's `TableConfig`. This computation is only valid at Hold'em tables.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.variant == GameVariant::Holdem @ ErrorCode::InvalidAction
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
     // --- Arcium Required Accounts ---
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Box<Account<'info, MXEAccount>>,
@@ -136,15 +353,82 @@ pub fn request_community_cards(
     ctx: Context<RequestCommunityCards>,
     computation_offset: u64,
 ) -> Result<()> {
-    let phase_u8 = match ctx.accounts.game_state.game_phase {
-        GamePhase::Flop => 0,
-        GamePhase::Turn => 1,
-        GamePhase::River => 2,
-        _ => return err!(ErrorCode::InvalidAction),
-    };
-    
-    let args = vec![Argument::PlaintextU8(phase_u8)]; // Client must also pass encrypted deck.
-    
+    require!(ctx.accounts.game_state.hand_in_progress, ErrorCode::NoHandInProgress);
+    // The circuit only understands the three streets, so nothing else is ever queued.
+    let phase_u8 = ctx
+        .accounts
+        .game_state
+        .game_phase
+        .reveal_street()
+        .ok_or(ErrorCode::InvalidRevealPhase)?;
+    // Only the next street may be revealed, and only once.
+    let game_state = &ctx.accounts.game_state;
+    require!(
+        game_state.cards_before_street() == Some(game_state.dealt_community_cards()),
+        ErrorCode::NoCardsToReveal
+    );
+
+    // A street whose reveal failed may be requested again, up to the platform's retry limit.
+    // Once the retries are used up the hand is voided and the chips returned instead, so a
+    // failing MPC cluster cannot freeze the pot.
+    if ctx
+        .accounts
+        .hand_state
+        .reveal_retries_exhausted(ctx.accounts.config.max_reveal_retries)?
+    {
+        let game_state = &mut ctx.accounts.game_state;
+        game_state.void_hand()?;
+        emit!(HandVoided {
+            table_id: game_state.table_id,
+            dealer: game_state.players[game_state.dealer_index as usize],
+        });
+        ctx.accounts.hand_state.reset_reveal_attempts();
+        return Ok(());
+    }
+
+    // During an all-in runout, streets are revealed no faster than the table's runout delay.
+    let game_state = &ctx.accounts.game_state;
+    let now = Clock::get()?.unix_timestamp;
+    let runout_delay = ctx.accounts.table_config.runout_delay_seconds as i64;
+    if game_state.runout && runout_delay > 0 && game_state.last_reveal_timestamp != 0 {
+        require!(
+            now - game_state.last_reveal_timestamp >= runout_delay,
+            ErrorCode::RunoutDelayNotElapsed
+        );
+    }
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.reveal_community_cards,
+    )?;
+
+    // Feed the reveal exactly the deck the previous step stored, refusing one that does not
+    // match its commitment (for instance, a deck left over from an earlier hand).
+    let hand_state = &ctx.accounts.hand_state;
+    hand_state.require_committed_deck()?;
+    let args = vec![
+        Argument::PlaintextU128(hand_state.deck_nonce()),
+        Argument::Account(
+            hand_state.key(),
+            HandState::DECK_OFFSET + HandState::DECK_NONCE_LEN,
+            HandState::DECK_CIPHERTEXTS_LEN,
+        ),
+        Argument::PlaintextU8(phase_u8),
+    ];
+    ctx.accounts.game_state.last_reveal_timestamp = now;
+    ctx.accounts.hand_state.computation_offset = computation_offset;
+    ctx.accounts.hand_state.record_reveal_request();
+    let reveal_events = ctx.accounts.table_config.reveal_events;
+    ctx.accounts.hand_state.reveal_announced = reveal_events;
+    if reveal_events {
+        emit!(CardRevealStarted {
+            table_id: ctx.accounts.game_state.table_id,
+            game_phase: ctx.accounts.game_state.game_phase,
+            attempt: ctx.accounts.hand_state.reveal_attempts,
+        });
+    }
+
     ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
     queue_computation(ctx.accounts, computation_offset, args, None, vec![RevealCommunityCardsCallback::callback_ix(&[])])?;
@@ -152,23 +436,133 @@ pub fn request_community_cards(
     Ok(())
 }
 
+/// Handler for the `request_run_out` instruction.
+pub fn request_run_out(ctx: Context<RequestRunOut>, computation_offset: u64) -> Result<()> {
+    let game_state = &ctx.accounts.game_state;
+    require!(game_state.hand_in_progress, ErrorCode::NoHandInProgress);
+    // Only an all-in runout skips the betting, and only streets still missing are revealed.
+    require!(game_state.runout, ErrorCode::InvalidAction);
+    let dealt = game_state.dealt_community_cards();
+    require!(
+        game_state.cards_before_street() == Some(dealt),
+        ErrorCode::NoCardsToReveal
+    );
+    // A table with a runout delay shows the runout street by street instead.
+    require!(
+        ctx.accounts.table_config.runout_delay_seconds == 0,
+        ErrorCode::InvalidAction
+    );
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.reveal_all_community_cards,
+    )?;
+
+    let hand_state = &ctx.accounts.hand_state;
+    hand_state.require_committed_deck()?;
+    let args = vec![
+        Argument::PlaintextU128(hand_state.deck_nonce()),
+        Argument::Account(
+            hand_state.key(),
+            HandState::DECK_OFFSET + HandState::DECK_NONCE_LEN,
+            HandState::DECK_CIPHERTEXTS_LEN,
+        ),
+        Argument::PlaintextU8(dealt as u8),
+    ];
+    ctx.accounts.game_state.last_reveal_timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.hand_state.computation_offset = computation_offset;
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![RevealAllCommunityCardsCallback::callback_ix(&[])])?;
+
+    Ok(())
+}
+
 /// Handler for the `request_showdown` instruction.
 pub fn request_showdown(ctx: Context<RequestShowdown>, computation_offset: u64) -> Result<()> {
+    // `determine_winner` evaluates the full board, so a hand headed for showdown (including an
+    // all-in runout still in progress) must have all five community cards out first.
+    let game_state = &ctx.accounts.game_state;
+    require!(game_state.hand_in_progress, ErrorCode::NoHandInProgress);
     require!(
-        ctx.accounts.game_state.game_phase == GamePhase::Showdown,
+        game_state.game_phase == GamePhase::Showdown || game_state.runout,
         ErrorCode::InvalidAction
     );
-    // Ensure the provided dealer account matches the one in game state for rent refund.
+    require!(!game_state.community_cards.contains(&255), ErrorCode::BoardIncomplete);
+    require!(game_state.game_phase == GamePhase::Showdown, ErrorCode::InvalidAction);
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.determine_winner,
+    )?;
+
+    let args = vec![]; // Client will pass encrypted cards and board state.
+    ctx.accounts.hand_state.computation_offset = computation_offset;
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![DetermineWinnerCallback::callback_ix(&[])])?;
+    
+    Ok(())
+}
+
+/// Handler for the `show_folded_cards` instruction.
+/// `show_mask` selects the cards to show: 1 for the first, 2 for the second, 3 for both.
+pub fn show_folded_cards(
+    ctx: Context<ShowFoldedCards>,
+    computation_offset: u64,
+    show_mask: u8,
+) -> Result<()> {
+    // Showing cards is strictly opt-in and only available to the player who folded.
     require!(
-        ctx.accounts.game_state.players[ctx.accounts.game_state.dealer_index as usize] == ctx.accounts.dealer_account.key(),
+        ctx.accounts.game_state.folded_player == ctx.accounts.payer.key(),
         ErrorCode::Unauthorized
     );
+    require!((1..=3).contains(&show_mask), ErrorCode::InvalidAction);
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.reveal_hole_cards,
+    )?;
+
+    let args = vec![Argument::PlaintextU8(show_mask)]; // Client must also pass the player's encrypted cards.
+    ctx.accounts.hand_state.computation_offset = computation_offset;
 
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![RevealHoleCardsCallback::callback_ix(&[])])?;
+
+    Ok(())
+}
+
+/// Handler for the `preview_showdown` instruction.
+pub fn preview_showdown(ctx: Context<PreviewShowdown>, computation_offset: u64) -> Result<()> {
+    // Only completed hands can be previewed, so the dry-run cannot be used to peek at a live hand.
+    require!(
+        matches!(
+            ctx.accounts.game_state.game_phase,
+            GamePhase::ReadyToSettle | GamePhase::HandOver
+        ),
+        ErrorCode::HandNotOver
+    );
+
+    require_cluster_set(&ctx.accounts.mxe_account)?;
+    verify_circuit_hash(
+        &ctx.accounts.comp_def_account,
+        &ctx.accounts.config.circuit_hashes.preview_winner,
+    )?;
+
+    // The dry run leaves `hand_state.computation_offset` alone: its callback writes nothing, and
+    // replacing the offset would make a pending `show_folded_cards` result stale.
     let args = vec![]; // Client will pass encrypted cards and board state.
 
     ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
-    queue_computation(ctx.accounts, computation_offset, args, None, vec![DetermineWinnerCallback::callback_ix(&[])])?;
-    
+    queue_computation(ctx.accounts, computation_offset, args, None, vec![PreviewWinnerCallback::callback_ix(&[])])?;
+
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,402 @@
+/**
+ * @description
+ * This file contains the logic for the `settle_hand` permissionless instruction.
+ * The showdown callback runs inside the constrained environment of an Arcium callback, so it
+ * only records the winner. Everything that pays out a hand — rake, token transfers, stack
+ * updates, and closing the `HandState` — happens here, in a normal instruction that anyone
+ * can submit and that can simply be retried if it fails.
+ *
+ * @key_features
+ * - Permissionless: Any account can settle a hand once its winner is known.
+ * - Applies the platform rake once the hand's betting reached the configured street, and
+ *   transfers it to the treasury, paying the configured share to the winner's referrer if they
+ *   have one. Time-raked tables are not pot-raked; their seat charges are taken when each hand
+ *   is dealt.
+ * - Accrues rake-back for each player whose `PlayerStats` account is supplied, in proportion
+ *   to their contribution to the pot.
+ * - Pays out all-in insurance from the insurance pool if the insured player lost.
+ * - Distributes the main pot and any side pots, each evenly among the showdown's winners
+ *   eligible for it, including the odd chips of a split pot, and fails with
+ *   `SettlementImbalance` unless the payouts and the rake add up to exactly the pot.
+ * - Closes the `HandState` account and refunds its rent to the dealer snapshotted in it, even
+ *   if the button has since moved. Tables with a hand retention period keep it open instead,
+ *   and the permissionless `reap_hand` closes it once the period is over.
+ *
+ * @dependencies
+ * - crate::state: Defines `Config`, `GameState`, `HandState` and `TableConfig`.
+ * - crate::error: Defines custom error codes.
+ * - anchor_lang & anchor_spl: For Solana and SPL Token operations.
+ */
+
+use crate::{
+    error::ErrorCode,
+    events::{ChallengerSeatOpened, HandResult},
+    state::{
+        Config, GamePhase, GameState, GameVariant, HandState, PlayerStats, RakeType, TableConfig, MAX_BPS,
+        MAX_PLAYERS,
+    },
+};
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+/// Defines the accounts required to settle a hand whose winner has been determined.
+#[derive(Accounts)]
+pub struct SettleHand<'info> {
+    /// The `GameState` account of the hand being settled.
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = game_state.game_phase == GamePhase::ReadyToSettle @ ErrorCode::NothingToSettle
+    )]
+    pub game_state: Box<Account<'info, GameState>>,
+
+    /// The hand's `HandState`, closed with its rent refunded to the dealer unless the table
+    /// retains finished hands.
+    #[account(
+        mut,
+        seeds = [b"hand", game_state.key().as_ref()],
+        bump
+    )]
+    pub hand_state: Box<Account<'info, HandState>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Box<Account<'info, TableConfig>>,
+
+    /// The game's escrow account, from which the rake is paid.
+    #[account(
+        mut,
+        seeds = [b"escrow", game_state.key().as_ref()],
+        bump,
+        constraint = escrow_account.mint == game_state.token_mint @ ErrorCode::TokenMintMismatch
+    )]
+    pub escrow_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: This is the dealer who paid for the HandState account's rent, as snapshotted
+    /// when the hand was dealt.
+    #[account(
+        mut,
+        constraint = hand_state.dealer == dealer_account.key() @ ErrorCode::Unauthorized
+    )]
+    pub dealer_account: UncheckedAccount<'info>,
+
+    /// CHECK: This is the treasury wallet that receives rake. It only has to be a token account
+    /// of the table's mint when rake is paid into it, which the handler checks.
+    #[account(mut, address = config.treasury_wallet)]
+    pub treasury_token_account: UncheckedAccount<'info>,
+
+    /// The token account of the winner's referrer. Required only when a referrer share is paid;
+    /// its owner and mint are validated in the handler.
+    #[account(mut)]
+    pub referrer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// The `PlayerStats` of the player in seat 0, if they accrue rake-back and lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[0].as_ref()],
+        bump
+    )]
+    pub seat0_stats: Option<Box<Account<'info, PlayerStats>>>,
+
+    /// The `PlayerStats` of the player in seat 1, if they accrue rake-back and lifetime stats.
+    #[account(
+        mut,
+        seeds = [b"player_stats", game_state.players[1].as_ref()],
+        bump
+    )]
+    pub seat1_stats: Option<Box<Account<'info, PlayerStats>>>,
+
+    /// The insurance pool for the table's token mint. Required only when an insured player lost.
+    #[account(
+        mut,
+        seeds = [b"insurance_pool", table_config.token_mint.as_ref()],
+        bump
+    )]
+    pub insurance_pool: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// The insured player's token account, which receives the insurance payout. Required only
+    /// when an insured player lost; its owner is validated in the handler.
+    #[account(mut)]
+    pub insured_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Defines the accounts required to close a retained `HandState` once its retention is over.
+#[derive(Accounts)]
+pub struct ReapHand<'info> {
+    /// CHECK: The `GameState` the hand belongs to, used to derive the `HandState` address and, if
+    /// the table is still open, to check it is not dealing. The table may have been closed since
+    /// the hand was settled.
+    pub game_state: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"hand", game_state.key().as_ref()],
+        bump,
+        close = dealer_account
+    )]
+    pub hand_state: Account<'info, HandState>,
+
+    /// CHECK: The dealer who paid for the `HandState` account's rent, as snapshotted in it.
+    #[account(
+        mut,
+        constraint = hand_state.dealer == dealer_account.key() @ ErrorCode::Unauthorized
+    )]
+    pub dealer_account: UncheckedAccount<'info>,
+}
+
+/// Handler for the `settle_hand` instruction.
+pub fn settle_hand(ctx: Context<SettleHand>) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let config = &ctx.accounts.config;
+    let winner_index = game_state.winner_index;
+
+    // A corrupted pot must fail the settlement cleanly rather than panic, or the hand could
+    // never be settled.
+    let total_pot = game_state
+        .pot
+        .checked_add(game_state.bets[0])
+        .and_then(|total| total.checked_add(game_state.bets[1]))
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let mut rake = 0;
+
+    // Rake Calculation. A Hold'em pot is only raked once the hand's betting reached the
+    // configured street (1 is "No Flop, No Drop"); an all-in runout's streets do not count.
+    // Five-card draw has no streets; any hand that reached a showdown is raked. Pots below the
+    // configured minimum are never raked. Time-raked tables pay by seat time instead of pot rake.
+    let is_draw = ctx.accounts.table_config.variant == GameVariant::FiveCardDraw;
+    let pot_raked = ctx.accounts.table_config.rake_type == RakeType::Pot;
+    let street_reached = game_state.final_betting_street >= config.rake_min_street;
+    let pot_large_enough = total_pot >= config.rake_min_pot;
+    if pot_raked && pot_large_enough && (street_reached || is_draw) {
+        rake = config.rake_for(total_pot);
+    }
+
+    // However the rake is configured, it can never take more than the pot.
+    rake = rake.min(total_pot);
+    let pot_after_rake = total_pot.checked_sub(rake).ok_or(ErrorCode::RakeExceedsPot)?;
+
+    let seeds = &[
+        b"game",
+        &game_state.table_id.to_le_bytes()[..],
+        &[ctx.bumps.game_state],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Part of the rake goes to the winner's referrer. A split pot has no single winner,
+    // so all of its rake goes to the treasury.
+    let referrer = if winner_index < 2 {
+        game_state.referrers[winner_index as usize]
+    } else {
+        Pubkey::default()
+    };
+    let referrer_share = if referrer != Pubkey::default() {
+        (rake as u128 * config.referrer_bps as u128 / MAX_BPS as u128) as u64
+    } else {
+        0
+    };
+
+    if referrer_share > 0 {
+        let referrer_token_account = ctx
+            .accounts
+            .referrer_token_account
+            .as_ref()
+            .ok_or(ErrorCode::InvalidReferrerAccount)?;
+        require!(
+            referrer_token_account.owner == referrer
+                && referrer_token_account.mint == ctx.accounts.table_config.token_mint,
+            ErrorCode::InvalidReferrerAccount
+        );
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.escrow_account.to_account_info(),
+            to: referrer_token_account.to_account_info(),
+            authority: game_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, referrer_share)?;
+    }
+
+    // Transfer the rest of the rake to the treasury.
+    let treasury_share = rake
+        .checked_sub(referrer_share)
+        .ok_or(ErrorCode::SettlementImbalance)?;
+    if treasury_share > 0 {
+        let treasury = TokenAccount::try_deserialize(
+            &mut &ctx.accounts.treasury_token_account.try_borrow_data()?[..],
+        )?;
+        require!(
+            treasury.mint == game_state.token_mint,
+            ErrorCode::TokenMintMismatch
+        );
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.escrow_account.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: game_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, treasury_share)?;
+    }
+
+    game_state.total_rake_collected = game_state
+        .total_rake_collected
+        .checked_add(rake)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    // Accrue rake-back. Each player's share of the rake is proportional to what they put
+    // into the pot.
+    let total_contributed =
+        game_state.total_contributed[0] as u128 + game_state.total_contributed[1] as u128;
+    if rake > 0 && config.rakeback_bps > 0 && total_contributed > 0 {
+        let seat_stats = [&mut ctx.accounts.seat0_stats, &mut ctx.accounts.seat1_stats];
+        for (seat, stats) in seat_stats.into_iter().enumerate() {
+            if let Some(stats) = stats {
+                let rake_share = rake as u128 * game_state.total_contributed[seat] as u128 / total_contributed;
+                let rakeback = (rake_share * config.rakeback_bps as u128 / MAX_BPS as u128) as u64;
+                stats.rakeback_owed = stats
+                    .rakeback_owed
+                    .checked_add(rakeback)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+            }
+        }
+    }
+
+    // Pay out all-in insurance if the insured player was outdrawn.
+    let insured_seat = game_state.insurance_leader as usize;
+    if game_state.insured_amount > 0 && winner_index as usize == 1 - insured_seat {
+        let insurance_pool = ctx
+            .accounts
+            .insurance_pool
+            .as_ref()
+            .ok_or(ErrorCode::InvalidInsuranceAccount)?;
+        let insured_token_account = ctx
+            .accounts
+            .insured_token_account
+            .as_ref()
+            .ok_or(ErrorCode::InvalidInsuranceAccount)?;
+        require!(
+            insured_token_account.owner == game_state.players[insured_seat]
+                && insured_token_account.mint == ctx.accounts.table_config.token_mint,
+            ErrorCode::InvalidInsuranceAccount
+        );
+        let config_seeds = &[b"config".as_ref(), &[ctx.bumps.config]];
+        let config_signer = &[&config_seeds[..]];
+        let cpi_accounts = Transfer {
+            from: insurance_pool.to_account_info(),
+            to: insured_token_account.to_account_info(),
+            authority: config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, config_signer);
+        token::transfer(cpi_ctx, game_state.insured_amount)?;
+    }
+
+    // Distribute pot. Every chip in it must end up with a player or in the rake. The chips are
+    // counted in u128, where the total cannot overflow.
+    let chips_in_play =
+        |stacks: &[u64; MAX_PLAYERS]| stacks.iter().map(|&stack| stack as u128).sum::<u128>();
+    let chips_before = chips_in_play(&game_state.stacks) + total_pot as u128;
+    // Each pot goes to the winners eligible for it, and the rake comes out of the main pot
+    // first. The pots must account for the whole pot.
+    let winner_mask = game_state.winner_mask;
+    require!(winner_mask != 0, ErrorCode::InvalidAction);
+    let side_pots = game_state.side_pots.clone();
+    require!(
+        side_pots
+            .iter()
+            .try_fold(0u64, |sum, side_pot| sum.checked_add(side_pot.amount))
+            == Some(total_pot),
+        ErrorCode::SettlementImbalance
+    );
+    let stacks_before = game_state.stacks;
+    let mut rake_left = rake;
+    for side_pot in side_pots {
+        let raked = rake_left.min(side_pot.amount);
+        rake_left -= raked;
+        game_state.award_pot(side_pot.amount - raked, side_pot.eligible, winner_mask)?;
+    }
+    require!(
+        chips_in_play(&game_state.stacks) + rake as u128 == chips_before,
+        ErrorCode::SettlementImbalance
+    );
+    // Awarding only ever adds to a stack.
+    let awarded = [
+        game_state.stacks[0].saturating_sub(stacks_before[0]),
+        game_state.stacks[1].saturating_sub(stacks_before[1]),
+    ];
+    game_state.record_hand_stats(
+        [
+            ctx.accounts.seat0_stats.as_deref_mut().map(|stats| &mut **stats),
+            ctx.accounts.seat1_stats.as_deref_mut().map(|stats| &mut **stats),
+        ],
+        awarded,
+        rake,
+    )?;
+    emit!(HandResult {
+        table_id: game_state.table_id,
+        winner_index,
+        pot_awarded: pot_after_rake,
+        rake_taken: rake,
+        was_showdown: true,
+        winning_rank: game_state.winning_rank,
+    });
+
+    // Reset game state for the next hand.
+    game_state.reset_for_next_hand();
+
+    // Under "winner stays", a busted player's seat is opened for the next challenger.
+    if ctx.accounts.table_config.winner_stays {
+        if let Some(seat) = game_state.open_busted_seat() {
+            emit!(ChallengerSeatOpened {
+                table_id: game_state.table_id,
+                winner: game_state.players[1 - seat],
+                open_seat: seat as u8,
+            });
+        }
+    }
+
+    // Close the HandState account and refund its rent to the dealer, or keep it for the
+    // table's retention period.
+    let retention = ctx.accounts.table_config.hand_retention_seconds;
+    if retention == 0 {
+        ctx.accounts
+            .hand_state
+            .close(ctx.accounts.dealer_account.to_account_info())?;
+    } else {
+        ctx.accounts.hand_state.retained_until = Clock::get()?
+            .unix_timestamp
+            .checked_add_unsigned(retention)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+
+    Ok(())
+}
+
+/// Handler for the `reap_hand` instruction.
+pub fn reap_hand(ctx: Context<ReapHand>) -> Result<()> {
+    let retained_until = ctx.accounts.hand_state.retained_until;
+    require!(retained_until != 0, ErrorCode::HandStillRetained);
+    require!(
+        Clock::get()?.unix_timestamp >= retained_until,
+        ErrorCode::HandStillRetained
+    );
+    // Dealing reuses the retained account for the new hand before the deal is queued, which
+    // only then clears `retained_until`. Reaping it in between would leave the deal unqueueable.
+    let game_info = ctx.accounts.game_state.to_account_info();
+    if game_info.owner == &crate::ID {
+        let game_state = GameState::try_deserialize(&mut &game_info.try_borrow_data()?[..])?;
+        require!(!game_state.hand_in_progress, ErrorCode::HandStillRetained);
+    }
+    Ok(())
+}
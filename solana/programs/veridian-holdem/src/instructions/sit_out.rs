@@ -0,0 +1,122 @@
+/**
+ * @description
+ * This file contains the sit-out instructions. A seated player can sit out between hands
+ * without giving up their seat, and a player who times out is sat out automatically. Heads-up, no hand can be dealt while a player is sitting out,
+ * so the active player passes each hand with the permissionless `pass_sitting_out_hand`
+ * instruction, at most once per turn timer, which applies the table's sitting-out blinds policy:
+ * - `TableConfig::sitout_posts_blinds` set: the absent player keeps posting the blind for their
+ *   position and forfeits it to the active player, as if they had posted and folded.
+ * - Otherwise (the default): the absent player skips the blind and owes it as a missed blind,
+ *   which is posted as dead money in the first hand they play after sitting back in.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` and `TableConfig` account structures.
+ * - crate::error: Defines custom error codes for validation.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::{
+    error::ErrorCode,
+    state::{GamePhase, GameState, TableConfig},
+};
+use anchor_lang::prelude::*;
+
+/// Defines the accounts required for a player to sit out or sit back in.
+#[derive(Accounts)]
+pub struct SetSittingOut<'info> {
+    /// The seated player changing their status.
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+}
+
+/// Defines the accounts required to pass a hand while a player is sitting out.
+#[derive(Accounts)]
+pub struct PassSittingOutHand<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, which holds the blinds and the sitting-out blinds policy.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+}
+
+/// Handler for the `set_sitting_out` instruction.
+pub fn set_sitting_out(ctx: Context<SetSittingOut>, sitting_out: bool) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let player_key = ctx.accounts.player.key();
+
+    // A player's status can only change between hands.
+    require!(
+        game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::HandNotOver
+    );
+
+    let seat = game_state
+        .players
+        .iter()
+        .position(|&p| p == player_key)
+        .ok_or(ErrorCode::PlayerNotInGame)?;
+    game_state.sitting_out[seat] = sitting_out;
+
+    Ok(())
+}
+
+/// Handler for the `pass_sitting_out_hand` instruction.
+pub fn pass_sitting_out_hand(ctx: Context<PassSittingOutHand>) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let table_config = &ctx.accounts.table_config;
+
+    require!(game_state.game_phase == GamePhase::HandOver, ErrorCode::InvalidAction);
+    // If both players are away there is nobody to play against, so nothing is charged.
+    let seat = match game_state.sitting_out {
+        [true, false] => 0,
+        [false, true] => 1,
+        _ => return err!(ErrorCode::InvalidAction),
+    };
+
+    // Hands pass at the pace of play, so the absent player cannot be charged faster than if
+    // they had been at the table and timed out.
+    let clock = Clock::get()?;
+    let (elapsed, turn_time) = game_state.turn_progress(seat, &clock, table_config);
+    require!(elapsed > turn_time, ErrorCode::TimerNotExpired);
+
+    // The absent player is charged the blind for the position they would have played.
+    let blind = if seat == game_state.dealer_index as usize {
+        table_config.small_blind
+    } else {
+        table_config.big_blind
+    };
+
+    if table_config.sitout_posts_blinds {
+        let posted = blind.min(game_state.stacks[seat]);
+        game_state.stacks[1 - seat] = game_state.stacks[1 - seat]
+            .checked_add(posted)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        game_state.stacks[seat] -= posted;
+    } else {
+        game_state.missed_blinds[seat] = game_state.missed_blinds[seat]
+            .checked_add(blind)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+
+    // Pass the button as if the hand had been played. A straddle declared for it lapses.
+    game_state.straddler = Pubkey::default();
+    game_state.dealer_index = 1 - game_state.dealer_index;
+    game_state.current_turn_index = game_state.dealer_index;
+    game_state.record_action_time(&clock);
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+/**
+ * @description
+ * This file contains the logic for the `declare_straddle` instruction, which lets the button
+ * declare a heads-up "button straddle" (Mississippi) for the next hand at tables that allow it.
+ *
+ * @key_features
+ * - Only the player holding the button for the next hand can declare, and only between hands.
+ * - The declaration covers a single hand: the deal posts the straddle (twice the big blind)
+ *   in place of the small blind and then clears it.
+ * - Declaring again re-straddles, doubling the straddle once more, up to the table's
+ *   `max_straddles` levels.
+ * - Preflop, the big blind acts first and the straddler acts last with the option. If the big
+ *   blind just calls, the straddler can still check or raise.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState` and `TableConfig` account structures.
+ * - crate::error: Defines custom error codes for validation.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::{
+    error::ErrorCode,
+    state::{GamePhase, GameState, TableConfig},
+};
+use anchor_lang::prelude::*;
+
+/// Defines the accounts required for the button to declare or withdraw a straddle.
+#[derive(Accounts)]
+pub struct DeclareStraddle<'info> {
+    /// The player holding the button for the next hand.
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, which must allow button straddles.
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump,
+        constraint = table_config.button_straddle @ ErrorCode::StraddleNotAllowed
+    )]
+    pub table_config: Account<'info, TableConfig>,
+}
+
+/// Handler for the `declare_straddle` instruction.
+/// `straddle` declares a straddle for the next hand (or re-straddles an earlier declaration),
+/// or withdraws the declaration.
+pub fn declare_straddle(ctx: Context<DeclareStraddle>, straddle: bool) -> Result<()> {
+    let game_state = &mut ctx.accounts.game_state;
+    let player_key = ctx.accounts.player.key();
+
+    require!(
+        game_state.game_phase == GamePhase::Idle || game_state.game_phase == GamePhase::HandOver,
+        ErrorCode::HandNotOver
+    );
+    require!(
+        game_state.players[game_state.dealer_index as usize] == player_key,
+        ErrorCode::Unauthorized
+    );
+
+    if !straddle {
+        game_state.straddler = Pubkey::default();
+        game_state.straddle_levels = 0;
+        return Ok(());
+    }
+
+    let levels = if game_state.straddle_declared() {
+        game_state.straddle_levels + 1
+    } else {
+        1
+    };
+    require!(
+        levels <= ctx.accounts.table_config.max_straddles,
+        ErrorCode::StraddleCapReached
+    );
+    game_state.straddler = player_key;
+    game_state.straddle_levels = levels;
+    // A straddle too large to count in chips could never be posted.
+    game_state.straddle_size(&ctx.accounts.table_config)?;
+
+    Ok(())
+}
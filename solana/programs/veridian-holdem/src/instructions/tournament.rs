@@ -0,0 +1,222 @@
+/**
+ * @description
+ * This file contains the tournament payout instructions. A tournament run as a series of
+ * heads-up matches has a prize pool that is paid out to its finishing places when it ends:
+ * 1. `create_payout_structure` defines how the pool is split, e.g. winner-take-all or
+ *    50/30/20 for the top three.
+ * 2. `create_tournament` creates the tournament and its prize pool escrow, which is funded with
+ *    ordinary token transfers (entry fees, added money).
+ * 3. `settle_tournament` pays the pool out to the finishing places, once.
+ *
+ * @key_features
+ * - Only the admin can create payout structures and tournaments, and report the results.
+ * - Shares are in basis points and must sum to exactly 100%. Rounding dust goes to first place,
+ *   so the escrow is always paid out in full.
+ *
+ * @dependencies
+ * - crate::state: Defines `Config`, `PayoutStructure` and `Tournament`.
+ * - crate::error: Defines custom error codes.
+ * - crate::events: Defines the `TournamentSettled` event.
+ * - anchor_lang & anchor_spl: For Solana and SPL Token operations.
+ *
+ * @notes
+ * - The finishing places' token accounts are passed as remaining accounts, first place first,
+ *   one for each paid place.
+ */
+
+use crate::{
+    error::ErrorCode,
+    events::TournamentSettled,
+    state::{Config, PayoutStructure, Tournament, MAX_BPS, MAX_PAID_PLACES},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+/// Defines the accounts required for the admin to create a payout structure.
+#[derive(Accounts)]
+#[instruction(structure_id: u64)]
+pub struct CreatePayoutStructure<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PayoutStructure::INIT_SPACE,
+        seeds = [b"payout_structure", &structure_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub payout_structure: Account<'info, PayoutStructure>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Defines the accounts required for the admin to create a tournament and its prize pool.
+#[derive(Accounts)]
+#[instruction(tournament_id: u64)]
+pub struct CreateTournament<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Tournament::INIT_SPACE,
+        seeds = [b"tournament", &tournament_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub tournament: Account<'info, Tournament>,
+
+    /// The tournament's prize pool, owned by the `tournament` PDA.
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"tournament_escrow", tournament.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = tournament,
+    )]
+    pub prize_pool: Account<'info, TokenAccount>,
+
+    pub payout_structure: Account<'info, PayoutStructure>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Defines the accounts required for the admin to pay out a tournament's prize pool.
+#[derive(Accounts)]
+pub struct SettleTournament<'info> {
+    #[account(
+        mut,
+        seeds = [b"tournament", &tournament.tournament_id.to_le_bytes()[..]],
+        bump,
+        constraint = !tournament.settled @ ErrorCode::TournamentAlreadySettled
+    )]
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(
+        mut,
+        seeds = [b"tournament_escrow", tournament.key().as_ref()],
+        bump
+    )]
+    pub prize_pool: Account<'info, TokenAccount>,
+
+    #[account(address = tournament.payout_structure)]
+    pub payout_structure: Account<'info, PayoutStructure>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Handler for the `create_payout_structure` instruction.
+/// `payouts_bps` lists each paid place's share of the prize pool, first place first.
+pub fn create_payout_structure(
+    ctx: Context<CreatePayoutStructure>,
+    structure_id: u64,
+    payouts_bps: Vec<u16>,
+) -> Result<()> {
+    require!(
+        !payouts_bps.is_empty() && payouts_bps.len() <= MAX_PAID_PLACES,
+        ErrorCode::InvalidPayoutStructure
+    );
+    require!(
+        payouts_bps.iter().all(|&bps| bps > 0)
+            && payouts_bps.iter().map(|&bps| bps as u32).sum::<u32>() == MAX_BPS as u32,
+        ErrorCode::InvalidPayoutStructure
+    );
+
+    let payout_structure = &mut ctx.accounts.payout_structure;
+    payout_structure.structure_id = structure_id;
+    payout_structure.paid_places = payouts_bps.len() as u8;
+    payout_structure.payouts_bps = [0; MAX_PAID_PLACES];
+    payout_structure.payouts_bps[..payouts_bps.len()].copy_from_slice(&payouts_bps);
+    Ok(())
+}
+
+/// Handler for the `create_tournament` instruction.
+pub fn create_tournament(ctx: Context<CreateTournament>, tournament_id: u64) -> Result<()> {
+    let tournament = &mut ctx.accounts.tournament;
+    tournament.tournament_id = tournament_id;
+    tournament.token_mint = ctx.accounts.token_mint.key();
+    tournament.payout_structure = ctx.accounts.payout_structure.key();
+    tournament.settled = false;
+    Ok(())
+}
+
+/// Handler for the `settle_tournament` instruction.
+pub fn settle_tournament<'info>(ctx: Context<'_, '_, 'info, 'info, SettleTournament<'info>>) -> Result<()> {
+    let payout_structure = &ctx.accounts.payout_structure;
+    let paid_places = payout_structure.paid_places as usize;
+    require!(
+        ctx.remaining_accounts.len() == paid_places,
+        ErrorCode::InvalidPayoutStructure
+    );
+
+    // Split the pool by the structure's shares. Rounding dust goes to first place.
+    let pool = ctx.accounts.prize_pool.amount;
+    let mut amounts: Vec<u64> = payout_structure.payouts_bps[..paid_places]
+        .iter()
+        .map(|&bps| (pool as u128 * bps as u128 / MAX_BPS as u128) as u64)
+        .collect();
+    amounts[0] += pool - amounts.iter().sum::<u64>();
+
+    let tournament = &ctx.accounts.tournament;
+    let seeds = &[
+        b"tournament",
+        &tournament.tournament_id.to_le_bytes()[..],
+        &[ctx.bumps.tournament],
+    ];
+    let signer = &[&seeds[..]];
+    let mut recipients = Vec::with_capacity(paid_places);
+    for (recipient_info, &amount) in ctx.remaining_accounts.iter().zip(amounts.iter()) {
+        let recipient = Account::<TokenAccount>::try_from(recipient_info)?;
+        require!(recipient.mint == tournament.token_mint, ErrorCode::InvalidAction);
+        recipients.push(recipient.key());
+        if amount == 0 {
+            continue;
+        }
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.prize_pool.to_account_info(),
+            to: recipient_info.clone(),
+            authority: tournament.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+    }
+
+    emit!(TournamentSettled {
+        tournament_id: tournament.tournament_id,
+        recipients,
+        amounts,
+    });
+
+    ctx.accounts.tournament.settled = true;
+    Ok(())
+}
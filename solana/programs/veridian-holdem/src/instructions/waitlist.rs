@@ -0,0 +1,101 @@
+/**
+ * @description
+ * This file contains the logic for the `join_waitlist`, `leave_waitlist` and
+ * `remove_from_waitlist` instructions. Challengers can queue for a table's next open seat, which
+ * is used by the "winner stays" format to chain heads-up matches without recreating the table.
+ *
+ * @key_features
+ * - Maintains an ordered, fixed-size waitlist on the `GameState` account.
+ * - Only tables using "winner stays" have a waitlist.
+ * - `join_table` only admits the challenger at the front of the queue while it is non-empty.
+ * - The admin can remove a challenger who never claims the open seat, so the queue moves on.
+ *
+ * @dependencies
+ * - crate::state: Defines the `GameState`, `TableConfig` and `Config` account structures.
+ * - crate::error: Defines custom error codes for validation.
+ * - anchor_lang: The core Anchor framework library.
+ */
+
+use crate::{
+    error::ErrorCode,
+    state::{Config, GameState, TableConfig},
+};
+use anchor_lang::prelude::*;
+
+/// Defines the accounts required to join or leave a table's waitlist.
+#[derive(Accounts)]
+pub struct UpdateWaitlist<'info> {
+    /// The challenger joining or leaving the waitlist.
+    pub player: Signer<'info>,
+
+    /// The `GameState` account holding the waitlist.
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    /// The table's `TableConfig`, which says whether the table uses "winner stays".
+    #[account(
+        seeds = [b"table_config", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub table_config: Account<'info, TableConfig>,
+}
+
+/// Defines the accounts required for the admin to remove a challenger from a table's waitlist.
+#[derive(Accounts)]
+pub struct RemoveFromWaitlist<'info> {
+    /// The `GameState` account holding the waitlist.
+    #[account(
+        mut,
+        seeds = [b"game", &game_state.table_id.to_le_bytes()[..]],
+        bump
+    )]
+    pub game_state: Account<'info, GameState>,
+
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The signer of the transaction, who must be the current administrator.
+    pub admin: Signer<'info>,
+}
+
+/// Handler for the `join_waitlist` instruction.
+pub fn join_waitlist(ctx: Context<UpdateWaitlist>) -> Result<()> {
+    require!(ctx.accounts.table_config.winner_stays, ErrorCode::WaitlistDisabled);
+    let game_state = &mut ctx.accounts.game_state;
+    let player_key = ctx.accounts.player.key();
+
+    // Seated players and players already queued cannot queue again.
+    require!(
+        !game_state.players.contains(&player_key) && !game_state.waitlist.contains(&player_key),
+        ErrorCode::InvalidAction
+    );
+
+    let slot = game_state
+        .waitlist
+        .iter()
+        .position(|&p| p == Pubkey::default())
+        .ok_or(ErrorCode::WaitlistFull)?;
+    game_state.waitlist[slot] = player_key;
+
+    Ok(())
+}
+
+/// Handler for the `leave_waitlist` instruction.
+pub fn leave_waitlist(ctx: Context<UpdateWaitlist>) -> Result<()> {
+    let player_key = ctx.accounts.player.key();
+    ctx.accounts.game_state.remove_from_waitlist(player_key)
+}
+
+/// Handler for the `remove_from_waitlist` instruction.
+/// Unblocks the queue when the challenger at its front never takes the open seat.
+pub fn remove_from_waitlist(ctx: Context<RemoveFromWaitlist>, player: Pubkey) -> Result<()> {
+    ctx.accounts.game_state.remove_from_waitlist(player)
+}
@@ -3,8 +3,11 @@ use arcium_anchor::prelude::*;
 use arcium_client::idl::arcium::types::{CircuitSource, OffChainCircuitSource};
 
 pub mod callbacks;
+pub mod computation;
 pub mod error;
+pub mod events;
 pub mod instructions;
+pub mod randomness;
 pub mod state;
 
 // Re-export modules to make their contents easily accessible to other parts of the program.
@@ -35,8 +38,121 @@ pub mod veridian_holdem {
         ctx: Context<SetRakeConfig>,
         rake_percentage: u8,
         rake_cap: u64,
+        referrer_bps: u16,
+        rakeback_bps: u16,
     ) -> Result<()> {
-        instructions::admin::set_rake_config(ctx, rake_percentage, rake_cap)
+        instructions::admin::set_rake_config(ctx, rake_percentage, rake_cap, referrer_bps, rakeback_bps)
+    }
+
+    /// Replaces the progressive rake tiers, which rake each pot by the highest tier its size
+    /// reaches. An empty list restores the flat rake. Only the current admin, as stored in the
+    /// `Config` account, can call this instruction.
+    pub fn set_rake_tiers(ctx: Context<SetRakeConfig>, rake_tiers: Vec<RakeTier>) -> Result<()> {
+        instructions::admin::set_rake_tiers(ctx, rake_tiers)
+    }
+
+    /// Updates the markup charged on all-in insurance.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_insurance_config(ctx: Context<SetInsuranceConfig>, insurance_markup_bps: u16) -> Result<()> {
+        instructions::admin::set_insurance_config(ctx, insurance_markup_bps)
+    }
+
+    /// Sets the earliest betting street a hand must reach before its pot is raked.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_rake_min_street(ctx: Context<SetRakeMinStreet>, rake_min_street: u8) -> Result<()> {
+        instructions::admin::set_rake_min_street(ctx, rake_min_street)
+    }
+
+    /// Sets the smallest pot that is raked; smaller pots pay no rake.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_rake_min_pot(ctx: Context<SetRakeMinPot>, rake_min_pot: u64) -> Result<()> {
+        instructions::admin::set_rake_min_pot(ctx, rake_min_pot)
+    }
+
+    /// Sets how many times a failed community card reveal may be retried before the hand is
+    /// voided. Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_max_reveal_retries(ctx: Context<SetMaxRevealRetries>, max_reveal_retries: u8) -> Result<()> {
+        instructions::admin::set_max_reveal_retries(ctx, max_reveal_retries)
+    }
+
+    /// Sets the penalty a player pays their opponent for leaving while ahead soon after sitting
+    /// down. Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_leave_penalty(
+        ctx: Context<SetLeavePenalty>,
+        leave_penalty_bps: u16,
+        leave_penalty_hands: u32,
+    ) -> Result<()> {
+        instructions::admin::set_leave_penalty(ctx, leave_penalty_bps, leave_penalty_hands)
+    }
+
+    /// Stops or resumes the dealing of new hands on every table. Hands already dealt can still
+    /// finish. Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::admin::set_paused(ctx, paused)
+    }
+
+    /// Proposes a new administrator, who takes over once they sign `accept_admin`. Only the
+    /// current admin, as stored in the `Config` account, can call this instruction.
+    pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::admin::transfer_admin(ctx, new_admin)
+    }
+
+    /// Completes an admin transfer. Only the administrator proposed by `transfer_admin` can
+    /// call this instruction.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::admin::accept_admin(ctx)
+    }
+
+    /// Caps the number of tables that may be open at once for a token mint, or removes the cap
+    /// with 0. Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_mint_table_cap(ctx: Context<SetMintTableCap>, max_tables: u32) -> Result<()> {
+        instructions::admin::set_mint_table_cap(ctx, max_tables)
+    }
+
+    /// Sets a table's maximum pace in hands per rolling hour, or removes the cap with 0.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_max_hands_per_hour(ctx: Context<SetMaxHandsPerHour>, max_hands_per_hour: u16) -> Result<()> {
+        instructions::admin::set_max_hands_per_hour(ctx, max_hands_per_hour)
+    }
+
+    /// Sets the seat charge taken from each player, per period, at time-raked tables.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_time_charge_config(
+        ctx: Context<SetTimeChargeConfig>,
+        time_charge: u64,
+        period_seconds: i64,
+    ) -> Result<()> {
+        instructions::admin::set_time_charge_config(ctx, time_charge, period_seconds)
+    }
+
+    /// Sets the largest buy-in a table may require. 0 removes the cap.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_max_buy_in_cap(ctx: Context<SetMaxBuyInCap>, max_buy_in_cap: u64) -> Result<()> {
+        instructions::admin::set_max_buy_in_cap(ctx, max_buy_in_cap)
+    }
+
+    /// Chooses whether a table is charged by pot rake or by time.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_table_rake_type(ctx: Context<SetTableRakeType>, rake_type: RakeType) -> Result<()> {
+        instructions::admin::set_table_rake_type(ctx, rake_type)
+    }
+
+    /// Changes a table's blinds between hands, e.g. when a tournament's blinds go up.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_table_blinds(ctx: Context<SetTableBlinds>, small_blind: u64, big_blind: u64) -> Result<()> {
+        instructions::admin::set_table_blinds(ctx, small_blind, big_blind)
+    }
+
+    /// Creates the all-in insurance pool for a token mint.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn init_insurance_pool(ctx: Context<InitInsurancePool>) -> Result<()> {
+        instructions::insurance::init_insurance_pool(ctx)
+    }
+
+    /// Updates the expected hashes of the published Arcis circuits.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn set_circuit_hashes(ctx: Context<SetCircuitHashes>, hashes: CircuitHashes) -> Result<()> {
+        instructions::admin::set_circuit_hashes(ctx, hashes)
     }
 
     /// Creates a new poker table with a specific configuration.
@@ -45,14 +161,89 @@ pub mod veridian_holdem {
         table_id: u64,
         small_blind: u64,
         big_blind: u64,
-        buy_in: u64,
+        min_buy_in: u64,
+        max_buy_in: u64,
+        buy_in_amount: u64,
+        options: TableOptions,
     ) -> Result<()> {
-        instructions::create_table::create_table(ctx, table_id, small_blind, big_blind, buy_in)
+        instructions::create_table::create_table(
+            ctx,
+            table_id,
+            small_blind,
+            big_blind,
+            min_buy_in,
+            max_buy_in,
+            buy_in_amount,
+            options,
+        )
     }
 
-    /// Allows a second player to join an existing, open poker table.
-    pub fn join_table(ctx: Context<JoinTable>) -> Result<()> {
-        instructions::join_table::join_table(ctx)
+    /// Allows a second player to join an existing, open poker table, buying in for any amount
+    /// within the table's buy-in range.
+    pub fn join_table(ctx: Context<JoinTable>, buy_in_amount: u64) -> Result<()> {
+        instructions::join_table::join_table(ctx, buy_in_amount)
+    }
+
+    /// Registers the player's x25519 encryption key ahead of joining any table.
+    pub fn register_encryption_key(
+        ctx: Context<RegisterEncryptionKey>,
+        encryption_pubkey: [u8; 32],
+    ) -> Result<()> {
+        instructions::profile::register_encryption_key(ctx, encryption_pubkey)
+    }
+
+    /// Adds chips to a seated player's stack between hands, up to the table's buy-in.
+    pub fn rebuy(ctx: Context<Rebuy>, amount: u64) -> Result<()> {
+        instructions::rebuy::rebuy(ctx, amount)
+    }
+
+    /// Switches auto-rebuy to a minimum stack on or off for a seated player.
+    pub fn set_auto_rebuy(ctx: Context<SetAutoRebuy>, min_stack: Option<u64>) -> Result<()> {
+        instructions::rebuy::set_auto_rebuy(ctx, min_stack)
+    }
+
+    /// A permissionless instruction that restores a busted player's stack through their
+    /// auto-rebuy allowance, or switches auto-rebuy off once the allowance is exhausted.
+    pub fn auto_rebuy(ctx: Context<AutoRebuy>, seat: u8) -> Result<()> {
+        instructions::rebuy::auto_rebuy(ctx, seat)
+    }
+
+    /// Declares, re-straddles or withdraws a button straddle for the next hand at tables that
+    /// allow it.
+    pub fn declare_straddle(ctx: Context<DeclareStraddle>, straddle: bool) -> Result<()> {
+        instructions::straddle::declare_straddle(ctx, straddle)
+    }
+
+    /// Sits the player out between hands, or back in, without giving up their seat.
+    pub fn set_sitting_out(ctx: Context<SetSittingOut>, sitting_out: bool) -> Result<()> {
+        instructions::sit_out::set_sitting_out(ctx, sitting_out)
+    }
+
+    /// A permissionless instruction that passes a hand while a player is sitting out,
+    /// applying the table's sitting-out blinds policy.
+    pub fn pass_sitting_out_hand(ctx: Context<PassSittingOutHand>) -> Result<()> {
+        instructions::sit_out::pass_sitting_out_hand(ctx)
+    }
+
+    /// Queues a challenger for the next open seat at a "winner stays" table.
+    pub fn join_waitlist(ctx: Context<UpdateWaitlist>) -> Result<()> {
+        instructions::waitlist::join_waitlist(ctx)
+    }
+
+    /// Removes a challenger from the table's waitlist.
+    pub fn leave_waitlist(ctx: Context<UpdateWaitlist>) -> Result<()> {
+        instructions::waitlist::leave_waitlist(ctx)
+    }
+
+    /// Removes a challenger who is holding up the table's waitlist.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn remove_from_waitlist(ctx: Context<RemoveFromWaitlist>, player: Pubkey) -> Result<()> {
+        instructions::waitlist::remove_from_waitlist(ctx, player)
+    }
+
+    /// Registers the referrer who receives a share of the rake from hands the player wins.
+    pub fn set_referrer(ctx: Context<SetReferrer>, referrer: Pubkey) -> Result<()> {
+        instructions::referral::set_referrer(ctx, referrer)
     }
 
     /// Step A: prepare accounts for a new hand (no Arcium queue here).
@@ -65,6 +256,27 @@ pub mod veridian_holdem {
         instructions::deal_new_hand::deal_new_hand_queue(ctx, computation_offset)
     }
 
+    /// A permissionless instruction to start a hand voided by a misdeal again, with the same
+    /// button and blinds. The deal is then queued as after `deal_new_hand_setup`.
+    pub fn redeal_after_misdeal(ctx: Context<RedealAfterMisdeal>) -> Result<()> {
+        instructions::deal_new_hand::redeal_after_misdeal(ctx)
+    }
+
+    /// Five-card draw: queues the confidential deal after `deal_new_hand_setup`.
+    pub fn deal_draw_hand_queue(ctx: Context<DealDrawHandQueue>, computation_offset: u64) -> Result<()> {
+        instructions::draw::deal_draw_hand_queue(ctx, computation_offset)
+    }
+
+    /// Five-card draw: discards the cards selected by `discard_mask` and draws replacements.
+    pub fn request_draw(ctx: Context<RequestDraw>, computation_offset: u64, discard_mask: u8) -> Result<()> {
+        instructions::draw::request_draw(ctx, computation_offset, discard_mask)
+    }
+
+    /// Five-card draw: requests the confidential showdown computation to determine the winner.
+    pub fn request_showdown_draw(ctx: Context<RequestShowdownDraw>, computation_offset: u64) -> Result<()> {
+        instructions::draw::request_showdown_draw(ctx, computation_offset)
+    }
+
     /// Processes a player's action (Fold, Check, Call, Bet, Raise).
     pub fn player_action(ctx: Context<PlayerAction>, action: Action) -> Result<()> {
         instructions::player_action::player_action(ctx, action)
@@ -78,35 +290,184 @@ pub mod veridian_holdem {
         instructions::request_cards::request_community_cards(ctx, computation_offset)
     }
 
+    /// Requests every missing community card at once during an all-in runout.
+    pub fn request_run_out(ctx: Context<RequestRunOut>, computation_offset: u64) -> Result<()> {
+        instructions::request_cards::request_run_out(ctx, computation_offset)
+    }
+
     /// Requests the confidential showdown computation to determine the winner.
     pub fn request_showdown(ctx: Context<RequestShowdown>, computation_offset: u64) -> Result<()> {
         instructions::request_cards::request_showdown(ctx, computation_offset)
     }
 
+    /// Runs a dry-run showdown for a completed hand and emits the result without moving chips.
+    pub fn preview_showdown(ctx: Context<PreviewShowdown>, computation_offset: u64) -> Result<()> {
+        instructions::request_cards::preview_showdown(ctx, computation_offset)
+    }
+
+    /// Returns, as return data, the versioned spec of the arguments the next confidential
+    /// computation at the table expects and where to source them.
+    pub fn get_computation_args_spec(ctx: Context<GetComputationArgsSpec>) -> Result<ComputationArgsSpec> {
+        instructions::args_spec::get_computation_args_spec(ctx)
+    }
+
+    /// Returns, as return data, which permissionless cranks would currently succeed at the
+    /// table and how long until the timed ones do.
+    pub fn crank_status(ctx: Context<CrankStatusQuery>) -> Result<CrankStatus> {
+        instructions::crank_status::crank_status(ctx)
+    }
+
+    /// Returns, as return data, the table's escrow balance next to the chips in play (stacks
+    /// and pot) and whether they reconcile.
+    pub fn audit_escrow(ctx: Context<AuditEscrow>) -> Result<EscrowAudit> {
+        instructions::audit::audit_escrow(ctx)
+    }
+
+    /// Requests a quote for insuring the river of an all-in runout.
+    pub fn request_insurance_quote(ctx: Context<RequestInsuranceQuote>, computation_offset: u64) -> Result<()> {
+        instructions::insurance::request_insurance_quote(ctx, computation_offset)
+    }
+
+    /// Lets the leading player of an all-in runout insure the pot against being outdrawn.
+    pub fn request_insurance(ctx: Context<RequestInsurance>, cover: u64) -> Result<()> {
+        instructions::insurance::request_insurance(ctx, cover)
+    }
+
+    /// Lets the player who folded the last hand opt to show one or both of their hole cards.
+    pub fn show_folded_cards(
+        ctx: Context<ShowFoldedCards>,
+        computation_offset: u64,
+        show_mask: u8,
+    ) -> Result<()> {
+        instructions::request_cards::show_folded_cards(ctx, computation_offset, show_mask)
+    }
+
     /// Allows a player to leave the table and withdraw their funds.
     pub fn leave_table(ctx: Context<LeaveTable>) -> Result<()> {
         instructions::leave_table::leave_table(ctx)
     }
 
+    /// Admin-only: flags a table for consolidation, naming the table its players must move to,
+    /// or clears the flag.
+    pub fn set_must_move_target(ctx: Context<SetMustMoveTarget>, target: Option<Pubkey>) -> Result<()> {
+        instructions::move_seat::set_must_move_target(ctx, target)
+    }
+
+    /// Moves a player's seat and stack to the table their current table must move to, transferring
+    /// the stack directly between the two escrows.
+    pub fn move_seat(ctx: Context<MoveSeat>) -> Result<()> {
+        instructions::move_seat::move_seat(ctx)
+    }
+
+    /// Admin-only: creates a fast-fold pool for tables playing for `token_mint` at these blinds.
+    pub fn create_fast_fold_pool(
+        ctx: Context<CreateFastFoldPool>,
+        pool_id: u64,
+        small_blind: u64,
+        big_blind: u64,
+    ) -> Result<()> {
+        instructions::fast_fold::create_fast_fold_pool(ctx, pool_id, small_blind, big_blind)
+    }
+
+    /// Admin-only: adds a table with the pool's stakes to a fast-fold pool.
+    pub fn add_fast_fold_table(ctx: Context<AddFastFoldTable>) -> Result<()> {
+        instructions::fast_fold::add_fast_fold_table(ctx)
+    }
+
+    /// Refreshes which player, if any, is waiting for an opponent at a fast-fold pool table.
+    pub fn sync_fast_fold_table(ctx: Context<SyncFastFoldTable>) -> Result<()> {
+        instructions::fast_fold::sync_fast_fold_table(ctx)
+    }
+
+    /// Folds the player's hand at a fast-fold pool table and reseats them, with their stack,
+    /// at another pool table chosen by the caller.
+    pub fn fast_fold_and_rematch(ctx: Context<FastFoldAndRematch>) -> Result<()> {
+        instructions::fast_fold::fast_fold_and_rematch(ctx)
+    }
+
+    /// Sends an emote from a seated player to the table, rate-limited per seat.
+    pub fn send_emote(ctx: Context<SendEmote>, code: u8) -> Result<()> {
+        instructions::emote::send_emote(ctx, code)
+    }
+
+    /// A permissionless instruction to recompute a table's `is_active` flag from its seats and
+    /// phase, correcting it if it has drifted.
+    pub fn reconcile_table_state(ctx: Context<ReconcileTableState>) -> Result<()> {
+        instructions::reconcile::reconcile_table_state(ctx)
+    }
+
     /// A permissionless instruction to fold on behalf of a player whose turn timer has expired.
     pub fn crank_fold(ctx: Context<CrankFold>) -> Result<()> {
         instructions::crank_fold::crank_fold(ctx)
     }
 
+    /// Lets the player waiting on the action fold their opponent once the opponent's turn
+    /// timer has expired, exactly as `crank_fold` would.
+    pub fn force_opponent_timeout(ctx: Context<ForceOpponentTimeout>) -> Result<()> {
+        instructions::crank_fold::force_opponent_timeout(ctx)
+    }
+
+    /// A permissionless instruction that pays out a hand once the showdown has recorded its winner.
+    pub fn settle_hand(ctx: Context<SettleHand>) -> Result<()> {
+        instructions::settlement::settle_hand(ctx)
+    }
+
+    /// Creates a tournament payout structure from each paid place's share, in basis points.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn create_payout_structure(
+        ctx: Context<CreatePayoutStructure>,
+        structure_id: u64,
+        payouts_bps: Vec<u16>,
+    ) -> Result<()> {
+        instructions::tournament::create_payout_structure(ctx, structure_id, payouts_bps)
+    }
+
+    /// Creates a tournament and its prize pool escrow, paid out by a payout structure.
+    /// Only the current admin, as stored in the `Config` account, can call this instruction.
+    pub fn create_tournament(ctx: Context<CreateTournament>, tournament_id: u64) -> Result<()> {
+        instructions::tournament::create_tournament(ctx, tournament_id)
+    }
+
+    /// Pays out a tournament's prize pool to its finishing places, passed as remaining
+    /// accounts first place first. Only the current admin can call this instruction.
+    pub fn settle_tournament<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleTournament<'info>>,
+    ) -> Result<()> {
+        instructions::tournament::settle_tournament(ctx)
+    }
+
+    /// Closes a settled hand's retained `HandState` once the table's retention period is over,
+    /// refunding its rent to the dealer who paid for it. Permissionless.
+    pub fn reap_hand(ctx: Context<ReapHand>) -> Result<()> {
+        instructions::settlement::reap_hand(ctx)
+    }
+
+    /// Creates the player's `PlayerStats` account so they start accruing rake-back.
+    pub fn init_player_stats(ctx: Context<InitPlayerStats>) -> Result<()> {
+        instructions::rakeback::init_player_stats(ctx)
+    }
+
+    /// Pays the player's accrued rake-back out of the treasury.
+    pub fn claim_rakeback(ctx: Context<ClaimRakeback>) -> Result<()> {
+        instructions::rakeback::claim_rakeback(ctx)
+    }
+
     // --- Arcium Callbacks ---
     // Callbacks are defined in the callbacks module
 
     // --- Arcium Comp Def Initializers ---
     // These instructions are required to register the Arcis circuits on-chain.
+    // Each circuit is registered with the hash currently expected by the `Config` account.
     
     pub fn init_shuffle_and_deal_comp_def(ctx: Context<InitShuffleAndDealCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.shuffle_and_deal;
         init_comp_def(
             ctx.accounts,
             true,
             0,
             Some(CircuitSource::OffChain(OffChainCircuitSource {
                 source: "https://arcium.s3.us-east-1.amazonaws.com/shuffle_and_deal_testnet.arcis".to_string(),
-                hash: [0; 32],
+                hash,
             })),
             None,
         )?;
@@ -114,13 +475,14 @@ pub mod veridian_holdem {
     }
     
     pub fn init_reveal_community_cards_comp_def(ctx: Context<InitRevealCommunityCardsCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.reveal_community_cards;
         init_comp_def(
             ctx.accounts,
             true,
             0,
             Some(CircuitSource::OffChain(OffChainCircuitSource {
                 source: "https://arcium.s3.us-east-1.amazonaws.com/reveal_community_cards_testnet.arcis".to_string(),
-                hash: [0; 32],
+                hash,
             })),
             None,
         )?;
@@ -128,13 +490,119 @@ pub mod veridian_holdem {
     }
 
     pub fn init_determine_winner_comp_def(ctx: Context<InitDetermineWinnerCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.determine_winner;
         init_comp_def(
             ctx.accounts,
             true,
             0,
             Some(CircuitSource::OffChain(OffChainCircuitSource {
                 source: "https://arcium.s3.us-east-1.amazonaws.com/determine_winner_testnet.arcis".to_string(),
-                hash: [0; 32],
+                hash,
+            })),
+            None,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_reveal_hole_cards_comp_def(ctx: Context<InitRevealHoleCardsCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.reveal_hole_cards;
+        init_comp_def(
+            ctx.accounts,
+            true,
+            0,
+            Some(CircuitSource::OffChain(OffChainCircuitSource {
+                source: "https://arcium.s3.us-east-1.amazonaws.com/reveal_hole_cards_testnet.arcis".to_string(),
+                hash,
+            })),
+            None,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_preview_winner_comp_def(ctx: Context<InitPreviewWinnerCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.preview_winner;
+        init_comp_def(
+            ctx.accounts,
+            true,
+            0,
+            Some(CircuitSource::OffChain(OffChainCircuitSource {
+                source: "https://arcium.s3.us-east-1.amazonaws.com/preview_winner_testnet.arcis".to_string(),
+                hash,
+            })),
+            None,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_shuffle_and_deal_draw_comp_def(ctx: Context<InitShuffleAndDealDrawCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.shuffle_and_deal_draw;
+        init_comp_def(
+            ctx.accounts,
+            true,
+            0,
+            Some(CircuitSource::OffChain(OffChainCircuitSource {
+                source: "https://arcium.s3.us-east-1.amazonaws.com/shuffle_and_deal_draw_testnet.arcis".to_string(),
+                hash,
+            })),
+            None,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_draw_cards_comp_def(ctx: Context<InitDrawCardsCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.draw_cards;
+        init_comp_def(
+            ctx.accounts,
+            true,
+            0,
+            Some(CircuitSource::OffChain(OffChainCircuitSource {
+                source: "https://arcium.s3.us-east-1.amazonaws.com/draw_cards_testnet.arcis".to_string(),
+                hash,
+            })),
+            None,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_determine_winner_draw_comp_def(ctx: Context<InitDetermineWinnerDrawCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.determine_winner_draw;
+        init_comp_def(
+            ctx.accounts,
+            true,
+            0,
+            Some(CircuitSource::OffChain(OffChainCircuitSource {
+                source: "https://arcium.s3.us-east-1.amazonaws.com/determine_winner_draw_testnet.arcis".to_string(),
+                hash,
+            })),
+            None,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_reveal_all_community_cards_comp_def(ctx: Context<InitRevealAllCommunityCardsCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.reveal_all_community_cards;
+        init_comp_def(
+            ctx.accounts,
+            true,
+            0,
+            Some(CircuitSource::OffChain(OffChainCircuitSource {
+                source: "https://arcium.s3.us-east-1.amazonaws.com/reveal_all_community_cards_testnet.arcis".to_string(),
+                hash,
+            })),
+            None,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_insurance_odds_comp_def(ctx: Context<InitInsuranceOddsCompDef>) -> Result<()> {
+        let hash = ctx.accounts.config.circuit_hashes.insurance_odds;
+        init_comp_def(
+            ctx.accounts,
+            true,
+            0,
+            Some(CircuitSource::OffChain(OffChainCircuitSource {
+                source: "https://arcium.s3.us-east-1.amazonaws.com/insurance_odds_testnet.arcis".to_string(),
+                hash,
             })),
             None,
         )?;
@@ -153,6 +621,9 @@ pub struct InitShuffleAndDealCompDef<'info> {
     /// CHECK: This account is validated by the Arcium program
     #[account(mut)]
     pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
@@ -167,6 +638,9 @@ pub struct InitRevealCommunityCardsCompDef<'info> {
     /// CHECK: This account is validated by the Arcium program
     #[account(mut)]
     pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
@@ -181,6 +655,128 @@ pub struct InitDetermineWinnerCompDef<'info> {
     /// CHECK: This account is validated by the Arcium program
     #[account(mut)]
     pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("reveal_hole_cards", payer)]
+#[derive(Accounts)]
+pub struct InitRevealHoleCardsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: This account is validated by the Arcium program
+    #[account(mut)]
+    pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("preview_winner", payer)]
+#[derive(Accounts)]
+pub struct InitPreviewWinnerCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: This account is validated by the Arcium program
+    #[account(mut)]
+    pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("shuffle_and_deal_draw", payer)]
+#[derive(Accounts)]
+pub struct InitShuffleAndDealDrawCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: This account is validated by the Arcium program
+    #[account(mut)]
+    pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("draw_cards", payer)]
+#[derive(Accounts)]
+pub struct InitDrawCardsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: This account is validated by the Arcium program
+    #[account(mut)]
+    pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("determine_winner_draw", payer)]
+#[derive(Accounts)]
+pub struct InitDetermineWinnerDrawCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: This account is validated by the Arcium program
+    #[account(mut)]
+    pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("insurance_odds", payer)]
+#[derive(Accounts)]
+pub struct InitInsuranceOddsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: This account is validated by the Arcium program
+    #[account(mut)]
+    pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("reveal_all_community_cards", payer)]
+#[derive(Accounts)]
+pub struct InitRevealAllCommunityCardsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: This account is validated by the Arcium program
+    #[account(mut)]
+    pub comp_def_account: UncheckedAccount<'info>,
+    /// The global `Config` account holding the expected circuit hash.
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
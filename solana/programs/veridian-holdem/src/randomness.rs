@@ -0,0 +1,81 @@
+/**
+ * @description
+ * This file contains the slot-hash randomness source. It derives a seed from the most recent
+ * entry of the `SlotHashes` sysvar mixed with commitments the players made before that slot,
+ * so that anyone can recompute a draw from the transaction's logs.
+ *
+ * It is a much weaker source than the MPC shuffle or a commit-reveal scheme, and is only used
+ * where a biased draw costs little, such as choosing the first button:
+ * - The slot leader produces the slot hash and can withhold or reorder blocks to influence it.
+ * - A player can choose which slot to submit their transaction in and, by simulating it first,
+ *   skip the slots whose hash gives an unfavourable draw.
+ * - The result is public as soon as the transaction lands, so it must never feed a secret.
+ * Deck shuffles and anything else that decides a pot keep using the MPC computation.
+ *
+ * @dependencies
+ * - anchor_lang: For sysvar access and hashing.
+ * - crate::error: Defines custom error codes.
+ * - crate::events: Defines the `ButtonDrawn` event.
+ * - crate::state: Defines the `GameState` account.
+ */
+
+use crate::{error::ErrorCode, events::ButtonDrawn, state::GameState};
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+
+/// A domain separator, so that a seed is never equal to another hash of the same inputs.
+const SLOT_HASH_SEED_DOMAIN: &[u8] = b"veridian:slot_hash_seed";
+
+/// Reads the most recent `(slot, hash)` entry of the `SlotHashes` sysvar. The sysvar is too
+/// large to deserialize on-chain, so the entry is read directly from the account data: a `u64`
+/// entry count followed by the entries, newest first.
+pub fn latest_slot_hash(slot_hashes: &AccountInfo) -> Result<(u64, [u8; 32])> {
+    require_keys_eq!(
+        *slot_hashes.key,
+        anchor_lang::solana_program::sysvar::slot_hashes::ID,
+        ErrorCode::InvalidAction
+    );
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 48, ErrorCode::InvalidAction);
+    let count = u64::from_le_bytes(data[0..8].try_into().unwrap());
+    require!(count > 0, ErrorCode::InvalidAction);
+    let slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&data[16..48]);
+    Ok((slot, hash))
+}
+
+/// Derives a seed from a slot hash and the players' commitments. The derivation is
+/// deterministic: the same slot hash and commitments, in the same order, always give the same
+/// seed, which is `sha256(domain || slot_hash || commitments...)`.
+pub fn derive_slot_hash_seed(slot_hash: &[u8; 32], commitments: &[&[u8]]) -> [u8; 32] {
+    let mut inputs: Vec<&[u8]> = Vec::with_capacity(commitments.len() + 2);
+    inputs.push(SLOT_HASH_SEED_DOMAIN);
+    inputs.push(&slot_hash[..]);
+    inputs.extend_from_slice(commitments);
+    hashv(&inputs).to_bytes()
+}
+
+/// Draws the first button of a new match from the latest slot hash. The players' keys and
+/// encryption keys serve as their commitments: both are fixed before the slot hash is known.
+/// Emits `ButtonDrawn` with everything needed to recompute the draw.
+pub fn draw_button(game_state: &mut GameState, slot_hashes: &AccountInfo) -> Result<()> {
+    let (slot, slot_hash) = latest_slot_hash(slot_hashes)?;
+    let seed = derive_slot_hash_seed(
+        &slot_hash,
+        &[
+            game_state.players[0].as_ref(),
+            game_state.players[1].as_ref(),
+            &game_state.encryption_keys[0][..],
+            &game_state.encryption_keys[1][..],
+        ],
+    );
+    game_state.dealer_index = seed[0] % 2;
+    game_state.current_turn_index = game_state.dealer_index;
+    emit!(ButtonDrawn {
+        table_id: game_state.table_id,
+        slot,
+        slot_hash,
+        dealer_index: game_state.dealer_index,
+    });
+    Ok(())
+}
@@ -14,12 +14,51 @@
  * - Constants like `MAX_PLAYERS` are used to ensure consistency and make the code more maintainable.
  */
 
-use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
 
 /// The maximum number of players at a table. For Heads-Up, this is always 2.
 pub const MAX_PLAYERS: usize = 2;
+/// The maximum number of challengers that can queue for a seat at a table.
+pub const MAX_WAITLIST: usize = 4;
 /// The duration of a player's turn in seconds before they can be folded by the crank.
 pub const TURN_TIME_SECONDS: i64 = 30;
+/// An action taken after this percentage of the player's turn timer has elapsed counts as slow.
+pub const SLOW_ACTION_THRESHOLD_PERCENT: i64 = 80;
+/// Seconds removed from a player's turn timer for each slow action they have taken.
+pub const SLOW_ACTION_PENALTY_SECONDS: i64 = 5;
+/// The shortest turn timer a chronically slow player can be reduced to.
+pub const MIN_TURN_TIME_SECONDS: i64 = 10;
+/// Seconds added back to each player's time bank at the start of every hand, up to the table's
+/// `time_bank_seconds`.
+pub const TIME_BANK_REFILL_SECONDS: i64 = 5;
+/// The nominal slot time, used to express `TableConfig::turn_grace_seconds` in slots on
+/// slot-timed tables.
+pub const MS_PER_SLOT: u64 = 400;
+/// The length of the rolling window over which `TableConfig::max_hands_per_hour` is enforced.
+pub const PACE_WINDOW_SECONDS: i64 = 3600;
+/// The denominator for values expressed in basis points.
+pub const MAX_BPS: u16 = 10_000;
+/// The most finishing places a tournament payout structure can pay.
+pub const MAX_PAID_PLACES: usize = 10;
+/// The most straddle levels a table can allow. Each level doubles the straddle.
+pub const MAX_STRADDLES: u8 = 8;
+/// The most tables a fast-fold pool can hold.
+pub const MAX_POOL_TABLES: usize = 16;
+/// The shortest interval, in seconds, between two emotes from the same seat.
+pub const EMOTE_COOLDOWN_SECONDS: i64 = 3;
+/// The number of retries of a failed community card reveal a new `Config` allows.
+pub const DEFAULT_MAX_REVEAL_RETRIES: u8 = 2;
+/// The largest early-leave penalty the admin may set, in basis points of the withdrawal.
+pub const MAX_LEAVE_PENALTY_BPS: u16 = 500;
+/// The largest share of a pot, in percent, the admin may set as the rake.
+pub const MAX_RAKE_PERCENTAGE: u8 = 100;
+/// The most pot-size rake tiers the admin may configure.
+pub const MAX_RAKE_TIERS: usize = 3;
+/// The most pots a hand can have: one per distinct all-in level, plus the pot above them.
+pub const MAX_SIDE_POTS: usize = MAX_PLAYERS;
+/// The longest a table may retain a settled hand's `HandState`, in seconds (a year).
+pub const MAX_HAND_RETENTION_SECONDS: u64 = 365 * 24 * 3600;
 
 /// Defines the current phase of a poker hand, dictating which actions are valid.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Copy)]
@@ -30,6 +69,9 @@ pub enum GamePhase {
     Dealing,
     /// The first betting round, before any community cards are revealed.
     PreFlop,
+    /// Five-card draw only: players take turns discarding and drawing replacement cards.
+    /// The final betting round that follows uses the `River` phase.
+    Draw,
     /// The second betting round, after the first three community cards (the flop) are revealed.
     Flop,
     /// The third betting round, after the fourth community card (the turn) is revealed.
@@ -38,10 +80,58 @@ pub enum GamePhase {
     River,
     /// Hands are being compared confidentially by Arcium to determine the winner.
     Showdown,
+    /// The winner has been determined and recorded; the pot is waiting to be paid out by `settle_hand`.
+    ReadyToSettle,
     /// The hand is complete, and the pot has been distributed. Waiting to start the next hand.
     HandOver,
 }
 
+impl GamePhase {
+    /// Returns the street byte the `reveal_community_cards` circuit expects for this phase:
+    /// 0 for the flop, 1 for the turn and 2 for the river. Other phases have no cards to reveal.
+    pub fn reveal_street(&self) -> Option<u8> {
+        match self {
+            GamePhase::Flop => Some(0),
+            GamePhase::Turn => Some(1),
+            GamePhase::River => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Returns the betting street of this phase: 0 preflop, 1 flop, 2 turn, 3 river. Other
+    /// phases have no betting and count as preflop.
+    pub fn betting_street(&self) -> u8 {
+        match self {
+            GamePhase::Flop => 1,
+            GamePhase::Turn => 2,
+            GamePhase::River => 3,
+            _ => 0,
+        }
+    }
+}
+
+/// The poker variant played at a table.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default)]
+pub enum GameVariant {
+    /// Heads-up Texas Hold'em: two hole cards and five community cards.
+    #[default]
+    Holdem,
+    /// Five-card draw: five hole cards, one draw, and two betting rounds.
+    FiveCardDraw,
+}
+
+/// How the platform charges for play at a table.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default)]
+pub enum RakeType {
+    /// A share of each pot that sees a flop, as set by `Config::rake_percentage` or
+    /// `Config::rake_tiers`.
+    #[default]
+    Pot,
+    /// A periodic seat charge taken from each seated player's stack, as set by
+    /// `Config::time_charge`. Pots are not raked.
+    Time,
+}
+
 /// Singleton PDA account for global administrative configuration.
 /// This account stores settings that apply to the entire platform, like rake rules.
 /// PDA Seeds: `[b"config"]`
@@ -57,6 +147,103 @@ pub struct Config {
     /// The maximum rake amount that can be taken from a single pot, specified in the smallest
     /// unit of the game's SPL token (e.g., lamports for SOL).
     pub rake_cap: u64,
+    /// The share of the rake, in basis points, paid to the winner's referrer (if they have one)
+    /// instead of the treasury.
+    pub referrer_bps: u16,
+    /// The share, in basis points, of each player's part of the rake that is credited back
+    /// to them as rake-back.
+    pub rakeback_bps: u16,
+    /// The markup, in basis points, added to the fair price of all-in insurance.
+    pub insurance_markup_bps: u16,
+    /// The amount charged to each seated player per `time_charge_period_seconds` at tables
+    /// using `RakeType::Time`.
+    pub time_charge: u64,
+    /// The length of one time-charge period, in seconds.
+    pub time_charge_period_seconds: i64,
+    /// The expected hashes of the published Arcis circuits. Computation definitions are
+    /// registered with these hashes, and queued computations are rejected if the
+    /// computation definition does not match.
+    pub circuit_hashes: CircuitHashes,
+    /// The largest buy-in any table may require, in the smallest unit of the table's token.
+    /// 0 means there is no platform cap.
+    pub max_buy_in_cap: u64,
+    /// The earliest betting street (0 preflop, 1 flop, 2 turn, 3 river) a Hold'em hand's betting
+    /// must reach before its pot is raked. 0 rakes every pot that reaches a showdown.
+    pub rake_min_street: u8,
+    /// How many times a failed community card reveal may be requested again before the hand is
+    /// voided and the chips returned.
+    pub max_reveal_retries: u8,
+    /// The penalty, in basis points of the withdrawal, a player pays their opponent for leaving
+    /// while ahead within `leave_penalty_hands` hands of sitting down. 0 disables it; it is at
+    /// most `MAX_LEAVE_PENALTY_BPS`.
+    pub leave_penalty_bps: u16,
+    /// How many hands after sitting down a player leaving while ahead pays the leave penalty.
+    pub leave_penalty_hands: u32,
+    /// The administrator proposed by `transfer_admin`, who becomes `admin` by signing
+    /// `accept_admin`. `Pubkey::default()` when no transfer is pending.
+    pub pending_admin: Pubkey,
+    /// When true, no new hand can be dealt anywhere on the platform. Hands already in progress
+    /// still play out, settle and pay out, and players can still leave.
+    pub paused: bool,
+    /// Progressive rake by pot size, in strictly increasing `pot_threshold` order. When set,
+    /// these replace `rake_percentage` and `rake_cap`; when empty, the flat rake applies.
+    #[max_len(MAX_RAKE_TIERS)]
+    pub rake_tiers: Vec<RakeTier>,
+    /// The smallest total pot, in the smallest unit of the table's token, that is raked at all.
+    /// Smaller pots pay no rake even when they reach `rake_min_street`. 0 rakes every pot.
+    pub rake_min_pot: u64,
+}
+
+impl Config {
+    /// The rake due on a raked pot of `total_pot`: the percentage and cap of the highest tier
+    /// whose threshold the pot reaches, or the flat rake if no tiers are set. A pot below the
+    /// first tier's threshold is not raked.
+    pub fn rake_for(&self, total_pot: u64) -> u64 {
+        let (percentage, cap) = if self.rake_tiers.is_empty() {
+            (self.rake_percentage, self.rake_cap)
+        } else {
+            match self.rake_tiers.iter().rev().find(|tier| total_pot >= tier.pot_threshold) {
+                Some(tier) => (tier.percentage, tier.cap),
+                None => return 0,
+            }
+        };
+        let rake = (total_pot as u128 * percentage as u128 / 100) as u64;
+        rake.min(cap)
+    }
+}
+
+/// One pot-size band of the progressive rake.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct RakeTier {
+    /// The smallest total pot, in the smallest unit of the table's token, this tier applies to.
+    pub pot_threshold: u64,
+    /// The percentage of the pot taken as rake, at most `MAX_RAKE_PERCENTAGE`.
+    pub percentage: u8,
+    /// The most rake this tier takes from a single pot.
+    pub cap: u64,
+}
+
+/// A pot and the seats that can win it. A hand's first pot is its main pot; each later one holds
+/// chips that only the players covering an all-in put in.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct SidePot {
+    pub amount: u64,
+    pub eligible: [bool; MAX_PLAYERS],
+}
+
+/// The expected SHA-256 hashes of each off-chain Arcis circuit used by the program.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct CircuitHashes {
+    pub shuffle_and_deal: [u8; 32],
+    pub reveal_community_cards: [u8; 32],
+    pub reveal_all_community_cards: [u8; 32],
+    pub determine_winner: [u8; 32],
+    pub reveal_hole_cards: [u8; 32],
+    pub preview_winner: [u8; 32],
+    pub shuffle_and_deal_draw: [u8; 32],
+    pub draw_cards: [u8; 32],
+    pub determine_winner_draw: [u8; 32],
+    pub insurance_odds: [u8; 32],
 }
 
 /// Stores the immutable configuration for a specific poker table, such as stakes and buy-in.
@@ -71,10 +258,119 @@ pub struct TableConfig {
     pub small_blind: u64,
     /// The big blind amount for this table.
     pub big_blind: u64,
-    /// The required buy-in amount to join the table.
-    pub buy_in: u64,
+    /// The smallest amount a player may buy in for.
+    pub min_buy_in: u64,
+    /// The largest amount a player may buy in for, and the most a rebuy may top a stack up to.
+    pub max_buy_in: u64,
     /// The mint address of the SPL Token used as the currency for this table (e.g., USDC).
     pub token_mint: Pubkey,
+    /// The maximum a single player may put into the pot during one hand ("cap" poker).
+    /// A value of 0 disables the cap. Reaching the cap closes betting for that player
+    /// exactly as if they were all-in.
+    pub hand_cap: u64,
+    /// "Winner stays" (king-of-the-hill) format: when a player busts, the winner keeps
+    /// their seat and stack and the loser's seat is opened for the next challenger.
+    pub winner_stays: bool,
+    /// "No limp" rule: preflop, the small blind may not just complete the big blind and
+    /// must either raise or fold.
+    pub no_limp: bool,
+    /// The poker variant played at this table.
+    pub variant: GameVariant,
+    /// The turn timer expressed in slots instead of seconds. A value of 0 keeps the
+    /// default timer based on the cluster's Unix timestamp.
+    pub turn_time_slots: u64,
+    /// Sitting-out blinds policy: when set, a player sitting out keeps posting (and forfeiting)
+    /// their blinds to hold their seat; otherwise they skip them and owe them as missed blinds.
+    pub sitout_posts_blinds: bool,
+    /// The number of times each player may rebuy during a match. A value of 0 allows unlimited
+    /// rebuys.
+    pub max_rebuys: u8,
+    /// Heads-up "button straddle" (Mississippi): when allowed, the button may declare a straddle
+    /// before a hand. They then post twice the big blind instead of the small blind, the big
+    /// blind acts first preflop, and the straddler acts last with the option.
+    pub button_straddle: bool,
+    /// The number of straddle levels allowed before a hand, each doubling the last: a straddle
+    /// of two big blinds, a re-straddle of four, and so on. Heads-up, re-straddling means the
+    /// button declaring again. At most `MAX_STRADDLES`.
+    pub max_straddles: u8,
+    /// Seconds of grace at the start of every turn before the turn timer starts, so players on
+    /// slow connections are not on the clock before they have even seen their turn. A value
+    /// of 0 starts the timer immediately.
+    pub turn_grace_seconds: u64,
+    /// The maximum number of hands that may be dealt in any rolling hour, set by the platform
+    /// operator for responsible gaming. A value of 0 disables the cap.
+    pub max_hands_per_hour: u16,
+    /// The multiple of the previous bet or raise that a raise must add at least. The standard
+    /// rule is 1; big-bet formats may require 2 or more. An all-in raise is always allowed.
+    pub min_raise_multiple: u8,
+    /// How the platform charges for play at this table, set by the platform operator.
+    pub rake_type: RakeType,
+    /// How long a settled hand's `HandState` is kept, so clients can still decrypt the hand
+    /// or refer to it in a dispute, before `reap_hand` may close it. A value of 0 closes it at
+    /// settlement. Dealing the next hand reuses the account either way. At most
+    /// `MAX_HAND_RETENTION_SECONDS`.
+    pub hand_retention_seconds: u64,
+    /// The minimum number of seconds between community card reveals during an all-in runout,
+    /// so clients have time to show each street. A value of 0 runs the board out at once.
+    pub runout_delay_seconds: u64,
+    /// Button ante: each hand, the button posts an ante of this many big blinds as dead money,
+    /// on top of their blind. Being a multiple, it follows the blinds when they change. A value
+    /// of 0 disables the ante.
+    pub button_ante_multiplier: u8,
+    /// The ante both players post each hand as dead money, before the blinds. A player whose
+    /// stack cannot cover the ante and their blind is all-in for what they have. A value of 0
+    /// disables the ante.
+    pub ante: u64,
+    /// The time bank each player starts with, in seconds: a reserve beyond the turn timer that
+    /// is drawn down whenever they take longer than the timer, and refilled by
+    /// `TIME_BANK_REFILL_SECONDS` each hand up to this amount. A value of 0 disables it.
+    pub time_bank_seconds: u64,
+    /// Whether every community card reveal is bracketed by `CardRevealStarted` and
+    /// `CardRevealCompleted` events, so clients can animate the board. Off by default, to spare
+    /// headless bots the extra events; it has no effect on play.
+    pub reveal_events: bool,
+}
+
+/// Optional rule variants chosen by the table creator. These are copied into the
+/// table's `TableConfig` at creation time. `Default` yields a standard no-limit table.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct TableOptions {
+    /// See `TableConfig::hand_cap`.
+    pub hand_cap: u64,
+    /// See `TableConfig::winner_stays`.
+    pub winner_stays: bool,
+    /// The seat (0 or 1) the creator takes. The joiner gets the other one.
+    pub creator_seat: u8,
+    /// See `TableConfig::no_limp`.
+    pub no_limp: bool,
+    /// See `TableConfig::variant`.
+    pub variant: GameVariant,
+    /// See `TableConfig::turn_time_slots`.
+    pub turn_time_slots: u64,
+    /// See `TableConfig::sitout_posts_blinds`.
+    pub sitout_posts_blinds: bool,
+    /// See `TableConfig::max_rebuys`.
+    pub max_rebuys: u8,
+    /// See `TableConfig::button_straddle`.
+    pub button_straddle: bool,
+    /// See `TableConfig::max_straddles`. A value of 0 is treated as 1.
+    pub max_straddles: u8,
+    /// See `TableConfig::turn_grace_seconds`.
+    pub turn_grace_seconds: u64,
+    /// See `TableConfig::min_raise_multiple`. A value of 0 is treated as 1.
+    pub min_raise_multiple: u8,
+    /// See `TableConfig::hand_retention_seconds`.
+    pub hand_retention_seconds: u64,
+    /// See `TableConfig::runout_delay_seconds`.
+    pub runout_delay_seconds: u64,
+    /// See `TableConfig::button_ante_multiplier`.
+    pub button_ante_multiplier: u8,
+    /// See `TableConfig::ante`.
+    pub ante: u64,
+    /// See `TableConfig::time_bank_seconds`.
+    pub time_bank_seconds: u64,
+    /// See `TableConfig::reveal_events`.
+    pub reveal_events: bool,
 }
 
 /// Holds the public, mutable state of a single poker table.
@@ -100,6 +396,9 @@ pub struct GameState {
     pub bets: [u64; MAX_PLAYERS],
     /// The five community cards. A value of 255 represents an un-dealt card.
     pub community_cards: [u8; 5],
+    /// The total amount each player has put into the pot over the whole hand,
+    /// including blinds. Reset when a new hand is dealt.
+    pub total_contributed: [u64; MAX_PLAYERS],
     /// Flags to track if a player is all-in.
     pub is_all_in: [bool; MAX_PLAYERS],
     /// The index (0 or 1) of the player whose turn it is to act.
@@ -108,20 +407,697 @@ pub struct GameState {
     pub dealer_index: u8,
     /// The Unix timestamp of the last action taken, used for the turn timer.
     pub last_action_timestamp: i64,
-    /// A flag indicating if a game is currently active at this table.
+    /// A flag indicating if a game is currently active at this table. It is derived state; see
+    /// `GameState::recompute_is_active`.
     pub is_active: bool,
+    /// The number of times each seated player has acted only after most of their turn
+    /// timer had elapsed. Each slow action shortens that player's future turn timer.
+    pub slow_action_count: [u8; MAX_PLAYERS],
+    /// Challengers queued for the next open seat, in order. A `Pubkey::default()` value
+    /// marks an unused slot; occupied slots are always packed at the front.
+    pub waitlist: [Pubkey; MAX_WAITLIST],
+    /// The showdown result recorded by the `determine_winner` callback while the hand is
+    /// `ReadyToSettle`: 0 or 1 for a single winner, 2 for a split pot.
+    pub winner_index: u8,
+    /// The player who folded the last hand and may still choose to show their cards.
+    /// Cleared once the cards are shown or the next hand is dealt.
+    pub folded_player: Pubkey,
+    /// The referrer registered by the player in each seat, or `Pubkey::default()` for none.
+    pub referrers: [Pubkey; MAX_PLAYERS],
+    /// The total amount each seat's current player has bought in for this session.
+    pub session_buy_in: [u64; MAX_PLAYERS],
+    /// The total amount each seat's current player has withdrawn this session, before leaving.
+    pub session_payout: [u64; MAX_PLAYERS],
+    /// Five-card draw only: set while the current player's draw computation is in flight.
+    pub draw_pending: bool,
+    /// The slot of the last action taken, used for the turn timer on slot-timed tables.
+    pub last_action_slot: u64,
+    /// The x25519 encryption key each seat's player registered in their `PlayerProfile`
+    /// before sitting down, or all zeroes if they did not register one.
+    pub encryption_keys: [[u8; 32]; MAX_PLAYERS],
+    /// Set once an all-in has closed the betting before the river. The missing streets are
+    /// then revealed in order without betting, and the hand goes to showdown after the river.
+    pub runout: bool,
+    /// Whether each seat's player is sitting out. No hand is dealt while a player sits out.
+    pub sitting_out: [bool; MAX_PLAYERS],
+    /// Blinds each seat's player skipped while sitting out, posted as dead money in the first
+    /// hand they play after sitting back in.
+    pub missed_blinds: [u64; MAX_PLAYERS],
+    /// The player ahead in the latest all-in insurance quote, or 255 if there is no quote.
+    pub insurance_leader: u8,
+    /// The number of river cards that would beat `insurance_leader`, from the latest quote.
+    pub insurance_outs: u8,
+    /// The cover `insurance_leader` bought this hand, paid from the insurance pool if they lose.
+    pub insured_amount: u64,
+    /// The number of times each seat's player has rebought during the current match.
+    pub rebuy_count: [u8; MAX_PLAYERS],
+    /// The stack each seat's player is automatically restored to after busting, if they opted in.
+    pub auto_rebuy_min: [Option<u64>; MAX_PLAYERS],
+    /// The token account each seat's auto-rebuys are pulled from, as a delegate of the owner.
+    pub auto_rebuy_accounts: [Pubkey; MAX_PLAYERS],
+    /// The `GameState` of the table the platform operator wants this table's players to move
+    /// to, for consolidating tables on busy platforms. While set, no new hand is dealt and
+    /// nobody can join; seated players move with `move_seat` (or leave).
+    pub must_move_target: Option<Pubkey>,
+    /// The player who declared a button straddle for the next hand, or `Pubkey::default()`.
+    /// The declaration only counts if that player still holds the button when the hand is dealt.
+    pub straddler: Pubkey,
+    /// Whether the current hand was straddled by the button, which changes the preflop action
+    /// order (see `TableConfig::button_straddle`).
+    pub straddled: bool,
+    /// The start of the current fixed pace window (see `record_deal_pace`).
+    pub pace_window_start: i64,
+    /// Hands dealt in the previous and the current pace window.
+    pub pace_hands: [u16; 2],
+    /// The size of the last full bet or raise on the current street, by which the next raise
+    /// must increase the bet (times `TableConfig::min_raise_multiple`). 0 before any bet.
+    pub last_raise_size: u64,
+    /// At time-raked tables, the start of the last period each seat's player was charged for,
+    /// or 0 if they have not been charged since sitting down.
+    pub time_charged_at: [i64; MAX_PLAYERS],
+    /// The Unix timestamp of the last community card reveal requested this hand, or 0.
+    pub last_reveal_timestamp: i64,
+    /// The straddle levels declared for the next hand and, once it is dealt, posted in the
+    /// current one (see `TableConfig::max_straddles`). 0 when there is no straddle.
+    pub straddle_levels: u8,
+    /// Set when a hand is dealt and cleared when it ends (or its deal is voided), so exactly
+    /// one hand is ever in flight across the deal, reveal and showdown transactions.
+    pub hand_in_progress: bool,
+    /// Set once the current hand's shuffle has been queued, so a deal is queued only once and
+    /// never once the hand is under way. Cleared when the next hand starts.
+    pub deal_queued: bool,
+    /// Whether each seat has voluntarily acted on the current street. Posting a blind or a
+    /// straddle does not count, and a bet or raise clears the opponent's flag, so a betting
+    /// round is closed once both seats have acted with their bets equal.
+    pub has_acted: [bool; MAX_PLAYERS],
+    /// The last betting street of the hand (see `GamePhase::betting_street`). The streets of an
+    /// all-in runout have no betting, so they do not count.
+    pub final_betting_street: u8,
+    /// The Unix timestamp of each seat's last emote, used to rate-limit `send_emote`.
+    pub last_emote_timestamp: [i64; MAX_PLAYERS],
+    /// The seconds left in each seat's time bank (see `TableConfig::time_bank_seconds`).
+    pub time_bank_seconds: [i64; MAX_PLAYERS],
+    /// The number of hands dealt to each seat since its player sat down, used for the leave
+    /// penalty (see `Config::leave_penalty_bps`).
+    pub hands_played: [u32; MAX_PLAYERS],
+    /// Set when the last hand was voided because its deal failed, or failed an integrity check, allowing
+    /// anyone to re-deal it with `redeal_after_misdeal`. Cleared when the next hand starts.
+    pub misdeal: bool,
+    /// The showdown winners recorded alongside `winner_index`: a bit per seat that shares the
+    /// pot. `settle_hand` splits each pot evenly among those eligible for it.
+    pub winner_mask: u8,
+    /// The hand's pots, rebuilt from the players' contributions whenever bets are collected.
+    /// Empty until the first betting round closes.
+    #[max_len(MAX_SIDE_POTS)]
+    pub side_pots: Vec<SidePot>,
+    /// The category of the winning hand revealed by a Hold'em showdown, reported in
+    /// `HandResult` when the hand is settled. `None` otherwise.
+    pub winning_rank: Option<u8>,
+    /// The pot rake this table has paid over its lifetime, referrer shares included, in the
+    /// smallest unit of the table's token.
+    pub total_rake_collected: u64,
+    /// The table's token mint, copied from `TableConfig::token_mint` so the token accounts a
+    /// settlement pays from and into can be checked against the game alone.
+    pub token_mint: Pubkey,
+}
+
+impl GameState {
+    /// Returns the turn duration in seconds for the given seat, shortened by
+    /// `SLOW_ACTION_PENALTY_SECONDS` per recorded slow action down to `MIN_TURN_TIME_SECONDS`.
+    pub fn turn_time_for(&self, seat: usize) -> i64 {
+        let penalty = self.slow_action_count[seat] as i64 * SLOW_ACTION_PENALTY_SECONDS;
+        (TURN_TIME_SECONDS - penalty).max(MIN_TURN_TIME_SECONDS)
+    }
+
+    /// Returns how much of the given seat's turn has elapsed together with the length of
+    /// that turn, both in seconds or, when the table's `turn_time_slots` is non-zero, in slots.
+    /// The slot timer is shortened for slow players in the same proportion as `turn_time_for`.
+    /// The table's grace period is not counted, so during it the elapsed time is not positive.
+    pub fn turn_progress(&self, seat: usize, clock: &Clock, table_config: &TableConfig) -> (i64, i64) {
+        let turn_time_slots = table_config.turn_time_slots;
+        if turn_time_slots == 0 {
+            let elapsed = clock.unix_timestamp
+                - self.last_action_timestamp
+                - table_config.turn_grace_seconds as i64;
+            return (elapsed, self.turn_time_for(seat));
+        }
+        let grace_slots = (table_config.turn_grace_seconds * 1000 / MS_PER_SLOT) as i64;
+        let elapsed = clock.slot.saturating_sub(self.last_action_slot) as i64 - grace_slots;
+        let limit = turn_time_slots as i64 * self.turn_time_for(seat) / TURN_TIME_SECONDS;
+        (elapsed, limit)
+    }
+
+    /// Returns the seat's remaining time bank in the unit of `turn_progress`: seconds or, on
+    /// slot-timed tables, slots.
+    pub fn time_bank_for(&self, seat: usize, table_config: &TableConfig) -> i64 {
+        let bank = self.time_bank_seconds[seat];
+        if table_config.turn_time_slots == 0 {
+            bank
+        } else {
+            bank * 1000 / MS_PER_SLOT as i64
+        }
+    }
+
+    /// Draws down the seat's time bank by however long the turn ran past its timer, given the
+    /// elapsed time and turn length from `turn_progress`.
+    pub fn use_time_bank(&mut self, seat: usize, elapsed: i64, turn_time: i64, table_config: &TableConfig) {
+        let overage = elapsed - turn_time;
+        if overage <= 0 {
+            return;
+        }
+        let overage_seconds = if table_config.turn_time_slots == 0 {
+            overage
+        } else {
+            (overage * MS_PER_SLOT as i64 + 999) / 1000
+        };
+        self.time_bank_seconds[seat] = (self.time_bank_seconds[seat] - overage_seconds).max(0);
+    }
+
+    /// Tops up each seat's time bank by `TIME_BANK_REFILL_SECONDS` at the start of a hand, capped
+    /// at the table's `time_bank_seconds`.
+    pub fn refill_time_banks(&mut self, table_config: &TableConfig) {
+        let cap = table_config.time_bank_seconds as i64;
+        for bank in self.time_bank_seconds.iter_mut() {
+            *bank = (*bank + TIME_BANK_REFILL_SECONDS).min(cap);
+        }
+    }
+
+    /// Returns the penalty owed by the player at `seat` for leaving now with their stack, under
+    /// the platform's leave penalty settings. Only a player who is ahead on the session, leaves
+    /// within `leave_penalty_hands` hands of sitting down, and leaves an opponent seated owes
+    /// one, and it never exceeds their winnings. Returns `ArithmeticOverflow` if the session
+    /// totals do not fit in a `u64`.
+    pub fn leave_penalty(&self, seat: usize, config: &Config) -> Result<u64> {
+        let opponent_seated = self.players[1 - seat] != Pubkey::default();
+        if config.leave_penalty_bps == 0
+            || !opponent_seated
+            || self.hands_played[seat] >= config.leave_penalty_hands
+        {
+            return Ok(0);
+        }
+        let stack = self.stacks[seat];
+        let winnings = stack
+            .checked_add(self.session_payout[seat])
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .saturating_sub(self.session_buy_in[seat]);
+        let penalty = (stack as u128 * config.leave_penalty_bps as u128 / MAX_BPS as u128) as u64;
+        Ok(penalty.min(winnings))
+    }
+
+    /// Restarts the turn timer from the current clock, in both seconds and slots.
+    pub fn record_action_time(&mut self, clock: &Clock) {
+        self.last_action_timestamp = clock.unix_timestamp;
+        self.last_action_slot = clock.slot;
+    }
+
+    /// Returns the number of community cards revealed so far.
+    pub fn dealt_community_cards(&self) -> usize {
+        self.community_cards.iter().take_while(|&&card| card != 255).count()
+    }
+
+    /// Returns the number of community cards that must already be revealed before the current
+    /// street's cards can be, or `None` if the current phase has no cards to reveal.
+    pub fn cards_before_street(&self) -> Option<usize> {
+        match self.game_phase {
+            GamePhase::Flop => Some(0),
+            GamePhase::Turn => Some(3),
+            GamePhase::River => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Writes the current street's revealed cards to its board slots: the flop fills slots 0-2,
+    /// the turn slot 3 and the river slot 4. The reveal always returns three cards, so the unused
+    /// ones of a single-card street are ignored. Returns the number of cards placed.
+    pub fn place_street(&mut self, revealed_cards: &[u8; 3]) -> Result<usize> {
+        let (start, count) = match self.game_phase {
+            GamePhase::Flop => (0, 3),
+            GamePhase::Turn => (3, 1),
+            GamePhase::River => (4, 1),
+            _ => return err!(ErrorCode::InvalidRevealPhase),
+        };
+        self.community_cards[start..start + count].copy_from_slice(&revealed_cards[..count]);
+        Ok(count)
+    }
+
+    /// Checks that the public board is consistent with the current phase: dealt cards form a
+    /// prefix of `community_cards`, are valid and distinct, and their count matches the street.
+    /// A street's cards are revealed only after the phase has advanced, so the previous street's
+    /// count is also accepted. Five-card draw never has a board.
+    pub fn check_board(&self, variant: GameVariant) -> Result<()> {
+        let dealt = self.dealt_community_cards();
+        let board = &self.community_cards[..dealt];
+        require!(
+            self.community_cards[dealt..].iter().all(|&card| card == 255),
+            ErrorCode::BoardPhaseMismatch
+        );
+        require!(
+            board.iter().enumerate().all(|(i, &card)| card < 52 && !board[..i].contains(&card)),
+            ErrorCode::BoardPhaseMismatch
+        );
+
+        let consistent = match (variant, self.game_phase) {
+            (GameVariant::FiveCardDraw, _) => dealt == 0,
+            (_, GamePhase::Flop) => dealt == 0 || dealt == 3,
+            (_, GamePhase::Turn) => dealt == 3 || dealt == 4,
+            (_, GamePhase::River) => dealt == 4 || dealt == 5,
+            (_, GamePhase::Showdown | GamePhase::ReadyToSettle) => dealt == 5,
+            _ => dealt == 0,
+        };
+        require!(consistent, ErrorCode::BoardPhaseMismatch);
+        Ok(())
+    }
+
+    /// Returns whether the player holding the button has declared a straddle for the next hand.
+    pub fn straddle_declared(&self) -> bool {
+        self.straddler != Pubkey::default() && self.straddler == self.players[self.dealer_index as usize]
+    }
+
+    /// Returns the size of a straddle at the declared levels: twice the big blind, doubled
+    /// again for each re-straddle. Returns `ArithmeticOverflow` if that does not fit in a `u64`.
+    pub fn straddle_size(&self, table_config: &TableConfig) -> Result<u64> {
+        Ok(1u64
+            .checked_shl(self.straddle_levels as u32)
+            .and_then(|multiplier| table_config.big_blind.checked_mul(multiplier))
+            .ok_or(ErrorCode::ArithmeticOverflow)?)
+    }
+
+    /// Returns the forced bet the button posts this hand: the straddle if they declared one,
+    /// otherwise the small blind.
+    pub fn button_blind(&self, table_config: &TableConfig) -> Result<u64> {
+        if self.straddle_declared() {
+            self.straddle_size(table_config)
+        } else {
+            Ok(table_config.small_blind)
+        }
+    }
+
+    /// Returns the ante the button posts this hand: the table's multiple of the current big blind,
+    /// or `ArithmeticOverflow` if that does not fit in a `u64`.
+    pub fn button_ante(&self, table_config: &TableConfig) -> Result<u64> {
+        Ok(table_config
+            .big_blind
+            .checked_mul(table_config.button_ante_multiplier as u64)
+            .ok_or(ErrorCode::ArithmeticOverflow)?)
+    }
+
+    /// Counts a new deal against the table's hourly pace, rejecting it with `PaceLimitExceeded`
+    /// if it would exceed `max_hands_per_hour` (0 disables the cap). The rolling hour is
+    /// approximated with a sliding window counter: all hands of the current fixed window plus
+    /// the previous window's hands, weighted by how much of it still lies within the last hour.
+    pub fn record_deal_pace(&mut self, now: i64, max_hands_per_hour: u16) -> Result<()> {
+        let since_start = now - self.pace_window_start;
+        if since_start >= 2 * PACE_WINDOW_SECONDS {
+            self.pace_window_start = now;
+            self.pace_hands = [0, 0];
+        } else if since_start >= PACE_WINDOW_SECONDS {
+            self.pace_window_start += PACE_WINDOW_SECONDS;
+            self.pace_hands = [self.pace_hands[1], 0];
+        }
+
+        if max_hands_per_hour > 0 {
+            let into_window = now - self.pace_window_start;
+            let previous = self.pace_hands[0] as i64 * (PACE_WINDOW_SECONDS - into_window)
+                / PACE_WINDOW_SECONDS;
+            let hands_last_hour = previous + self.pace_hands[1] as i64;
+            require!(
+                hands_last_hour < max_hands_per_hour as i64,
+                ErrorCode::PaceLimitExceeded
+            );
+        }
+        self.pace_hands[1] = self.pace_hands[1].saturating_add(1);
+        Ok(())
+    }
+
+    /// Clears any all-in insurance quote and cover for the hand.
+    pub fn clear_insurance(&mut self) {
+        self.insurance_leader = 255;
+        self.insurance_outs = 0;
+        self.insured_amount = 0;
+    }
+
+    /// Resets all per-hand state once a hand has been settled and passes the button,
+    /// leaving the table in `HandOver` ready for the next deal.
+    pub fn reset_for_next_hand(&mut self) {
+        self.game_phase = GamePhase::HandOver;
+        self.hand_in_progress = false;
+        self.pot = 0;
+        self.bets = [0; MAX_PLAYERS];
+        self.last_raise_size = 0;
+        self.has_acted = [false; MAX_PLAYERS];
+        self.community_cards = [255; 5];
+        self.is_all_in = [false; MAX_PLAYERS];
+        self.runout = false;
+        self.side_pots.clear();
+        self.winning_rank = None;
+        self.clear_insurance();
+        // Swap the dealer button for the next hand.
+        self.dealer_index = 1 - self.dealer_index;
+        // The turn for the next hand starts with the player who is now the small blind/button.
+        self.current_turn_index = self.dealer_index;
+    }
+
+    /// Ends a hand whose deal failed before any chips went in. The button stays where it was,
+    /// and the table waits for `redeal_after_misdeal` to deal again.
+    pub fn record_misdeal(&mut self) {
+        self.game_phase = GamePhase::HandOver;
+        self.hand_in_progress = false;
+        self.current_turn_index = self.dealer_index;
+        self.misdeal = true;
+    }
+
+    /// Voids the hand in progress and returns the chips to the players: each gets back what
+    /// they put in, and dead money (antes and missed blinds), which is not recorded per seat, is
+    /// split evenly with any odd chip to the player out of position. The button stays where it
+    /// was, so the same dealer deals again. If the chips in play do not add up, nothing is
+    /// moved and `ArithmeticOverflow` is returned.
+    pub fn void_hand(&mut self) -> Result<()> {
+        let dealer = self.dealer_index;
+        let dead = self
+            .pot
+            .checked_add(self.bets[0])
+            .and_then(|total| total.checked_add(self.bets[1]))
+            .and_then(|total| total.checked_sub(self.total_contributed[0]))
+            .and_then(|total| total.checked_sub(self.total_contributed[1]))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let mut stacks = self.stacks;
+        for (seat, stack) in stacks.iter_mut().enumerate() {
+            let odd_chip = if seat == 1 - dealer as usize { dead % 2 } else { 0 };
+            *stack = stack
+                .checked_add(self.total_contributed[seat])
+                .and_then(|stack| stack.checked_add(dead / 2 + odd_chip))
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        self.stacks = stacks;
+        self.total_contributed = [0; MAX_PLAYERS];
+        self.reset_for_next_hand();
+        self.dealer_index = dealer;
+        self.current_turn_index = dealer;
+        Ok(())
+    }
+
+    /// Derives `is_active` from the seats and the phase alone: a table is active while both
+    /// seats are taken, or while a hand is under way. Instructions that change the seating call
+    /// this once they are done, rather than setting the flag themselves.
+    pub fn recompute_is_active(&mut self) {
+        let seats_full = !self.players.contains(&Pubkey::default());
+        let mid_hand = !matches!(self.game_phase, GamePhase::Idle | GamePhase::HandOver);
+        self.is_active = seats_full || mid_hand;
+    }
+
+    /// Moves the button past empty seats to the next seated player, so a dealer who has left
+    /// never leaves the table without a valid dealer. Does nothing if the dealer is still seated
+    /// or the table is empty.
+    pub fn seat_dealer(&mut self) {
+        let dealer = self.dealer_index as usize;
+        if let Some(seat) = (1..=MAX_PLAYERS)
+            .map(|offset| (dealer + offset) % MAX_PLAYERS)
+            .find(|&seat| self.players[seat] != Pubkey::default())
+        {
+            if self.players[dealer] == Pubkey::default() {
+                self.dealer_index = seat as u8;
+                self.current_turn_index = self.dealer_index;
+            }
+        }
+    }
+
+    /// Removes `player` from the waitlist, moving everyone queued behind them up one place.
+    pub fn remove_from_waitlist(&mut self, player: Pubkey) -> Result<()> {
+        let slot = self
+            .waitlist
+            .iter()
+            .position(|&p| p == player)
+            .ok_or(ErrorCode::PlayerNotInGame)?;
+
+        // Keep the queue packed at the front so its order is preserved.
+        self.waitlist[slot..].rotate_left(1);
+        self.waitlist[MAX_WAITLIST - 1] = Pubkey::default();
+        Ok(())
+    }
+
+    /// Under the "winner stays" format, vacates the seat of a player who has busted so the
+    /// next challenger can join, while the winner keeps their seat and stack. A player with
+    /// auto-rebuy switched on keeps their seat until the `auto_rebuy` crank has run.
+    /// Returns the index of the opened seat, if any. Must be called after the pot is awarded.
+    pub fn open_busted_seat(&mut self) -> Option<usize> {
+        let seat = (0..MAX_PLAYERS).find(|&i| {
+            self.players[i] != Pubkey::default()
+                && self.stacks[i] == 0
+                && self.auto_rebuy_min[i].is_none()
+        })?;
+        self.players[seat] = Pubkey::default();
+        self.slow_action_count[seat] = 0;
+        self.referrers[seat] = Pubkey::default();
+        self.encryption_keys[seat] = [0; 32];
+        self.sitting_out[seat] = false;
+        self.missed_blinds[seat] = 0;
+        self.rebuy_count[seat] = 0;
+        self.auto_rebuy_min[seat] = None;
+        self.auto_rebuy_accounts[seat] = Pubkey::default();
+        self.hands_played[seat] = 0;
+        self.game_phase = GamePhase::Idle;
+        self.recompute_is_active();
+        self.seat_dealer();
+        Some(seat)
+    }
+
+    /// Returns the time charge due from a seat at `now`, and records the periods it covers.
+    /// Charges are taken in advance: a player is charged one period when first dealt in, and
+    /// then once for every further period that has started. The charge never exceeds the stack.
+    /// Nothing is charged while no period is configured.
+    pub fn take_time_charge(&mut self, seat: usize, now: i64, charge: u64, period: i64) -> Result<u64> {
+        if period <= 0 {
+            return Ok(0);
+        }
+        let charged_at = self.time_charged_at[seat];
+        let periods = if charged_at == 0 {
+            self.time_charged_at[seat] = now;
+            1
+        } else {
+            let periods = (now - charged_at) / period;
+            self.time_charged_at[seat] = charged_at + periods * period;
+            periods as u64
+        };
+        let amount = periods
+            .checked_mul(charge)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .min(self.stacks[seat]);
+        self.stacks[seat] -= amount;
+        Ok(amount)
+    }
+
+    /// Returns the uncalled part of the larger bet on the current street to its owner: whatever
+    /// the other player did not match, because they folded or were all-in for less. Those chips
+    /// were never contested, so they leave the pot and the player's contribution for the hand.
+    /// A player who gets chips back is no longer all-in unless they are still at `hand_cap`.
+    /// Returns the amount returned.
+    pub fn return_uncalled_bet(&mut self, hand_cap: u64) -> Result<u64> {
+        let (high, low) = if self.bets[0] >= self.bets[1] { (0, 1) } else { (1, 0) };
+        let uncalled = self.bets[high] - self.bets[low];
+        if uncalled > 0 {
+            self.bets[high] = self.bets[low];
+            self.stacks[high] = self.stacks[high]
+                .checked_add(uncalled)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            self.total_contributed[high] = self.total_contributed[high]
+                .checked_sub(uncalled)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            self.is_all_in[high] = hand_cap > 0 && self.total_contributed[high] >= hand_cap;
+        }
+        Ok(uncalled)
+    }
+
+    /// Rebuilds `side_pots` from each seat's contribution to the collected pot. Every all-in
+    /// caps a pot at their contribution: the pot takes what each seat put in between the
+    /// previous cap and this one, and only the seats that reached the cap can win it. Dead
+    /// money (antes and missed blinds) is not recorded per seat and goes to the main pot.
+    /// Returns `ArithmeticOverflow`, leaving `side_pots` as it was, if a total overflows.
+    pub fn build_side_pots(&mut self) -> Result<()> {
+        let mut levels: Vec<u64> = (0..MAX_PLAYERS)
+            .filter(|&seat| self.is_all_in[seat])
+            .map(|seat| self.total_contributed[seat])
+            .collect();
+        levels.push(self.total_contributed.iter().copied().max().unwrap_or(0));
+        levels.sort_unstable();
+        levels.dedup();
+
+        let mut side_pots = Vec::with_capacity(MAX_SIDE_POTS);
+        let mut previous_level = 0;
+        for level in levels {
+            let amount = self
+                .total_contributed
+                .iter()
+                .map(|&contributed| contributed.min(level) - contributed.min(previous_level))
+                .try_fold(0u64, |total, share| total.checked_add(share))
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            let eligible = std::array::from_fn(|seat| {
+                self.players[seat] != Pubkey::default() && self.total_contributed[seat] >= level
+            });
+            if amount > 0 {
+                side_pots.push(SidePot { amount, eligible });
+            }
+            previous_level = level;
+        }
+
+        let contributed = self
+            .total_contributed
+            .iter()
+            .try_fold(0u64, |total, &amount| total.checked_add(amount))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let dead = self
+            .pot
+            .checked_add(self.bets[0])
+            .and_then(|total| total.checked_add(self.bets[1]))
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .saturating_sub(contributed);
+        match side_pots.first_mut() {
+            Some(main_pot) => {
+                main_pot.amount = main_pot
+                    .amount
+                    .checked_add(dead)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?
+            }
+            None if dead > 0 => side_pots.push(SidePot {
+                amount: dead,
+                eligible: self.players.map(|player| player != Pubkey::default()),
+            }),
+            None => {}
+        }
+        self.side_pots = side_pots;
+        Ok(())
+    }
+
+    /// Awards `amount` from a pot to the showdown winners in `winner_mask` who are eligible for
+    /// it, split evenly with any odd chips going one each to the winners closest to the left of
+    /// the button. A pot none of them reached holds only the chips of the seat that did, and
+    /// goes back to it; the showdown does not rank the other hands, so such a pot must have a
+    /// single eligible seat.
+    pub fn award_pot(&mut self, amount: u64, eligible: [bool; MAX_PLAYERS], winner_mask: u8) -> Result<()> {
+        let eligible_mask = (0..MAX_PLAYERS)
+            .filter(|&seat| eligible[seat])
+            .fold(0u8, |mask, seat| mask | (1 << seat));
+        let mut winners = winner_mask & eligible_mask;
+        if winners == 0 {
+            require!(eligible_mask.count_ones() == 1, ErrorCode::InvalidAction);
+            winners = eligible_mask;
+        }
+
+        let winner_count = winners.count_ones() as u64;
+        let share = amount / winner_count;
+        let mut odd_chips = amount % winner_count;
+        for offset in 1..=MAX_PLAYERS {
+            let seat = (self.dealer_index as usize + offset) % MAX_PLAYERS;
+            if winners & (1 << seat) != 0 {
+                let odd_chip = odd_chips.min(1);
+                self.stacks[seat] = self.stacks[seat]
+                    .checked_add(share + odd_chip)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+                odd_chips -= odd_chip;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the hand just decided to the `PlayerStats` of each seat whose account was passed.
+    /// `awarded` is what each seat won from the pot after rake, which is shared out in
+    /// proportion to what each seat put in. Must be called before the hand's contributions
+    /// are reset.
+    pub fn record_hand_stats(
+        &self,
+        seat_stats: [Option<&mut PlayerStats>; MAX_PLAYERS],
+        awarded: [u64; MAX_PLAYERS],
+        rake: u64,
+    ) -> Result<()> {
+        let total_contributed: u128 = self.total_contributed.iter().map(|&amount| amount as u128).sum();
+        for (seat, stats) in seat_stats.into_iter().enumerate() {
+            let Some(stats) = stats else {
+                continue;
+            };
+            let rake_share = if total_contributed > 0 {
+                (rake as u128 * self.total_contributed[seat] as u128 / total_contributed) as u64
+            } else {
+                0
+            };
+            stats.record_hand(awarded[seat], self.total_contributed[seat], rake_share)?;
+        }
+        Ok(())
+    }
+
+    /// Moves `amount` chips from a player's stack into their current bet and updates the
+    /// per-hand contribution tracker. The player is flagged all-in once their stack is empty
+    /// or, on capped tables, once their contribution reaches `hand_cap`.
+    /// Callers are responsible for validating that `amount` does not exceed the stack; if it
+    /// does, or a total overflows, nothing is moved and `ArithmeticOverflow` is returned.
+    pub fn commit_chips(&mut self, seat: usize, amount: u64, hand_cap: u64) -> Result<()> {
+        let stack = self.stacks[seat]
+            .checked_sub(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let bet = self.bets[seat]
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let total_contributed = self.total_contributed[seat]
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.stacks[seat] = stack;
+        self.bets[seat] = bet;
+        self.total_contributed[seat] = total_contributed;
+        if stack == 0 || (hand_cap > 0 && total_contributed >= hand_cap) {
+            self.is_all_in[seat] = true;
+        }
+        Ok(())
+    }
+
+    /// Adds a rebuy of `amount` chips to the player at `seat`: to their stack, their session
+    /// buy-in and their rebuy count. If a total overflows, nothing changes and
+    /// `ArithmeticOverflow` is returned.
+    pub fn record_rebuy(&mut self, seat: usize, amount: u64) -> Result<()> {
+        let stack = self.stacks[seat]
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let session_buy_in = self.session_buy_in[seat]
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let rebuy_count = self.rebuy_count[seat]
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.stacks[seat] = stack;
+        self.session_buy_in[seat] = session_buy_in;
+        self.rebuy_count[seat] = rebuy_count;
+        Ok(())
+    }
+
+    /// Moves `amount` chips from a player's stack straight into the pot as dead money (antes and
+    /// missed blinds), which counts towards neither their bet nor their contribution. As with
+    /// `commit_chips`, if the stack is too short or the pot overflows, nothing is moved and
+    /// `ArithmeticOverflow` is returned.
+    pub fn post_dead_money(&mut self, seat: usize, amount: u64) -> Result<()> {
+        let stack = self.stacks[seat]
+            .checked_sub(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let pot = self.pot.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.stacks[seat] = stack;
+        self.pot = pot;
+        Ok(())
+    }
 }
 
 /// A temporary account holding encrypted, confidential data for the current hand.
-/// This account is created at the start of a hand and closed at the end to reclaim rent.
+/// This account is created at the start of a hand and closed at the end to reclaim rent,
+/// or kept for `TableConfig::hand_retention_seconds` and then closed by `reap_hand`.
 /// PDA Seeds: `[b"hand", game_state.key().as_ref()]`
 #[account]
 #[derive(InitSpace)]
 pub struct HandState {
-    /// Encrypted hole cards for each player. Each blob contains a serialized `SharedEncryptedStruct<2>`
-    /// from Arcium, which includes the public key, nonce, and two ciphertexts. Size is padded to 64 bytes.
-    pub encrypted_hole_cards: [[u8; 64]; MAX_PLAYERS],
-    /// The remaining 48 cards of the deck plus metadata, encrypted as a single blob for use by the Arcium MXE.
+    /// Encrypted hole cards for each player, each a serialized `SharedEncryptedStruct<2>` from
+    /// Arcium of exactly `HOLE_CARDS_LEN` bytes: the x25519 public key the cards are encrypted
+    /// to, the nonce (little-endian) at `HOLE_CARDS_NONCE_OFFSET`, then the two ciphertexts.
+    pub encrypted_hole_cards: [[u8; 112]; MAX_PLAYERS],
+    /// Five-card draw only: each player's five cards as a serialized `SharedEncryptedStruct<5>`
+    /// of exactly `DRAW_HAND_LEN` bytes (public key, nonce and five ciphertexts), padded to 256
+    /// bytes. The remaining deck is
+    /// stored in the `encrypted_deck_part*` fields.
+    pub encrypted_draw_hands: [[u8; 256]; MAX_PLAYERS],
+    /// The board deck left after the deal (48 cards heads-up) plus metadata, encrypted as a single blob for use by the Arcium MXE.
     /// This stores a serialized `MXEEncryptedStruct<49>`, which is 16 bytes for the nonce
     /// and 49 * 32 = 1568 bytes for the ciphertexts, totaling 1584 bytes.
     /// Split into smaller chunks to reduce stack usage.
@@ -129,10 +1105,278 @@ pub struct HandState {
     pub encrypted_deck_part2: [u8; 512],
     pub encrypted_deck_part3: [u8; 512],
     pub encrypted_deck_part4: [u8; 48],
-    /// The computation offset used to queue the shuffle instruction. This provides a
-    /// verifiable on-chain link for auditing the integrity of the shuffle, as the original
-    /// transaction signature is not available inside an instruction.
+    /// The offset of the latest computation queued for this hand: the shuffle, then each reveal,
+    /// draw, insurance quote, showdown or shown hand. Callbacks that write to the table must come
+    /// from this computation, so a late result from an aborted or earlier hand is rejected
+    /// instead of corrupting the live one. A `preview_showdown` dry run does not replace it.
     pub computation_offset: u64,
+    /// The dealer who created this account and paid its rent. The button can move before the
+    /// account is closed, so settlement refunds the rent to this snapshot rather than the
+    /// current dealer.
+    pub dealer: Pubkey,
+    /// A hash of the encrypted deck stored by the latest step (shuffle, reveal or draw), so a
+    /// reveal can only ever be fed the deck the previous step produced. Zero until dealt.
+    pub deck_commitment: [u8; 32],
+    /// At tables that retain finished hands, the Unix timestamp after which `reap_hand` may
+    /// close this account. 0 while the hand is in play.
+    pub retained_until: i64,
+    /// The number of times the current street's reveal has been requested. Reset once a
+    /// street is revealed.
+    pub reveal_attempts: u8,
+    /// Set when the latest reveal computation reported a failure, allowing it to be requested
+    /// again.
+    pub reveal_failed: bool,
+    /// Set when `CardRevealStarted` was emitted for the reveal in flight, so its callback emits
+    /// the matching `CardRevealCompleted` (see `TableConfig::reveal_events`).
+    pub reveal_announced: bool,
+}
+
+impl HandState {
+    /// The length of a player's encrypted hole cards: a 32-byte public key, a 16-byte nonce and
+    /// two 32-byte ciphertexts.
+    pub const HOLE_CARDS_LEN: u32 = 32 + 16 + 2 * 32;
+    /// The offset of the nonce within a player's encrypted hole cards, after the public key.
+    pub const HOLE_CARDS_NONCE_OFFSET: u32 = 32;
+    /// The length of a player's encrypted five-card draw hand: a 32-byte public key, a 16-byte
+    /// nonce and five 32-byte ciphertexts. It is padded to 256 bytes in `encrypted_draw_hands`.
+    pub const DRAW_HAND_LEN: u32 = 32 + 16 + 5 * 32;
+    /// The offset of `encrypted_draw_hands` in the account data: after the discriminator and
+    /// the hole cards.
+    pub const DRAW_HANDS_OFFSET: u32 = 8 + MAX_PLAYERS as u32 * Self::HOLE_CARDS_LEN;
+    /// The offset of `encrypted_deck_part1` in the account data: after the discriminator, the
+    /// hole cards and the draw hands.
+    pub const DECK_OFFSET: u32 = Self::DRAW_HANDS_OFFSET + (MAX_PLAYERS * 256) as u32;
+    /// The length of the encrypted deck's nonce, stored at the start of the deck.
+    pub const DECK_NONCE_LEN: u32 = 16;
+    /// The length of the encrypted deck's ciphertexts, which follow the nonce.
+    pub const DECK_CIPHERTEXTS_LEN: u32 = 49 * 32;
+
+    /// Hashes the encrypted deck as currently stored.
+    pub fn hash_deck(&self) -> [u8; 32] {
+        hashv(&[
+            &self.encrypted_deck_part1,
+            &self.encrypted_deck_part2,
+            &self.encrypted_deck_part3,
+            &self.encrypted_deck_part4,
+        ])
+        .to_bytes()
+    }
+
+    /// Checks that a deck has been committed to and that the stored deck is still the one the
+    /// last deal or reveal committed to, so a reveal is never fed a swapped deck.
+    pub fn require_committed_deck(&self) -> Result<()> {
+        require!(
+            self.deck_commitment != [0; 32] && self.hash_deck() == self.deck_commitment,
+            ErrorCode::DeckCommitmentMismatch
+        );
+        Ok(())
+    }
+
+    /// Returns the nonce the encrypted deck was encrypted with.
+    pub fn deck_nonce(&self) -> u128 {
+        let mut nonce = [0u8; 16];
+        nonce.copy_from_slice(&self.encrypted_deck_part1[..Self::DECK_NONCE_LEN as usize]);
+        u128::from_le_bytes(nonce)
+    }
+
+    /// Returns the nonce a player's hole cards were encrypted with, which they need to decrypt
+    /// them.
+    pub fn hole_cards_nonce(&self, seat: usize) -> u128 {
+        let start = Self::HOLE_CARDS_NONCE_OFFSET as usize;
+        let mut nonce = [0u8; 16];
+        nonce.copy_from_slice(&self.encrypted_hole_cards[seat][start..start + 16]);
+        u128::from_le_bytes(nonce)
+    }
+
+    /// Checks whether the current street may be requested again. Returns `true` once a failing
+    /// reveal has used up its `max_retries` retries, when the hand should be voided instead. A
+    /// reveal that is still in flight may not be requested again.
+    pub fn reveal_retries_exhausted(&self, max_retries: u8) -> Result<bool> {
+        if self.reveal_attempts == 0 {
+            return Ok(false);
+        }
+        require!(self.reveal_failed, ErrorCode::RevealInFlight);
+        Ok(self.reveal_attempts > max_retries)
+    }
+
+    /// Counts another request for the current street's reveal.
+    pub fn record_reveal_request(&mut self) {
+        self.reveal_attempts += 1;
+        self.reveal_failed = false;
+    }
+
+    /// Clears the reveal bookkeeping once a street is revealed or the hand moves on.
+    pub fn reset_reveal_attempts(&mut self) {
+        self.reveal_attempts = 0;
+        self.reveal_failed = false;
+    }
+}
+
+/// Per-player loyalty and lifetime statistics, shared across all tables.
+/// PDA Seeds: `[b"player_stats", player.key().as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct PlayerStats {
+    /// The player these statistics belong to.
+    pub player: Pubkey,
+    /// Rake-back accrued from settled hands and not yet claimed.
+    pub rakeback_owed: u64,
+    /// The total rake-back the player has claimed so far.
+    pub rakeback_claimed: u64,
+    /// The hands the player finished, by fold or showdown, while this account was passed in.
+    pub hands_played: u64,
+    /// The hands in which the player won all or part of the pot.
+    pub hands_won: u64,
+    /// What the player has won from pots, after rake, minus what they put into them.
+    pub net_profit: i64,
+    /// The player's share of the rake, in proportion to what they put into each raked pot.
+    pub total_rake_contributed: u64,
+}
+
+impl PlayerStats {
+    /// Adds one finished hand in which the player was `awarded` chips from the pot after
+    /// putting `contributed` into it, and `rake_contributed` was their share of its rake.
+    pub fn record_hand(&mut self, awarded: u64, contributed: u64, rake_contributed: u64) -> Result<()> {
+        let profit = i64::try_from(awarded)
+            .ok()
+            .zip(i64::try_from(contributed).ok())
+            .and_then(|(awarded, contributed)| awarded.checked_sub(contributed))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.hands_played = self.hands_played.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        if awarded > 0 {
+            self.hands_won = self.hands_won.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        self.net_profit = self.net_profit.checked_add(profit).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.total_rake_contributed = self
+            .total_rake_contributed
+            .checked_add(rake_contributed)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// A player's registered details, shared across all tables.
+/// PDA Seeds: `[b"player_profile", player.key().as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct PlayerProfile {
+    /// The player this profile belongs to.
+    pub player: Pubkey,
+    /// The player's x25519 public key, used to encrypt the cards dealt to them.
+    pub encryption_pubkey: [u8; 32],
+}
+
+/// A tournament payout structure: the share of the prize pool paid to each finishing place.
+/// Created by the admin and shared by any number of tournaments.
+/// PDA Seeds: `[b"payout_structure", structure_id.to_le_bytes().as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct PayoutStructure {
+    pub structure_id: u64,
+    /// The number of places paid: 1 for winner-take-all, 2 for top-2, and so on.
+    pub paid_places: u8,
+    /// The share of the prize pool, in basis points, paid to each place, first place first.
+    /// The first `paid_places` entries sum to `MAX_BPS`; the rest are zero.
+    pub payouts_bps: [u16; MAX_PAID_PLACES],
+}
+
+/// A tournament's prize pool, held in its own escrow and paid out once by `settle_tournament`
+/// according to its `PayoutStructure`.
+/// PDA Seeds: `[b"tournament", tournament_id.to_le_bytes().as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct Tournament {
+    pub tournament_id: u64,
+    /// The mint of the SPL Token the prize pool is paid in.
+    pub token_mint: Pubkey,
+    /// The `PayoutStructure` account the prize pool is split by.
+    pub payout_structure: Pubkey,
+    /// Whether the prize pool has been paid out.
+    pub settled: bool,
+}
+
+/// A fast-fold pool: tables with the same stakes whose players can fold and be reseated at another
+/// pool table at once with `fast_fold_and_rematch`. Matchmaking happens off-chain; the pool
+/// records which tables belong to it and who is sitting alone at one, waiting for an opponent.
+/// PDA Seeds: `[b"fast_fold_pool", pool_id.to_le_bytes().as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct FastFoldPool {
+    pub pool_id: u64,
+    /// The mint of the SPL Token every pool table plays for.
+    pub token_mint: Pubkey,
+    /// The blinds every pool table plays at.
+    pub small_blind: u64,
+    pub big_blind: u64,
+    /// The `GameState` keys of the pool's tables. Unused slots are `Pubkey::default()`.
+    pub tables: [Pubkey; MAX_POOL_TABLES],
+    /// For each of `tables`, the player sitting alone at it waiting for an opponent, or
+    /// `Pubkey::default()` if the table is empty or full.
+    pub waiting: [Pubkey; MAX_POOL_TABLES],
+}
+
+impl FastFoldPool {
+    /// Returns whether the table with this `GameState` key belongs to the pool.
+    pub fn contains_table(&self, table: &Pubkey) -> bool {
+        *table != Pubkey::default() && self.tables.contains(table)
+    }
+
+    /// Refreshes a pool table's entry in `waiting` from its seats. Does nothing for a table
+    /// outside the pool.
+    pub fn sync_waiting(&mut self, table: &Pubkey, game_state: &GameState) {
+        if let Some(index) = self.tables.iter().position(|t| t == table) {
+            let mut seated = game_state.players.iter().filter(|&&p| p != Pubkey::default());
+            self.waiting[index] = match (seated.next(), seated.next()) {
+                (Some(&player), None) => player,
+                _ => Pubkey::default(),
+            };
+        }
+    }
+}
+
+/// Risk-isolation totals for one token mint across all of its cash tables. `create_table` refuses
+/// a new table once `max_tables` are open for the mint.
+/// PDA Seeds: `[b"mint_exposure", token_mint.as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct MintExposure {
+    pub token_mint: Pubkey,
+    /// The most tables that may be open at once for this mint, set by the admin. 0 means no cap.
+    pub max_tables: u32,
+    /// The number of tables currently open for this mint.
+    pub active_tables: u32,
+    /// The total bought into this mint's tables (buy-ins and rebuys, less withdrawals on leaving).
+    /// Rake and fees paid out of the escrows are not deducted, so this is an upper bound on the
+    /// value the escrows hold.
+    pub escrowed: u64,
+}
+
+impl MintExposure {
+    /// Counts a newly created table, failing if the mint is already at its cap.
+    pub fn open_table(&mut self) -> Result<()> {
+        require!(
+            self.max_tables == 0 || self.active_tables < self.max_tables,
+            ErrorCode::MintTableCapReached
+        );
+        self.active_tables += 1;
+        Ok(())
+    }
+
+    /// Counts a closed table. Saturates, since tables created before the counter existed were
+    /// never counted.
+    pub fn close_table(&mut self) {
+        self.active_tables = self.active_tables.saturating_sub(1);
+    }
+
+    /// Records chips deposited into one of the mint's escrows.
+    pub fn deposit(&mut self, amount: u64) {
+        self.escrowed = self.escrowed.saturating_add(amount);
+    }
+
+    /// Records chips withdrawn from one of the mint's escrows.
+    pub fn withdraw(&mut self, amount: u64) {
+        self.escrowed = self.escrowed.saturating_sub(amount);
+    }
 }
 
 /// A simple signer account for PDA-based signing.
@@ -144,4 +1388,377 @@ pub struct SignerAccount {
     pub is_signer: bool,
     /// The bump seed used to derive this PDA.
     pub bump: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `GameState` as it would deserialize from a freshly allocated, zeroed account.
+    fn game_state() -> GameState {
+        GameState::deserialize(&mut &vec![0u8; GameState::INIT_SPACE][..]).unwrap()
+    }
+
+    fn board(phase: GamePhase, community_cards: [u8; 5]) -> GameState {
+        let mut game_state = game_state();
+        game_state.game_phase = phase;
+        game_state.community_cards = community_cards;
+        game_state
+    }
+
+    fn hand_state() -> HandState {
+        HandState::deserialize(&mut &vec![0u8; HandState::INIT_SPACE][..]).unwrap()
+    }
+
+    fn table_config(big_blind: u64) -> TableConfig {
+        let mut table_config =
+            TableConfig::deserialize(&mut &vec![0u8; TableConfig::INIT_SPACE][..]).unwrap();
+        table_config.small_blind = big_blind / 2;
+        table_config.big_blind = big_blind;
+        table_config
+    }
+
+    fn is_overflow(result: Result<u64>) -> bool {
+        result.unwrap_err() == ErrorCode::ArithmeticOverflow.into()
+    }
+
+    fn is_mismatch(result: Result<()>) -> bool {
+        result.unwrap_err() == ErrorCode::BoardPhaseMismatch.into()
+    }
+
+    #[test]
+    fn board_matching_the_phase_is_accepted() {
+        let cases = [
+            (GamePhase::PreFlop, [255; 5]),
+            (GamePhase::Flop, [255; 5]),
+            (GamePhase::Flop, [0, 1, 2, 255, 255]),
+            (GamePhase::Turn, [0, 1, 2, 255, 255]),
+            (GamePhase::Turn, [0, 1, 2, 3, 255]),
+            (GamePhase::River, [0, 1, 2, 3, 255]),
+            (GamePhase::River, [0, 1, 2, 3, 51]),
+            (GamePhase::Showdown, [0, 1, 2, 3, 51]),
+            (GamePhase::ReadyToSettle, [0, 1, 2, 3, 51]),
+        ];
+        for (phase, cards) in cases {
+            assert!(board(phase, cards).check_board(GameVariant::Holdem).is_ok());
+        }
+    }
+
+    #[test]
+    fn board_ahead_of_or_behind_the_phase_is_rejected() {
+        let cases = [
+            (GamePhase::PreFlop, [0, 1, 2, 255, 255]),
+            (GamePhase::Flop, [0, 1, 2, 3, 255]),
+            (GamePhase::Turn, [255; 5]),
+            (GamePhase::River, [0, 1, 2, 255, 255]),
+            (GamePhase::Showdown, [0, 1, 2, 3, 255]),
+            (GamePhase::HandOver, [0, 1, 2, 3, 4]),
+        ];
+        for (phase, cards) in cases {
+            assert!(is_mismatch(
+                board(phase, cards).check_board(GameVariant::Holdem)
+            ));
+        }
+    }
+
+    #[test]
+    fn malformed_board_is_rejected() {
+        // A gap before a dealt card, a duplicate, and a card outside the deck.
+        for cards in [
+            [0, 1, 255, 3, 255],
+            [0, 1, 1, 255, 255],
+            [0, 1, 52, 255, 255],
+        ] {
+            assert!(is_mismatch(
+                board(GamePhase::Flop, cards).check_board(GameVariant::Holdem)
+            ));
+        }
+    }
+
+    #[test]
+    fn five_card_draw_never_has_a_board() {
+        assert!(board(GamePhase::River, [255; 5])
+            .check_board(GameVariant::FiveCardDraw)
+            .is_ok());
+        assert!(is_mismatch(
+            board(GamePhase::River, [0, 1, 2, 3, 4]).check_board(GameVariant::FiveCardDraw)
+        ));
+    }
+
+    fn is_deck_mismatch(result: Result<()>) -> bool {
+        result.unwrap_err() == ErrorCode::DeckCommitmentMismatch.into()
+    }
+
+    #[test]
+    fn uncommitted_deck_is_rejected() {
+        assert!(is_deck_mismatch(hand_state().require_committed_deck()));
+    }
+
+    #[test]
+    fn deck_tampered_between_streets_is_rejected() {
+        // The shuffle stores a deck and commits to it.
+        let mut hand_state = hand_state();
+        hand_state.encrypted_deck_part1[..16].copy_from_slice(&7u128.to_le_bytes());
+        hand_state.encrypted_deck_part2[0] = 1;
+        hand_state.deck_commitment = hand_state.hash_deck();
+        assert!(hand_state.require_committed_deck().is_ok());
+
+        // The flop's reveal stores the updated deck and commits to it in turn.
+        hand_state.encrypted_deck_part3[5] = 2;
+        hand_state.deck_commitment = hand_state.hash_deck();
+        assert!(hand_state.require_committed_deck().is_ok());
+
+        // Any change to the deck before the turn is requested no longer matches.
+        for part in 0..4 {
+            let mut tampered = hand_state.clone();
+            match part {
+                0 => tampered.encrypted_deck_part1[100] ^= 1,
+                1 => tampered.encrypted_deck_part2[0] ^= 1,
+                2 => tampered.encrypted_deck_part3[511] ^= 1,
+                _ => tampered.encrypted_deck_part4[0] ^= 1,
+            }
+            assert!(is_deck_mismatch(tampered.require_committed_deck()));
+        }
+    }
+
+    #[test]
+    fn button_ante_overflowing_a_u64_is_rejected() {
+        let mut table_config = table_config(u64::MAX / 2);
+        table_config.button_ante_multiplier = 2;
+        assert_eq!(game_state().button_ante(&table_config).unwrap(), u64::MAX - 1);
+
+        table_config.button_ante_multiplier = 3;
+        assert!(is_overflow(game_state().button_ante(&table_config)));
+    }
+
+    #[test]
+    fn leave_penalty_overflowing_the_session_totals_is_rejected() {
+        let mut config = Config::deserialize(&mut &vec![0u8; Config::INIT_SPACE][..]).unwrap();
+        config.leave_penalty_bps = 1_000;
+        config.leave_penalty_hands = 10;
+        let mut game_state = game_state();
+        game_state.players = [Pubkey::new_unique(), Pubkey::new_unique()];
+        game_state.stacks[0] = 2_000;
+        game_state.session_buy_in[0] = 1_000;
+        assert_eq!(game_state.leave_penalty(0, &config).unwrap(), 200);
+
+        game_state.session_payout[0] = u64::MAX;
+        assert!(is_overflow(game_state.leave_penalty(0, &config)));
+    }
+
+    #[test]
+    fn straddle_overflowing_a_u64_is_rejected() {
+        let table_config = table_config(u64::MAX / 4 + 1);
+        let mut game_state = game_state();
+        game_state.players = [Pubkey::new_unique(), Pubkey::new_unique()];
+        game_state.straddler = game_state.players[0];
+        game_state.straddle_levels = 1;
+        assert_eq!(game_state.button_blind(&table_config).unwrap(), u64::MAX / 2 + 1);
+
+        // Shifting would silently drop the high bit and leave a straddle of 0.
+        game_state.straddle_levels = 2;
+        assert!(is_overflow(game_state.straddle_size(&table_config)));
+        assert!(is_overflow(game_state.button_blind(&table_config)));
+    }
+
+    #[test]
+    fn void_hand_returns_every_chip() {
+        let mut game_state = game_state();
+        game_state.dealer_index = 0;
+        game_state.stacks = [900, 800];
+        game_state.pot = 203;
+        game_state.bets = [100, 0];
+        game_state.total_contributed = [100, 200];
+        assert!(game_state.void_hand().is_ok());
+        assert_eq!(game_state.stacks, [1_001, 1_002]);
+        assert_eq!(game_state.pot, 0);
+    }
+
+    #[test]
+    fn void_hand_with_inconsistent_chips_is_rejected() {
+        // More recorded as contributed than is in the pot and bets.
+        let mut game_state = game_state();
+        game_state.stacks = [900, 800];
+        game_state.pot = 100;
+        game_state.total_contributed = [100, 200];
+        assert!(game_state.void_hand().unwrap_err() == ErrorCode::ArithmeticOverflow.into());
+        assert_eq!(game_state.stacks, [900, 800]);
+
+        // A refund that does not fit in a stack.
+        game_state.stacks = [u64::MAX, 800];
+        game_state.pot = 300;
+        assert!(game_state.void_hand().unwrap_err() == ErrorCode::ArithmeticOverflow.into());
+        assert_eq!(game_state.stacks, [u64::MAX, 800]);
+    }
+
+    #[test]
+    fn side_pots_overflowing_a_u64_are_rejected() {
+        let mut game_state = game_state();
+        game_state.players = [Pubkey::new_unique(), Pubkey::new_unique()];
+        game_state.total_contributed = [u64::MAX, 1];
+        game_state.is_all_in = [false, true];
+        assert!(game_state.build_side_pots().unwrap_err() == ErrorCode::ArithmeticOverflow.into());
+        assert!(game_state.side_pots.is_empty());
+    }
+
+    #[test]
+    fn dead_money_overflowing_the_pot_is_rejected() {
+        let mut game_state = game_state();
+        game_state.stacks = [500, 500];
+        assert!(game_state.post_dead_money(0, 200).is_ok());
+        assert_eq!((game_state.stacks[0], game_state.pot), (300, 200));
+
+        game_state.pot = u64::MAX;
+        assert!(game_state.post_dead_money(1, 1).unwrap_err() == ErrorCode::ArithmeticOverflow.into());
+        assert_eq!((game_state.stacks[1], game_state.pot), (500, u64::MAX));
+    }
+
+    #[test]
+    fn rebuy_overflowing_the_stack_is_rejected() {
+        let mut game_state = game_state();
+        game_state.stacks = [1_000, 0];
+        assert!(game_state.record_rebuy(0, 500).is_ok());
+        assert_eq!(
+            (game_state.stacks[0], game_state.session_buy_in[0], game_state.rebuy_count[0]),
+            (1_500, 500, 1)
+        );
+
+        assert!(game_state.record_rebuy(0, u64::MAX).unwrap_err() == ErrorCode::ArithmeticOverflow.into());
+        assert_eq!(
+            (game_state.stacks[0], game_state.session_buy_in[0], game_state.rebuy_count[0]),
+            (1_500, 500, 1)
+        );
+    }
+    fn clock(slot: u64, unix_timestamp: i64) -> Clock {
+        Clock {
+            slot,
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    fn expired(game_state: &GameState, seat: usize, clock: &Clock, table_config: &TableConfig) -> bool {
+        let (elapsed, turn_time) = game_state.turn_progress(seat, clock, table_config);
+        elapsed > turn_time
+    }
+
+    #[test]
+    fn slot_timer_fires_on_slot_progress_alone() {
+        let mut game_state = game_state();
+        game_state.last_action_slot = 1_000;
+        game_state.last_action_timestamp = 5_000;
+        let mut table_config = table_config(100);
+        table_config.turn_time_slots = 50;
+
+        assert_eq!(game_state.turn_progress(0, &clock(1_050, 5_000), &table_config), (50, 50));
+        assert!(!expired(&game_state, 0, &clock(1_050, 5_000), &table_config));
+        // The timestamp neither holds back nor brings forward a slot-timed crank.
+        assert!(expired(&game_state, 0, &clock(1_051, 5_000), &table_config));
+        assert!(!expired(&game_state, 0, &clock(1_050, 1_000_000), &table_config));
+    }
+
+    #[test]
+    fn timestamp_timer_ignores_slots() {
+        let mut game_state = game_state();
+        game_state.last_action_slot = 1_000;
+        game_state.last_action_timestamp = 5_000;
+        let table_config = table_config(100);
+
+        assert!(!expired(&game_state, 0, &clock(1_000_000, 5_030), &table_config));
+        assert!(expired(&game_state, 0, &clock(1_000, 5_031), &table_config));
+    }
+
+    #[test]
+    fn slot_timer_is_shortened_for_slow_players_and_extended_by_the_grace_period() {
+        let mut game_state = game_state();
+        game_state.last_action_slot = 1_000;
+        game_state.slow_action_count = [2, 0];
+        let mut table_config = table_config(100);
+        table_config.turn_time_slots = 60;
+
+        // Two slow actions take the turn from 30 to 20 seconds, so from 60 to 40 slots.
+        assert_eq!(game_state.turn_progress(0, &clock(1_041, 0), &table_config), (41, 40));
+        assert_eq!(game_state.turn_progress(1, &clock(1_041, 0), &table_config), (41, 60));
+
+        // A four-second grace period is ten slots.
+        table_config.turn_grace_seconds = 4;
+        assert_eq!(game_state.turn_progress(0, &clock(1_041, 0), &table_config), (31, 40));
+        assert!(expired(&game_state, 0, &clock(1_051, 0), &table_config));
+    }
+    #[test]
+    fn misdeal_ends_the_hand_and_keeps_the_button() {
+        let mut game_state = game_state();
+        game_state.stacks = [1_000, 1_000];
+        game_state.dealer_index = 1;
+        game_state.current_turn_index = 0;
+        game_state.game_phase = GamePhase::Dealing;
+        game_state.hand_in_progress = true;
+
+        game_state.record_misdeal();
+
+        assert!(game_state.game_phase == GamePhase::HandOver);
+        assert!(game_state.misdeal && !game_state.hand_in_progress);
+        assert_eq!((game_state.dealer_index, game_state.current_turn_index), (1, 1));
+        assert_eq!(game_state.stacks, [1_000, 1_000]);
+    }
+    #[test]
+    fn failing_reveal_is_retried_until_the_limit() {
+        // The flop is requested and its reveal fails.
+        let mut hand_state = hand_state();
+        assert!(!hand_state.reveal_retries_exhausted(1).unwrap());
+        hand_state.record_reveal_request();
+        hand_state.reveal_failed = true;
+
+        // Its one allowed retry fails as well.
+        assert!(!hand_state.reveal_retries_exhausted(1).unwrap());
+        hand_state.record_reveal_request();
+        assert_eq!(hand_state.reveal_attempts, 2);
+        hand_state.reveal_failed = true;
+
+        // The next request voids the hand instead.
+        assert!(hand_state.reveal_retries_exhausted(1).unwrap());
+        hand_state.reset_reveal_attempts();
+        assert!(!hand_state.reveal_retries_exhausted(1).unwrap());
+    }
+
+    #[test]
+    fn failing_reveal_without_retries_voids_the_hand() {
+        let mut hand_state = hand_state();
+        hand_state.record_reveal_request();
+        hand_state.reveal_failed = true;
+        assert!(hand_state.reveal_retries_exhausted(0).unwrap());
+    }
+
+    #[test]
+    fn reveal_in_flight_cannot_be_requested_again() {
+        let mut hand_state = hand_state();
+        hand_state.record_reveal_request();
+        assert!(
+            hand_state.reveal_retries_exhausted(3).unwrap_err() == ErrorCode::RevealInFlight.into()
+        );
+    }
+    #[test]
+    fn each_street_is_placed_in_its_board_slots() {
+        let mut game_state = board(GamePhase::Flop, [255; 5]);
+        assert_eq!(game_state.place_street(&[10, 20, 30]).unwrap(), 3);
+        assert_eq!(game_state.community_cards, [10, 20, 30, 255, 255]);
+
+        game_state.game_phase = GamePhase::Turn;
+        assert_eq!(game_state.place_street(&[40, 255, 255]).unwrap(), 1);
+        assert_eq!(game_state.community_cards, [10, 20, 30, 40, 255]);
+
+        game_state.game_phase = GamePhase::River;
+        assert_eq!(game_state.place_street(&[50, 255, 255]).unwrap(), 1);
+        assert_eq!(game_state.community_cards, [10, 20, 30, 40, 50]);
+        assert!(game_state.check_board(GameVariant::Holdem).is_ok());
+    }
+
+    #[test]
+    fn street_is_not_placed_outside_a_reveal_phase() {
+        let mut game_state = board(GamePhase::PreFlop, [255; 5]);
+        assert!(
+            game_state.place_street(&[10, 20, 30]).unwrap_err() == ErrorCode::InvalidRevealPhase.into()
+        );
+        assert_eq!(game_state.community_cards, [255; 5]);
+    }
 }
\ No newline at end of file
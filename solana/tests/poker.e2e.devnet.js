@@ -17,14 +17,17 @@ const {
   getClockAccAddress,
   getClusterAccAddress,
   getStakingPoolAccAddress,
+  x25519,
 } = require('@arcium-hq/client');
 const crypto = require('crypto');
 const {
   TOKEN_PROGRAM_ID,
   getAssociatedTokenAddressSync,
   getOrCreateAssociatedTokenAccount,
+  getAccount,
   createMint,
   mintTo,
+  approve,
 } = require('@solana/spl-token');
 
 // Helpers
@@ -116,6 +119,7 @@ describe('Poker E2E (devnet)', () => {
   const smallBlind = 1000n;
   const bigBlind = 2000n;
   const buyIn = 100_000n; // 100k units of mint (9 decimals default)
+  const tableOptions = { handCap: new anchor.BN(0), winnerStays: false, creatorSeat: 0, noLimp: false, variant: { holdem: {} }, turnTimeSlots: new anchor.BN(0), sitoutPostsBlinds: false, maxRebuys: 0, buttonStraddle: false, turnGraceSeconds: new anchor.BN(0), minRaiseMultiple: 1, handRetentionSeconds: new anchor.BN(0), runoutDelaySeconds: new anchor.BN(0), buttonAnteMultiplier: 0, maxStraddles: 0, ante: new anchor.BN(0), timeBankSeconds: new anchor.BN(0), revealEvents: false };
 
   it('runs encrypted deal + reveals (devnet)', async () => {
     // Derive PDAs for table
@@ -152,7 +156,10 @@ describe('Poker E2E (devnet)', () => {
           new anchor.BN(tableId.toString()),
           new anchor.BN(smallBlind.toString()),
           new anchor.BN(bigBlind.toString()),
+          new anchor.BN((buyIn / 2n).toString()),
+          new anchor.BN((2n * buyIn).toString()),
           new anchor.BN(buyIn.toString()),
+          tableOptions,
         )
         .accounts({
           tableConfig: tableConfigPda,
@@ -161,6 +168,8 @@ describe('Poker E2E (devnet)', () => {
           creator: wallet.publicKey,
           tokenMint: mint,
           creatorTokenAccount: creatorAta,
+          config: pda('config', [], programId),
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -170,21 +179,25 @@ describe('Poker E2E (devnet)', () => {
 
     // Join second player if needed
     const gameNow = await program.account.gameState.fetch(gamePda);
+    let joiner;
     if (gameNow.players[1].equals(PublicKey.default)) {
-      const joiner = Keypair.generate();
+      joiner = Keypair.generate();
       await ensureAirdrop(connection, joiner.publicKey, 1);
       // Create joiner ATA and fund buy-in using helper
       const joinerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, joiner.publicKey)).address;
       await mintTo(connection, wallet.payer, mint, joinerAta, wallet.payer, Number(buyIn));
 
       await program.methods
-        .joinTable()
+        .joinTable(new anchor.BN(buyIn.toString()))
         .accounts({
           gameState: gamePda,
           tableConfig: tableConfigPda,
           escrowAccount: escrowPda,
           joiner: joiner.publicKey,
           joinerTokenAccount: joinerAta,
+          config: pda('config', [], programId),
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([joiner])
@@ -234,14 +247,22 @@ describe('Poker E2E (devnet)', () => {
 
     // Only setup if hand_state doesn't exist
     if (!handStateExists) {
+      // The button is drawn when the second player joins, so deal as whoever holds it.
+      const gameBeforeDeal = await program.account.gameState.fetch(gamePda);
+      const dealerKey = gameBeforeDeal.players[gameBeforeDeal.dealerIndex];
+      const dealerSigners = dealerKey.equals(wallet.publicKey) ? [] : [joiner];
       await program.methods
         .dealNewHandSetup(dealOffsetBN)
         .accounts({
-          payer: wallet.publicKey,
+          payer: dealerKey,
           gameState: gamePda,
           handState: handPda,
+          tableConfig: tableConfigPda,
+          mxeAccount,
+          config: pda('config', [], programId),
           systemProgram: SystemProgram.programId,
         })
+        .signers(dealerSigners)
         .rpc();
     }
 
@@ -286,6 +307,7 @@ describe('Poker E2E (devnet)', () => {
       payer: wallet.publicKey,
       gameState: gamePda,
       handState: handPda,
+      tableConfig: tableConfigPda,
       signPdaAccount: signPda,
       mxeAccount,
       mempoolAccount,
@@ -341,6 +363,7 @@ describe('Poker E2E (devnet)', () => {
           payer: wallet.publicKey,
           gameState: gamePda,
           handState: handPda,
+          tableConfig: tableConfigPda,
           signPdaAccount: signPda,
           mxeAccount,
           mempoolAccount,
@@ -386,8 +409,7 @@ describe('Poker E2E (devnet)', () => {
         payer: wallet.publicKey,
         gameState: gamePda,
         handState: handPda,
-        treasuryTokenAccount: wallet.publicKey, // Using wallet as placeholder
-        dealerAccount: wallet.publicKey, // Using wallet as placeholder
+        tableConfig: tableConfigPda,
         signPdaAccount: signPda,
         mxeAccount,
         mempoolAccount,
@@ -405,4 +427,4514 @@ describe('Poker E2E (devnet)', () => {
 
     console.log('✅ requestShowdown instruction executed successfully (no stack overflow)!');
   });
+
+  // Creates a fresh table funded by the wallet and seats a newly generated joiner, who buys in
+  // for `joinerBuyIn`. The table plays for a new mint unless one the wallet can mint is given.
+  async function setUpHeadsUpTable(tableId, options = {}, existingMint = null, joinerBuyIn = buyIn) {
+    const tableConfigPda = pda('table_config', [u64le(tableId)], programId);
+    const gamePda = pda('game', [u64le(tableId)], programId);
+    const escrowPda = pda('escrow', [gamePda.toBuffer()], programId);
+    const handPda = pda('hand', [gamePda.toBuffer()], programId);
+
+    await ensureAirdrop(connection, wallet.publicKey, 2);
+    const mint = existingMint ?? await createMint(connection, wallet.payer, wallet.publicKey, null, 9);
+    const creatorAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, wallet.publicKey)).address;
+    await mintTo(connection, wallet.payer, mint, creatorAta, wallet.payer, Number(buyIn));
+    await program.methods
+      .createTable(
+        new anchor.BN(tableId.toString()),
+        new anchor.BN(smallBlind.toString()),
+        new anchor.BN(bigBlind.toString()),
+        new anchor.BN((buyIn / 2n).toString()),
+        new anchor.BN((2n * buyIn).toString()),
+        new anchor.BN(buyIn.toString()),
+        { ...tableOptions, ...options },
+      )
+      .accounts({
+        tableConfig: tableConfigPda,
+        gameState: gamePda,
+        escrowAccount: escrowPda,
+        creator: wallet.publicKey,
+        tokenMint: mint,
+        creatorTokenAccount: creatorAta,
+        config: pda('config', [], programId),
+        mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const joiner = Keypair.generate();
+    await ensureAirdrop(connection, joiner.publicKey, 1);
+    const joinerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, joiner.publicKey)).address;
+    await mintTo(connection, wallet.payer, mint, joinerAta, wallet.payer, Number(joinerBuyIn));
+    const joinSignature = await program.methods
+      .joinTable(new anchor.BN(joinerBuyIn.toString()))
+      .accounts({
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        joiner: joiner.publicKey,
+        joinerTokenAccount: joinerAta,
+        config: pda('config', [], programId),
+        mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+        slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([joiner])
+      .rpc();
+
+    return { tableConfigPda, gamePda, escrowPda, handPda, joiner, mint, joinSignature };
+  }
+
+  // Sets up and queues the deal of a new hand at a table from `setUpHeadsUpTable`, as the dealer.
+  // Pass `treasury` to pay the time charges of a time-raked table.
+  async function dealHand(table, offsetNumber, treasury = null) {
+    const { dealerKey, dealerSigners } = await setUpDeal(table, offsetNumber, treasury);
+    const { computationAccount, shuffleAndDealCompDefAccount } = await queueDeal(
+      table,
+      offsetNumber,
+      dealerKey,
+      dealerSigners,
+    );
+    return { dealerKey, computationAccount, shuffleAndDealCompDefAccount };
+  }
+
+  // Runs the setup step of a deal at a table from `setUpHeadsUpTable`, as the dealer, without
+  // queueing it.
+  async function setUpDeal({ tableConfigPda, gamePda, escrowPda, handPda, joiner }, offsetNumber, treasury = null) {
+    const game = await program.account.gameState.fetch(gamePda);
+    const dealerKey = game.players[game.dealerIndex];
+    const dealerSigners = dealerKey.equals(wallet.publicKey) ? [] : [joiner];
+    await program.methods
+      .dealNewHandSetup(new anchor.BN(offsetNumber))
+      .accounts({
+        payer: dealerKey,
+        gameState: gamePda,
+        handState: handPda,
+        tableConfig: tableConfigPda,
+        mxeAccount: getMXEAccAddress(programId),
+        config: pda('config', [], programId),
+        escrowAccount: treasury ? escrowPda : null,
+        treasuryTokenAccount: treasury,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: treasury ? TOKEN_PROGRAM_ID : null,
+      })
+      .signers(dealerSigners)
+      .rpc();
+    return { dealerKey, dealerSigners };
+  }
+
+  // Queues the shuffle of a deal set up with `setUpDeal`, paid for by `payerKey`.
+  async function queueDeal({ tableConfigPda, gamePda, handPda }, offsetNumber, payerKey, signers = []) {
+    const signPda = new PublicKey('BkkX4G853JQZtsvVSbGb4UA3BLzbaktq8Sw1X75w8paB');
+    const mxeAccount = getMXEAccAddress(programId);
+    const clusterOffset = Number(process.env.ARCIUM_CLUSTER_OFFSET || 1116522165);
+    const shuffleAndDealCompDefAccount = getCompDefAccAddress(
+      programId,
+      Buffer.from(getCompDefAccOffset('shuffle_and_deal')).readUInt32LE(0),
+    );
+    const dealOffsetBN = new anchor.BN(offsetNumber);
+    const computationAccount = getComputationAccAddress(programId, dealOffsetBN);
+    await program.methods
+      .dealNewHandQueue(dealOffsetBN)
+      .accounts({
+        payer: payerKey,
+        gameState: gamePda,
+        handState: handPda,
+        tableConfig: tableConfigPda,
+        signPdaAccount: signPda,
+        mxeAccount,
+        mempoolAccount: getMempoolAccAddress(programId),
+        executingPool: getExecutingPoolAccAddress(programId),
+        computationAccount,
+        compDefAccount: shuffleAndDealCompDefAccount,
+        clusterAccount: getClusterAccAddress(clusterOffset),
+        poolAccount: await resolveFeePoolPda(provider, getArciumProgAddress()),
+        clockAccount: getClockAccAddress(),
+        instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        arciumProgram: getArciumProgAddress(),
+      })
+      .signers(signers)
+      .rpc();
+
+    return { computationAccount, shuffleAndDealCompDefAccount };
+  }
+
+  // Queues the reveal of the next street at a table from `setUpHeadsUpTable` and, unless
+  // `awaitReveal` is false, waits for it. Returns the signatures of the request and, if
+  // awaited, of the finalized callback.
+  async function revealStreet({ tableConfigPda, gamePda, handPda }, offsetNumber, awaitReveal = true) {
+    const offsetBN = new anchor.BN(offsetNumber);
+    const computationAccount = getComputationAccAddress(programId, offsetBN);
+    const clusterOffset = Number(process.env.ARCIUM_CLUSTER_OFFSET || 1116522165);
+    const requestSignature = await program.methods
+      .requestCommunityCards(offsetBN)
+      .accounts({
+        payer: wallet.publicKey,
+        gameState: gamePda,
+        handState: handPda,
+        tableConfig: tableConfigPda,
+        signPdaAccount: new PublicKey('BkkX4G853JQZtsvVSbGb4UA3BLzbaktq8Sw1X75w8paB'),
+        mxeAccount: getMXEAccAddress(programId),
+        mempoolAccount: getMempoolAccAddress(programId),
+        executingPool: getExecutingPoolAccAddress(programId),
+        computationAccount,
+        compDefAccount: getCompDefAccAddress(
+          programId,
+          Buffer.from(getCompDefAccOffset('reveal_community_cards')).readUInt32LE(0),
+        ),
+        clusterAccount: getClusterAccAddress(clusterOffset),
+        poolAccount: await resolveFeePoolPda(provider, getArciumProgAddress()),
+        clockAccount: getClockAccAddress(),
+        instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        arciumProgram: getArciumProgAddress(),
+      })
+      .rpc({ commitment: 'processed', skipPreflight: false, maxRetries: 3 });
+    let finalizeSignature = null;
+    if (awaitReveal) {
+      finalizeSignature = await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    }
+    return { requestSignature, finalizeSignature };
+  }
+
+  // Queues the rest of the board at once during an all-in runout and waits for the reveal.
+  // Pass `cranker` to have a keypair other than the wallet pay for the request.
+  async function requestRunOut({ tableConfigPda, gamePda, handPda }, offsetNumber, cranker = null) {
+    const offsetBN = new anchor.BN(offsetNumber);
+    const computationAccount = getComputationAccAddress(programId, offsetBN);
+    const clusterOffset = Number(process.env.ARCIUM_CLUSTER_OFFSET || 1116522165);
+    await program.methods
+      .requestRunOut(offsetBN)
+      .accounts({
+        payer: cranker ? cranker.publicKey : wallet.publicKey,
+        gameState: gamePda,
+        handState: handPda,
+        tableConfig: tableConfigPda,
+        signPdaAccount: new PublicKey('BkkX4G853JQZtsvVSbGb4UA3BLzbaktq8Sw1X75w8paB'),
+        mxeAccount: getMXEAccAddress(programId),
+        mempoolAccount: getMempoolAccAddress(programId),
+        executingPool: getExecutingPoolAccAddress(programId),
+        computationAccount,
+        compDefAccount: getCompDefAccAddress(
+          programId,
+          Buffer.from(getCompDefAccOffset('reveal_all_community_cards')).readUInt32LE(0),
+        ),
+        clusterAccount: getClusterAccAddress(clusterOffset),
+        poolAccount: await resolveFeePoolPda(provider, getArciumProgAddress()),
+        clockAccount: getClockAccAddress(),
+        instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        arciumProgram: getArciumProgAddress(),
+      })
+      .signers(cranker ? [cranker] : [])
+      .rpc({ commitment: 'processed', skipPreflight: false, maxRetries: 3 });
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+  }
+
+  // Queues the showdown at a table from `setUpHeadsUpTable` and waits for the winner, returning
+  // the signature of the finalized callback. Pass `cranker` to have a keypair other than the
+  // wallet pay for the request.
+  async function requestShowdown({ tableConfigPda, gamePda, handPda }, offsetNumber, cranker = null) {
+    const offsetBN = new anchor.BN(offsetNumber);
+    const computationAccount = getComputationAccAddress(programId, offsetBN);
+    const clusterOffset = Number(process.env.ARCIUM_CLUSTER_OFFSET || 1116522165);
+    await program.methods
+      .requestShowdown(offsetBN)
+      .accounts({
+        payer: cranker ? cranker.publicKey : wallet.publicKey,
+        gameState: gamePda,
+        handState: handPda,
+        tableConfig: tableConfigPda,
+        signPdaAccount: new PublicKey('BkkX4G853JQZtsvVSbGb4UA3BLzbaktq8Sw1X75w8paB'),
+        mxeAccount: getMXEAccAddress(programId),
+        mempoolAccount: getMempoolAccAddress(programId),
+        executingPool: getExecutingPoolAccAddress(programId),
+        computationAccount,
+        compDefAccount: getCompDefAccAddress(
+          programId,
+          Buffer.from(getCompDefAccOffset('determine_winner')).readUInt32LE(0),
+        ),
+        clusterAccount: getClusterAccAddress(clusterOffset),
+        poolAccount: await resolveFeePoolPda(provider, getArciumProgAddress()),
+        clockAccount: getClockAccAddress(),
+        instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        arciumProgram: getArciumProgAddress(),
+      })
+      .signers(cranker ? [cranker] : [])
+      .rpc({ commitment: 'processed', skipPreflight: false, maxRetries: 3 });
+    return awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+  }
+
+  // Plays the dealt hand at a table from `setUpHeadsUpTable` down to the showdown: the button
+  // limps, the big blind checks its option, both players check every street, and the showdown
+  // is queued. Uses four offsets, and returns the signature of the showdown callback.
+  async function checkDownToShowdown(table, firstOffset) {
+    const { tableConfigPda, gamePda, joiner } = table;
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [joiner])
+        .rpc();
+    };
+    await act({ call: {} });
+    await act({ check: {} });
+    for (const [i, street] of ['flop', 'turn', 'river'].entries()) {
+      await revealStreet(table, firstOffset + i);
+      while (street in (await program.account.gameState.fetch(gamePda)).gamePhase) {
+        await act({ check: {} });
+      }
+    }
+    return requestShowdown(table, firstOffset + 3);
+  }
+
+  // Settles the hand at a table from `setUpHeadsUpTable` once its winner is recorded, and
+  // returns the transaction signature. `preInstructions` are sent ahead of the settlement.
+  async function settleHand({ tableConfigPda, gamePda, escrowPda, handPda }, dealerKey, preInstructions = []) {
+    const configPda = pda('config', [], programId);
+    const config = await program.account.config.fetch(configPda);
+    return program.methods
+      .settleHand()
+      .accounts({
+        gameState: gamePda,
+        handState: handPda,
+        config: configPda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        dealerAccount: dealerKey,
+        treasuryTokenAccount: config.treasuryWallet,
+        referrerTokenAccount: null,
+        seat0Stats: null,
+        seat1Stats: null,
+        insurancePool: null,
+        insuredTokenAccount: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .preInstructions(preInstructions)
+      .rpc({ commitment: 'confirmed' });
+  }
+
+  // Sends a shuffle output whose integrity check failed straight to the callback for a deal from
+  // `dealHand`, bypassing Arcium: ComputationOutputs::Success(([p1, p2], deck, deck_valid = false)),
+  // Borsh-encoded.
+  async function injectFailedShuffle({ tableConfigPda, gamePda, handPda }, dealerKey, computationAccount, shuffleAndDealCompDefAccount) {
+    const borshBytes = (bytes) => {
+      const len = Buffer.alloc(4);
+      len.writeUInt32LE(bytes.length);
+      return Buffer.concat([len, Buffer.from(bytes)]);
+    };
+    const holeCardsCount = Buffer.alloc(4);
+    holeCardsCount.writeUInt32LE(2);
+    const data = Buffer.concat([
+      crypto.createHash('sha256').update('global:shuffle_and_deal_callback').digest().slice(0, 8),
+      Buffer.from([0]),
+      holeCardsCount,
+      borshBytes(new Uint8Array(32)),
+      borshBytes(new Uint8Array(32)),
+      borshBytes(new Uint8Array(64)),
+      Buffer.from([0]),
+    ]);
+    const callbackIx = new anchor.web3.TransactionInstruction({
+      programId,
+      keys: [
+        { pubkey: gamePda, isSigner: false, isWritable: true },
+        { pubkey: handPda, isSigner: false, isWritable: true },
+        { pubkey: computationAccount, isSigner: false, isWritable: false },
+        { pubkey: tableConfigPda, isSigner: false, isWritable: false },
+        { pubkey: shuffleAndDealCompDefAccount, isSigner: false, isWritable: false },
+        { pubkey: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: getArciumProgAddress(), isSigner: false, isWritable: false },
+        { pubkey: dealerKey, isSigner: false, isWritable: true },
+      ],
+      data,
+    });
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(callbackIx));
+  }
+
+  // A misdeal only comes from a shuffle that fails inside Arcium, which cannot be brought about on
+  // devnet. The void and the re-deal that follows are covered by the program's unit tests; these
+  // tests check that a failed shuffle cannot be forged to void a hand, and the re-deal's guard.
+  it('refuses a shuffle output that Arcium did not deliver (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { gamePda, handPda } = table;
+
+    const { dealerKey, computationAccount, shuffleAndDealCompDefAccount } = await dealHand(table, 900);
+    let forgeError;
+    try {
+      await injectFailedShuffle(table, dealerKey, computationAccount, shuffleAndDealCompDefAccount);
+    } catch (e) {
+      forgeError = e;
+    }
+    expect(forgeError, 'a callback sent by anyone but Arcium must be refused').to.exist;
+    const parsed = anchor.AnchorError.parse(forgeError.logs ?? []);
+    expect(parsed?.error.errorCode.code).to.equal('UnauthorizedCallback');
+
+    // The hand is untouched, and the real shuffle deals it as usual.
+    const pending = await program.account.gameState.fetch(gamePda);
+    expect(pending.misdeal).to.be.false;
+    expect(pending.gamePhase).to.have.property('dealing');
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    expect(dealt.gamePhase).to.have.property('preFlop');
+    expect(await connection.getAccountInfo(handPda)).to.not.be.null;
+  });
+
+  it('enforces the hourly pace cap (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, handPda, joiner } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [joiner]);
+
+    // The wallet must be the config admin to set the pace cap.
+    await program.methods
+      .setMaxHandsPerHour(1)
+      .accounts({
+        tableConfig: tableConfigPda,
+        config: pda('config', [], programId),
+        admin: wallet.publicKey,
+      })
+      .rpc();
+
+    // The first hand of the hour is dealt, and ends at once: the button acts first preflop
+    // and folds.
+    const { computationAccount } = await dealHand(table, 910);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const button = dealt.players[dealt.dealerIndex];
+    await program.methods
+      .playerAction({ fold: {} })
+      .accounts({ player: button, gameState: gamePda, tableConfig: tableConfigPda })
+      .signers(signersFor(button))
+      .rpc();
+
+    // A second hand within the same hour would exceed the cap.
+    const afterFold = await program.account.gameState.fetch(gamePda);
+    expect(afterFold.gamePhase).to.have.property('handOver');
+    const nextDealer = afterFold.players[afterFold.dealerIndex];
+    let paceError;
+    try {
+      await program.methods
+        .dealNewHandSetup(new anchor.BN(911))
+        .accounts({
+          payer: nextDealer,
+          gameState: gamePda,
+          handState: handPda,
+          tableConfig: tableConfigPda,
+          mxeAccount: getMXEAccAddress(programId),
+          config: pda('config', [], programId),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(signersFor(nextDealer))
+        .rpc();
+    } catch (e) {
+      paceError = e;
+    }
+    expect(paceError, 'the second deal of the hour must be rejected').to.exist;
+    expect(paceError.error.errorCode.code).to.equal('PaceLimitExceeded');
+  });
+
+  it('returns an uncalled all-in shove when the opponent folds (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+      return game.currentTurnIndex;
+    };
+
+    // Limp and check preflop and check the flop down to reach the turn with one big blind each
+    // in the pot.
+    const { computationAccount } = await dealHand(table, 920);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await act({ call: {} });
+    await act({ check: {} });
+    await revealStreet(table, 921);
+    while ('flop' in (await program.account.gameState.fetch(gamePda)).gamePhase) {
+      await act({ check: {} });
+    }
+    await revealStreet(table, 922);
+
+    // Shove the rest of the stack on the turn; the opponent folds instead of calling.
+    const turn = await program.account.gameState.fetch(gamePda);
+    expect(turn.gamePhase).to.have.property('turn');
+    const shover = turn.currentTurnIndex;
+    await act({ bet: [turn.stacks[shover]] });
+    await act({ fold: {} });
+
+    // The uncalled shove comes back on top of the pot, so the shover is up exactly the
+    // opponent's big blind.
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.gamePhase).to.have.property('handOver');
+    expect(after.stacks[shover].toString()).to.equal((buyIn + bigBlind).toString());
+    expect(after.stacks[1 - shover].toString()).to.equal((buyIn - bigBlind).toString());
+    // Only the big blind the opponent matched counts as contributed to the hand.
+    expect(after.totalContributed[shover].toString()).to.equal(bigBlind.toString());
+  });
+
+  it('enforces a 2x minimum raise multiple (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { minRaiseMultiple: 2 });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+
+    const { computationAccount } = await dealHand(table, 930);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const button = dealt.players[dealt.dealerIndex];
+    const raise = (to) =>
+      program.methods
+        .playerAction({ raise: [new anchor.BN(to.toString())] })
+        .accounts({ player: button, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(button))
+        .rpc();
+
+    // A min-raise to two big blinds only adds one big blind, half the required raise.
+    let raiseError;
+    try {
+      await raise(2n * bigBlind);
+    } catch (e) {
+      raiseError = e;
+    }
+    expect(raiseError, 'a raise below twice the big blind must be rejected').to.exist;
+    expect(raiseError.error.errorCode.code).to.equal('InvalidBetAmount');
+
+    // Raising to three big blinds adds the required two.
+    await raise(3n * bigBlind);
+    const raised = await program.account.gameState.fetch(gamePda);
+    expect(raised.bets[dealt.dealerIndex].toString()).to.equal((3n * bigBlind).toString());
+    expect(raised.lastRaiseSize.toString()).to.equal((2n * bigBlind).toString());
+  });
+
+  it('reports only the sitting-out pass as crankable between hands (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, joiner } = table;
+
+    // The joiner sits out before the first hand, so the only crank left is passing the hand
+    // once their turn timer has run out.
+    await program.methods
+      .setSittingOut(true)
+      .accounts({ player: joiner.publicKey, gameState: gamePda })
+      .signers([joiner])
+      .rpc();
+    const status = () =>
+      program.methods.crankStatus().accounts({ gameState: gamePda, tableConfig: tableConfigPda }).view();
+
+    // Wait out whatever the status says is left of the timer.
+    const waiting = await status();
+    expect(waiting.phase).to.have.property('handOver');
+    expect(waiting.passSittingOutHand.applicable).to.be.true;
+    await new Promise(r => setTimeout(r, (waiting.passSittingOutHand.remaining.toNumber() + 2) * 1000));
+
+    const ready = await status();
+    expect(ready.passSittingOutHand.applicable).to.be.true;
+    expect(ready.passSittingOutHand.remaining.toNumber()).to.equal(0);
+    expect(ready.crankFold.applicable).to.be.false;
+    expect(ready.settleHand).to.be.false;
+  });
+
+  it('takes time charges at the deal and leaves the pot unraked (devnet)', async () => {
+    // The wallet must be the config admin and able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const config = await program.account.config.fetch(configPda);
+    const treasury = config.treasuryWallet;
+    const treasuryMint = (await getAccount(connection, treasury)).mint;
+    const timeCharge = 500n;
+    await program.methods
+      .setTimeChargeConfig(new anchor.BN(timeCharge.toString()), new anchor.BN(3600))
+      .accounts({ config: configPda, admin: wallet.publicKey })
+      .rpc();
+
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+    const { tableConfigPda, gamePda } = table;
+    await program.methods
+      .setTableRakeType({ time: {} })
+      .accounts({ tableConfig: tableConfigPda, config: configPda, admin: wallet.publicKey })
+      .rpc();
+    const treasuryBalance = async () => (await getAccount(connection, treasury)).amount;
+
+    // Dealing the first hand charges both players one period up front.
+    const treasuryBefore = await treasuryBalance();
+    const { dealerKey, computationAccount } = await dealHand(table, 940, treasury);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    expect((await treasuryBalance()) - treasuryBefore).to.equal(2n * timeCharge);
+    const dealt = await program.account.gameState.fetch(gamePda);
+    expect(dealt.timeChargedAt[0].toNumber()).to.be.greaterThan(0);
+    expect(dealt.stacks[dealt.dealerIndex].toString()).to.equal((buyIn - timeCharge - smallBlind).toString());
+
+    // Limp and check the hand down to a showdown, which would be raked at a pot-raked table.
+    await checkDownToShowdown(table, 941);
+    await settleHand(table, dealerKey);
+
+    // The whole pot went back to the players: only the time charges reached the treasury.
+    const settled = await program.account.gameState.fetch(gamePda);
+    const totalStacks = settled.stacks.reduce((sum, stack) => sum + BigInt(stack.toString()), 0n);
+    expect(totalStacks.toString()).to.equal((2n * (buyIn - timeCharge)).toString());
+    expect((await treasuryBalance()) - treasuryBefore).to.equal(2n * timeCharge);
+  });
+
+  it('rejects a community card reveal outside the flop, turn and river (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { computationAccount } = await dealHand(table, 950);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    // Preflop has no street for the circuit to reveal, so nothing is queued.
+    const dealt = await program.account.gameState.fetch(table.gamePda);
+    expect(dealt.gamePhase).to.have.property('preFlop');
+    let revealError;
+    try {
+      await revealStreet(table, 951);
+    } catch (e) {
+      revealError = e;
+    }
+    expect(revealError, 'a preflop reveal must be rejected').to.exist;
+    expect(revealError.error.errorCode.code).to.equal('InvalidRevealPhase');
+    expect(await connection.getAccountInfo(getComputationAccAddress(programId, new anchor.BN(951)))).to.be.null;
+  });
+
+  it('rejects a showdown before the board is complete (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    // An all-in preflop is headed for showdown, but no community card is out yet.
+    const { computationAccount } = await dealHand(table, 960);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await act({ raise: [new anchor.BN(buyIn.toString())] });
+    await act({ call: {} });
+    const runout = await program.account.gameState.fetch(gamePda);
+    expect(runout.runout).to.be.true;
+    expect(runout.communityCards[0]).to.equal(255);
+
+    let showdownError;
+    try {
+      await requestShowdown(table, 961);
+    } catch (e) {
+      showdownError = e;
+    }
+    expect(showdownError, 'a showdown without a full board must be rejected').to.exist;
+    expect(showdownError.error.errorCode.code).to.equal('BoardIncomplete');
+  });
+
+  it('retains a settled hand until it is reaped (devnet)', async () => {
+    const retentionSeconds = 20;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), {
+      handRetentionSeconds: new anchor.BN(retentionSeconds),
+    });
+    const { gamePda, handPda } = table;
+
+    const { dealerKey, computationAccount } = await dealHand(table, 970);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await checkDownToShowdown(table, 971);
+    await settleHand(table, dealerKey);
+
+    // The settled hand's ciphertexts can still be read.
+    const retained = await program.account.handState.fetch(handPda);
+    expect(retained.retainedUntil.toNumber()).to.be.greaterThan(0);
+    expect(retained.encryptedHoleCards[0].some((byte) => byte !== 0)).to.be.true;
+    const reap = () =>
+      program.methods
+        .reapHand()
+        .accounts({ gameState: gamePda, handState: handPda, dealerAccount: dealerKey })
+        .rpc();
+
+    let reapError;
+    try {
+      await reap();
+    } catch (e) {
+      reapError = e;
+    }
+    expect(reapError, 'the hand must not be reaped within its retention period').to.exist;
+    expect(reapError.error.errorCode.code).to.equal('HandStillRetained');
+
+    await new Promise(r => setTimeout(r, (retentionSeconds + 5) * 1000));
+    await reap();
+    expect(await connection.getAccountInfo(handPda)).to.be.null;
+  });
+
+  it('pays a tournament prize pool out on a top-3 split (devnet)', async () => {
+    // The wallet must be the config admin.
+    const configPda = pda('config', [], programId);
+    const createStructure = (structureId, payoutsBps) =>
+      program.methods
+        .createPayoutStructure(new anchor.BN(structureId.toString()), payoutsBps)
+        .accounts({
+          payoutStructure: pda('payout_structure', [u64le(structureId)], programId),
+          config: configPda,
+          admin: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // Shares that do not add up to 100% are rejected.
+    let structureError;
+    try {
+      await createStructure(BigInt(Date.now()), [5000, 3000, 1000]);
+    } catch (e) {
+      structureError = e;
+    }
+    expect(structureError, 'a 90% payout structure must be rejected').to.exist;
+    expect(structureError.error.errorCode.code).to.equal('InvalidPayoutStructure');
+
+    const structureId = BigInt(Date.now()) + 1n;
+    await createStructure(structureId, [5000, 3000, 2000]);
+    const payoutStructurePda = pda('payout_structure', [u64le(structureId)], programId);
+    const structure = await program.account.payoutStructure.fetch(payoutStructurePda);
+    expect(structure.paidPlaces).to.equal(3);
+    expect(structure.payoutsBps.slice(0, 3).reduce((sum, bps) => sum + bps, 0)).to.equal(10_000);
+
+    // Fund a prize pool with an odd amount, so first place also takes the rounding dust.
+    const tournamentId = BigInt(Date.now());
+    const tournamentPda = pda('tournament', [u64le(tournamentId)], programId);
+    const prizePoolPda = pda('tournament_escrow', [tournamentPda.toBuffer()], programId);
+    const mint = await createMint(connection, wallet.payer, wallet.publicKey, null, 9);
+    await program.methods
+      .createTournament(new anchor.BN(tournamentId.toString()))
+      .accounts({
+        tournament: tournamentPda,
+        prizePool: prizePoolPda,
+        payoutStructure: payoutStructurePda,
+        tokenMint: mint,
+        config: configPda,
+        admin: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+    await mintTo(connection, wallet.payer, mint, prizePoolPda, wallet.payer, 1001);
+
+    const places = [];
+    for (let i = 0; i < 3; i++) {
+      const owner = Keypair.generate().publicKey;
+      places.push((await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, owner)).address);
+    }
+    await program.methods
+      .settleTournament()
+      .accounts({
+        tournament: tournamentPda,
+        prizePool: prizePoolPda,
+        payoutStructure: payoutStructurePda,
+        config: configPda,
+        admin: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(places.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+      .rpc();
+
+    const paid = await Promise.all(places.map(async (place) => (await getAccount(connection, place)).amount));
+    expect(paid).to.deep.equal([501n, 300n, 200n]);
+    expect((await getAccount(connection, prizePoolPda)).amount).to.equal(0n);
+    expect((await program.account.tournament.fetch(tournamentPda)).settled).to.be.true;
+  });
+
+  it('never rakes more than the pot, even with a pathological rake cap (devnet)', async () => {
+    // The wallet must be the config admin and able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const setRake = (percentage, cap) =>
+      program.methods
+        .setRakeConfig(percentage, cap, original.referrerBps, original.rakebackBps)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc();
+
+    // A percentage above 100% is rejected outright.
+    let configError;
+    try {
+      await setRake(101, original.rakeCap);
+    } catch (e) {
+      configError = e;
+    }
+    expect(configError, 'a rake above 100% must be rejected').to.exist;
+    expect(configError.error.errorCode.code).to.equal('RakeTooHigh');
+
+    // The largest rake allowed, with a cap far above any pot, takes exactly the pot.
+    await setRake(100, new anchor.BN('18446744073709551615'));
+    try {
+      const treasury = original.treasuryWallet;
+      const treasuryMint = (await getAccount(connection, treasury)).mint;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+      const { dealerKey, computationAccount } = await dealHand(table, 980);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      await checkDownToShowdown(table, 981);
+      const treasuryBefore = (await getAccount(connection, treasury)).amount;
+      await settleHand(table, dealerKey);
+
+      const settled = await program.account.gameState.fetch(table.gamePda);
+      expect(settled.stacks.map((stack) => stack.toString())).to.deep.equal([
+        (buyIn - bigBlind).toString(),
+        (buyIn - bigBlind).toString(),
+      ]);
+      expect((await getAccount(connection, treasury)).amount - treasuryBefore).to.equal(2n * bigBlind);
+    } finally {
+      await setRake(original.rakePercentage, original.rakeCap);
+    }
+  });
+
+  it('spaces runout reveals by the table runout delay (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { runoutDelaySeconds: new anchor.BN(60) });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    // Both players are all-in preflop, so the board runs out without betting.
+    const { computationAccount } = await dealHand(table, 990);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await act({ raise: [new anchor.BN(buyIn.toString())] });
+    await act({ call: {} });
+    expect((await program.account.gameState.fetch(gamePda)).runout).to.be.true;
+
+    // The flop is revealed at once; the turn straight after it is too fast.
+    await revealStreet(table, 991);
+    expect((await program.account.gameState.fetch(gamePda)).gamePhase).to.have.property('turn');
+    let revealError;
+    try {
+      await revealStreet(table, 992);
+    } catch (e) {
+      revealError = e;
+    }
+    expect(revealError, 'a reveal within the runout delay must be rejected').to.exist;
+    expect(revealError.error.errorCode.code).to.equal('RunoutDelayNotElapsed');
+  });
+
+  it('audits the escrow against the chips in play (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { gamePda, escrowPda, mint } = table;
+    const audit = () =>
+      program.methods.auditEscrow().accounts({ gameState: gamePda, escrowAccount: escrowPda }).view();
+
+    // A healthy table holds exactly both buy-ins.
+    const healthy = await audit();
+    expect(healthy.escrowBalance.toString()).to.equal((2n * buyIn).toString());
+    expect(healthy.stacksTotal.toString()).to.equal((2n * buyIn).toString());
+    expect(healthy.pot.toNumber()).to.equal(0);
+    expect(healthy.reconciled).to.be.true;
+
+    // Tokens that reach the escrow outside of play no longer match the stacks.
+    await mintTo(connection, wallet.payer, mint, escrowPda, wallet.payer, 1);
+    const corrupted = await audit();
+    expect(corrupted.escrowBalance.toString()).to.equal((2n * buyIn + 1n).toString());
+    expect(corrupted.reconciled).to.be.false;
+  });
+
+  it('scales the button ante with the big blind (devnet)', async () => {
+    // The wallet must be the config admin to raise the blinds.
+    const configPda = pda('config', [], programId);
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { buttonAnteMultiplier: 1 });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const foldButton = async () => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction({ fold: {} })
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    // The button posts one big blind as dead money next to the blinds.
+    let { computationAccount } = await dealHand(table, 993);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    expect(game.pot.toString()).to.equal(bigBlind.toString());
+    expect(game.stacks[game.dealerIndex].toString()).to.equal((buyIn - smallBlind - bigBlind).toString());
+    await foldButton();
+
+    // Once the blinds double, so does the ante.
+    await program.methods
+      .setTableBlinds(new anchor.BN((2n * smallBlind).toString()), new anchor.BN((2n * bigBlind).toString()))
+      .accounts({ tableConfig: tableConfigPda, gameState: gamePda, config: configPda, admin: wallet.publicKey })
+      .rpc();
+    ({ computationAccount } = await dealHand(table, 994));
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.pot.toString()).to.equal((2n * bigBlind).toString());
+    expect(game.bets[game.dealerIndex].toString()).to.equal((2n * smallBlind).toString());
+  });
+
+  it('rejects a callback for a superseded computation (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { gamePda, handPda } = table;
+
+    // The hand now belongs to the deal queued at offset 996.
+    const { computationAccount } = await dealHand(table, 996);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    expect((await program.account.handState.fetch(handPda)).computationOffset.toNumber()).to.equal(996);
+
+    // A result delivered for any other computation is refused before it touches the hand. The
+    // computation account is checked with the other accounts, before the handler checks that
+    // Arcium delivered the callback, so calling it directly still reports the stale result.
+    let callbackError;
+    try {
+      await program.methods
+        .revealCommunityCardsCallback({ failure: {} })
+        .accounts({
+          gameState: gamePda,
+          handState: handPda,
+          computationAccount: getComputationAccAddress(programId, new anchor.BN(995)),
+          compDefAccount: getCompDefAccAddress(
+            programId,
+            Buffer.from(getCompDefAccOffset('reveal_community_cards')).readUInt32LE(0),
+          ),
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          arciumProgram: getArciumProgAddress(),
+        })
+        .rpc();
+    } catch (e) {
+      callbackError = e;
+    }
+    expect(callbackError, 'a stale callback must be rejected').to.exist;
+    expect(callbackError.error.errorCode.code).to.equal('StaleCallback');
+  });
+
+  it('rejects a determine_winner callback for a superseded computation (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { gamePda, handPda } = table;
+    const { computationAccount } = await dealHand(table, 1116);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await checkDownToShowdown(table, 1117);
+
+    // The showdown queued at offset 1120 is now the hand's latest computation.
+    expect((await program.account.handState.fetch(handPda)).computationOffset.toNumber()).to.equal(1120);
+    const before = await program.account.gameState.fetch(gamePda);
+    expect('readyToSettle' in before.gamePhase).to.equal(true);
+
+    // A winner delivered for the deal's computation cannot overwrite the recorded result.
+    let callbackError;
+    try {
+      await program.methods
+        .determineWinnerCallback({ failure: {} })
+        .accounts({
+          gameState: gamePda,
+          handState: handPda,
+          computationAccount: getComputationAccAddress(programId, new anchor.BN(1116)),
+          compDefAccount: getCompDefAccAddress(
+            programId,
+            Buffer.from(getCompDefAccOffset('determine_winner')).readUInt32LE(0),
+          ),
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          arciumProgram: getArciumProgAddress(),
+        })
+        .rpc();
+    } catch (e) {
+      callbackError = e;
+    }
+    expect(callbackError, 'a stale showdown callback must be rejected').to.exist;
+    expect(callbackError.error.errorCode.code).to.equal('StaleCallback');
+
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.winnerMask).to.equal(before.winnerMask);
+    expect('readyToSettle' in after.gamePhase).to.equal(true);
+  });
+
+  it('caps re-straddles at the table maximum (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { buttonStraddle: true, maxStraddles: 2 });
+    const { tableConfigPda, gamePda } = table;
+    const game = await program.account.gameState.fetch(gamePda);
+    const button = game.players[game.dealerIndex];
+    const straddle = () =>
+      program.methods
+        .declareStraddle(true)
+        .accounts({ player: button, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(button.equals(wallet.publicKey) ? [] : [table.joiner])
+        .rpc();
+
+    // A straddle and one re-straddle fit the cap of two levels.
+    await straddle();
+    await straddle();
+    expect((await program.account.gameState.fetch(gamePda)).straddleLevels).to.equal(2);
+
+    let straddleError;
+    try {
+      await straddle();
+    } catch (e) {
+      straddleError = e;
+    }
+    expect(straddleError, 'a third straddle level must be rejected').to.exist;
+    expect(straddleError.error.errorCode.code).to.equal('StraddleCapReached');
+  });
+
+  it('requires opening bets of at least the big blind unless all-in (devnet)', async () => {
+    // The cap leaves one big blind for the flop and less than one for the turn, where betting
+    // up to the cap is all-in.
+    const handCap = 2n * bigBlind + bigBlind / 2n;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { handCap: new anchor.BN(handCap.toString()) });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+    const bet = (amount) => act({ bet: [new anchor.BN(amount.toString())] });
+
+    const { computationAccount } = await dealHand(table, 997);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await act({ call: {} });
+    await act({ check: {} });
+    await revealStreet(table, 998);
+
+    // On the flop, a bet below the big blind is rejected and one of exactly the big blind is not.
+    let betError;
+    try {
+      await bet(bigBlind - 1n);
+    } catch (e) {
+      betError = e;
+    }
+    expect(betError, 'a bet below the big blind must be rejected').to.exist;
+    expect(betError.error.errorCode.code).to.equal('BetBelowMinimum');
+    await bet(bigBlind);
+    await act({ call: {} });
+    await revealStreet(table, 999);
+
+    // On the turn, the half big blind left under the cap can still be bet.
+    await bet(bigBlind / 2n);
+    const game = await program.account.gameState.fetch(gamePda);
+    expect(game.totalContributed.some((c) => c.toString() === handCap.toString())).to.be.true;
+  });
+
+  it('runs out the board once both players reach the hand cap (devnet)', async () => {
+    // A cap below the big blind could not cover the blinds, so the table is refused.
+    let capError;
+    try {
+      await setUpHeadsUpTable(BigInt(Date.now()), { handCap: new anchor.BN((bigBlind - 1n).toString()) });
+    } catch (e) {
+      capError = e;
+    }
+    expect(capError, 'a cap below the big blind must be rejected').to.exist;
+    expect(capError.error.errorCode.code).to.equal('HandCapBelowBigBlind');
+
+    const handCap = 3n * bigBlind;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { handCap: new anchor.BN(handCap.toString()) });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+      return program.account.gameState.fetch(gamePda);
+    };
+    const raiseTo = (total) => act({ raise: [new anchor.BN(total.toString())] });
+
+    const { computationAccount } = await dealHand(table, 1130);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    // Both stacks could cover far more, but nobody may put in more than the cap.
+    let raiseError;
+    try {
+      await raiseTo(handCap + 1n);
+    } catch (e) {
+      raiseError = e;
+    }
+    expect(raiseError, 'a raise past the cap must be rejected').to.exist;
+    expect(raiseError.error.errorCode.code).to.equal('HandCapExceeded');
+
+    // Raising to the cap counts as all-in, so the call closes the betting for the whole hand.
+    let game = await raiseTo(handCap);
+    expect(game.isAllIn.filter(Boolean)).to.have.length(1);
+    game = await act({ call: {} });
+    expect(game.totalContributed.map((c) => c.toString())).to.deep.equal([handCap.toString(), handCap.toString()]);
+    expect(game.isAllIn).to.deep.equal([true, true]);
+    expect(game.runout).to.be.true;
+    expect(game.stacks.every((stack) => stack.gt(new anchor.BN(0)))).to.be.true;
+
+    await requestRunOut(table, 1131);
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.gamePhase).to.have.property('showdown');
+    expect(game.communityCards.every((card) => card < 52)).to.be.true;
+  });
+
+  it('balances the books when an uncalled bet is returned and the pot is raked (devnet)', async () => {
+    // Heads-up there are no side pots; an all-in for less than the bet is the case where
+    // chips go back uncalled. The wallet must be able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const treasury = (await program.account.config.fetch(configPda)).treasuryWallet;
+    const treasuryMint = (await getAccount(connection, treasury)).mint;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+    const { tableConfigPda, gamePda, escrowPda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    // The button folds the first hand, leaving the stacks uneven.
+    let { computationAccount } = await dealHand(table, 1000);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await act({ fold: {} });
+
+    // The bigger stack shoves and the shorter one calls all-in for less.
+    const dealt = await dealHand(table, 1001);
+    await awaitComputationFinalization(provider, dealt.computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    const big = game.stacks[0].gt(game.stacks[1]) ? 0 : 1;
+    if (game.currentTurnIndex !== big) {
+      await act({ call: {} });
+      game = await program.account.gameState.fetch(gamePda);
+    }
+    await act({ raise: [game.stacks[big].add(game.bets[big])] });
+    await act({ call: {} });
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.runout).to.be.true;
+    expect(game.totalContributed[0].toString()).to.equal(game.totalContributed[1].toString());
+    expect(game.stacks[big].gtn(0)).to.be.true;
+
+    await revealStreet(table, 1002);
+    await revealStreet(table, 1003);
+    await revealStreet(table, 1004);
+    await requestShowdown(table, 1005);
+    const treasuryBefore = (await getAccount(connection, treasury)).amount;
+    await settleHand(table, dealt.dealerKey);
+
+    // Every chip bought in is now in a stack or in the treasury's rake.
+    const settled = await program.account.gameState.fetch(gamePda);
+    const rake = (await getAccount(connection, treasury)).amount - treasuryBefore;
+    const stacks = BigInt(settled.stacks[0].toString()) + BigInt(settled.stacks[1].toString());
+    expect(rake > 0n).to.be.true;
+    expect(stacks + rake).to.equal(2n * buyIn);
+    const audit = await program.methods
+      .auditEscrow()
+      .accounts({ gameState: gamePda, escrowAccount: escrowPda })
+      .view();
+    expect(audit.reconciled).to.be.true;
+  });
+
+  it('sizes minimum raises by the last raise, except all-in (devnet)', async () => {
+    // The cap leaves the big blind short of a full re-raise, so reaching it is all-in.
+    const handCap = 2n * bigBlind + bigBlind / 2n;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { handCap: new anchor.BN(handCap.toString()) });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const raiseTo = async (total) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction({ raise: [new anchor.BN(total.toString())] })
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    const { computationAccount } = await dealHand(table, 1006);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    // Raising to 1.5 big blinds only raises by half a big blind.
+    let raiseError;
+    try {
+      await raiseTo(bigBlind + bigBlind / 2n);
+    } catch (e) {
+      raiseError = e;
+    }
+    expect(raiseError, 'a raise below the minimum must be rejected').to.exist;
+    expect(raiseError.error.errorCode.code).to.equal('InvalidBetAmount');
+
+    // Raising to two big blinds is the minimum raise of one big blind.
+    await raiseTo(2n * bigBlind);
+    let game = await program.account.gameState.fetch(gamePda);
+    expect(game.lastRaiseSize.toString()).to.equal(bigBlind.toString());
+
+    // The re-raise to the cap is half a big blind short of the minimum, but is all-in.
+    await raiseTo(handCap);
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.bets.map((b) => b.toString())).to.include(handCap.toString());
+    expect(game.lastRaiseSize.toString()).to.equal(bigBlind.toString());
+  });
+
+  it('stores each player\'s hole cards in the documented layout (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { computationAccount } = await dealHand(table, 1007);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    // Public key (32 bytes), nonce (16 bytes, little-endian), then two 32-byte ciphertexts.
+    const hand = await program.account.handState.fetch(table.handPda);
+    const game = await program.account.gameState.fetch(table.gamePda);
+    expect(game.gamePhase).to.have.property('preFlop');
+    for (const blob of hand.encryptedHoleCards) {
+      const bytes = Buffer.from(blob);
+      expect(bytes.length).to.equal(32 + 16 + 2 * 32);
+      const nonce = bytes.subarray(32, 48).readBigUInt64LE(0) + (bytes.subarray(32, 48).readBigUInt64LE(8) << 64n);
+      expect(nonce > 0n).to.be.true;
+      expect(bytes.subarray(48).some((byte) => byte !== 0)).to.be.true;
+    }
+  });
+
+  it('returns the uncalled part of a raise folded to without creating chips (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, escrowPda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    // The button raises to three big blinds and the big blind folds with only one in.
+    const { computationAccount } = await dealHand(table, 1008);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const button = (await program.account.gameState.fetch(gamePda)).dealerIndex;
+    await act({ raise: [new anchor.BN((3n * bigBlind).toString())] });
+    await act({ fold: {} });
+
+    // Only the matched big blind changes hands; the two uncalled big blinds went back.
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.stacks[button].toString()).to.equal((buyIn + bigBlind).toString());
+    expect(after.stacks[1 - button].toString()).to.equal((buyIn - bigBlind).toString());
+    expect(after.totalContributed[button].toString()).to.equal(bigBlind.toString());
+    const audit = await program.methods
+      .auditEscrow()
+      .accounts({ gameState: gamePda, escrowAccount: escrowPda })
+      .view();
+    expect(audit.stacksTotal.toString()).to.equal((2n * buyIn).toString());
+    expect(audit.reconciled).to.be.true;
+  });
+
+  it('allows only one hand in progress at a time (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const expectError = async (promise, code) => {
+      let error;
+      try {
+        await promise;
+      } catch (e) {
+        error = e;
+      }
+      expect(error, `expected ${code}`).to.exist;
+      expect(error.error.errorCode.code).to.equal(code);
+    };
+
+    // A second deal while the first hand is live is refused.
+    const { computationAccount } = await dealHand(table, 1009);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    expect(game.handInProgress).to.be.true;
+    await expectError(dealHand(table, 1010), 'HandAlreadyInProgress');
+
+    // Once the hand ends, nothing can be revealed until the next one is dealt.
+    const button = game.players[game.dealerIndex];
+    await program.methods
+      .playerAction({ fold: {} })
+      .accounts({ player: button, gameState: gamePda, tableConfig: tableConfigPda })
+      .signers(button.equals(wallet.publicKey) ? [] : [table.joiner])
+      .rpc();
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.handInProgress).to.be.false;
+    await expectError(revealStreet(table, 1011), 'NoHandInProgress');
+  });
+
+  it('calls an all-in only for what the shorter stack committed (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+    const shoveAndCall = async () => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const shover = game.currentTurnIndex;
+      await act({ raise: [game.stacks[shover].add(game.bets[shover])] });
+      await act({ call: {} });
+      return shover;
+    };
+
+    // Even stacks: the call exactly matches the shove and both players are all-in.
+    let { computationAccount } = await dealHand(table, 1012);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await shoveAndCall();
+    let game = await program.account.gameState.fetch(gamePda);
+    expect(game.stacks.map((s) => s.toString())).to.deep.equal(['0', '0']);
+    expect(game.totalContributed.map((c) => c.toString())).to.deep.equal([buyIn.toString(), buyIn.toString()]);
+
+    // Uneven stacks: a second table's button folds once, then the shorter stack shoves and the
+    // covering caller keeps the difference.
+    const uneven = await setUpHeadsUpTable(BigInt(Date.now()));
+    const unevenAct = async (action) => {
+      const g = await program.account.gameState.fetch(uneven.gamePda);
+      const player = g.players[g.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: uneven.gamePda, tableConfig: uneven.tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [uneven.joiner])
+        .rpc();
+    };
+    ({ computationAccount } = await dealHand(uneven, 1013));
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await unevenAct({ fold: {} });
+    ({ computationAccount } = await dealHand(uneven, 1014));
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    game = await program.account.gameState.fetch(uneven.gamePda);
+    const short = game.stacks[0].lt(game.stacks[1]) ? 0 : 1;
+    const shortTotal = game.stacks[short].add(game.bets[short]);
+    if (game.currentTurnIndex !== short) {
+      await unevenAct({ call: {} });
+    }
+    await unevenAct({ raise: [shortTotal] });
+    await unevenAct({ call: {} });
+    game = await program.account.gameState.fetch(uneven.gamePda);
+    expect(game.totalContributed[1 - short].toString()).to.equal(shortTotal.toString());
+    expect(game.stacks[1 - short].toString()).to.equal((2n * buyIn - BigInt(shortTotal.toString()) * 2n).toString());
+    expect(game.isAllIn[1 - short]).to.be.false;
+  });
+
+  it('lets the waiting player force an expired opponent timeout (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const { computationAccount } = await dealHand(table, 1015);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const onClock = dealt.currentTurnIndex;
+    const waiting = dealt.players[1 - onClock];
+    const force = (player) =>
+      program.methods
+        .forceOpponentTimeout()
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [table.joiner])
+        .rpc();
+    const expectError = async (promise, code) => {
+      let error;
+      try {
+        await promise;
+      } catch (e) {
+        error = e;
+      }
+      expect(error, `expected ${code}`).to.exist;
+      expect(error.error.errorCode.code).to.equal(code);
+    };
+
+    // Neither the player on the clock nor an unexpired timer can be forced.
+    await expectError(force(dealt.players[onClock]), 'Unauthorized');
+    await expectError(force(waiting), 'TimerNotExpired');
+
+    await new Promise(r => setTimeout(r, 35_000)); // TURN_TIME_SECONDS is 30
+    await force(waiting);
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.gamePhase).to.have.property('handOver');
+    expect(after.stacks[1 - onClock].toString()).to.equal((buyIn + smallBlind).toString());
+  });
+
+  it('gives the big blind the option after a limp (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    // Limp, then the big blind checks its option: on to the flop.
+    let { computationAccount } = await dealHand(table, 1016);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    const bigBlindSeat = 1 - game.dealerIndex;
+    await act({ call: {} });
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.gamePhase).to.have.property('preFlop');
+    expect(game.currentTurnIndex).to.equal(bigBlindSeat);
+    await act({ check: {} });
+    expect((await program.account.gameState.fetch(gamePda)).gamePhase).to.have.property('flop');
+    await act({ fold: {} });
+
+    // Limp, then the big blind raises its option: the button acts again preflop.
+    ({ computationAccount } = await dealHand(table, 1017));
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    game = await program.account.gameState.fetch(gamePda);
+    const button = game.dealerIndex;
+    await act({ call: {} });
+    await act({ raise: [new anchor.BN((3n * bigBlind).toString())] });
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.gamePhase).to.have.property('preFlop');
+    expect(game.currentTurnIndex).to.equal(button);
+  });
+
+  it('rejects a table buy-in above the platform cap (devnet)', async () => {
+    // The wallet must be the config admin.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const setCap = (cap) =>
+      program.methods
+        .setMaxBuyInCap(new anchor.BN(cap.toString()))
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc();
+
+    await setCap(buyIn - 1n);
+    try {
+      let error;
+      try {
+        await setUpHeadsUpTable(BigInt(Date.now()));
+      } catch (e) {
+        error = e;
+      }
+      expect(error, 'a buy-in above the cap must be rejected').to.exist;
+      expect(error.error.errorCode.code).to.equal('ExceedsPlatformCap');
+
+      // A buy-in at the cap is allowed.
+      await setCap(buyIn);
+      await setUpHeadsUpTable(BigInt(Date.now()));
+    } finally {
+      await setCap(BigInt(original.maxBuyInCap.toString()));
+    }
+  });
+
+  it('closes a betting round only once both players have acted (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+      return program.account.gameState.fetch(gamePda);
+    };
+    const bet = (chips) => ({ bet: [new anchor.BN(chips.toString())] });
+    const raise = (chips) => ({ raise: [new anchor.BN(chips.toString())] });
+
+    const { computationAccount } = await dealHand(table, 1018);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    expect(game.hasActed).to.deep.equal([false, false]);
+
+    // Limp and check the option: preflop closes with both flags set, then they are cleared.
+    await act({ call: {} });
+    game = await act({ check: {} });
+    expect(game.gamePhase).to.have.property('flop');
+    expect(game.hasActed).to.deep.equal([false, false]);
+    await revealStreet(table, 1019);
+
+    // Check-check closes the flop.
+    game = await act({ check: {} });
+    expect(game.gamePhase).to.have.property('flop');
+    expect(game.hasActed.filter(Boolean)).to.have.length(1);
+    game = await act({ check: {} });
+    expect(game.gamePhase).to.have.property('turn');
+    await revealStreet(table, 1020);
+
+    // Bet-call closes the turn.
+    game = await act(bet(bigBlind));
+    expect(game.gamePhase).to.have.property('turn');
+    game = await act({ call: {} });
+    expect(game.gamePhase).to.have.property('river');
+    await revealStreet(table, 1021);
+
+    // Bet-raise-call closes the river: the raise re-opens the action for the bettor.
+    const bettor = (await program.account.gameState.fetch(gamePda)).currentTurnIndex;
+    game = await act(bet(bigBlind));
+    game = await act(raise(3n * bigBlind));
+    expect(game.gamePhase).to.have.property('river');
+    expect(game.currentTurnIndex).to.equal(bettor);
+    expect(game.hasActed[bettor]).to.be.false;
+    game = await act({ call: {} });
+    expect(game.gamePhase).to.have.property('showdown');
+  });
+
+  it('closes the betting when an all-in is called (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+      return program.account.gameState.fetch(gamePda);
+    };
+
+    const { computationAccount } = await dealHand(table, 1022);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    // The button shoves; the big blind has not acted yet, so the betting stays open.
+    let game = await act({ raise: [new anchor.BN(buyIn.toString())] });
+    expect(game.gamePhase).to.have.property('preFlop');
+    expect(game.runout).to.be.false;
+
+    // The call closes the betting for the rest of the hand: the board runs out.
+    game = await act({ call: {} });
+    expect(game.gamePhase).to.have.property('flop');
+    expect(game.runout).to.be.true;
+    expect(game.pot.toString()).to.equal((2n * buyIn).toString());
+  });
+
+  it('runs out exactly the missing streets after an all-in (devnet)', async () => {
+    // Plays `streets` streets (0 preflop, 1 the flop, 2 the turn) before the shove, then runs
+    // out the board. Returns the board as it was at the shove and the table after the runout.
+    const playAllIn = async (streets, offset) => {
+      const table = await setUpHeadsUpTable(BigInt(Date.now()));
+      const { tableConfigPda, gamePda } = table;
+      const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+      const act = async (action) => {
+        const game = await program.account.gameState.fetch(gamePda);
+        const player = game.players[game.currentTurnIndex];
+        await program.methods
+          .playerAction(action)
+          .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+          .signers(signersFor(player))
+          .rpc();
+      };
+
+      const { computationAccount } = await dealHand(table, offset);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      if (streets > 0) {
+        // Limp and check the option, then check through each street before anyone shoves.
+        await act({ call: {} });
+        await act({ check: {} });
+        for (let street = 1; street <= streets; street++) {
+          await revealStreet(table, offset + street);
+          if (street < streets) {
+            await act({ check: {} });
+            await act({ check: {} });
+          }
+        }
+      }
+      const game = await program.account.gameState.fetch(gamePda);
+      const stack = game.stacks[game.currentTurnIndex];
+      await act(streets > 0 ? { bet: [stack] } : { raise: [new anchor.BN(buyIn.toString())] });
+      await act({ call: {} });
+
+      await requestRunOut(table, offset + streets + 1);
+      return { table, shoveBoard: game.communityCards, game: await program.account.gameState.fetch(gamePda) };
+    };
+
+    // An all-in preflop reveals all five cards, one on the flop the turn and river, and one on
+    // the turn only the river. The cards already out are kept.
+    for (const [streets, offset, shown, extraOffset] of [[0, 1023, 0, 1177], [1, 1026, 3, 1178], [2, 1173, 4, 1179]]) {
+      const { table, shoveBoard, game } = await playAllIn(streets, offset);
+      expect(shoveBoard.filter((card) => card < 52)).to.have.length(shown);
+      expect(game.gamePhase).to.have.property('showdown');
+      expect(game.communityCards.every((card) => card < 52)).to.be.true;
+      expect(new Set(game.communityCards).size).to.equal(5);
+      expect(game.communityCards.slice(0, shown)).to.deep.equal(shoveBoard.slice(0, shown));
+
+      // Once the board is complete, nothing is left to reveal.
+      let runoutError;
+      try {
+        await requestRunOut(table, extraOffset);
+      } catch (e) {
+        runoutError = e;
+      }
+      expect(runoutError, 'a complete board must not be run out again').to.exist;
+      expect(runoutError.error.errorCode.code).to.equal('NoCardsToReveal');
+    }
+  });
+
+  it('folds into a seat at another fast-fold pool table (devnet)', async () => {
+    // The wallet must be the config admin, since only the admin can create pools.
+    const configPda = pda('config', [], programId);
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+
+    // A second table with the same stakes, where another player is waiting alone.
+    const targetTableId = BigInt(Date.now()) + 1n;
+    const target = {
+      tableConfigPda: pda('table_config', [u64le(targetTableId)], programId),
+      gamePda: pda('game', [u64le(targetTableId)], programId),
+    };
+    target.escrowPda = pda('escrow', [target.gamePda.toBuffer()], programId);
+    const waiter = Keypair.generate();
+    await ensureAirdrop(connection, waiter.publicKey, 1);
+    const waiterAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, table.mint, waiter.publicKey)).address;
+    await mintTo(connection, wallet.payer, table.mint, waiterAta, wallet.payer, Number(buyIn));
+    await program.methods
+      .createTable(
+        new anchor.BN(targetTableId.toString()),
+        new anchor.BN(smallBlind.toString()),
+        new anchor.BN(bigBlind.toString()),
+        new anchor.BN((buyIn / 2n).toString()),
+        new anchor.BN((2n * buyIn).toString()),
+        new anchor.BN(buyIn.toString()),
+        tableOptions,
+      )
+      .accounts({
+        tableConfig: target.tableConfigPda,
+        gameState: target.gamePda,
+        escrowAccount: target.escrowPda,
+        creator: waiter.publicKey,
+        tokenMint: table.mint,
+        creatorTokenAccount: waiterAta,
+        config: configPda,
+        mintExposure: pda('mint_exposure', [table.mint.toBuffer()], programId),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([waiter])
+      .rpc();
+
+    const poolId = BigInt(Date.now());
+    const poolPda = pda('fast_fold_pool', [u64le(poolId)], programId);
+    await program.methods
+      .createFastFoldPool(
+        new anchor.BN(poolId.toString()),
+        new anchor.BN(smallBlind.toString()),
+        new anchor.BN(bigBlind.toString()),
+      )
+      .accounts({
+        pool: poolPda,
+        tokenMint: table.mint,
+        config: configPda,
+        admin: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    for (const { gamePda, tableConfigPda } of [table, target]) {
+      await program.methods
+        .addFastFoldTable()
+        .accounts({ pool: poolPda, gameState: gamePda, tableConfig: tableConfigPda, config: configPda, admin: wallet.publicKey })
+        .rpc();
+    }
+    let pool = await program.account.fastFoldPool.fetch(poolPda);
+    expect(pool.waiting[1].equals(waiter.publicKey)).to.be.true;
+
+    const { computationAccount } = await dealHand(table, 1029);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(table.gamePda);
+    const folderSeat = dealt.currentTurnIndex;
+    const folder = dealt.players[folderSeat];
+    const stayer = dealt.players[1 - folderSeat];
+
+    await program.methods
+      .fastFoldAndRematch()
+      .accounts({
+        player: folder,
+        pool: poolPda,
+        gameState: table.gamePda,
+        tableConfig: table.tableConfigPda,
+        escrowAccount: table.escrowPda,
+        targetGameState: target.gamePda,
+        targetEscrowAccount: target.escrowPda,
+        slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers(folder.equals(wallet.publicKey) ? [] : [table.joiner])
+      .rpc();
+
+    // The fold paid the blinds to the player left behind, who now waits for a new opponent.
+    const source = await program.account.gameState.fetch(table.gamePda);
+    expect(source.gamePhase).to.have.property('idle');
+    expect(source.players.filter((p) => p.equals(folder))).to.have.length(0);
+    const stayerSeat = source.players.findIndex((p) => p.equals(stayer));
+    expect(source.stacks[stayerSeat].toString()).to.equal((buyIn + smallBlind).toString());
+
+    // The folder sat down at the pool table with the rest of their stack, and the match starts.
+    const rematch = await program.account.gameState.fetch(target.gamePda);
+    expect(rematch.isActive).to.be.true;
+    const folderSeatThere = rematch.players.findIndex((p) => p.equals(folder));
+    expect(rematch.stacks[folderSeatThere].toString()).to.equal((buyIn - smallBlind).toString());
+    const targetEscrow = await getAccount(connection, target.escrowPda);
+    expect(targetEscrow.amount.toString()).to.equal((2n * buyIn - smallBlind).toString());
+
+    pool = await program.account.fastFoldPool.fetch(poolPda);
+    expect(pool.waiting[0].equals(stayer)).to.be.true;
+    expect(pool.waiting[1].equals(PublicKey.default)).to.be.true;
+  });
+
+  it('writes each revealed street to its board slots (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, handPda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+    // Reveals the next street through the MPC network and returns the board.
+    const reveal = async (offset) => {
+      await revealStreet(table, offset);
+      return (await program.account.gameState.fetch(gamePda)).communityCards;
+    };
+    const isCard = (card) => card < 52;
+
+    const { computationAccount } = await dealHand(table, 1030);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await act({ call: {} });
+    await act({ check: {} });
+
+    // The flop fills slots 0-2, the turn slot 3 and the river slot 4, leaving earlier streets
+    // as they were.
+    const flop = await reveal(1340);
+    expect(flop.slice(0, 3).every(isCard)).to.be.true;
+    expect(flop.slice(3)).to.deep.equal([255, 255]);
+    await act({ check: {} });
+    await act({ check: {} });
+    const turn = await reveal(1341);
+    expect(turn.slice(0, 3)).to.deep.equal(flop.slice(0, 3));
+    expect(isCard(turn[3])).to.be.true;
+    expect(turn[4]).to.equal(255);
+    await act({ check: {} });
+    await act({ check: {} });
+    const river = await reveal(1342);
+    expect(river.slice(0, 4)).to.deep.equal(turn.slice(0, 4));
+    expect(isCard(river[4])).to.be.true;
+    expect(new Set(river).size).to.equal(5);
+  });
+
+  it('does not rake a hand whose betting ended before the minimum street (devnet)', async () => {
+    // The wallet must be the config admin and able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const setRakeMinStreet = (street) =>
+      program.methods
+        .setRakeMinStreet(street)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc();
+    const setRake = (percentage) =>
+      program.methods
+        .setRakeConfig(percentage, original.rakeCap, original.referrerBps, original.rakebackBps)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc();
+
+    await setRakeMinStreet(2); // No rake before the turn.
+    await setRake(5);
+    try {
+      const treasury = original.treasuryWallet;
+      const treasuryMint = (await getAccount(connection, treasury)).mint;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+      const { tableConfigPda, gamePda } = table;
+      const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+      const act = async (action) => {
+        const game = await program.account.gameState.fetch(gamePda);
+        const player = game.players[game.currentTurnIndex];
+        await program.methods
+          .playerAction(action)
+          .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+          .signers(signersFor(player))
+          .rpc();
+      };
+
+      // The betting ends on the flop with a called all-in; the turn and river are run out.
+      const { dealerKey, computationAccount } = await dealHand(table, 1031);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      await act({ call: {} });
+      await act({ check: {} });
+      await revealStreet(table, 1032);
+      const game = await program.account.gameState.fetch(gamePda);
+      await act({ bet: [game.stacks[game.currentTurnIndex]] });
+      await act({ call: {} });
+      await requestRunOut(table, 1033);
+      await requestShowdown(table, 1034);
+
+      const treasuryBefore = (await getAccount(connection, treasury)).amount;
+      await settleHand(table, dealerKey);
+      expect((await getAccount(connection, treasury)).amount).to.equal(treasuryBefore);
+      const settled = await program.account.gameState.fetch(gamePda);
+      expect(settled.finalBettingStreet).to.equal(1);
+      const stacks = settled.stacks.reduce((sum, stack) => sum + BigInt(stack.toString()), 0n);
+      expect(stacks.toString()).to.equal((2n * buyIn).toString());
+    } finally {
+      await setRake(original.rakePercentage);
+      await setRakeMinStreet(original.rakeMinStreet);
+    }
+  });
+
+  it('draws the first button deterministically from the slot hash (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const game = await program.account.gameState.fetch(table.gamePda);
+
+    // Find the `ButtonDrawn` event in the join transaction's logs.
+    await connection.confirmTransaction(table.joinSignature, 'confirmed');
+    const tx = await connection.getTransaction(table.joinSignature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(programId, program.coder);
+    const drawn = [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name === 'buttonDrawn');
+    expect(drawn).to.not.be.undefined;
+    expect(drawn.data.dealerIndex).to.equal(game.dealerIndex);
+
+    // Recompute the draw from the recorded slot hash and the players' commitments.
+    const derive = (slotHash) =>
+      crypto
+        .createHash('sha256')
+        .update(Buffer.from('veridian:slot_hash_seed'))
+        .update(Buffer.from(slotHash))
+        .update(game.players[0].toBuffer())
+        .update(game.players[1].toBuffer())
+        .update(Buffer.from(game.encryptionKeys[0]))
+        .update(Buffer.from(game.encryptionKeys[1]))
+        .digest();
+    const slotHash = drawn.data.slotHash;
+    expect(derive(slotHash)[0] % 2).to.equal(game.dealerIndex);
+    // The derivation is a pure function of its inputs.
+    expect(derive(slotHash).equals(derive(slotHash))).to.equal(true);
+    expect(drawn.data.slot.toNumber()).to.be.lessThan(tx.slot);
+  });
+
+  it('posts the table ante from both players before the blinds (devnet)', async () => {
+    const ante = 500n;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { ante: new anchor.BN(ante.toString()) });
+    let { computationAccount } = await dealHand(table, 1035);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(table.gamePda);
+    const button = game.dealerIndex;
+    expect(game.pot.toString()).to.equal((2n * ante).toString());
+    expect(game.bets[button].toString()).to.equal(smallBlind.toString());
+    expect(game.bets[1 - button].toString()).to.equal(bigBlind.toString());
+    expect(game.stacks[button].toString()).to.equal((buyIn - ante - smallBlind).toString());
+    expect(game.stacks[1 - button].toString()).to.equal((buyIn - ante - bigBlind).toString());
+    expect(game.isAllIn).to.deep.equal([false, false]);
+
+    // Without an ante, only the blinds go in, as before.
+    const plain = await setUpHeadsUpTable(BigInt(Date.now()));
+    ({ computationAccount } = await dealHand(plain, 1036));
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    game = await program.account.gameState.fetch(plain.gamePda);
+    expect(game.pot.toString()).to.equal('0');
+    expect(game.stacks[game.dealerIndex].toString()).to.equal((buyIn - smallBlind).toString());
+    expect(game.stacks[1 - game.dealerIndex].toString()).to.equal((buyIn - bigBlind).toString());
+  });
+
+  it('puts a stack that cannot cover the ante and blind all-in (devnet)', async () => {
+    // After this ante each player has 1,500 left: enough for the small blind but not the big.
+    const ante = buyIn - 1500n;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { ante: new anchor.BN(ante.toString()) });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+
+    const { computationAccount } = await dealHand(table, 1037);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    const button = game.dealerIndex;
+    expect(game.pot.toString()).to.equal((2n * ante).toString());
+    expect(game.bets[1 - button].toString()).to.equal('1500');
+    expect(game.stacks[1 - button].toString()).to.equal('0');
+    expect(game.isAllIn[1 - button]).to.be.true;
+
+    // The big blind is all-in for more than the small blind, so the button still has to call.
+    expect(game.currentTurnIndex).to.equal(button);
+    const player = game.players[button];
+    await program.methods
+      .playerAction({ call: {} })
+      .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+      .signers(signersFor(player))
+      .rpc();
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.runout).to.be.true;
+    expect(Object.keys(game.gamePhase)[0]).to.equal('flop');
+    expect(game.pot.toString()).to.equal((2n * buyIn).toString());
+  });
+
+  it('rate-limits rapid emotes from the same seat (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const sendEmote = (code) =>
+      program.methods
+        .sendEmote(code)
+        .accounts({ player: wallet.publicKey, gameState: table.gamePda })
+        .rpc();
+
+    await sendEmote(1);
+    let emoteError;
+    try {
+      await sendEmote(2);
+    } catch (e) {
+      emoteError = e;
+    }
+    expect(emoteError, 'a second emote within the cooldown must be rejected').to.exist;
+    expect(emoteError.error.errorCode.code).to.equal('EmoteRateLimited');
+
+    // The opponent's seat has its own limit.
+    await program.methods
+      .sendEmote(3)
+      .accounts({ player: table.joiner.publicKey, gameState: table.gamePda })
+      .signers([table.joiner])
+      .rpc();
+  });
+
+  it('lets the joiner buy in for any amount within the table\'s range (devnet)', async () => {
+    // Tables from `setUpHeadsUpTable` accept buy-ins from half to twice `buyIn`.
+    const minBuyIn = buyIn / 2n;
+    const maxBuyIn = 2n * buyIn;
+    for (const amount of [minBuyIn, maxBuyIn]) {
+      const { gamePda, escrowPda, joiner } = await setUpHeadsUpTable(BigInt(Date.now()), {}, null, amount);
+      const game = await program.account.gameState.fetch(gamePda);
+      const seat = game.players.findIndex((p) => p.equals(joiner.publicKey));
+      expect(game.stacks[seat].toString()).to.equal(amount.toString());
+      expect(game.sessionBuyIn[seat].toString()).to.equal(amount.toString());
+      expect((await getAccount(connection, escrowPda)).amount.toString()).to.equal((buyIn + amount).toString());
+    }
+
+    for (const amount of [minBuyIn - 1n, maxBuyIn + 1n]) {
+      let buyInError;
+      try {
+        await setUpHeadsUpTable(BigInt(Date.now()), {}, null, amount);
+      } catch (e) {
+        buyInError = e;
+      }
+      expect(buyInError, `a buy-in of ${amount} must be rejected`).to.exist;
+      expect(buyInError.error.errorCode.code).to.equal('InvalidBuyInAmount');
+    }
+  });
+
+  it('lets cranks run an idle all-in hand through to settlement (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      return program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc({ commitment: 'confirmed' });
+    };
+
+    // The button shoves pre-flop and is called; after that the players do nothing.
+    const { dealerKey, computationAccount } = await dealHand(table, 1038);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    await act({ raise: [dealt.stacks[dealt.currentTurnIndex].add(dealt.bets[dealt.currentTurnIndex])] });
+    const callSignature = await act({ call: {} });
+
+    // The closing call announces that the hand now only needs cranks.
+    const tx = await connection.getTransaction(callSignature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(programId, program.coder);
+    const events = [...parser.parseLogs(tx.meta.logMessages)];
+    const required = events.find((e) => e.name === 'autoProgressRequired');
+    expect(required).to.not.be.undefined;
+    expect(required.data.gamePhase).to.have.property('flop');
+    const status = await program.methods
+      .crankStatus()
+      .accounts({ gameState: gamePda, tableConfig: tableConfigPda })
+      .view();
+    expect(status.advanceToShowdown.applicable).to.be.true;
+    expect(status.advanceToShowdown.remaining.toNumber()).to.equal(0);
+
+    // A third party with no seat at the table drives the rest of the hand.
+    const cranker = Keypair.generate();
+    await ensureAirdrop(connection, cranker.publicKey, 1);
+    await requestRunOut(table, 1039, cranker);
+    await requestShowdown(table, 1040, cranker);
+    await settleHand(table, dealerKey);
+
+    const settled = await program.account.gameState.fetch(gamePda);
+    expect(settled.gamePhase).to.have.property('handOver');
+    const stacks = settled.stacks.reduce((sum, stack) => sum + BigInt(stack.toString()), 0n);
+    expect(stacks <= 2n * buyIn).to.be.true;
+  });
+
+  // A failing reveal cannot be produced on devnet: only Arcium may deliver a callback, and
+  // the cluster does not fail on request. The retry limit and the void that follows it are
+  // covered by the `HandState` and `void_hand` unit tests.
+  it('counts a reveal while it is in flight and clears the count once the street is out (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, handPda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    const { computationAccount } = await dealHand(table, 1041);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await act({ call: {} });
+    await act({ check: {} });
+
+    await revealStreet(table, 1042, false);
+    let hand = await program.account.handState.fetch(handPda);
+    expect(hand.revealAttempts).to.equal(1);
+    expect(hand.revealFailed).to.be.false;
+
+    await awaitComputationFinalization(provider, getComputationAccAddress(programId, new anchor.BN(1042)), programId, 'confirmed');
+    hand = await program.account.handState.fetch(handPda);
+    expect(hand.revealAttempts).to.equal(0);
+    expect(hand.revealFailed).to.be.false;
+    const game = await program.account.gameState.fetch(gamePda);
+    expect(game.communityCards.slice(0, 3).every((card) => card < 52)).to.be.true;
+  });
+
+  it('sits out a timed-out player until they sit back in (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, joiner } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [joiner]);
+
+    const { computationAccount } = await dealHand(table, 1045);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const onClock = dealt.currentTurnIndex;
+    const absent = dealt.players[onClock];
+
+    await new Promise(r => setTimeout(r, 35_000)); // TURN_TIME_SECONDS is 30
+    await program.methods
+      .crankFold()
+      .accounts({ gameState: gamePda, tableConfig: tableConfigPda })
+      .rpc();
+    let game = await program.account.gameState.fetch(gamePda);
+    expect(game.sittingOut[onClock]).to.be.true;
+
+    // No hand is dealt while the absent player sits out.
+    let dealError;
+    try {
+      await dealHand(table, 1046);
+    } catch (e) {
+      dealError = e;
+    }
+    expect(dealError, 'a deal with a player sitting out must be rejected').to.exist;
+    expect(dealError.error.errorCode.code).to.equal('PlayerSittingOut');
+
+    // Once they sit back in, the next hand is dealt as usual.
+    await program.methods
+      .setSittingOut(false)
+      .accounts({ player: absent, gameState: gamePda })
+      .signers(signersFor(absent))
+      .rpc();
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.sittingOut).to.deep.equal([false, false]);
+    const redeal = await dealHand(table, 1047);
+    await awaitComputationFinalization(provider, redeal.computationAccount, programId, 'confirmed');
+    expect((await program.account.gameState.fetch(gamePda)).gamePhase).to.have.property('preFlop');
+  });
+
+  it('draws down the time bank of a player who acts after their timer (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { timeBankSeconds: new anchor.BN(20) });
+    const { tableConfigPda, gamePda, joiner } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [joiner]);
+
+    const { computationAccount } = await dealHand(table, 1048);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    expect(game.timeBankSeconds.map((b) => b.toNumber())).to.deep.equal([20, 20]);
+    const onClock = game.currentTurnIndex;
+    const player = game.players[onClock];
+
+    // Act 8 seconds past the 30-second timer, inside the bank.
+    await new Promise(r => setTimeout(r, 38_000));
+    await program.methods
+      .playerAction({ call: {} })
+      .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+      .signers(signersFor(player))
+      .rpc();
+    game = await program.account.gameState.fetch(gamePda);
+    const left = game.timeBankSeconds[onClock].toNumber();
+    expect(left).to.be.greaterThan(0);
+    expect(left).to.be.at.most(12);
+    // The opponent's bank is untouched.
+    expect(game.timeBankSeconds[1 - onClock].toNumber()).to.equal(20);
+  });
+
+  it('only folds a timed-out player once their time bank is exhausted (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { timeBankSeconds: new anchor.BN(15) });
+    const { tableConfigPda, gamePda } = table;
+
+    const { computationAccount } = await dealHand(table, 1049);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const onClock = dealt.currentTurnIndex;
+
+    // Past the turn timer, the bank still covers the player.
+    await new Promise(r => setTimeout(r, 35_000));
+    let crankError;
+    try {
+      await program.methods
+        .crankFold()
+        .accounts({ gameState: gamePda, tableConfig: tableConfigPda })
+        .rpc();
+    } catch (e) {
+      crankError = e;
+    }
+    expect(crankError, 'the time bank must extend the turn timer').to.exist;
+    expect(crankError.error.errorCode.code).to.equal('TimerNotExpired');
+
+    // Once the timer and the whole bank have run out, the fold goes through.
+    await new Promise(r => setTimeout(r, 15_000));
+    await program.methods
+      .crankFold()
+      .accounts({ gameState: gamePda, tableConfig: tableConfigPda })
+      .rpc();
+    const game = await program.account.gameState.fetch(gamePda);
+    expect(game.gamePhase).to.have.property('handOver');
+    expect(game.timeBankSeconds[onClock].toNumber()).to.equal(0);
+    expect(game.timeBankSeconds[1 - onClock].toNumber()).to.equal(15);
+  });
+
+  it('reconciles is_active with the seats as players come and go (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, escrowPda, joiner, mint } = table;
+    const parser = new anchor.EventParser(programId, program.coder);
+
+    // Reconciles the table and returns the `TableStateReconciled` event.
+    const reconcile = async () => {
+      const signature = await program.methods
+        .reconcileTableState()
+        .accounts({ gameState: gamePda })
+        .rpc({ commitment: 'confirmed' });
+      const tx = await connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const event = [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name === 'tableStateReconciled');
+      expect(event).to.not.be.undefined;
+      return event.data;
+    };
+
+    // Every seat change now derives the flag, so a drifted table cannot be produced from here;
+    // these checks pin down what reconciling computes at each point instead.
+    // Both seats are taken, so the table is active and reconciling leaves it so.
+    let reconciled = await reconcile();
+    expect(reconciled.wasActive).to.be.true;
+    expect(reconciled.isActive).to.be.true;
+
+    // Once the joiner leaves, the flag the seat change derived is already correct.
+    const joinerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, joiner.publicKey)).address;
+    await program.methods
+      .leaveTable()
+      .accounts({
+        player: joiner.publicKey,
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        playerTokenAccount: joinerAta,
+        config: pda('config', [], programId),
+        mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([joiner])
+      .rpc();
+    expect((await program.account.gameState.fetch(gamePda)).isActive).to.be.false;
+    reconciled = await reconcile();
+    expect(reconciled.wasActive).to.be.false;
+    expect(reconciled.isActive).to.be.false;
+  });
+
+  it('charges the leave penalty only to a winner who leaves early (devnet)', async () => {
+    // The wallet must be the config admin.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const setLeavePenalty = (bps, hands) =>
+      program.methods
+        .setLeavePenalty(bps, hands)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc();
+
+    // Plays one hand in which the player on the clock folds, then has the winner leave.
+    // Returns the winner's withdrawal and the stack the opponent is left with.
+    const winAndLeave = async (offset) => {
+      const table = await setUpHeadsUpTable(BigInt(Date.now()));
+      const { tableConfigPda, gamePda, escrowPda, joiner, mint } = table;
+      const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [joiner]);
+      const { computationAccount } = await dealHand(table, offset);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      let game = await program.account.gameState.fetch(gamePda);
+      const folder = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction({ fold: {} })
+        .accounts({ player: folder, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(folder))
+        .rpc();
+
+      game = await program.account.gameState.fetch(gamePda);
+      const winnerIndex = 1 - game.players.findIndex((p) => p.equals(folder));
+      const winner = game.players[winnerIndex];
+      const stack = BigInt(game.stacks[winnerIndex].toString());
+      const winnerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, winner)).address;
+      await program.methods
+        .leaveTable()
+        .accounts({
+          player: winner,
+          gameState: gamePda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          playerTokenAccount: winnerAta,
+          config: configPda,
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers(signersFor(winner))
+        .rpc();
+      game = await program.account.gameState.fetch(gamePda);
+      return {
+        stack,
+        withdrawn: (await getAccount(connection, winnerAta)).amount,
+        opponentStack: BigInt(game.stacks[1 - winnerIndex].toString()),
+      };
+    };
+
+    try {
+      // Leaving after one hand, within the two-hand window, costs 5% of the stack, at most
+      // the winnings, which go to the opponent.
+      await setLeavePenalty(500, 2);
+      const early = await winAndLeave(1050);
+      const winnings = early.stack - buyIn;
+      expect(winnings > 0n).to.be.true;
+      const expected = early.stack * 500n / 10_000n < winnings ? early.stack * 500n / 10_000n : winnings;
+      expect(early.withdrawn).to.equal(early.stack - expected);
+      expect(early.opponentStack).to.equal(2n * buyIn - early.stack + expected);
+
+      // With a one-hand window, the same hand is already outside it.
+      await setLeavePenalty(500, 1);
+      const late = await winAndLeave(1051);
+      expect(late.withdrawn).to.equal(late.stack);
+      expect(late.opponentStack).to.equal(2n * buyIn - late.stack);
+    } finally {
+      await setLeavePenalty(original.leavePenaltyBps, original.leavePenaltyHands);
+    }
+  });
+
+  it('rejects player actions during an all-in runout with a specific error (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (player, action) =>
+      program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+
+    // The button shoves pre-flop and is called, so the rest of the board is run out.
+    const { computationAccount } = await dealHand(table, 1052);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    const shover = game.players[game.currentTurnIndex];
+    await act(shover, { raise: [game.stacks[game.currentTurnIndex].add(game.bets[game.currentTurnIndex])] });
+    game = await program.account.gameState.fetch(gamePda);
+    await act(game.players[game.currentTurnIndex], { call: {} });
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.runout).to.be.true;
+
+    // Neither player can act, whoever the turn index points at.
+    for (const player of game.players) {
+      let actionError;
+      try {
+        await act(player, { check: {} });
+      } catch (e) {
+        actionError = e;
+      }
+      expect(actionError, 'acting during a runout must be rejected').to.exist;
+      expect(actionError.error.errorCode.code).to.equal('ActionNotAllowedInRunout');
+    }
+  });
+
+  it('emits a HandResult matching the stacks for fold and showdown wins (devnet)', async () => {
+    const parser = new anchor.EventParser(programId, program.coder);
+    const handResult = async (signature) => {
+      const tx = await connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const event = [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name === 'handResult');
+      expect(event).to.not.be.undefined;
+      return event.data;
+    };
+    const stacksOf = async (gamePda) =>
+      (await program.account.gameState.fetch(gamePda)).stacks.map((s) => BigInt(s.toString()));
+
+    // A fold: the opponent wins the blinds, unraked.
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    let { dealerKey, computationAccount } = await dealHand(table, 1053);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    const folderIndex = game.currentTurnIndex;
+    const folder = game.players[folderIndex];
+    const beforeFold = game.stacks.map((s) => BigInt(s.toString()));
+    const foldSignature = await program.methods
+      .playerAction({ fold: {} })
+      .accounts({ player: folder, gameState: gamePda, tableConfig: tableConfigPda })
+      .signers(signersFor(folder))
+      .rpc({ commitment: 'confirmed' });
+    const folded = await handResult(foldSignature);
+    const afterFold = await stacksOf(gamePda);
+    expect(folded.winnerIndex).to.equal(1 - folderIndex);
+    expect(folded.wasShowdown).to.be.false;
+    expect(folded.rakeTaken.toString()).to.equal('0');
+    expect(folded.winningRank).to.be.null;
+    // The small blind folds: the big blind's uncalled excess is returned, then the matched
+    // blinds are awarded.
+    const bets = game.bets.map((b) => BigInt(b.toString()));
+    const uncalled = bets[1 - folderIndex] - bets[folderIndex];
+    expect(folded.potAwarded.toString()).to.equal((2n * bets[folderIndex]).toString());
+    expect(afterFold[1 - folderIndex] - beforeFold[1 - folderIndex]).to.equal(
+      BigInt(folded.potAwarded.toString()) + uncalled,
+    );
+
+    // A showdown: what the winner (or both, on a split) gains plus the rake is the whole pot.
+    ({ dealerKey, computationAccount } = await dealHand(table, 1054));
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await checkDownToShowdown(table, 1055);
+    const { winnerMask } = await program.account.gameState.fetch(gamePda);
+    const beforeSettle = await stacksOf(gamePda);
+    const shown = await handResult(await settleHand(table, dealerKey));
+    // The showdown's winner mask has one bit for a single winner and both for a split.
+    expect(winnerMask).to.equal(shown.winnerIndex < 2 ? 1 << shown.winnerIndex : 0b11);
+    const afterSettle = await stacksOf(gamePda);
+    expect(shown.wasShowdown).to.be.true;
+    // Only the winning hand's category is disclosed, as a number from high card to straight flush.
+    expect(shown.winningRank).to.be.within(0, 8);
+    const gained = afterSettle[0] - beforeSettle[0] + (afterSettle[1] - beforeSettle[1]);
+    expect(gained).to.equal(BigInt(shown.potAwarded.toString()));
+    expect(gained + BigInt(shown.rakeTaken.toString())).to.equal(2n * bigBlind);
+    if (shown.winnerIndex < 2) {
+      expect(afterSettle[1 - shown.winnerIndex]).to.equal(beforeSettle[1 - shown.winnerIndex]);
+    }
+  });
+
+  it('brackets each board reveal with start and completion events when enabled (devnet)', async () => {
+    const parser = new anchor.EventParser(programId, program.coder);
+    const eventsIn = async (signature) => {
+      await connection.confirmTransaction(signature, 'confirmed');
+      const tx = await connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      return [...parser.parseLogs(tx.meta.logMessages)];
+    };
+
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { revealEvents: true });
+    const { tableConfigPda, gamePda, joiner } = table;
+    const { computationAccount } = await dealHand(table, 1059);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    for (const action of [{ call: {} }, { check: {} }]) {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [joiner])
+        .rpc();
+    }
+
+    // The flop request announces the reveal, and its callback completes it with the cards.
+    const { requestSignature, finalizeSignature } = await revealStreet(table, 1060);
+    const started = (await eventsIn(requestSignature)).find((e) => e.name === 'cardRevealStarted');
+    expect(started).to.not.be.undefined;
+    expect(started.data.gamePhase).to.have.property('flop');
+    expect(started.data.attempt).to.equal(1);
+    const completed = (await eventsIn(finalizeSignature)).find((e) => e.name === 'cardRevealCompleted');
+    expect(completed).to.not.be.undefined;
+    expect(completed.data.gamePhase).to.have.property('flop');
+    const game = await program.account.gameState.fetch(gamePda);
+    expect(Buffer.from(completed.data.cards)).to.deep.equal(Buffer.from(game.communityCards.slice(0, 3)));
+    expect((await program.account.handState.fetch(table.handPda)).revealAnnounced).to.be.false;
+  });
+
+  it('refuses a re-deal when no hand was misdealt (devnet)', async () => {
+    const { tableConfigPda, gamePda, handPda } = await setUpHeadsUpTable(BigInt(Date.now()));
+    const cranker = Keypair.generate();
+    await ensureAirdrop(connection, cranker.publicKey, 1);
+    let redealError;
+    try {
+      await program.methods
+        .redealAfterMisdeal()
+        .accounts({
+          payer: cranker.publicKey,
+          gameState: gamePda,
+          handState: handPda,
+          tableConfig: tableConfigPda,
+          mxeAccount: getMXEAccAddress(programId),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cranker])
+        .rpc();
+    } catch (e) {
+      redealError = e;
+    }
+    expect(redealError, 'a re-deal needs a misdeal').to.exist;
+    expect(redealError.error.errorCode.code).to.equal('NoMisdeal');
+  });
+
+  it('refuses a table beyond the mint\'s table cap and reports the mint totals (devnet)', async () => {
+    // The wallet must be the config admin.
+    await ensureAirdrop(connection, wallet.publicKey, 2);
+    const mint = await createMint(connection, wallet.payer, wallet.publicKey, null, 9);
+    const mintExposurePda = pda('mint_exposure', [mint.toBuffer()], programId);
+    const capSignature = await program.methods
+      .setMintTableCap(1)
+      .accounts({
+        mintExposure: mintExposurePda,
+        tokenMint: mint,
+        config: pda('config', [], programId),
+        admin: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc({ commitment: 'confirmed' });
+    const capTx = await connection.getTransaction(capSignature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    const capEvents = [...new anchor.EventParser(programId, program.coder).parseLogs(capTx.meta.logMessages)];
+    const capEvent = capEvents.find((e) => e.name === 'mintExposureUpdated');
+    expect(capEvent.data.maxTables).to.equal(1);
+    expect(capEvent.data.activeTables).to.equal(0);
+
+    // The first table fits under the cap, and both buy-ins are counted.
+    const tableId = BigInt(Date.now());
+    await setUpHeadsUpTable(tableId, {}, mint);
+    let exposure = await program.account.mintExposure.fetch(mintExposurePda);
+    expect(exposure.activeTables).to.equal(1);
+    expect(exposure.escrowed.toString()).to.equal((2n * buyIn).toString());
+
+    // A second table for the same mint is refused.
+    let capError;
+    try {
+      await setUpHeadsUpTable(tableId + 1n, {}, mint);
+    } catch (e) {
+      capError = e;
+    }
+    expect(capError.error.errorCode.code).to.equal('MintTableCapReached');
+    exposure = await program.account.mintExposure.fetch(mintExposurePda);
+    expect(exposure.activeTables).to.equal(1);
+  });
+
+  it('keeps a covered all-in to a main pot and returns the remainder (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, null, buyIn / 2n);
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    // The creator covers the joiner's half-size buy-in, shoves, and is called all-in for less.
+    const dealt = await dealHand(table, 1063);
+    await awaitComputationFinalization(provider, dealt.computationAccount, programId, 'confirmed');
+    let game = await program.account.gameState.fetch(gamePda);
+    const big = game.players.findIndex((p) => p.equals(wallet.publicKey));
+    const short = 1 - big;
+    const bigTotal = BigInt(game.stacks[big].add(game.bets[big]).toString());
+    const shortTotal = BigInt(game.stacks[short].add(game.bets[short]).toString());
+    if (game.currentTurnIndex !== big) {
+      await act({ call: {} });
+      game = await program.account.gameState.fetch(gamePda);
+    }
+    await act({ raise: [game.stacks[big].add(game.bets[big])] });
+    await act({ call: {} });
+
+    // Only what the short stack matched is contested, in a single main pot both can win; the
+    // rest of the shove went back to the covering stack.
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.sidePots).to.have.lengthOf(1);
+    expect(game.sidePots[0].amount.toString()).to.equal((2n * shortTotal).toString());
+    expect(game.sidePots[0].eligible).to.deep.equal([true, true]);
+    expect(game.stacks[big].toString()).to.equal((bigTotal - shortTotal).toString());
+    expect(game.stacks[short].toString()).to.equal('0');
+
+    await revealStreet(table, 1064);
+    await revealStreet(table, 1065);
+    await revealStreet(table, 1066);
+    await requestShowdown(table, 1067);
+    const signature = await settleHand(table, dealt.dealerKey);
+    const tx = await connection.getTransaction(signature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    const result = [...new anchor.EventParser(programId, program.coder).parseLogs(tx.meta.logMessages)]
+      .find((e) => e.name === 'handResult').data;
+
+    // The main pot was paid out, less the rake, and the pots were cleared for the next hand.
+    game = await program.account.gameState.fetch(gamePda);
+    const stacks = BigInt(game.stacks[0].toString()) + BigInt(game.stacks[1].toString());
+    expect(stacks + BigInt(result.rakeTaken.toString())).to.equal(bigTotal + shortTotal);
+    expect(game.stacks[big].gte(new anchor.BN((bigTotal - shortTotal).toString()))).to.be.true;
+    expect(game.sidePots).to.have.lengthOf(0);
+  });
+
+  it('hands the admin role over only once the proposed key accepts (devnet)', async () => {
+    // The wallet must be the config admin; the role is handed back at the end.
+    const configPda = pda('config', [], programId);
+    const successor = Keypair.generate();
+    const outsider = Keypair.generate();
+
+    // Only the current admin may propose a successor.
+    let proposeError;
+    try {
+      await program.methods
+        .transferAdmin(outsider.publicKey)
+        .accounts({ config: configPda, admin: outsider.publicKey })
+        .signers([outsider])
+        .rpc();
+    } catch (e) {
+      proposeError = e;
+    }
+    expect(proposeError.error.errorCode.code).to.equal('Unauthorized');
+
+    await program.methods
+      .transferAdmin(successor.publicKey)
+      .accounts({ config: configPda, admin: wallet.publicKey })
+      .rpc({ commitment: 'confirmed' });
+    let config = await program.account.config.fetch(configPda);
+    expect(config.admin.equals(wallet.publicKey)).to.equal(true);
+    expect(config.pendingAdmin.equals(successor.publicKey)).to.equal(true);
+
+    // Any key other than the proposed one is refused.
+    let acceptError;
+    try {
+      await program.methods
+        .acceptAdmin()
+        .accounts({ config: configPda, newAdmin: outsider.publicKey })
+        .signers([outsider])
+        .rpc();
+    } catch (e) {
+      acceptError = e;
+    }
+    expect(acceptError.error.errorCode.code).to.equal('NotPendingAdmin');
+
+    const acceptSignature = await program.methods
+      .acceptAdmin()
+      .accounts({ config: configPda, newAdmin: successor.publicKey })
+      .signers([successor])
+      .rpc({ commitment: 'confirmed' });
+    const acceptTx = await connection.getTransaction(acceptSignature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    const acceptEvents = [...new anchor.EventParser(programId, program.coder).parseLogs(acceptTx.meta.logMessages)];
+    const transferred = acceptEvents.find((e) => e.name === 'adminTransferred');
+    expect(transferred.data.previousAdmin.equals(wallet.publicKey)).to.equal(true);
+    expect(transferred.data.newAdmin.equals(successor.publicKey)).to.equal(true);
+    config = await program.account.config.fetch(configPda);
+    expect(config.admin.equals(successor.publicKey)).to.equal(true);
+    expect(config.pendingAdmin.equals(PublicKey.default)).to.equal(true);
+
+    // Hand the role back so the remaining tests keep running as admin.
+    await program.methods
+      .transferAdmin(wallet.publicKey)
+      .accounts({ config: configPda, admin: successor.publicKey })
+      .signers([successor])
+      .rpc({ commitment: 'confirmed' });
+    await program.methods
+      .acceptAdmin()
+      .accounts({ config: configPda, newAdmin: wallet.publicKey })
+      .rpc({ commitment: 'confirmed' });
+    config = await program.account.config.fetch(configPda);
+    expect(config.admin.equals(wallet.publicKey)).to.equal(true);
+  });
+
+  it('blocks new deals while paused but lets a dealt hand finish (devnet)', async () => {
+    // The wallet must be the config admin.
+    const configPda = pda('config', [], programId);
+    const setPaused = (paused) =>
+      program.methods
+        .setPaused(paused)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc({ commitment: 'confirmed' });
+
+    const running = await setUpHeadsUpTable(BigInt(Date.now()));
+    const idle = await setUpHeadsUpTable(BigInt(Date.now()) + 1n);
+    const { computationAccount } = await dealHand(running, 1068);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    await setPaused(true);
+    try {
+      expect((await program.account.config.fetch(configPda)).paused).to.equal(true);
+
+      // No table can start a hand.
+      let dealError;
+      try {
+        await dealHand(idle, 1069);
+      } catch (e) {
+        dealError = e;
+      }
+      expect(dealError.error.errorCode.code).to.equal('PlatformPaused');
+
+      // The hand already dealt still plays out and pays the winner.
+      const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [running.joiner]);
+      let game = await program.account.gameState.fetch(running.gamePda);
+      const folder = game.players[game.currentTurnIndex];
+      const winnerIndex = 1 - game.currentTurnIndex;
+      const totalBefore = BigInt(game.stacks[winnerIndex].add(game.bets[winnerIndex]).toString());
+      await program.methods
+        .playerAction({ fold: {} })
+        .accounts({ player: folder, gameState: running.gamePda, tableConfig: running.tableConfigPda })
+        .signers(signersFor(folder))
+        .rpc({ commitment: 'confirmed' });
+      game = await program.account.gameState.fetch(running.gamePda);
+      expect(game.gamePhase).to.have.property('handOver');
+      expect(BigInt(game.stacks[winnerIndex].toString()) > totalBefore).to.equal(true);
+    } finally {
+      await setPaused(false);
+    }
+  });
+
+  it('validates the rake percentage and cap (devnet)', async () => {
+    // The wallet must be the config admin.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const setRake = (percentage, cap) =>
+      program.methods
+        .setRakeConfig(percentage, new anchor.BN(cap), original.referrerBps, original.rakebackBps)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc({ commitment: 'confirmed' });
+    const rakeError = async (percentage, cap) => {
+      try {
+        await setRake(percentage, cap);
+      } catch (e) {
+        return e.error.errorCode.code;
+      }
+      return null;
+    };
+
+    try {
+      // A typical rake is stored as given.
+      await setRake(5, 1000);
+      let config = await program.account.config.fetch(configPda);
+      expect(config.rakePercentage).to.equal(5);
+      expect(config.rakeCap.toString()).to.equal('1000');
+
+      // The maximum itself is allowed; one above it is not.
+      await setRake(100, 1000);
+      expect((await program.account.config.fetch(configPda)).rakePercentage).to.equal(100);
+      expect(await rakeError(101, 1000)).to.equal('RakeTooHigh');
+      expect(await rakeError(255, 1000)).to.equal('RakeTooHigh');
+
+      // A cap without a rake is rejected; no rake and no cap is fine.
+      expect(await rakeError(0, 1000)).to.equal('InvalidAction');
+      await setRake(0, 0);
+      config = await program.account.config.fetch(configPda);
+      expect(config.rakePercentage).to.equal(0);
+      expect(config.rakeCap.toString()).to.equal('0');
+    } finally {
+      await program.methods
+        .setRakeConfig(original.rakePercentage, original.rakeCap, original.referrerBps, original.rakebackBps)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc({ commitment: 'confirmed' });
+    }
+  });
+
+  it('rakes each pot by the tier its size reaches (devnet)', async () => {
+    // The wallet must be the config admin and able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const tier = (potThreshold, percentage, cap) => ({
+      potThreshold: new anchor.BN(potThreshold),
+      percentage,
+      cap: new anchor.BN(cap),
+    });
+    const setRakeTiers = (tiers) =>
+      program.methods
+        .setRakeTiers(tiers)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc({ commitment: 'confirmed' });
+
+    // Thresholds must strictly increase.
+    let tierError;
+    try {
+      await setRakeTiers([tier(10_000, 3, 5_000), tier(10_000, 5, 1_000)]);
+    } catch (e) {
+      tierError = e;
+    }
+    expect(tierError.error.errorCode.code).to.equal('InvalidAction');
+
+    // Small pots are unraked, mid pots pay 3% under a loose cap, large pots 5% up to a tight one.
+    await setRakeTiers([tier(0, 0, 0), tier(10_000, 3, 5_000), tier(50_000, 5, 1_000)]);
+    try {
+      const treasury = original.treasuryWallet;
+      const treasuryMint = (await getAccount(connection, treasury)).mint;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+      const { tableConfigPda, gamePda } = table;
+      const act = async (action) => {
+        const game = await program.account.gameState.fetch(gamePda);
+        const player = game.players[game.currentTurnIndex];
+        await program.methods
+          .playerAction(action)
+          .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+          .signers(player.equals(wallet.publicKey) ? [] : [table.joiner])
+          .rpc();
+      };
+
+      // Each hand is raised preflop to `raiseTo` (or just called), then checked down.
+      const rakeOfHand = async (firstOffset, raiseTo) => {
+        const { dealerKey, computationAccount } = await dealHand(table, firstOffset);
+        await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+        if (raiseTo) {
+          await act({ raise: [new anchor.BN(raiseTo)] });
+          await act({ call: {} });
+          for (const [i, street] of ['flop', 'turn', 'river'].entries()) {
+            await revealStreet(table, firstOffset + 1 + i);
+            while (street in (await program.account.gameState.fetch(gamePda)).gamePhase) {
+              await act({ check: {} });
+            }
+          }
+          await requestShowdown(table, firstOffset + 4);
+        } else {
+          await checkDownToShowdown(table, firstOffset + 1);
+        }
+        const treasuryBefore = (await getAccount(connection, treasury)).amount;
+        await settleHand(table, dealerKey);
+        return (await getAccount(connection, treasury)).amount - treasuryBefore;
+      };
+
+      // A 4,000 pot, in the first tier: no rake.
+      expect(await rakeOfHand(1070, null)).to.equal(0n);
+      // A 20,000 pot, in the second tier: 3% is under its cap.
+      expect(await rakeOfHand(1075, 10_000)).to.equal(600n);
+      // An 80,000 pot, in the third tier: 5% would be 4,000, but the tier caps it.
+      expect(await rakeOfHand(1080, 40_000)).to.equal(1_000n);
+    } finally {
+      await setRakeTiers(original.rakeTiers);
+    }
+  });
+
+  it('does not rake a pot below the minimum raked pot (devnet)', async () => {
+    // The wallet must be the config admin and able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const admin = { config: configPda, admin: wallet.publicKey };
+    const setRakeMinPot = (minPot) =>
+      program.methods.setRakeMinPot(new anchor.BN(minPot.toString())).accounts(admin).rpc({ commitment: 'confirmed' });
+    const setRake = (percentage, cap) =>
+      program.methods
+        .setRakeConfig(percentage, cap, original.referrerBps, original.rakebackBps)
+        .accounts(admin)
+        .rpc({ commitment: 'confirmed' });
+
+    // A flat 5% rake with no effective cap, so any rake taken is easy to spot.
+    await program.methods.setRakeTiers([]).accounts(admin).rpc({ commitment: 'confirmed' });
+    await setRake(5, new anchor.BN('18446744073709551615'));
+    try {
+      const treasury = original.treasuryWallet;
+      const treasuryMint = (await getAccount(connection, treasury)).mint;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+      const rakeOfHand = async (firstOffset) => {
+        const { dealerKey, computationAccount } = await dealHand(table, firstOffset);
+        await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+        await checkDownToShowdown(table, firstOffset + 1);
+        const treasuryBefore = (await getAccount(connection, treasury)).amount;
+        await settleHand(table, dealerKey);
+        return (await getAccount(connection, treasury)).amount - treasuryBefore;
+      };
+
+      // Each checked-down pot is both big blinds. One unit short of the minimum: no rake.
+      const pot = 2n * bigBlind;
+      await setRakeMinPot(pot + 1n);
+      expect(await rakeOfHand(1085)).to.equal(0n);
+
+      // Exactly at the minimum: the usual rake.
+      await setRakeMinPot(pot);
+      expect(await rakeOfHand(1090)).to.equal((pot * 5n) / 100n);
+    } finally {
+      await setRakeMinPot(original.rakeMinPot);
+      await setRake(original.rakePercentage, original.rakeCap);
+      await program.methods.setRakeTiers(original.rakeTiers).accounts(admin).rpc({ commitment: 'confirmed' });
+    }
+  });
+
+  it('accumulates the rake a table has paid (devnet)', async () => {
+    // The wallet must be the config admin and able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const admin = { config: configPda, admin: wallet.publicKey };
+    const setRake = (percentage, cap) =>
+      program.methods
+        .setRakeConfig(percentage, cap, original.referrerBps, original.rakebackBps)
+        .accounts(admin)
+        .rpc({ commitment: 'confirmed' });
+
+    await program.methods.setRakeTiers([]).accounts(admin).rpc({ commitment: 'confirmed' });
+    await program.methods.setRakeMinPot(new anchor.BN(0)).accounts(admin).rpc({ commitment: 'confirmed' });
+    await setRake(5, new anchor.BN('18446744073709551615'));
+    try {
+      const treasury = original.treasuryWallet;
+      const treasuryMint = (await getAccount(connection, treasury)).mint;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+      const totalRake = async () =>
+        BigInt((await program.account.gameState.fetch(table.gamePda)).totalRakeCollected.toString());
+      expect(await totalRake()).to.equal(0n);
+
+      let collected = 0n;
+      for (const firstOffset of [1095, 1100]) {
+        const { dealerKey, computationAccount } = await dealHand(table, firstOffset);
+        await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+        await checkDownToShowdown(table, firstOffset + 1);
+        const treasuryBefore = (await getAccount(connection, treasury)).amount;
+        await settleHand(table, dealerKey);
+        const rake = (await getAccount(connection, treasury)).amount - treasuryBefore;
+        expect(rake > 0n).to.equal(true);
+        collected += rake;
+        expect(await totalRake()).to.equal(collected);
+      }
+    } finally {
+      await setRake(original.rakePercentage, original.rakeCap);
+      await program.methods.setRakeMinPot(original.rakeMinPot).accounts(admin).rpc({ commitment: 'confirmed' });
+      await program.methods.setRakeTiers(original.rakeTiers).accounts(admin).rpc({ commitment: 'confirmed' });
+    }
+  });
+
+  it('records lifetime results in PlayerStats for a fold win (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, joiner } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [joiner]);
+    const statsPda = (player) => pda('player_stats', [player.toBuffer()], programId);
+    for (const player of [wallet.publicKey, joiner.publicKey]) {
+      if (!(await program.account.playerStats.fetchNullable(statsPda(player)))) {
+        await program.methods
+          .initPlayerStats()
+          .accounts({ playerStats: statsPda(player), player, systemProgram: SystemProgram.programId })
+          .signers(signersFor(player))
+          .rpc({ commitment: 'confirmed' });
+      }
+    }
+
+    const { computationAccount } = await dealHand(table, 1105);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const game = await program.account.gameState.fetch(gamePda);
+    const folderIndex = game.currentTurnIndex;
+    const folder = game.players[folderIndex];
+    const winner = game.players[1 - folderIndex];
+    const before = {
+      folder: await program.account.playerStats.fetch(statsPda(folder)),
+      winner: await program.account.playerStats.fetch(statsPda(winner)),
+    };
+
+    await program.methods
+      .playerAction({ fold: {} })
+      .accounts({
+        player: folder,
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        seat0Stats: statsPda(game.players[0]),
+        seat1Stats: statsPda(game.players[1]),
+      })
+      .signers(signersFor(folder))
+      .rpc({ commitment: 'confirmed' });
+
+    // Only the folder's bet was matched; the winner nets exactly that, and the folder loses it.
+    const matched = BigInt(game.bets[folderIndex].toString());
+    const after = {
+      folder: await program.account.playerStats.fetch(statsPda(folder)),
+      winner: await program.account.playerStats.fetch(statsPda(winner)),
+    };
+    const delta = (role, field) => BigInt(after[role][field].toString()) - BigInt(before[role][field].toString());
+    expect(delta('winner', 'handsPlayed')).to.equal(1n);
+    expect(delta('winner', 'handsWon')).to.equal(1n);
+    expect(delta('winner', 'netProfit')).to.equal(matched);
+    expect(delta('folder', 'handsPlayed')).to.equal(1n);
+    expect(delta('folder', 'handsWon')).to.equal(0n);
+    expect(delta('folder', 'netProfit')).to.equal(-matched);
+    expect(delta('winner', 'totalRakeContributed')).to.equal(0n);
+  });
+
+  it('rejects raises beyond the stack cleanly, up to the largest amount (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const { computationAccount } = await dealHand(table, 1110);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const before = await program.account.gameState.fetch(gamePda);
+    const seat = before.currentTurnIndex;
+    const player = before.players[seat];
+    const act = (action) =>
+      program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    const actionError = async (action) => {
+      try {
+        await act(action);
+      } catch (e) {
+        return e.error.errorCode.code;
+      }
+      return null;
+    };
+
+    // One chip more than the player has, and the largest amount an instruction can carry.
+    const covered = before.stacks[seat].add(before.bets[seat]);
+    expect(await actionError({ raise: [covered.addn(1)] })).to.equal('InsufficientFunds');
+    expect(await actionError({ raise: [new anchor.BN('18446744073709551615')] })).to.equal('InsufficientFunds');
+
+    // Nothing moved, and the player can still act normally.
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.stacks.map((s) => s.toString())).to.deep.equal(before.stacks.map((s) => s.toString()));
+    expect(after.bets.map((b) => b.toString())).to.deep.equal(before.bets.map((b) => b.toString()));
+    await act({ call: {} });
+  });
+
+  it('refuses to pay rake into a treasury of another mint (devnet)', async () => {
+    // The wallet must be the config admin.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const admin = { config: configPda, admin: wallet.publicKey };
+    const setRake = (percentage, cap) =>
+      program.methods
+        .setRakeConfig(percentage, cap, original.referrerBps, original.rakebackBps)
+        .accounts(admin)
+        .rpc({ commitment: 'confirmed' });
+
+    await program.methods.setRakeTiers([]).accounts(admin).rpc({ commitment: 'confirmed' });
+    await program.methods.setRakeMinPot(new anchor.BN(0)).accounts(admin).rpc({ commitment: 'confirmed' });
+    await setRake(5, new anchor.BN('18446744073709551615'));
+    try {
+      // A fresh mint, so the platform treasury holds a different token than the table.
+      const table = await setUpHeadsUpTable(BigInt(Date.now()));
+      const game = await program.account.gameState.fetch(table.gamePda);
+      expect(game.tokenMint.equals(table.mint)).to.equal(true);
+      const { dealerKey, computationAccount } = await dealHand(table, 1111);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      await checkDownToShowdown(table, 1112);
+
+      let settleError;
+      try {
+        await settleHand(table, dealerKey);
+      } catch (e) {
+        settleError = e;
+      }
+      expect(settleError.error.errorCode.code).to.equal('TokenMintMismatch');
+
+      // With nothing to rake, the treasury is never paid and the hand settles.
+      await setRake(0, new anchor.BN(0));
+      await settleHand(table, dealerKey);
+      expect((await program.account.gameState.fetch(table.gamePda)).gamePhase).to.have.property('handOver');
+    } finally {
+      await setRake(original.rakePercentage, original.rakeCap);
+      await program.methods.setRakeMinPot(original.rakeMinPot).accounts(admin).rpc({ commitment: 'confirmed' });
+      await program.methods.setRakeTiers(original.rakeTiers).accounts(admin).rpc({ commitment: 'confirmed' });
+    }
+  });
+
+  it('pays a leaving player their stack out of escrow (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, escrowPda, joiner, mint } = table;
+    const joinerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, joiner.publicKey)).address;
+    const game = await program.account.gameState.fetch(gamePda);
+    const seat = game.players.findIndex((p) => p.equals(joiner.publicKey));
+    const stack = BigInt(game.stacks[seat].toString());
+    expect(stack > 0n).to.equal(true);
+    const walletBefore = (await getAccount(connection, joinerAta)).amount;
+    const escrowBefore = (await getAccount(connection, escrowPda)).amount;
+
+    // The escrow is owned by the game PDA, which must sign the withdrawal.
+    await program.methods
+      .leaveTable()
+      .accounts({
+        player: joiner.publicKey,
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        playerTokenAccount: joinerAta,
+        config: pda('config', [], programId),
+        mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([joiner])
+      .rpc({ commitment: 'confirmed' });
+
+    expect((await getAccount(connection, joinerAta)).amount - walletBefore).to.equal(stack);
+    expect(escrowBefore - (await getAccount(connection, escrowPda)).amount).to.equal(stack);
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.players[seat].equals(PublicKey.default)).to.equal(true);
+    expect(after.stacks[seat].toString()).to.equal('0');
+  });
+
+  it('keeps the winner seated and gives the open seat to the next challenger (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { winnerStays: true });
+    const { tableConfigPda, gamePda, escrowPda, mint } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+    const fundedPlayer = async () => {
+      const player = Keypair.generate();
+      await ensureAirdrop(connection, player.publicKey, 1);
+      const ata = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, player.publicKey)).address;
+      await mintTo(connection, wallet.payer, mint, ata, wallet.payer, Number(buyIn));
+      return { player, ata };
+    };
+    const join = ({ player, ata }) =>
+      program.methods
+        .joinTable(new anchor.BN(buyIn.toString()))
+        .accounts({
+          gameState: gamePda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          joiner: player.publicKey,
+          joinerTokenAccount: ata,
+          config: pda('config', [], programId),
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([player])
+        .rpc();
+
+    // A challenger queues while the match is being played.
+    const challenger = await fundedPlayer();
+    await program.methods
+      .joinWaitlist()
+      .accounts({ player: challenger.player.publicKey, gameState: gamePda, tableConfig: tableConfigPda })
+      .signers([challenger.player])
+      .rpc();
+
+    // Both players go all-in each hand until one of them busts; a split pot plays again.
+    let game;
+    for (let offset = 1121; ; offset += 3) {
+      expect(offset, 'a player should bust within three all-in hands').to.be.below(1130);
+      const { dealerKey, computationAccount } = await dealHand(table, offset);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      game = await program.account.gameState.fetch(gamePda);
+      await act({ raise: [game.stacks[game.currentTurnIndex].add(game.bets[game.currentTurnIndex])] });
+      await act({ call: {} });
+      await requestRunOut(table, offset + 1);
+      await requestShowdown(table, offset + 2);
+      await settleHand(table, dealerKey);
+      game = await program.account.gameState.fetch(gamePda);
+      if (game.players.some((player) => player.equals(PublicKey.default))) {
+        break;
+      }
+    }
+
+    // The loser's seat is open; the winner keeps their seat and the whole stack they won.
+    const openSeat = game.players.findIndex((player) => player.equals(PublicKey.default));
+    const winnerSeat = 1 - openSeat;
+    const winner = game.players[winnerSeat];
+    const winnerStack = game.stacks[winnerSeat];
+    expect(winnerStack.gt(new anchor.BN(buyIn.toString()))).to.be.true;
+
+    // Nobody can jump the queue for the open seat.
+    let queueError;
+    try {
+      await join(await fundedPlayer());
+    } catch (e) {
+      queueError = e;
+    }
+    expect(queueError, 'a player who did not queue must wait their turn').to.exist;
+    expect(queueError.error.errorCode.code).to.equal('NotNextOnWaitlist');
+
+    // The challenger at the front of the queue takes the open seat against the same winner.
+    await join(challenger);
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.players[openSeat].equals(challenger.player.publicKey)).to.be.true;
+    expect(game.stacks[openSeat].toString()).to.equal(buyIn.toString());
+    expect(game.players[winnerSeat].equals(winner)).to.be.true;
+    expect(game.stacks[winnerSeat].toString()).to.equal(winnerStack.toString());
+    expect(game.waitlist.every((player) => player.equals(PublicKey.default))).to.be.true;
+  });
+
+  it('only keeps a waitlist at winner-stays tables (devnet)', async () => {
+    const { tableConfigPda, gamePda } = await setUpHeadsUpTable(BigInt(Date.now()));
+    let waitlistError;
+    try {
+      await program.methods
+        .joinWaitlist()
+        .accounts({ player: wallet.publicKey, gameState: gamePda, tableConfig: tableConfigPda })
+        .rpc();
+    } catch (e) {
+      waitlistError = e;
+    }
+    expect(waitlistError, 'a table without winner stays has no waitlist').to.exist;
+    expect(waitlistError.error.errorCode.code).to.equal('WaitlistDisabled');
+  });
+
+  it('keeps the showdown callback well inside the compute budget by settling separately (devnet)', async () => {
+    // Sums the compute units our program reported in a transaction's logs, including when it
+    // ran as a callback inside an Arcium transaction.
+    const unitsUsedBy = async (signature) => {
+      const tx = await connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const consumed = new RegExp(`^Program ${programId.toBase58()} consumed (\\d+) of (\\d+) compute units$`);
+      return tx.meta.logMessages
+        .map((line) => line.match(consumed))
+        .filter(Boolean)
+        .reduce((total, [, used]) => total + Number(used), 0);
+    };
+
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { dealerKey, computationAccount } = await dealHand(table, 1132);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const callbackSignature = await checkDownToShowdown(table, 1133);
+    const settleSignature = await settleHand(table, dealerKey);
+
+    // The callback only records the winner; the rake, transfers, stats and closing the hand are
+    // all paid for by `settle_hand`, which can simply be retried with a larger budget.
+    const callbackUnits = await unitsUsedBy(callbackSignature);
+    const settleUnits = await unitsUsedBy(settleSignature);
+    console.log(`Compute units: determine_winner_callback ${callbackUnits}, settle_hand ${settleUnits}`);
+    expect(callbackUnits).to.be.above(0);
+    expect(callbackUnits).to.be.below(settleUnits);
+    expect(settleUnits).to.be.below(200_000);
+  });
+
+  it('shrinks the turn timer of a player who keeps acting at the last moment (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+      return program.account.gameState.fetch(gamePda);
+    };
+    const clockLeft = async () => {
+      const status = await program.methods
+        .crankStatus()
+        .accounts({ gameState: gamePda, tableConfig: tableConfigPda })
+        .view();
+      return status.crankFold.remaining.toNumber();
+    };
+    // Acts with a few seconds of the clock left, well past the slow-action threshold.
+    const actSlowly = async (action) => {
+      const remaining = await clockLeft();
+      await new Promise(r => setTimeout(r, Math.max(0, remaining - 4) * 1000));
+      return act(action);
+    };
+
+    const { dealerKey, computationAccount } = await dealHand(table, 1137);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const button = dealt.currentTurnIndex;
+    expect(dealt.players[button].equals(dealerKey)).to.be.true;
+    expect(dealt.slowActionCount).to.deep.equal([0, 0]);
+
+    // The button tanks preflop; the big blind checks its option at once and is not penalized.
+    let game = await actSlowly({ call: {} });
+    expect(game.slowActionCount[button]).to.equal(1);
+    const bigBlindClock = await clockLeft();
+    game = await act({ check: {} });
+    expect(game.slowActionCount[1 - button]).to.equal(0);
+
+    // The button tanks again on the flop, after the big blind checks.
+    await revealStreet(table, 1138);
+    await act({ check: {} });
+    game = await actSlowly({ check: {} });
+    expect(game.slowActionCount[button]).to.equal(2);
+    expect(game.slowActionCount[1 - button]).to.equal(0);
+
+    // On the turn the button's full clock is two penalties shorter than the big blind's was.
+    await revealStreet(table, 1139);
+    await act({ check: {} });
+    const buttonClock = await clockLeft();
+    expect(bigBlindClock).to.be.within(27, 31);
+    expect(buttonClock).to.be.within(17, 21);
+  });
+
+  it('rejects a deal once the expected circuit hash no longer matches (devnet)', async () => {
+    // The wallet must be the config admin.
+    const configPda = pda('config', [], programId);
+    const original = (await program.account.config.fetch(configPda)).circuitHashes;
+    const setHashes = (hashes) =>
+      program.methods
+        .setCircuitHashes(hashes)
+        .accounts({ config: configPda, admin: wallet.publicKey })
+        .rpc({ commitment: 'confirmed' });
+
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+
+    // Expect a shuffle circuit other than the one the computation definition was registered with.
+    const republished = { ...original, shuffleAndDeal: original.shuffleAndDeal.map((byte) => byte ^ 0xff) };
+    const updateSignature = await setHashes(republished);
+    try {
+      const tx = await connection.getTransaction(updateSignature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(programId, program.coder);
+      const updated = [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name === 'circuitHashesUpdated');
+      expect(updated).to.not.be.undefined;
+      expect(updated.data.admin.equals(wallet.publicKey)).to.be.true;
+      expect(updated.data.hashes.shuffleAndDeal).to.deep.equal(republished.shuffleAndDeal);
+
+      let dealError;
+      try {
+        await dealHand(table, 1140);
+      } catch (e) {
+        dealError = e;
+      }
+      expect(dealError, 'a deal against a mismatched circuit must be rejected').to.exist;
+      expect(dealError.error.errorCode.code).to.equal('CircuitHashMismatch');
+      expect((await program.account.gameState.fetch(table.gamePda)).gamePhase).to.have.property('handOver');
+    } finally {
+      await setHashes(original);
+    }
+
+    // With the hashes restored the same table deals normally.
+    const { computationAccount } = await dealHand(table, 1141);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    expect((await program.account.gameState.fetch(table.gamePda)).gamePhase).to.have.property('preFlop');
+  });
+
+  it('settles a recorded showdown separately and can retry a failed settlement (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { gamePda, escrowPda, handPda } = table;
+    const escrowBalance = async () => (await getAccount(connection, escrowPda)).amount;
+
+    const { dealerKey, computationAccount } = await dealHand(table, 1142);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await checkDownToShowdown(table, 1143);
+
+    // The showdown callback only records the winner: no chips or tokens have moved yet.
+    const recorded = await program.account.gameState.fetch(gamePda);
+    expect(recorded.gamePhase).to.have.property('readyToSettle');
+    expect(recorded.winnerIndex).to.be.at.most(2);
+    const unsettledStacks = recorded.stacks.map((stack) => stack.toString());
+    expect(recorded.stacks.reduce((total, stack) => total.add(stack), recorded.pot).toString()).to.equal(
+      (2n * buyIn).toString(),
+    );
+    expect(await escrowBalance()).to.equal(2n * buyIn);
+    expect(await connection.getAccountInfo(handPda)).to.not.be.null;
+
+    // A settlement that runs out of compute fails as a whole and leaves the hand to settle.
+    let settleError;
+    try {
+      await settleHand(table, dealerKey, [anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 2_000 })]);
+    } catch (e) {
+      settleError = e;
+    }
+    expect(settleError, 'a settlement without enough compute must fail').to.exist;
+    expect(String(settleError)).to.match(/exceeded CUs meter|computational budget exceeded/i);
+    const failed = await program.account.gameState.fetch(gamePda);
+    expect(failed.gamePhase).to.have.property('readyToSettle');
+    expect(failed.stacks.map((stack) => stack.toString())).to.deep.equal(unsettledStacks);
+    expect(await escrowBalance()).to.equal(2n * buyIn);
+    expect(await connection.getAccountInfo(handPda)).to.not.be.null;
+
+    // Anyone can simply retry it.
+    await settleHand(table, dealerKey);
+    const settled = await program.account.gameState.fetch(gamePda);
+    expect(settled.gamePhase).to.have.property('handOver');
+    expect(settled.pot.toNumber()).to.equal(0);
+    expect(await connection.getAccountInfo(handPda)).to.be.null;
+
+    // The settlement cannot be applied twice.
+    let replayError;
+    try {
+      await settleHand(table, dealerKey);
+    } catch (e) {
+      replayError = e;
+    }
+    expect(replayError, 'a settled hand must not be settled again').to.exist;
+  });
+
+  it('refunds hand rent to the dealer who paid it after the button has moved (devnet)', async () => {
+    const retentionSeconds = 10;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), {
+      handRetentionSeconds: new anchor.BN(retentionSeconds),
+    });
+    const { gamePda, handPda } = table;
+
+    const { dealerKey, computationAccount } = await dealHand(table, 1147);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await checkDownToShowdown(table, 1148);
+    expect((await program.account.handState.fetch(handPda)).dealer.equals(dealerKey)).to.be.true;
+
+    // Settling rotates the button, so the current dealer is no longer the one who paid the rent.
+    await settleHand(table, dealerKey);
+    const rotated = await program.account.gameState.fetch(gamePda);
+    const newDealer = rotated.players[rotated.dealerIndex];
+    expect(newDealer.equals(dealerKey)).to.be.false;
+
+    await new Promise(r => setTimeout(r, (retentionSeconds + 5) * 1000));
+    const reap = (dealerAccount) =>
+      program.methods
+        .reapHand()
+        .accounts({ gameState: gamePda, handState: handPda, dealerAccount })
+        .rpc({ commitment: 'confirmed' });
+
+    // The new button cannot collect the rent.
+    let reapError;
+    try {
+      await reap(newDealer);
+    } catch (e) {
+      reapError = e;
+    }
+    expect(reapError, 'the rent must not go to the new button').to.exist;
+    expect(reapError.error.errorCode.code).to.equal('Unauthorized');
+
+    // The dealer snapshotted in the hand gets it back, less the fee if they also paid for the reap.
+    const rent = (await connection.getAccountInfo(handPda)).lamports;
+    const before = await connection.getBalance(dealerKey, 'confirmed');
+    await reap(dealerKey);
+    const after = await connection.getBalance(dealerKey, 'confirmed');
+    expect(await connection.getAccountInfo(handPda)).to.be.null;
+    expect(after - before).to.be.at.least(rent - 10_000);
+  });
+
+  it('lets a folded player show one or both hole cards (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, handPda, joiner } = table;
+    const clusterOffset = Number(process.env.ARCIUM_CLUSTER_OFFSET || 1116522165);
+    const parser = new anchor.EventParser(programId, program.coder);
+
+    // Deals a hand, folds it preflop, and returns the player who folded.
+    const dealAndFold = async (offset) => {
+      const { computationAccount } = await dealHand(table, offset);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      const game = await program.account.gameState.fetch(gamePda);
+      const folder = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction({ fold: {} })
+        .accounts({ player: folder, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(folder.equals(wallet.publicKey) ? [] : [joiner])
+        .rpc();
+      return folder;
+    };
+    // Asks to show the cards in `showMask` and returns the cards the callback published.
+    const show = async (player, offset, showMask) => {
+      const offsetBN = new anchor.BN(offset);
+      const computationAccount = getComputationAccAddress(programId, offsetBN);
+      await program.methods
+        .showFoldedCards(offsetBN, showMask)
+        .accounts({
+          payer: player,
+          gameState: gamePda,
+          handState: handPda,
+          signPdaAccount: new PublicKey('BkkX4G853JQZtsvVSbGb4UA3BLzbaktq8Sw1X75w8paB'),
+          config: pda('config', [], programId),
+          tableConfig: tableConfigPda,
+          mxeAccount: getMXEAccAddress(programId),
+          mempoolAccount: getMempoolAccAddress(programId),
+          executingPool: getExecutingPoolAccAddress(programId),
+          computationAccount,
+          compDefAccount: getCompDefAccAddress(
+            programId,
+            Buffer.from(getCompDefAccOffset('reveal_hole_cards')).readUInt32LE(0),
+          ),
+          clusterAccount: getClusterAccAddress(clusterOffset),
+          poolAccount: await resolveFeePoolPda(provider, getArciumProgAddress()),
+          clockAccount: getClockAccAddress(),
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          arciumProgram: getArciumProgAddress(),
+        })
+        .signers(player.equals(wallet.publicKey) ? [] : [joiner])
+        .rpc({ commitment: 'processed', skipPreflight: false, maxRetries: 3 });
+      const signature = await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      const tx = await connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const shown = [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name === 'cardsShown');
+      expect(shown).to.not.be.undefined;
+      expect(shown.data.player.equals(player)).to.be.true;
+      return shown.data.cards;
+    };
+    const expectShowRejected = async (player, offset, showMask, code) => {
+      let showError;
+      try {
+        await show(player, offset, showMask);
+      } catch (e) {
+        showError = e;
+      }
+      expect(showError, `showing with mask ${showMask} must be rejected`).to.exist;
+      expect(showError.error.errorCode.code).to.equal(code);
+    };
+
+    // First hand: the folder shows only their first card; the second stays hidden.
+    const firstFolder = await dealAndFold(1152);
+    const winner = (await program.account.gameState.fetch(gamePda)).players.find((p) => !p.equals(firstFolder));
+    await expectShowRejected(winner, 1153, 1, 'Unauthorized');
+    await expectShowRejected(firstFolder, 1153, 0, 'InvalidAction');
+    await expectShowRejected(firstFolder, 1153, 4, 'InvalidAction');
+    const oneCard = await show(firstFolder, 1153, 1);
+    expect(oneCard[0]).to.be.below(52);
+    expect(oneCard[1]).to.equal(255);
+
+    // The cards can only be shown once.
+    await expectShowRejected(firstFolder, 1154, 2, 'Unauthorized');
+
+    // Second hand: the folder shows both cards.
+    const secondFolder = await dealAndFold(1155);
+    const bothCards = await show(secondFolder, 1156, 3);
+    expect(bothCards.every((card) => card < 52)).to.be.true;
+    expect(bothCards[0]).to.not.equal(bothCards[1]);
+  });
+
+  it('splits the rake between the treasury and the winner\'s referrer (devnet)', async () => {
+    // The wallet must be the config admin and able to mint the treasury's token.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const admin = { config: configPda, admin: wallet.publicKey };
+    const setRake = (percentage, cap, referrerBps) =>
+      program.methods
+        .setRakeConfig(percentage, cap, referrerBps, original.rakebackBps)
+        .accounts(admin)
+        .rpc({ commitment: 'confirmed' });
+
+    // A flat 5% rake with no effective cap, a fifth of which goes to the referrer.
+    const referrerBps = 2_000;
+    await program.methods.setRakeTiers([]).accounts(admin).rpc({ commitment: 'confirmed' });
+    await program.methods.setRakeMinPot(new anchor.BN(0)).accounts(admin).rpc({ commitment: 'confirmed' });
+    await setRake(5, new anchor.BN('18446744073709551615'), referrerBps);
+    try {
+      const treasury = original.treasuryWallet;
+      const treasuryMint = (await getAccount(connection, treasury)).mint;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryMint);
+      const { tableConfigPda, gamePda, escrowPda, handPda, joiner } = table;
+
+      // Both players were brought in by the same referrer, so whoever wins, the referrer is paid.
+      const referrer = Keypair.generate();
+      const referrerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, treasuryMint, referrer.publicKey))
+        .address;
+      for (const [player, signers] of [[wallet.publicKey, []], [joiner.publicKey, [joiner]]]) {
+        await program.methods
+          .setReferrer(referrer.publicKey)
+          .accounts({ player, gameState: gamePda })
+          .signers(signers)
+          .rpc();
+      }
+
+      const { dealerKey, computationAccount } = await dealHand(table, 1157);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      await checkDownToShowdown(table, 1158);
+      const { winnerIndex } = await program.account.gameState.fetch(gamePda);
+
+      const treasuryBefore = (await getAccount(connection, treasury)).amount;
+      await program.methods
+        .settleHand()
+        .accounts({
+          gameState: gamePda,
+          handState: handPda,
+          config: configPda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          dealerAccount: dealerKey,
+          treasuryTokenAccount: treasury,
+          referrerTokenAccount: referrerAta,
+          seat0Stats: null,
+          seat1Stats: null,
+          insurancePool: null,
+          insuredTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: 'confirmed' });
+
+      // The checked-down pot is both big blinds. A split pot has no single winner, so its
+      // referrer gets nothing.
+      const rake = (2n * bigBlind * 5n) / 100n;
+      const referrerShare = winnerIndex < 2 ? (rake * BigInt(referrerBps)) / 10_000n : 0n;
+      expect(referrerShare).to.be.lessThan(rake);
+      expect((await getAccount(connection, referrerAta)).amount).to.equal(referrerShare);
+      expect((await getAccount(connection, treasury)).amount - treasuryBefore).to.equal(rake - referrerShare);
+    } finally {
+      await setRake(original.rakePercentage, original.rakeCap, original.referrerBps);
+      await program.methods.setRakeMinPot(original.rakeMinPot).accounts(admin).rpc({ commitment: 'confirmed' });
+      await program.methods.setRakeTiers(original.rakeTiers).accounts(admin).rpc({ commitment: 'confirmed' });
+    }
+  });
+
+  it('previews the showdown winner without moving any chips (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, escrowPda, handPda } = table;
+    const clusterOffset = Number(process.env.ARCIUM_CLUSTER_OFFSET || 1116522165);
+    const parser = new anchor.EventParser(programId, program.coder);
+    const preview = async (offset) => {
+      const offsetBN = new anchor.BN(offset);
+      const computationAccount = getComputationAccAddress(programId, offsetBN);
+      await program.methods
+        .previewShowdown(offsetBN)
+        .accounts({
+          payer: wallet.publicKey,
+          gameState: gamePda,
+          handState: handPda,
+          signPdaAccount: new PublicKey('BkkX4G853JQZtsvVSbGb4UA3BLzbaktq8Sw1X75w8paB'),
+          config: pda('config', [], programId),
+          tableConfig: tableConfigPda,
+          mxeAccount: getMXEAccAddress(programId),
+          mempoolAccount: getMempoolAccAddress(programId),
+          executingPool: getExecutingPoolAccAddress(programId),
+          computationAccount,
+          compDefAccount: getCompDefAccAddress(
+            programId,
+            Buffer.from(getCompDefAccOffset('preview_winner')).readUInt32LE(0),
+          ),
+          clusterAccount: getClusterAccAddress(clusterOffset),
+          poolAccount: await resolveFeePoolPda(provider, getArciumProgAddress()),
+          clockAccount: getClockAccAddress(),
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          arciumProgram: getArciumProgAddress(),
+        })
+        .rpc({ commitment: 'processed', skipPreflight: false, maxRetries: 3 });
+      const signature = await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      const tx = await connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      return [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name === 'showdownPreviewed');
+    };
+    // Everything a preview must leave alone.
+    const snapshot = async () => {
+      const game = await program.account.gameState.fetch(gamePda);
+      return {
+        phase: Object.keys(game.gamePhase)[0],
+        stacks: game.stacks.map((stack) => stack.toString()),
+        pot: game.pot.toString(),
+        winnerIndex: game.winnerIndex,
+        escrow: (await getAccount(connection, escrowPda)).amount,
+      };
+    };
+
+    // A live hand cannot be previewed.
+    const { dealerKey, computationAccount } = await dealHand(table, 1162);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    let previewError;
+    try {
+      await preview(1163);
+    } catch (e) {
+      previewError = e;
+    }
+    expect(previewError, 'a live hand must not be previewed').to.exist;
+    expect(previewError.error.errorCode.code).to.equal('HandNotOver');
+
+    // Once the showdown is recorded, the preview reports the same winner and changes nothing.
+    await checkDownToShowdown(table, 1164);
+    const before = await snapshot();
+    expect(before.phase).to.equal('readyToSettle');
+    const previewed = await preview(1168);
+    expect(previewed).to.not.be.undefined;
+    expect(previewed.data.winnerIndex).to.equal(before.winnerIndex);
+    expect(await snapshot()).to.deep.equal(before);
+
+    // The hand still settles normally afterwards.
+    await settleHand(table, dealerKey);
+    expect((await snapshot()).phase).to.equal('handOver');
+  });
+
+  it('closes the table cleanly when both players leave back to back, in either order (devnet)', async () => {
+    // Seats two players at a fresh table and returns what each must get back.
+    const seatBoth = async () => {
+      const table = await setUpHeadsUpTable(BigInt(Date.now()));
+      const { gamePda, tableConfigPda, escrowPda, joiner, mint } = table;
+      const game = await program.account.gameState.fetch(gamePda);
+      const leavers = await Promise.all(
+        [wallet.payer, joiner].map(async (keypair) => {
+          const seat = game.players.findIndex((p) => p.equals(keypair.publicKey));
+          const ata = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, keypair.publicKey)).address;
+          const tokensBefore = (await getAccount(connection, ata)).amount;
+          return { keypair, ata, stack: BigInt(game.stacks[seat].toString()), tokensBefore };
+        }),
+      );
+      const rent = (
+        await Promise.all([gamePda, tableConfigPda, escrowPda].map((key) => connection.getAccountInfo(key)))
+      ).reduce((total, info) => total + info.lamports, 0);
+      return { table, leavers, rent };
+    };
+    const leave = ({ table: { gamePda, tableConfigPda, escrowPda, mint } }, { keypair, ata }) =>
+      program.methods
+        .leaveTable()
+        .accounts({
+          player: keypair.publicKey,
+          gameState: gamePda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          playerTokenAccount: ata,
+          config: pda('config', [], programId),
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers(keypair.publicKey.equals(wallet.publicKey) ? [] : [keypair])
+        .rpc({ commitment: 'confirmed' });
+    const expectPaidAndClosed = async ({ table, leavers }) => {
+      for (const { ata, stack, tokensBefore } of leavers) {
+        expect((await getAccount(connection, ata)).amount - tokensBefore).to.equal(stack);
+      }
+      for (const key of [table.gamePda, table.tableConfigPda, table.escrowPda]) {
+        expect(await connection.getAccountInfo(key, 'confirmed')).to.be.null;
+      }
+    };
+
+    // The wallet leaves first, then the joiner. The first leave only vacates a seat; the second
+    // closes the table and refunds all of its rent to the joiner, whose fees the wallet pays.
+    const walletFirst = await seatBoth();
+    const [creator, joiner] = walletFirst.leavers;
+    await leave(walletFirst, creator);
+    const halfEmpty = await program.account.gameState.fetch(walletFirst.table.gamePda);
+    expect(halfEmpty.isActive).to.be.false;
+    expect(halfEmpty.players.filter((p) => !p.equals(PublicKey.default))).to.have.length(1);
+    const joinerLamports = await connection.getBalance(joiner.keypair.publicKey, 'confirmed');
+    await leave(walletFirst, joiner);
+    expect((await connection.getBalance(joiner.keypair.publicKey, 'confirmed')) - joinerLamports).to.equal(
+      walletFirst.rent,
+    );
+    await expectPaidAndClosed(walletFirst);
+
+    // The joiner leaves first, then the wallet, which now closes the table.
+    const joinerFirst = await seatBoth();
+    await leave(joinerFirst, joinerFirst.leavers[1]);
+    await leave(joinerFirst, joinerFirst.leavers[0]);
+    await expectPaidAndClosed(joinerFirst);
+  });
+
+  it('refuses to deal when the button cannot cover their blind and ante (devnet)', async () => {
+    // The button antes thirty big blinds, more than the joiner's minimum buy-in.
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { buttonAnteMultiplier: 30 }, null, buyIn / 2n);
+    const { tableConfigPda, gamePda, joiner } = table;
+    const owed = smallBlind + 30n * bigBlind;
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [joiner])
+        .rpc();
+    };
+
+    // Hand the button to the joiner. If the wallet has it, the wallet limps and the joiner folds
+    // the big blind, which keeps the joiner short.
+    let game = await program.account.gameState.fetch(gamePda);
+    if (game.players[game.dealerIndex].equals(wallet.publicKey)) {
+      const { computationAccount } = await dealHand(table, 1169);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      await act({ call: {} });
+      await act({ fold: {} });
+      game = await program.account.gameState.fetch(gamePda);
+    }
+    const button = game.dealerIndex;
+    expect(game.players[button].equals(joiner.publicKey)).to.be.true;
+    const shortStack = BigInt(game.stacks[button].toString());
+    expect(shortStack >= smallBlind && shortStack < owed).to.be.true;
+
+    let dealError;
+    try {
+      await dealHand(table, 1170);
+    } catch (e) {
+      dealError = e;
+    }
+    expect(dealError, 'a button short of blind plus ante must not be dealt in').to.exist;
+    expect(dealError.error.errorCode.code).to.equal('CannotCoverBlind');
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.gamePhase).to.have.property('handOver');
+    expect(after.stacks.map((stack) => stack.toString())).to.deep.equal(game.stacks.map((stack) => stack.toString()));
+  });
+
+  it('reports each player\'s net result for a session with rebuys (devnet)', async () => {
+    const joinBuyIn = buyIn / 2n;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, null, joinBuyIn);
+    const { tableConfigPda, gamePda, escrowPda, joiner, mint } = table;
+    const parser = new anchor.EventParser(programId, program.coder);
+    const mintExposure = pda('mint_exposure', [mint.toBuffer()], programId);
+    const joinerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, joiner.publicKey)).address;
+    const walletAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, wallet.publicKey)).address;
+    const leave = async (player, playerTokenAccount, signers) => {
+      const signature = await program.methods
+        .leaveTable()
+        .accounts({
+          player,
+          gameState: gamePda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          playerTokenAccount,
+          config: pda('config', [], programId),
+          mintExposure,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers(signers)
+        .rpc({ commitment: 'confirmed' });
+      const tx = await connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      return [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name === 'sessionEnded').data;
+    };
+
+    // One hand changes the stacks: whoever acts first folds.
+    const { computationAccount } = await dealHand(table, 1171);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const folder = dealt.players[dealt.currentTurnIndex];
+    await program.methods
+      .playerAction({ fold: {} })
+      .accounts({ player: folder, gameState: gamePda, tableConfig: tableConfigPda })
+      .signers(folder.equals(wallet.publicKey) ? [] : [joiner])
+      .rpc();
+
+    // The joiner tops up twice.
+    const rebuys = [20_000n, 15_000n];
+    await mintTo(connection, wallet.payer, mint, joinerAta, wallet.payer, Number(rebuys[0] + rebuys[1]));
+    for (const amount of rebuys) {
+      await program.methods
+        .rebuy(new anchor.BN(amount.toString()))
+        .accounts({
+          player: joiner.publicKey,
+          gameState: gamePda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          playerTokenAccount: joinerAta,
+          mintExposure,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([joiner])
+        .rpc();
+    }
+
+    // The joiner's session counts the join and both rebuys, and its net is what the tokens show.
+    const joinerTokensBefore = (await getAccount(connection, joinerAta)).amount;
+    const joinerSession = await leave(joiner.publicKey, joinerAta, [joiner]);
+    const joinerWithdrawn = (await getAccount(connection, joinerAta)).amount - joinerTokensBefore;
+    const joinerBoughtIn = joinBuyIn + rebuys[0] + rebuys[1];
+    expect(joinerSession.player.equals(joiner.publicKey)).to.be.true;
+    expect(BigInt(joinerSession.totalBuyIn.toString())).to.equal(joinerBoughtIn);
+    expect(joinerSession.cashedOut.toNumber()).to.equal(0);
+    expect(BigInt(joinerSession.finalWithdrawal.toString())).to.equal(joinerWithdrawn);
+    expect(BigInt(joinerSession.net.toString())).to.equal(joinerWithdrawn - joinerBoughtIn);
+
+    // The creator's session has no rebuys. An unraked fold moves chips only between the two,
+    // so their results cancel out.
+    const walletTokensBefore = (await getAccount(connection, walletAta)).amount;
+    const walletSession = await leave(wallet.publicKey, walletAta, []);
+    const walletWithdrawn = (await getAccount(connection, walletAta)).amount - walletTokensBefore;
+    expect(BigInt(walletSession.totalBuyIn.toString())).to.equal(buyIn);
+    expect(BigInt(walletSession.net.toString())).to.equal(walletWithdrawn - buyIn);
+    expect(BigInt(walletSession.net.toString()) + BigInt(joinerSession.net.toString())).to.equal(0n);
+  });
+
+  it('rejects a preflop limp at a no-limp table (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { noLimp: true });
+    const { tableConfigPda, gamePda } = table;
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [table.joiner])
+        .rpc();
+      return program.account.gameState.fetch(gamePda);
+    };
+    const raiseTo = (total) => act({ raise: [new anchor.BN(total.toString())] });
+
+    const { computationAccount } = await dealHand(table, 1172);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const button = dealt.dealerIndex;
+    expect(dealt.currentTurnIndex).to.equal(button);
+
+    // The button may not just complete the big blind, and nothing changes when it tries.
+    let limpError;
+    try {
+      await act({ call: {} });
+    } catch (e) {
+      limpError = e;
+    }
+    expect(limpError, 'a limp must be rejected').to.exist;
+    expect(limpError.error.errorCode.code).to.equal('LimpNotAllowed');
+    const afterLimp = await program.account.gameState.fetch(gamePda);
+    expect(afterLimp.currentTurnIndex).to.equal(button);
+    expect(afterLimp.bets.map((bet) => bet.toString())).to.deep.equal(dealt.bets.map((bet) => bet.toString()));
+
+    // Raising is allowed, and once the pot is raised calls are too, including the button's.
+    await raiseTo(3n * bigBlind);
+    await raiseTo(6n * bigBlind);
+    const flop = await act({ call: {} });
+    expect(flop.gamePhase).to.have.property('flop');
+    expect(flop.bets.every((bet) => bet.toNumber() === 0)).to.be.true;
+  });
+
+  it('tells players to wait while the hand is still being dealt (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, joiner } = table;
+    const actAs = (player, action) =>
+      program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [joiner])
+        .rpc();
+
+    // The shuffle is queued but its callback has not landed yet.
+    const { computationAccount } = await dealHand(table, 1180);
+    const dealing = await program.account.gameState.fetch(gamePda);
+    expect(dealing.gamePhase).to.have.property('dealing');
+
+    // Neither player can act, whoever the turn will belong to.
+    for (const [player, action] of [[wallet.publicKey, { call: {} }], [joiner.publicKey, { check: {} }]]) {
+      let actionError;
+      try {
+        await actAs(player, action);
+      } catch (e) {
+        actionError = e;
+      }
+      expect(actionError, 'acting before the deal lands must be rejected').to.exist;
+      expect(actionError.error.errorCode.code).to.equal('HandNotDealt');
+    }
+
+    // Once the cards are dealt, the button can act.
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    expect(dealt.gamePhase).to.have.property('preFlop');
+    await actAs(dealt.players[dealt.currentTurnIndex], { call: {} });
+  });
+
+  it('moves the button to a seated player when the dealer leaves (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, escrowPda, joiner, mint } = table;
+    const mintExposure = pda('mint_exposure', [mint.toBuffer()], programId);
+    const keypairs = [wallet.payer, joiner];
+    const keypairOf = (key) => keypairs.find((keypair) => keypair.publicKey.equals(key));
+
+    // The dealer leaves between hands.
+    let game = await program.account.gameState.fetch(gamePda);
+    const dealer = keypairOf(game.players[game.dealerIndex]);
+    const stayer = game.players.find((p) => !p.equals(dealer.publicKey));
+    const dealerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, dealer.publicKey)).address;
+    await program.methods
+      .leaveTable()
+      .accounts({
+        player: dealer.publicKey,
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        playerTokenAccount: dealerAta,
+        config: pda('config', [], programId),
+        mintExposure,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers(dealer.publicKey.equals(wallet.publicKey) ? [] : [dealer])
+      .rpc();
+
+    // The button is not left on the empty seat.
+    game = await program.account.gameState.fetch(gamePda);
+    expect(game.players[game.dealerIndex].equals(stayer)).to.be.true;
+    expect(game.currentTurnIndex).to.equal(game.dealerIndex);
+
+    // A newcomer takes the empty seat, and the next hand deals with a seated player on the button.
+    const newcomer = Keypair.generate();
+    keypairs.push(newcomer);
+    await ensureAirdrop(connection, newcomer.publicKey, 1);
+    const newcomerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, newcomer.publicKey)).address;
+    await mintTo(connection, wallet.payer, mint, newcomerAta, wallet.payer, Number(buyIn));
+    await program.methods
+      .joinTable(new anchor.BN(buyIn.toString()))
+      .accounts({
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        joiner: newcomer.publicKey,
+        joinerTokenAccount: newcomerAta,
+        config: pda('config', [], programId),
+        mintExposure,
+        slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([newcomer])
+      .rpc();
+    game = await program.account.gameState.fetch(gamePda);
+    const seated = [stayer, newcomer.publicKey];
+    expect(seated.some((key) => key.equals(game.players[game.dealerIndex]))).to.be.true;
+
+    const button = keypairOf(game.players[game.dealerIndex]);
+    const { dealerKey, computationAccount } = await dealHand({ ...table, joiner: button }, 1181);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    expect(dealerKey.equals(dealer.publicKey)).to.be.false;
+    const dealt = await program.account.gameState.fetch(gamePda);
+    expect(dealt.gamePhase).to.have.property('preFlop');
+    expect((await program.account.handState.fetch(table.handPda)).dealer.equals(dealerKey)).to.be.true;
+  });
+
+  it('refuses to reap a retained hand reused by a deal before it is queued (devnet)', async () => {
+    const retentionSeconds = 5;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), {
+      handRetentionSeconds: new anchor.BN(retentionSeconds),
+    });
+    const { gamePda, handPda } = table;
+
+    const { dealerKey, computationAccount } = await dealHand(table, 1182);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    await checkDownToShowdown(table, 1183);
+    await settleHand(table, dealerKey);
+    await new Promise(r => setTimeout(r, (retentionSeconds + 5) * 1000));
+
+    // The next deal's setup reuses the expired account; it cannot be reaped before the queue.
+    const next = await setUpDeal(table, 1187);
+    let reapError;
+    try {
+      await program.methods
+        .reapHand()
+        .accounts({ gameState: gamePda, handState: handPda, dealerAccount: dealerKey })
+        .rpc();
+    } catch (e) {
+      reapError = e;
+    }
+    expect(reapError, 'a hand being dealt must not be reaped').to.exist;
+    expect(reapError.error.errorCode.code).to.equal('HandStillRetained');
+
+    const queued = await queueDeal(table, 1187, next.dealerKey, next.dealerSigners);
+    await awaitComputationFinalization(provider, queued.computationAccount, programId, 'confirmed');
+    expect((await program.account.gameState.fetch(gamePda)).gamePhase).to.have.property('preFlop');
+    expect((await program.account.handState.fetch(handPda)).retainedUntil.toNumber()).to.equal(0);
+  });
+
+  it('queues a hand\'s deal only once and never once it is under way (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, handPda, joiner } = table;
+    const expectError = async (promise, code) => {
+      let error;
+      try {
+        await promise;
+      } catch (e) {
+        error = e;
+      }
+      expect(error, `expected ${code}`).to.exist;
+      expect(error.error.errorCode.code).to.equal(code);
+    };
+
+    // A second shuffle for the same hand is refused while the first is still in flight.
+    const { dealerKey, dealerSigners } = await setUpDeal(table, 1188);
+    const { computationAccount } = await queueDeal(table, 1188, dealerKey, dealerSigners);
+    await expectError(queueDeal(table, 1189, wallet.publicKey), 'DealAlreadyQueued');
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    // Once the first deal lands, no deal can be queued into the hand, preflop or on the flop.
+    const dealt = await program.account.handState.fetch(handPda);
+    await expectError(queueDeal(table, 1190, wallet.publicKey), 'HandAlreadyInProgress');
+    for (const action of [{ call: {} }, { check: {} }]) {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(player.equals(wallet.publicKey) ? [] : [joiner])
+        .rpc();
+    }
+    await revealStreet(table, 1191);
+    await expectError(queueDeal(table, 1192, wallet.publicKey), 'HandAlreadyInProgress');
+
+    // The hand is still the one first dealt.
+    const game = await program.account.gameState.fetch(gamePda);
+    expect(game.gamePhase).to.have.property('flop');
+    expect(game.pot.toString()).to.equal((2n * bigBlind).toString());
+    const hand = await program.account.handState.fetch(handPda);
+    expect(hand.computationOffset.toNumber()).to.equal(1191);
+    expect(Buffer.from(hand.encryptedHoleCards[0]).equals(Buffer.from(dealt.encryptedHoleCards[0]))).to.be.true;
+  });
+
+  it('stops rebuys at the table limit and above the maximum buy-in (devnet)', async () => {
+    const maxRebuys = 2;
+    const joinBuyIn = buyIn / 2n;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { maxRebuys }, null, joinBuyIn);
+    const { tableConfigPda, gamePda, escrowPda, joiner, mint } = table;
+    const joinerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, joiner.publicKey)).address;
+    await mintTo(connection, wallet.payer, mint, joinerAta, wallet.payer, Number(buyIn));
+    const rebuy = (amount) =>
+      program.methods
+        .rebuy(new anchor.BN(amount.toString()))
+        .accounts({
+          player: joiner.publicKey,
+          gameState: gamePda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          playerTokenAccount: joinerAta,
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([joiner])
+        .rpc();
+    const expectError = async (promise, code) => {
+      let error;
+      try {
+        await promise;
+      } catch (e) {
+        error = e;
+      }
+      expect(error, `expected ${code}`).to.exist;
+      expect(error.error.errorCode.code).to.equal(code);
+    };
+    const game = await program.account.gameState.fetch(gamePda);
+    const seat = game.players.findIndex((p) => p.equals(joiner.publicKey));
+
+    // A top-up past the maximum buy-in is refused, including one that would overflow the stack.
+    await expectError(rebuy(2n * buyIn), 'InvalidAction');
+    await expectError(rebuy(2n ** 64n - 1n), 'InvalidAction');
+
+    // Rebuys within the limit are added to the stack and counted.
+    const amount = 10_000n;
+    for (let i = 0; i < maxRebuys; i++) {
+      await rebuy(amount);
+    }
+    let after = await program.account.gameState.fetch(gamePda);
+    expect(after.rebuyCount[seat]).to.equal(maxRebuys);
+    expect(BigInt(after.stacks[seat].toString())).to.equal(joinBuyIn + BigInt(maxRebuys) * amount);
+
+    // One more is refused and changes nothing.
+    await expectError(rebuy(amount), 'RebuyLimitReached');
+    after = await program.account.gameState.fetch(gamePda);
+    expect(after.rebuyCount[seat]).to.equal(maxRebuys);
+    expect(BigInt(after.stacks[seat].toString())).to.equal(joinBuyIn + BigInt(maxRebuys) * amount);
+  });
+
+  it('accrues rake-back on a raked hand and pays it out of the treasury on claim (devnet)', async () => {
+    // The wallet must be the config admin, able to mint the treasury's token and own the
+    // treasury, so it can approve the config PDA to pay claims out of it.
+    const configPda = pda('config', [], programId);
+    const original = await program.account.config.fetch(configPda);
+    const admin = { config: configPda, admin: wallet.publicKey };
+    const setRake = (percentage, cap, rakebackBps) =>
+      program.methods
+        .setRakeConfig(percentage, cap, original.referrerBps, rakebackBps)
+        .accounts(admin)
+        .rpc({ commitment: 'confirmed' });
+
+    // A flat 5% rake with no effective cap, half of which is credited back to the players.
+    const rakebackBps = 5_000;
+    await program.methods.setRakeTiers([]).accounts(admin).rpc({ commitment: 'confirmed' });
+    await program.methods.setRakeMinPot(new anchor.BN(0)).accounts(admin).rpc({ commitment: 'confirmed' });
+    await setRake(5, new anchor.BN('18446744073709551615'), rakebackBps);
+    try {
+      const treasury = original.treasuryWallet;
+      const treasuryAccount = await getAccount(connection, treasury);
+      expect(treasuryAccount.owner.equals(wallet.publicKey), 'the wallet must own the treasury').to.be.true;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, treasuryAccount.mint);
+      const { gamePda, handPda, escrowPda, tableConfigPda, joiner } = table;
+      const statsPda = (player) => pda('player_stats', [player.toBuffer()], programId);
+      for (const [player, signers] of [[wallet.publicKey, []], [joiner.publicKey, [joiner]]]) {
+        if (!(await program.account.playerStats.fetchNullable(statsPda(player)))) {
+          await program.methods
+            .initPlayerStats()
+            .accounts({ playerStats: statsPda(player), player, systemProgram: SystemProgram.programId })
+            .signers(signers)
+            .rpc({ commitment: 'confirmed' });
+        }
+      }
+      const joinerStatsBefore = await program.account.playerStats.fetch(statsPda(joiner.publicKey));
+
+      const { dealerKey, computationAccount } = await dealHand(table, 1193);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      await checkDownToShowdown(table, 1194);
+      const game = await program.account.gameState.fetch(gamePda);
+      await program.methods
+        .settleHand()
+        .accounts({
+          gameState: gamePda,
+          handState: handPda,
+          config: configPda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          dealerAccount: dealerKey,
+          treasuryTokenAccount: treasury,
+          referrerTokenAccount: null,
+          seat0Stats: statsPda(game.players[0]),
+          seat1Stats: statsPda(game.players[1]),
+          insurancePool: null,
+          insuredTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: 'confirmed' });
+
+      // The checked-down pot is both big blinds; each player's half of its rake earns the
+      // rake-back rate.
+      const rake = (2n * bigBlind * 5n) / 100n;
+      const rakeback = (rake / 2n) * BigInt(rakebackBps) / 10_000n;
+      expect(rakeback > 0n).to.be.true;
+      const accrued = await program.account.playerStats.fetch(statsPda(joiner.publicKey));
+      expect(BigInt(accrued.rakebackOwed.toString()) - BigInt(joinerStatsBefore.rakebackOwed.toString())).to.equal(rakeback);
+
+      // Claiming pays everything owed from the treasury to the player and records it.
+      const owed = BigInt(accrued.rakebackOwed.toString());
+      await approve(connection, wallet.payer, treasury, configPda, wallet.publicKey, owed);
+      const joinerAta = getAssociatedTokenAddressSync(treasuryAccount.mint, joiner.publicKey);
+      const treasuryBefore = (await getAccount(connection, treasury)).amount;
+      const joinerBefore = (await getAccount(connection, joinerAta)).amount;
+      await program.methods
+        .claimRakeback()
+        .accounts({
+          playerStats: statsPda(joiner.publicKey),
+          player: joiner.publicKey,
+          config: configPda,
+          treasuryTokenAccount: treasury,
+          playerTokenAccount: joinerAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([joiner])
+        .rpc({ commitment: 'confirmed' });
+      expect(treasuryBefore - (await getAccount(connection, treasury)).amount).to.equal(owed);
+      expect((await getAccount(connection, joinerAta)).amount - joinerBefore).to.equal(owed);
+      const claimed = await program.account.playerStats.fetch(statsPda(joiner.publicKey));
+      expect(claimed.rakebackOwed.toNumber()).to.equal(0);
+      expect(BigInt(claimed.rakebackClaimed.toString()) - BigInt(accrued.rakebackClaimed.toString())).to.equal(owed);
+    } finally {
+      await setRake(original.rakePercentage, original.rakeCap, original.rakebackBps);
+      await program.methods.setRakeMinPot(original.rakeMinPot).accounts(admin).rpc({ commitment: 'confirmed' });
+      await program.methods.setRakeTiers(original.rakeTiers).accounts(admin).rpc({ commitment: 'confirmed' });
+    }
+  });
+
+  it('pays river insurance out of the pool when the insured leader is outdrawn (devnet)', async () => {
+    // The wallet must be the config admin, since only the admin can create the insurance pool.
+    // The cards are shuffled for real, so all-in hands are played at fresh tables until the
+    // river outdraws an insured leader. Each hand uses six offsets.
+    const maxHands = 12;
+    const configPda = pda('config', [], programId);
+    const { insuranceMarkupBps } = await program.account.config.fetch(configPda);
+    await ensureAirdrop(connection, wallet.publicKey, 2);
+    const mint = await createMint(connection, wallet.payer, wallet.publicKey, null, 9);
+    const insurancePool = pda('insurance_pool', [mint.toBuffer()], programId);
+    await program.methods
+      .initInsurancePool()
+      .accounts({
+        insurancePool,
+        config: configPda,
+        tokenMint: mint,
+        admin: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc({ commitment: 'confirmed' });
+    await mintTo(connection, wallet.payer, mint, insurancePool, wallet.payer, Number(10n * buyIn));
+
+    let paidOut = false;
+    for (let hand = 0; hand < maxHands && !paidOut; hand++) {
+      const offset = 1200 + 6 * hand;
+      const table = await setUpHeadsUpTable(BigInt(Date.now()), {}, mint);
+      const { tableConfigPda, gamePda, handPda, joiner } = table;
+      const keypairFor = (key) => (key.equals(wallet.publicKey) ? wallet.payer : joiner);
+      const ataFor = (key) => getAssociatedTokenAddressSync(mint, key);
+      const act = async (action) => {
+        const game = await program.account.gameState.fetch(gamePda);
+        const player = game.players[game.currentTurnIndex];
+        await program.methods
+          .playerAction(action)
+          .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+          .signers(player.equals(wallet.publicKey) ? [] : [joiner])
+          .rpc();
+      };
+
+      // Both players are all-in preflop, and the board is run out to the turn.
+      const { dealerKey, computationAccount } = await dealHand(table, offset);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      await act({ raise: [new anchor.BN(buyIn.toString())] });
+      await act({ call: {} });
+      await revealStreet(table, offset + 1);
+      await revealStreet(table, offset + 2);
+
+      // Quote the river.
+      const quoteOffset = new anchor.BN(offset + 3);
+      const quoteComputation = getComputationAccAddress(programId, quoteOffset);
+      await program.methods
+        .requestInsuranceQuote(quoteOffset)
+        .accounts({
+          payer: wallet.publicKey,
+          gameState: gamePda,
+          handState: handPda,
+          signPdaAccount: new PublicKey('BkkX4G853JQZtsvVSbGb4UA3BLzbaktq8Sw1X75w8paB'),
+          config: configPda,
+          tableConfig: tableConfigPda,
+          mxeAccount: getMXEAccAddress(programId),
+          mempoolAccount: getMempoolAccAddress(programId),
+          executingPool: getExecutingPoolAccAddress(programId),
+          computationAccount: quoteComputation,
+          compDefAccount: getCompDefAccAddress(
+            programId,
+            Buffer.from(getCompDefAccOffset('insurance_odds')).readUInt32LE(0),
+          ),
+          clusterAccount: getClusterAccAddress(Number(process.env.ARCIUM_CLUSTER_OFFSET || 1116522165)),
+          poolAccount: await resolveFeePoolPda(provider, getArciumProgAddress()),
+          clockAccount: getClockAccAddress(),
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          arciumProgram: getArciumProgAddress(),
+        })
+        .rpc({ commitment: 'confirmed' });
+      await awaitComputationFinalization(provider, quoteComputation, programId, 'confirmed');
+
+      // The leader, if any, insures the whole pot at the quoted price.
+      const quoted = await program.account.gameState.fetch(gamePda);
+      const leaderSeat = quoted.insuranceLeader;
+      const outs = BigInt(quoted.insuranceOuts);
+      let cover = 0n;
+      let leader = null;
+      if (leaderSeat < 2 && outs > 0n) {
+        leader = quoted.players[leaderSeat];
+        cover = 2n * buyIn;
+        const fairPremium = (cover * outs + (44n - outs) - 1n) / (44n - outs);
+        const premium = (fairPremium * (10_000n + BigInt(insuranceMarkupBps)) + 9_999n) / 10_000n;
+        await mintTo(connection, wallet.payer, mint, ataFor(leader), wallet.payer, Number(premium));
+        const poolBefore = (await getAccount(connection, insurancePool)).amount;
+        await program.methods
+          .requestInsurance(new anchor.BN(cover.toString()))
+          .accounts({
+            player: leader,
+            gameState: gamePda,
+            config: configPda,
+            tableConfig: tableConfigPda,
+            insurancePool,
+            playerTokenAccount: ataFor(leader),
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([keypairFor(leader)])
+          .rpc({ commitment: 'confirmed' });
+        expect((await getAccount(connection, insurancePool)).amount - poolBefore).to.equal(premium);
+        expect((await program.account.gameState.fetch(gamePda)).insuredAmount.toString()).to.equal(cover.toString());
+      }
+
+      // The river comes and the hand is settled, paying out the cover if the leader lost.
+      await revealStreet(table, offset + 4);
+      await requestShowdown(table, offset + 5);
+      const { winnerIndex } = await program.account.gameState.fetch(gamePda);
+      const insuredAta = ataFor(leader ?? wallet.publicKey);
+      const poolBefore = (await getAccount(connection, insurancePool)).amount;
+      const insuredBefore = (await getAccount(connection, insuredAta)).amount;
+      const configAccount = await program.account.config.fetch(configPda);
+      await program.methods
+        .settleHand()
+        .accounts({
+          gameState: gamePda,
+          handState: handPda,
+          config: configPda,
+          tableConfig: tableConfigPda,
+          escrowAccount: table.escrowPda,
+          dealerAccount: dealerKey,
+          treasuryTokenAccount: configAccount.treasuryWallet,
+          referrerTokenAccount: null,
+          seat0Stats: null,
+          seat1Stats: null,
+          insurancePool,
+          insuredTokenAccount: insuredAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: 'confirmed' });
+      const suckOut = leader !== null && winnerIndex === 1 - leaderSeat;
+      const payout = suckOut ? cover : 0n;
+      expect(poolBefore - (await getAccount(connection, insurancePool)).amount).to.equal(payout);
+      expect((await getAccount(connection, insuredAta)).amount - insuredBefore).to.equal(payout);
+      paidOut = suckOut;
+    }
+    expect(paidOut, `no insured leader was outdrawn on the river in ${maxHands} hands; rerun`).to.be.true;
+  });
+
+  it('auto-rebuys a busted player to their minimum stack until the rebuy limit is reached (devnet)', async () => {
+    // Each player may rebuy once, and both have auto-rebuy to the minimum buy-in switched on with
+    // an allowance for two rebuys, so the table limit is what stops it. Both players go all-in
+    // each hand until a player busts a second time. Each hand uses three offsets.
+    const maxHands = 16;
+    const minStack = buyIn / 2n;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { maxRebuys: 1 });
+    const { tableConfigPda, gamePda, escrowPda, joiner, mint } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [joiner]);
+    const ataFor = (key) => getAssociatedTokenAddressSync(mint, key);
+    const act = async (action) => {
+      const game = await program.account.gameState.fetch(gamePda);
+      const player = game.players[game.currentTurnIndex];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+    const autoRebuy = (seat, player) =>
+      program.methods
+        .autoRebuy(seat)
+        .accounts({
+          gameState: gamePda,
+          tableConfig: tableConfigPda,
+          escrowAccount: escrowPda,
+          playerTokenAccount: ataFor(player),
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: 'confirmed' });
+
+    let game = await program.account.gameState.fetch(gamePda);
+    for (const player of game.players) {
+      await mintTo(connection, wallet.payer, mint, ataFor(player), wallet.payer, Number(2n * minStack));
+      await approve(connection, wallet.payer, ataFor(player), gamePda, player, Number(2n * minStack), signersFor(player));
+      await program.methods
+        .setAutoRebuy(new anchor.BN(minStack.toString()))
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda, playerTokenAccount: ataFor(player) })
+        .signers(signersFor(player))
+        .rpc({ commitment: 'confirmed' });
+    }
+
+    // A player who still has chips is not topped up.
+    let earlyError;
+    try {
+      await autoRebuy(0, game.players[0]);
+    } catch (e) {
+      earlyError = e;
+    }
+    expect(earlyError, 'auto-rebuy only fires on a bust').to.exist;
+    expect(earlyError.error.errorCode.code).to.equal('InvalidAction');
+
+    let capped = false;
+    for (let hand = 0; hand < maxHands && !capped; hand++) {
+      const offset = 1280 + 3 * hand;
+      const { dealerKey, computationAccount } = await dealHand(table, offset);
+      await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+      game = await program.account.gameState.fetch(gamePda);
+      await act({ raise: [game.stacks[game.currentTurnIndex].add(game.bets[game.currentTurnIndex])] });
+      await act({ call: {} });
+      await requestRunOut(table, offset + 1);
+      await requestShowdown(table, offset + 2);
+      await settleHand(table, dealerKey);
+
+      game = await program.account.gameState.fetch(gamePda);
+      const seat = game.stacks.findIndex((stack) => stack.isZero());
+      if (seat === -1) {
+        continue;
+      }
+      const ata = ataFor(game.players[seat]);
+      const fundsBefore = (await getAccount(connection, ata)).amount;
+      const rebuysBefore = game.rebuyCount[seat];
+      await autoRebuy(seat, game.players[seat]);
+      const after = await program.account.gameState.fetch(gamePda);
+      const pulled = fundsBefore - (await getAccount(connection, ata)).amount;
+      expect(after.players[seat].equals(game.players[seat]), 'a busted player keeps their seat').to.be.true;
+      if (rebuysBefore === 0) {
+        // The first bust restores the minimum stack out of the allowance.
+        expect(after.stacks[seat].toString()).to.equal(minStack.toString());
+        expect(after.rebuyCount[seat]).to.equal(1);
+        expect(pulled).to.equal(minStack);
+        expect(after.autoRebuyMin[seat].toString()).to.equal(minStack.toString());
+      } else {
+        // At the limit, the crank switches auto-rebuy off without pulling anything.
+        expect(after.stacks[seat].isZero()).to.be.true;
+        expect(after.rebuyCount[seat]).to.equal(1);
+        expect(pulled).to.equal(0n);
+        expect(after.autoRebuyMin[seat]).to.be.null;
+        capped = true;
+      }
+    }
+    expect(capped, `no player busted twice in ${maxHands} hands; rerun`).to.be.true;
+  });
+
+  it('folds a timed-out player once enough slots pass on a slot-timed table (devnet)', async () => {
+    const turnTimeSlots = 25;
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { turnTimeSlots: new anchor.BN(turnTimeSlots) });
+    const { tableConfigPda, gamePda } = table;
+    const crank = () =>
+      program.methods
+        .crankFold()
+        .accounts({ gameState: gamePda, tableConfig: tableConfigPda })
+        .rpc({ commitment: 'confirmed' });
+
+    const { computationAccount } = await dealHand(table, 1330);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const dealt = await program.account.gameState.fetch(gamePda);
+    const deadline = dealt.lastActionSlot.toNumber() + turnTimeSlots;
+
+    // Before the slots have passed, the crank is refused.
+    let crankError;
+    try {
+      await crank();
+    } catch (e) {
+      crankError = e;
+    }
+    expect(crankError, 'the slot timer must not have expired yet').to.exist;
+    expect(crankError.error.errorCode.code).to.equal('TimerNotExpired');
+
+    // Once the cluster is past the deadline slot, the player on the clock is folded.
+    while ((await connection.getSlot('confirmed')) <= deadline + 1) {
+      await new Promise(r => setTimeout(r, 1_000));
+    }
+    await crank();
+    const game = await program.account.gameState.fetch(gamePda);
+    expect(game.gamePhase).to.have.property('handOver');
+    expect(BigInt(game.stacks[1 - dealt.currentTurnIndex].toString()) > buyIn, 'the opponent wins the blinds').to.be.true;
+  });
+
+  it('seats a player with the encryption key they registered beforehand and deals to it (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()));
+    const { tableConfigPda, gamePda, escrowPda, handPda, joiner, mint } = table;
+    const config = pda('config', [], programId);
+    const mintExposure = pda('mint_exposure', [mint.toBuffer()], programId);
+
+    // Free the second seat for a player who registers their key before joining.
+    const joinerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, joiner.publicKey)).address;
+    await program.methods
+      .leaveTable()
+      .accounts({
+        player: joiner.publicKey,
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        playerTokenAccount: joinerAta,
+        config,
+        mintExposure,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([joiner])
+      .rpc();
+
+    const player = Keypair.generate();
+    await ensureAirdrop(connection, player.publicKey, 1);
+    const playerAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, player.publicKey)).address;
+    await mintTo(connection, wallet.payer, mint, playerAta, wallet.payer, Number(buyIn));
+    const encryptionKey = x25519.getPublicKey(x25519.utils.randomPrivateKey());
+    const profilePda = pda('player_profile', [player.publicKey.toBuffer()], programId);
+    await program.methods
+      .registerEncryptionKey(Array.from(encryptionKey))
+      .accounts({ playerProfile: profilePda, player: player.publicKey, systemProgram: SystemProgram.programId })
+      .signers([player])
+      .rpc();
+    await program.methods
+      .joinTable(new anchor.BN(buyIn.toString()))
+      .accounts({
+        gameState: gamePda,
+        tableConfig: tableConfigPda,
+        escrowAccount: escrowPda,
+        joiner: player.publicKey,
+        joinerTokenAccount: playerAta,
+        joinerProfile: profilePda,
+        config,
+        mintExposure,
+        slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([player])
+      .rpc();
+
+    // Joining picks the key up from the profile, with no separate step.
+    const game = await program.account.gameState.fetch(gamePda);
+    const seat = game.players.findIndex((p) => p.equals(player.publicKey));
+    expect(Buffer.from(game.encryptionKeys[seat]).equals(Buffer.from(encryptionKey))).to.be.true;
+
+    // The deal encrypts the player's hole cards to that key.
+    const { computationAccount } = await dealHand({ ...table, joiner: player }, 1343);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const hand = await program.account.handState.fetch(handPda);
+    expect(Buffer.from(hand.encryptedHoleCards[seat].slice(0, 32)).equals(Buffer.from(encryptionKey))).to.be.true;
+  });
+
+  it('has the big blind act before a button straddler\'s option (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { buttonStraddle: true });
+    const { tableConfigPda, gamePda } = table;
+    const signersFor = (key) => (key.equals(wallet.publicKey) ? [] : [table.joiner]);
+    const act = async (seat, action) => {
+      const player = (await program.account.gameState.fetch(gamePda)).players[seat];
+      await program.methods
+        .playerAction(action)
+        .accounts({ player, gameState: gamePda, tableConfig: tableConfigPda })
+        .signers(signersFor(player))
+        .rpc();
+    };
+
+    const before = await program.account.gameState.fetch(gamePda);
+    const button = before.dealerIndex;
+    const bigBlindSeat = 1 - button;
+    await program.methods
+      .declareStraddle(true)
+      .accounts({ player: before.players[button], gameState: gamePda, tableConfig: tableConfigPda })
+      .signers(signersFor(before.players[button]))
+      .rpc();
+    const { computationAccount } = await dealHand(table, 1344);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+
+    // The button's straddle is the last live blind, so the big blind acts first.
+    const dealt = await program.account.gameState.fetch(gamePda);
+    expect(dealt.straddled).to.be.true;
+    expect(dealt.bets[button].toString()).to.equal((2n * bigBlind).toString());
+    expect(dealt.bets[bigBlindSeat].toString()).to.equal(bigBlind.toString());
+    expect(dealt.currentTurnIndex).to.equal(bigBlindSeat);
+
+    // Calling the straddle does not close the round: the straddler still has the option.
+    await act(bigBlindSeat, { call: {} });
+    const afterCall = await program.account.gameState.fetch(gamePda);
+    expect(afterCall.gamePhase).to.have.property('preFlop');
+    expect(afterCall.currentTurnIndex).to.equal(button);
+
+    // The straddler checking their option closes preflop.
+    await act(button, { check: {} });
+    const afterOption = await program.account.gameState.fetch(gamePda);
+    expect(afterOption.gamePhase).to.have.property('flop');
+    expect(afterOption.pot.toString()).to.equal((4n * bigBlind).toString());
+  });
+
+  it('holds off the turn timer for the table\'s grace period (devnet)', async () => {
+    const table = await setUpHeadsUpTable(BigInt(Date.now()), { turnGraceSeconds: new anchor.BN(15) });
+    const { tableConfigPda, gamePda } = table;
+    const crank = () =>
+      program.methods
+        .crankFold()
+        .accounts({ gameState: gamePda, tableConfig: tableConfigPda })
+        .rpc();
+
+    const { computationAccount } = await dealHand(table, 1345);
+    await awaitComputationFinalization(provider, computationAccount, programId, 'confirmed');
+    const onClock = (await program.account.gameState.fetch(gamePda)).currentTurnIndex;
+
+    // The normal turn timer has run out, but the grace period has not.
+    await new Promise(r => setTimeout(r, 35_000)); // TURN_TIME_SECONDS is 30
+    let crankError;
+    try {
+      await crank();
+    } catch (e) {
+      crankError = e;
+    }
+    expect(crankError, 'crank_fold must be rejected within the grace window').to.exist;
+    expect(crankError.error.errorCode.code).to.equal('TimerNotExpired');
+
+    // Once the grace period is over too, the player on the clock is folded.
+    await new Promise(r => setTimeout(r, 15_000));
+    await crank();
+    const after = await program.account.gameState.fetch(gamePda);
+    expect(after.gamePhase).to.have.property('handOver');
+    expect(after.stacks[1 - onClock].toString()).to.equal((buyIn + smallBlind).toString());
+  });
 });
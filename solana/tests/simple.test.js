@@ -50,7 +50,57 @@ describe('Simple Veridian Holdem Test', () => {
     expect(program.methods.dealNewHandSetup).to.exist;
     expect(program.methods.dealNewHandQueue).to.exist;
     expect(program.methods.requestCommunityCards).to.exist;
+    expect(program.methods.requestRunOut).to.exist;
     expect(program.methods.requestShowdown).to.exist;
+    expect(program.methods.showFoldedCards).to.exist;
+    expect(program.methods.setReferrer).to.exist;
+    expect(program.methods.previewShowdown).to.exist;
+    expect(program.methods.dealDrawHandQueue).to.exist;
+    expect(program.methods.requestDraw).to.exist;
+    expect(program.methods.requestShowdownDraw).to.exist;
+    expect(program.methods.initPlayerStats).to.exist;
+    expect(program.methods.claimRakeback).to.exist;
+    expect(program.methods.registerEncryptionKey).to.exist;
+    expect(program.methods.setSittingOut).to.exist;
+    expect(program.methods.passSittingOutHand).to.exist;
+    expect(program.methods.requestInsuranceQuote).to.exist;
+    expect(program.methods.requestInsurance).to.exist;
+    expect(program.methods.rebuy).to.exist;
+    expect(program.methods.setAutoRebuy).to.exist;
+    expect(program.methods.autoRebuy).to.exist;
+    expect(program.methods.getComputationArgsSpec).to.exist;
+    expect(program.methods.setMustMoveTarget).to.exist;
+    expect(program.methods.moveSeat).to.exist;
+    expect(program.methods.declareStraddle).to.exist;
+    expect(program.methods.setMaxHandsPerHour).to.exist;
+    expect(program.methods.crankStatus).to.exist;
+    expect(program.methods.setTimeChargeConfig).to.exist;
+    expect(program.methods.setTableRakeType).to.exist;
+    expect(program.methods.reapHand).to.exist;
+    expect(program.methods.createPayoutStructure).to.exist;
+    expect(program.methods.createTournament).to.exist;
+    expect(program.methods.settleTournament).to.exist;
+    expect(program.methods.auditEscrow).to.exist;
+    expect(program.methods.setTableBlinds).to.exist;
+    expect(program.methods.forceOpponentTimeout).to.exist;
+    expect(program.methods.setMaxBuyInCap).to.exist;
+    expect(program.methods.createFastFoldPool).to.exist;
+    expect(program.methods.addFastFoldTable).to.exist;
+    expect(program.methods.syncFastFoldTable).to.exist;
+    expect(program.methods.fastFoldAndRematch).to.exist;
+    expect(program.methods.setRakeMinStreet).to.exist;
+    expect(program.methods.sendEmote).to.exist;
+    expect(program.methods.setMaxRevealRetries).to.exist;
+    expect(program.methods.reconcileTableState).to.exist;
+    expect(program.methods.setLeavePenalty).to.exist;
+    expect(program.methods.redealAfterMisdeal).to.exist;
+    expect(program.methods.setMintTableCap).to.exist;
+    expect(program.methods.transferAdmin).to.exist;
+    expect(program.methods.setPaused).to.exist;
+    expect(program.methods.setRakeTiers).to.exist;
+    expect(program.methods.setRakeMinPot).to.exist;
+    expect(program.methods.acceptAdmin).to.exist;
+    expect(program.methods.removeFromWaitlist).to.exist;
     console.log('✅ All required methods exist');
   });
 
@@ -59,6 +109,7 @@ describe('Simple Veridian Holdem Test', () => {
     const smallBlind = 1000n;
     const bigBlind = 2000n;
     const buyIn = 100_000n;
+    const tableOptions = { handCap: new anchor.BN(0), winnerStays: false, creatorSeat: 1, noLimp: false, variant: { holdem: {} }, turnTimeSlots: new anchor.BN(0), sitoutPostsBlinds: false, maxRebuys: 0, buttonStraddle: false, turnGraceSeconds: new anchor.BN(0), minRaiseMultiple: 1, handRetentionSeconds: new anchor.BN(0), runoutDelaySeconds: new anchor.BN(0), buttonAnteMultiplier: 0, maxStraddles: 0, ante: new anchor.BN(0), timeBankSeconds: new anchor.BN(0), revealEvents: false };
 
     // Derive PDAs for table
     const tableConfigPda = pda('table_config', [u64le(tableId)], programId);
@@ -96,7 +147,10 @@ describe('Simple Veridian Holdem Test', () => {
           new anchor.BN(tableId.toString()),
           new anchor.BN(smallBlind.toString()),
           new anchor.BN(bigBlind.toString()),
+          new anchor.BN((buyIn / 2n).toString()),
+          new anchor.BN((2n * buyIn).toString()),
           new anchor.BN(buyIn.toString()),
+          tableOptions,
         )
         .accounts({
           tableConfig: tableConfigPda,
@@ -105,6 +159,8 @@ describe('Simple Veridian Holdem Test', () => {
           creator: wallet.publicKey,
           tokenMint: mint,
           creatorTokenAccount: creatorAta,
+          config: pda('config', [], programId),
+          mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
           systemProgram: SystemProgram.programId,
           tokenProgram: require('@solana/spl-token').TOKEN_PROGRAM_ID,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -120,8 +176,102 @@ describe('Simple Veridian Holdem Test', () => {
     expect(gameState.tableId.toString()).to.equal(tableId.toString());
     expect(gameState.smallBlind.toString()).to.equal(smallBlind.toString());
     expect(gameState.bigBlind.toString()).to.equal(bigBlind.toString());
-    expect(gameState.buyIn.toString()).to.equal(buyIn.toString());
+    expect(gameState.stacks[tableOptions.creatorSeat].toString()).to.equal(buyIn.toString());
+    // The creator sits in the seat they asked for.
+    expect(gameState.players[tableOptions.creatorSeat].equals(wallet.publicKey)).to.be.true;
+    expect(gameState.players[1 - tableOptions.creatorSeat].equals(PublicKey.default)).to.be.true;
 
     console.log('✅ Table verification successful');
   });
+
+  it('should move a stack between two tables atomically', async () => {
+    // The wallet must be the config admin, since only the admin can flag a table for consolidation.
+    const { createMint, getOrCreateAssociatedTokenAccount, mintTo, getAccount, TOKEN_PROGRAM_ID } = require('@solana/spl-token');
+    const buyIn = 100_000n;
+    const tableOptions = { handCap: new anchor.BN(0), winnerStays: false, creatorSeat: 0, noLimp: false, variant: { holdem: {} }, turnTimeSlots: new anchor.BN(0), sitoutPostsBlinds: false, maxRebuys: 0, buttonStraddle: false, turnGraceSeconds: new anchor.BN(0), minRaiseMultiple: 1, handRetentionSeconds: new anchor.BN(0), runoutDelaySeconds: new anchor.BN(0), buttonAnteMultiplier: 0, maxStraddles: 0, ante: new anchor.BN(0), timeBankSeconds: new anchor.BN(0), revealEvents: false };
+    const sourceTableId = BigInt(Date.now());
+    const targetTableId = sourceTableId + 1n;
+
+    await ensureAirdrop(connection, wallet.publicKey, 2);
+    const mover = Keypair.generate();
+    await ensureAirdrop(connection, mover.publicKey, 1);
+
+    const mint = await createMint(connection, wallet.payer, wallet.publicKey, null, 9);
+    const creatorAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, wallet.publicKey)).address;
+    const moverAta = (await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, mover.publicKey)).address;
+    await mintTo(connection, wallet.payer, mint, creatorAta, wallet.payer, Number(buyIn));
+    await mintTo(connection, wallet.payer, mint, moverAta, wallet.payer, Number(buyIn));
+
+    const tablePdas = (tableId) => {
+      const gameState = pda('game', [u64le(tableId)], programId);
+      return {
+        tableConfig: pda('table_config', [u64le(tableId)], programId),
+        gameState,
+        escrowAccount: pda('escrow', [gameState.toBuffer()], programId),
+      };
+    };
+    const source = tablePdas(sourceTableId);
+    const target = tablePdas(targetTableId);
+
+    const createTable = (tableId, pdas, creator, creatorTokenAccount, signers) => program.methods
+      .createTable(
+        new anchor.BN(tableId.toString()),
+        new anchor.BN(1000),
+        new anchor.BN(2000),
+        new anchor.BN((buyIn / 2n).toString()),
+        new anchor.BN((2n * buyIn).toString()),
+        new anchor.BN(buyIn.toString()),
+        tableOptions,
+      )
+      .accounts({
+        ...pdas,
+        creator,
+        tokenMint: mint,
+        creatorTokenAccount,
+        config: pda('config', [], programId),
+        mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers(signers)
+      .rpc();
+    await createTable(sourceTableId, source, mover.publicKey, moverAta, [mover]);
+    await createTable(targetTableId, target, wallet.publicKey, creatorAta, []);
+
+    await program.methods
+      .setMustMoveTarget(target.gameState)
+      .accounts({
+        gameState: source.gameState,
+        config: pda('config', [], programId),
+        admin: wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .moveSeat()
+      .accounts({
+        player: mover.publicKey,
+        gameState: source.gameState,
+        tableConfig: source.tableConfig,
+        escrowAccount: source.escrowAccount,
+        targetGameState: target.gameState,
+        targetTableConfig: target.tableConfig,
+        targetEscrowAccount: target.escrowAccount,
+        mintExposure: pda('mint_exposure', [mint.toBuffer()], programId),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([mover])
+      .rpc();
+
+    // The stack arrived in the target escrow in the same transaction, and the emptied source
+    // table was closed.
+    const targetGame = await program.account.gameState.fetch(target.gameState);
+    expect(targetGame.players[1].equals(mover.publicKey)).to.be.true;
+    expect(targetGame.stacks[1].toString()).to.equal(buyIn.toString());
+    const targetEscrow = await getAccount(connection, target.escrowAccount);
+    expect(targetEscrow.amount.toString()).to.equal((2n * buyIn).toString());
+    expect(await connection.getAccountInfo(source.gameState)).to.be.null;
+    expect(await connection.getAccountInfo(source.escrowAccount)).to.be.null;
+  });
 });